@@ -0,0 +1,220 @@
+use crate::config::AlertsConfig;
+use crate::output::truncate_message;
+use crate::pipeline::LookupAlerts;
+use discord_callsign_bot::Error;
+use serenity::all::{ChannelId, CreateAllowedMentions, CreateMessage, Http, UserId};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// Services whose repeated failures page an admin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertSource {
+    Qrz,
+    GitHub,
+}
+
+impl AlertSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Qrz => "QRZ",
+            Self::GitHub => "GitHub",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct FailureRun {
+    consecutive: u32,
+    last_alert: Option<Instant>,
+}
+
+/// Counts consecutive failures per source and decides when an alert is due
+#[derive(Debug)]
+struct Tracker {
+    threshold: u32,
+    min_interval: Duration,
+    runs: HashMap<AlertSource, FailureRun>,
+}
+
+impl Tracker {
+    /// Record a failure; returns the run length when an alert should go out, at
+    /// most once per `min_interval` for each source
+    fn failure(&mut self, source: AlertSource, now: Instant) -> Option<u32> {
+        let run = self.runs.entry(source).or_default();
+        run.consecutive += 1;
+        let quiet = run
+            .last_alert
+            .is_none_or(|at| now.duration_since(at) >= self.min_interval);
+        if run.consecutive >= self.threshold && quiet {
+            run.last_alert = Some(now);
+            return Some(run.consecutive);
+        }
+        None
+    }
+
+    /// Record a success; returns the failed run's length if it had been alerted on
+    fn success(&mut self, source: AlertSource) -> Option<u32> {
+        let run = self.runs.remove(&source)?;
+        run.last_alert.map(|_| run.consecutive)
+    }
+}
+
+/// Sends `[alerts]` to an admin's DMs and/or a channel when a service keeps failing
+pub struct Alerts {
+    user_id: Option<u64>,
+    channel_id: Option<u64>,
+    dry_run: bool,
+    tracker: Mutex<Tracker>,
+    /// Set once the gateway is ready; alerts before then are only logged
+    http: OnceLock<Arc<Http>>,
+}
+
+impl Alerts {
+    /// `None` when there's nowhere to send alerts; the channel defaults to `ops_channel_id`
+    pub fn new(config: &AlertsConfig, ops_channel_id: Option<u64>, dry_run: bool) -> Option<Self> {
+        let channel_id = config.channel_id.or(ops_channel_id);
+        if config.user_id.is_none() && channel_id.is_none() {
+            return None;
+        }
+        Some(Self {
+            user_id: config.user_id,
+            channel_id,
+            dry_run,
+            tracker: Mutex::new(Tracker {
+                threshold: config.threshold.max(1),
+                min_interval: Duration::from_secs(config.min_interval_secs),
+                runs: HashMap::new(),
+            }),
+            http: OnceLock::new(),
+        })
+    }
+
+    pub fn set_http(&self, http: Arc<Http>) {
+        let _ = self.http.set(http);
+    }
+
+    /// Record a failed request, alerting once the run reaches the threshold
+    pub fn failure(&self, source: AlertSource, class: &str, error: &dyn Display) {
+        let due = self
+            .tracker
+            .lock()
+            .expect("alert tracker lock poisoned")
+            .failure(source, Instant::now());
+        if let Some(consecutive) = due {
+            self.send(format!(
+                "🚨 **{}** has failed {} times in a row ({} error). Last error: {}",
+                source.label(),
+                consecutive,
+                class,
+                error
+            ));
+        }
+    }
+
+    /// Record a successful request, announcing recovery from an alerted run
+    pub fn success(&self, source: AlertSource) {
+        let recovered = self
+            .tracker
+            .lock()
+            .expect("alert tracker lock poisoned")
+            .success(source);
+        if let Some(failures) = recovered {
+            self.send(format!(
+                "✅ **{}** is working again after {} failures.",
+                source.label(),
+                failures
+            ));
+        }
+    }
+
+    fn send(&self, content: String) {
+        if self.dry_run {
+            info!("[dry run] Would send alert: {}", content);
+            return;
+        }
+        let Some(http) = self.http.get().cloned() else {
+            error!("Not connected; alert not sent: {}", content);
+            return;
+        };
+        let (user_id, channel_id) = (self.user_id, self.channel_id);
+        tokio::spawn(async move {
+            let message = || {
+                CreateMessage::new()
+                    .content(truncate_message(&content))
+                    .allowed_mentions(CreateAllowedMentions::new())
+            };
+            if let Some(user_id) = user_id {
+                if let Err(e) = UserId::new(user_id).direct_message(&http, message()).await {
+                    warn!("Failed to DM alert to user {}: {}", user_id, e);
+                }
+            }
+            if let Some(channel_id) = channel_id {
+                if let Err(e) = ChannelId::new(channel_id)
+                    .send_message(&http, message())
+                    .await
+                {
+                    warn!("Failed to post alert to channel {}: {}", channel_id, e);
+                }
+            }
+        });
+    }
+}
+
+impl LookupAlerts for Alerts {
+    fn lookup_succeeded(&self) {
+        self.success(AlertSource::Qrz);
+    }
+
+    fn lookup_failed(&self, error: &Error) {
+        self.failure(AlertSource::Qrz, error.label(), error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> Tracker {
+        Tracker {
+            threshold: 3,
+            min_interval: Duration::from_secs(3600),
+            runs: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_alerts_after_threshold_then_rate_limits() {
+        let mut tracker = tracker();
+        let start = Instant::now();
+        assert_eq!(tracker.failure(AlertSource::GitHub, start), None);
+        assert_eq!(tracker.failure(AlertSource::GitHub, start), None);
+        assert_eq!(tracker.failure(AlertSource::GitHub, start), Some(3));
+        // Still failing, but the last alert was too recent
+        assert_eq!(tracker.failure(AlertSource::GitHub, start), None);
+        assert_eq!(
+            tracker.failure(AlertSource::GitHub, start + Duration::from_secs(3600)),
+            Some(5)
+        );
+        // Sources are counted separately
+        assert_eq!(tracker.failure(AlertSource::Qrz, start), None);
+    }
+
+    #[test]
+    fn test_success_resets_and_reports_recovery() {
+        let mut tracker = tracker();
+        let now = Instant::now();
+        tracker.failure(AlertSource::Qrz, now);
+        tracker.failure(AlertSource::Qrz, now);
+        // Never alerted, so there's no recovery to announce
+        assert_eq!(tracker.success(AlertSource::Qrz), None);
+        assert_eq!(tracker.failure(AlertSource::Qrz, now), None);
+
+        tracker.failure(AlertSource::Qrz, now);
+        tracker.failure(AlertSource::Qrz, now);
+        assert_eq!(tracker.success(AlertSource::Qrz), Some(3));
+        assert_eq!(tracker.success(AlertSource::Qrz), None);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::qrz::CallsignInfo;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serenity::all::{CreateEmbed, CreateEmbedFooter};
+
+/// Custom ID of the `/apply` modal
+pub const MODAL_ID: &str = "application:submit";
+
+/// Custom IDs of the modal's text inputs
+pub const CALLSIGN_INPUT_ID: &str = "callsign";
+pub const NAME_INPUT_ID: &str = "name";
+
+/// Prefix of the review buttons' custom IDs: `application:approve:<user ID>`
+pub const APPROVE_PREFIX: &str = "application:approve:";
+pub const DENY_PREFIX: &str = "application:deny:";
+
+/// How an applicant's callsign and name compare to QRZ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Verification {
+    /// QRZ has the callsign under a name sharing a word with the one given
+    Matched {
+        qrz_name: String,
+    },
+    /// QRZ has the callsign under someone else's name, or no name
+    Mismatch {
+        qrz_name: Option<String>,
+    },
+    NotFound,
+    /// QRZ isn't configured or the lookup failed
+    Unchecked,
+}
+
+impl Verification {
+    /// Compare the name an applicant gave with their callsign's QRZ record
+    pub fn check(name: &str, info: &CallsignInfo) -> Self {
+        let full_name: Vec<&str> = [&info.fname, &info.name]
+            .into_iter()
+            .flatten()
+            .map(|part| part.trim())
+            .filter(|part| !part.is_empty())
+            .collect();
+        let qrz_name = (!full_name.is_empty()).then(|| full_name.join(" "));
+        let qrz_words: Vec<String> = [&info.fname, &info.name, &info.nickname]
+            .into_iter()
+            .flatten()
+            .flat_map(|part| words(part))
+            .collect();
+        let matched = words(name).iter().any(|word| qrz_words.contains(word));
+        match qrz_name {
+            Some(qrz_name) if matched => Verification::Matched { qrz_name },
+            qrz_name => Verification::Mismatch { qrz_name },
+        }
+    }
+
+    /// "✅ Matches QRZ (Jay Smith)"
+    pub fn describe(&self) -> String {
+        match self {
+            Verification::Matched { qrz_name } => format!("✅ Matches QRZ ({})", qrz_name),
+            Verification::Mismatch {
+                qrz_name: Some(qrz_name),
+            } => format!("⚠️ QRZ lists {}", qrz_name),
+            Verification::Mismatch { qrz_name: None } => "⚠️ QRZ has no name".to_string(),
+            Verification::NotFound => "❌ Not found on QRZ".to_string(),
+            Verification::Unchecked => "❔ Not checked".to_string(),
+        }
+    }
+}
+
+/// Lowercase words of a name, ignoring punctuation such as "J." or "O'Brien"
+fn words(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_alphabetic())
+        .filter(|word| word.chars().count() > 1)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// A prospective member's `/apply` submission awaiting an admin; kept in `Storage`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Application {
+    pub callsign: String,
+    pub name: String,
+    pub verification: Verification,
+    pub submitted_at: DateTime<Utc>,
+}
+
+/// The review card posted for admins
+pub fn review_embed(user_id: u64, application: &Application) -> CreateEmbed {
+    CreateEmbed::new()
+        .title(format!("Membership application: {}", application.callsign))
+        .url(format!("https://www.qrz.com/db/{}", application.callsign))
+        .fields(review_fields(user_id, application))
+        .footer(CreateEmbedFooter::new("Approve or deny below"))
+}
+
+/// Name/value/inline triples shown on the review card
+pub fn review_fields(user_id: u64, application: &Application) -> Vec<(&'static str, String, bool)> {
+    vec![
+        ("Applicant", format!("<@{}>", user_id), true),
+        ("Callsign", application.callsign.clone(), true),
+        ("Name given", application.name.clone(), true),
+        ("QRZ", application.verification.describe(), false),
+        (
+            "Submitted",
+            format!("<t:{}:f>", application.submitted_at.timestamp()),
+            false,
+        ),
+    ]
+}
+
+pub fn approve_id(user_id: u64) -> String {
+    format!("{}{}", APPROVE_PREFIX, user_id)
+}
+
+pub fn deny_id(user_id: u64) -> String {
+    format!("{}{}", DENY_PREFIX, user_id)
+}
+
+/// Whether a review button approves, and the applicant's user ID
+pub fn parse_review_id(custom_id: &str) -> Option<(bool, u64)> {
+    if let Some(user_id) = custom_id.strip_prefix(APPROVE_PREFIX) {
+        return Some((true, user_id.parse().ok()?));
+    }
+    let user_id = custom_id.strip_prefix(DENY_PREFIX)?;
+    Some((false, user_id.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(fname: &str, name: &str) -> CallsignInfo {
+        CallsignInfo {
+            fname: Some(fname.to_string()),
+            name: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let qrz = info("Jay", "Smith");
+        assert_eq!(
+            Verification::check("jay", &qrz),
+            Verification::Matched {
+                qrz_name: "Jay Smith".to_string()
+            }
+        );
+        assert!(matches!(
+            Verification::check("J. Smith", &qrz),
+            Verification::Matched { .. }
+        ));
+        assert_eq!(
+            Verification::check("Forrest", &qrz),
+            Verification::Mismatch {
+                qrz_name: Some("Jay Smith".to_string())
+            }
+        );
+        // Initials alone aren't enough
+        assert!(matches!(
+            Verification::check("J S", &qrz),
+            Verification::Mismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            Verification::Matched {
+                qrz_name: "Jay Smith".to_string()
+            }
+            .describe(),
+            "✅ Matches QRZ (Jay Smith)"
+        );
+        assert_eq!(Verification::NotFound.describe(), "❌ Not found on QRZ");
+    }
+
+    #[test]
+    fn test_review_ids() {
+        assert_eq!(approve_id(42), "application:approve:42");
+        assert_eq!(parse_review_id(&approve_id(42)), Some((true, 42)));
+        assert_eq!(parse_review_id(&deny_id(42)), Some((false, 42)));
+        assert_eq!(parse_review_id("application:approve:x"), None);
+        assert_eq!(parse_review_id("quiz:next:extra"), None);
+    }
+}
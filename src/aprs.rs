@@ -0,0 +1,187 @@
+use crate::config::AprsConfig;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::header::USER_AGENT;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Trackers usually beacon with an SSID (W6JSV-9 in a car, -7 on a handheld), so
+/// the bare callsign and every SSID are queried; aprs.fi takes up to 20 names per request
+const MAX_SSID: u8 = 15;
+
+/// Response from aprs.fi's `get` endpoint with `what=loc`; numbers arrive as strings
+#[derive(Debug, Deserialize)]
+struct LocResponse {
+    result: String,
+    description: Option<String>,
+    #[serde(default)]
+    entries: Vec<LocEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocEntry {
+    name: String,
+    lat: String,
+    lng: String,
+    lasttime: String,
+    comment: Option<String>,
+    status: Option<String>,
+}
+
+/// A station's last reported position
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    /// The station that beaconed, with its SSID, e.g. "W6JSV-9"
+    pub station: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub last_heard: DateTime<Utc>,
+    pub comment: Option<String>,
+    pub status: Option<String>,
+}
+
+/// Looks up last-heard APRS positions by callsign on aprs.fi
+pub struct AprsClient {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    /// `None` results are cached too, so callsigns without a tracker aren't re-queried
+    cache: Mutex<HashMap<String, (Instant, Option<Position>)>>,
+    cache_ttl: Duration,
+}
+
+impl AprsClient {
+    pub fn new(config: &AprsConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: config.url.clone(),
+            api_key: config.api_key.clone(),
+            cache: Mutex::new(HashMap::new()),
+            cache_ttl: Duration::from_secs(config.cache_ttl_secs),
+        }
+    }
+
+    /// The most recently heard of the callsign's stations, or `None` if aprs.fi
+    /// has never heard any of them
+    pub async fn last_position(&self, callsign: &str) -> Result<Option<Position>> {
+        if let Some(position) = self.cached(callsign) {
+            debug!("APRS cache hit for {}", callsign);
+            return Ok(position);
+        }
+
+        debug!("Looking up APRS position for {}", callsign);
+        let response = self
+            .client
+            .get(&self.url)
+            .header(USER_AGENT, "discord-callsign-bot")
+            .query(&[
+                ("name", station_names(callsign).as_str()),
+                ("what", "loc"),
+                ("apikey", self.api_key.as_str()),
+                ("format", "json"),
+            ])
+            .send()
+            .await
+            .context("Failed to send request to aprs.fi")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("aprs.fi returned error {}", response.status());
+        }
+
+        let body: LocResponse = response
+            .json()
+            .await
+            .context("Failed to parse aprs.fi response")?;
+        if body.result != "ok" {
+            anyhow::bail!(
+                "aprs.fi lookup failed: {}",
+                body.description.unwrap_or(body.result)
+            );
+        }
+        let position = latest(body.entries);
+
+        self.cache
+            .lock()
+            .expect("APRS cache lock poisoned")
+            .insert(callsign.to_string(), (Instant::now(), position.clone()));
+
+        Ok(position)
+    }
+
+    fn cached(&self, callsign: &str) -> Option<Option<Position>> {
+        let cache = self.cache.lock().expect("APRS cache lock poisoned");
+        cache
+            .get(callsign)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < self.cache_ttl)
+            .map(|(_, position)| position.clone())
+    }
+}
+
+/// "W6JSV,W6JSV-1,...,W6JSV-15"
+fn station_names(callsign: &str) -> String {
+    let callsign = callsign.to_uppercase();
+    std::iter::once(callsign.clone())
+        .chain((1..=MAX_SSID).map(|ssid| format!("{}-{}", callsign, ssid)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The most recently heard entry; entries with unreadable coordinates or times are skipped
+fn latest(entries: Vec<LocEntry>) -> Option<Position> {
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            Some(Position {
+                lat: entry.lat.parse().ok()?,
+                lon: entry.lng.parse().ok()?,
+                last_heard: DateTime::from_timestamp(entry.lasttime.parse().ok()?, 0)?,
+                station: entry.name,
+                comment: entry.comment.filter(|c| !c.trim().is_empty()),
+                status: entry.status.filter(|s| !s.trim().is_empty()),
+            })
+        })
+        .max_by_key(|position| position.last_heard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_station_names() {
+        let names = station_names("w6jsv");
+        assert!(names.starts_with("W6JSV,W6JSV-1,"));
+        assert!(names.ends_with(",W6JSV-15"));
+        assert_eq!(names.split(',').count(), 16);
+    }
+
+    #[test]
+    fn test_latest() {
+        let response: LocResponse = serde_json::from_str(
+            r#"{"command": "get", "result": "ok", "found": 3, "what": "loc", "entries": [
+                {"name": "W6JSV-7", "type": "l", "time": "1791000000", "lasttime": "1791000100",
+                 "lat": "37.77", "lng": "-122.41", "comment": "HT"},
+                {"name": "W6JSV-9", "type": "l", "time": "1791100000", "lasttime": "1791100500",
+                 "lat": "37.80", "lng": "-122.27", "comment": "", "status": "QRV 146.52"},
+                {"name": "W6JSV", "type": "l", "time": "1", "lasttime": "bogus",
+                 "lat": "0", "lng": "0"}
+            ]}"#,
+        )
+        .unwrap();
+        let position = latest(response.entries).unwrap();
+        assert_eq!(position.station, "W6JSV-9");
+        assert_eq!(position.lon, -122.27);
+        assert_eq!(position.last_heard.timestamp(), 1791100500);
+        assert_eq!(position.comment, None);
+        assert_eq!(position.status.as_deref(), Some("QRV 146.52"));
+
+        let empty: LocResponse = serde_json::from_str(
+            r#"{"command": "get", "result": "ok", "found": 0, "entries": []}"#,
+        )
+        .unwrap();
+        assert_eq!(latest(empty.entries), None);
+    }
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+/// Exponential backoff: `initial`, doubling each attempt, capped at `max`
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self { initial, max }
+    }
+
+    /// Delay before retry number `attempt` (1-based)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+
+    /// `delay(attempt)` with "equal jitter": half fixed, half random, so clients
+    /// retrying after the same failure don't all hit the server at once
+    pub fn jittered(&self, attempt: u32) -> Duration {
+        let delay = self.delay(attempt);
+        let half = delay / 2;
+        half + half.mul_f64(rand::random::<f64>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_doubles_and_caps() {
+        let backoff = Backoff::new(Duration::from_secs(5), Duration::from_secs(60));
+        assert_eq!(backoff.delay(1), Duration::from_secs(5));
+        assert_eq!(backoff.delay(2), Duration::from_secs(10));
+        assert_eq!(backoff.delay(4), Duration::from_secs(40));
+        assert_eq!(backoff.delay(5), Duration::from_secs(60));
+        assert_eq!(backoff.delay(100), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_jittered_stays_within_bounds() {
+        let backoff = Backoff::new(Duration::from_secs(4), Duration::from_secs(60));
+        for _ in 0..100 {
+            let delay = backoff.jittered(2);
+            assert!(delay >= Duration::from_secs(4) && delay <= Duration::from_secs(8));
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use crate::output::html::{decode_entities, html_to_text};
+use crate::output::sanitize_field;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use reqwest::header::USER_AGENT;
+use serenity::all::{CreateEmbed, CreateEmbedFooter, Timestamp};
+use std::collections::HashSet;
+
+/// Discord caps embed titles at 256 characters
+const MAX_TITLE_CHARS: usize = 256;
+
+/// Longest summary shown under a bulletin's title, in characters
+const MAX_SUMMARY_CHARS: usize = 300;
+
+/// An RSS feed such as ARRL's news or W1AW bulletins
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feed {
+    /// The channel's `<title>`, shown in each post's footer
+    pub title: Option<String>,
+    /// In feed order, which is newest first
+    pub items: Vec<Bulletin>,
+}
+
+/// One item from a feed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bulletin {
+    /// The item's `<guid>`, falling back to its link, then its title; recorded in
+    /// `Storage` once the item is posted
+    pub id: String,
+    pub title: String,
+    pub link: Option<String>,
+    pub summary: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+}
+
+/// Parse an RSS 2.0 feed. Like `propagation::parse()`, values are pulled out by tag name
+/// rather than with a full XML parser; items without a title are skipped.
+pub fn parse(xml: &str) -> Result<Feed> {
+    if !xml.contains("<channel") {
+        anyhow::bail!("Feed has no <channel> element");
+    }
+    let item = Regex::new(r"(?s)<item\b[^>]*>(.*?)</item>").expect("Failed to compile item regex");
+
+    // The channel's own title comes before its first item
+    let header = &xml[..xml.find("<item").unwrap_or(xml.len())];
+    Ok(Feed {
+        title: tag(header, "title").and_then(|title| text(&title, MAX_TITLE_CHARS)),
+        items: item
+            .captures_iter(xml)
+            .filter_map(|captures| parse_item(&captures[1]))
+            .collect(),
+    })
+}
+
+fn parse_item(xml: &str) -> Option<Bulletin> {
+    let title = tag(xml, "title").and_then(|title| text(&title, MAX_TITLE_CHARS))?;
+    let link = tag(xml, "link")
+        .map(|link| link.trim().to_string())
+        .filter(|link| link.starts_with("https://") || link.starts_with("http://"));
+    let id = tag(xml, "guid")
+        .map(|guid| guid.trim().to_string())
+        .filter(|guid| !guid.is_empty())
+        .or_else(|| link.clone())
+        .unwrap_or_else(|| title.clone());
+    Some(Bulletin {
+        id,
+        title,
+        link,
+        summary: tag(xml, "description").and_then(|d| text(&d, MAX_SUMMARY_CHARS)),
+        published: tag(xml, "pubDate")
+            .and_then(|date| DateTime::parse_from_rfc2822(date.trim()).ok())
+            .map(|date| date.with_timezone(&Utc)),
+    })
+}
+
+/// The contents of the first `<name>` or `<name attr="...">` element, unwrapping CDATA
+/// or decoding entities. Descriptions are usually HTML either way.
+fn tag(xml: &str, name: &str) -> Option<String> {
+    let open = format!("<{}", name);
+    let mut from = 0;
+    let start = loop {
+        let at = from + xml[from..].find(&open)? + open.len();
+        // Skip longer names sharing the prefix, such as <linkage> for <link>
+        if xml[at..].starts_with(['>', ' ', '\t', '\r', '\n']) {
+            let end = at + xml[at..].find('>')?;
+            if xml[..end].ends_with('/') {
+                return None;
+            }
+            break end + 1;
+        }
+        from = at;
+    };
+    let end = start + xml[start..].find(&format!("</{}>", name))?;
+    let value = xml[start..end].trim();
+    Some(
+        match value
+            .strip_prefix("<![CDATA[")
+            .and_then(|cdata| cdata.strip_suffix("]]>"))
+        {
+            Some(cdata) => cdata.to_string(),
+            None => decode_entities(value),
+        },
+    )
+}
+
+/// Plain text from a feed value that may hold HTML, or `None` when it's empty
+fn text(value: &str, max_chars: usize) -> Option<String> {
+    let text = sanitize_field(&html_to_text(value), max_chars);
+    (!text.is_empty()).then_some(text)
+}
+
+/// Split a feed's unseen items into those to post, oldest first, and those to record as
+/// seen without posting: only the newest `max_posts` are posted, so a feed the bot
+/// hasn't reached in a while doesn't flood the channel
+pub fn select<'a>(
+    items: &'a [Bulletin],
+    seen: &HashSet<String>,
+    max_posts: usize,
+) -> (Vec<&'a Bulletin>, Vec<&'a Bulletin>) {
+    let mut unseen: Vec<&Bulletin> = Vec::new();
+    for item in items {
+        if !seen.contains(&item.id) && !unseen.iter().any(|u| u.id == item.id) {
+            unseen.push(item);
+        }
+    }
+    let skipped = unseen.split_off(unseen.len().min(max_posts));
+    unseen.reverse();
+    (unseen, skipped)
+}
+
+pub fn embed(bulletin: &Bulletin, source: Option<&str>) -> CreateEmbed {
+    let mut embed = CreateEmbed::new().title(&bulletin.title);
+    if let Some(link) = &bulletin.link {
+        embed = embed.url(link);
+    }
+    if let Some(summary) = &bulletin.summary {
+        embed = embed.description(summary);
+    }
+    if let Some(source) = source {
+        embed = embed.footer(CreateEmbedFooter::new(source));
+    }
+    if let Some(published) = bulletin
+        .published
+        .and_then(|published| Timestamp::from_unix_timestamp(published.timestamp()).ok())
+    {
+        embed = embed.timestamp(published);
+    }
+    embed
+}
+
+/// Fetches `[guilds.bulletins]` feeds
+pub struct BulletinClient {
+    client: reqwest::Client,
+}
+
+impl BulletinClient {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn fetch(&self, url: &str) -> Result<Feed> {
+        let response = self
+            .client
+            .get(url)
+            .header(USER_AGENT, "discord-callsign-bot")
+            .send()
+            .await
+            .context("Failed to send request for feed")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Feed returned error {}", response.status());
+        }
+
+        let body = response.text().await.context("Failed to read feed")?;
+        parse(&body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const FEED_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom">
+<channel>
+<title>ARRL News</title>
+<link>https://www.arrl.org/news</link>
+<atom:link href="https://www.arrl.org/news/rss" rel="self" type="application/rss+xml" />
+<item>
+<title>ARRL Propagation Bulletin ARLP042 (2026)</title>
+<link>https://www.arrl.org/news/arrl-propagation-bulletin-arlp042-2026</link>
+<guid isPermaLink="false">arlp042-2026</guid>
+<pubDate>Fri, 16 Oct 2026 20:15:00 GMT</pubDate>
+<description>&lt;p&gt;Solar activity &amp;amp; flux rose this week.&lt;/p&gt;</description>
+</item>
+<item>
+<title><![CDATA[W1AW Fall Schedule & Qualifying Runs]]></title>
+<link>https://www.arrl.org/news/w1aw-fall-schedule</link>
+<description><![CDATA[<p>Code practice resumes <b>Monday</b>.</p>]]></description>
+</item>
+<item>
+<link>https://www.arrl.org/news/untitled</link>
+</item>
+</channel>
+</rss>"#;
+
+    fn bulletin(id: &str) -> Bulletin {
+        Bulletin {
+            id: id.to_string(),
+            title: id.to_string(),
+            link: None,
+            summary: None,
+            published: None,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let feed = parse(FEED_XML).unwrap();
+        assert_eq!(feed.title.as_deref(), Some("ARRL News"));
+        assert_eq!(
+            feed.items,
+            vec![
+                Bulletin {
+                    id: "arlp042-2026".to_string(),
+                    title: "ARRL Propagation Bulletin ARLP042 (2026)".to_string(),
+                    link: Some(
+                        "https://www.arrl.org/news/arrl-propagation-bulletin-arlp042-2026"
+                            .to_string()
+                    ),
+                    summary: Some("Solar activity & flux rose this week.".to_string()),
+                    published: Some(Utc.with_ymd_and_hms(2026, 10, 16, 20, 15, 0).unwrap()),
+                },
+                Bulletin {
+                    id: "https://www.arrl.org/news/w1aw-fall-schedule".to_string(),
+                    title: "W1AW Fall Schedule & Qualifying Runs".to_string(),
+                    link: Some("https://www.arrl.org/news/w1aw-fall-schedule".to_string()),
+                    summary: Some("Code practice resumes Monday.".to_string()),
+                    published: None,
+                },
+            ]
+        );
+
+        assert!(parse("<html>Service unavailable</html>").is_err());
+    }
+
+    #[test]
+    fn test_tag() {
+        assert_eq!(
+            tag("<linkage>x</linkage><link>y</link>", "link").as_deref(),
+            Some("y")
+        );
+        assert_eq!(tag("<guid/>", "guid"), None);
+        assert_eq!(
+            tag("<title>A &amp; B</title>", "title").as_deref(),
+            Some("A & B")
+        );
+    }
+
+    #[test]
+    fn test_select() {
+        // Feed order is newest first
+        let items = vec![bulletin("d"), bulletin("c"), bulletin("b"), bulletin("a")];
+        let seen = HashSet::from(["a".to_string()]);
+        let ids = |bulletins: Vec<&Bulletin>| {
+            bulletins
+                .into_iter()
+                .map(|b| b.id.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let (post, skip) = select(&items, &seen, 5);
+        assert_eq!(ids(post), vec!["b", "c", "d"]);
+        assert!(skip.is_empty());
+
+        let (post, skip) = select(&items, &seen, 2);
+        assert_eq!(ids(post), vec!["c", "d"]);
+        assert_eq!(ids(skip), vec!["b"]);
+
+        let seen: HashSet<String> = items.iter().map(|b| b.id.clone()).collect();
+        assert_eq!(select(&items, &seen, 5), (vec![], vec![]));
+    }
+}
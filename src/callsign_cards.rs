@@ -0,0 +1,108 @@
+use crate::qrz::CallsignInfo;
+use crate::welcome::qth;
+use serenity::all::CreateEmbed;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Remembers which callsigns got a card in a guild, so a call everyone is talking
+/// about gets one per `[guilds.callsign_cards] repeat_after_secs`
+pub struct CardLimiter {
+    window: Duration,
+    posted: Mutex<HashMap<String, Instant>>,
+}
+
+impl CardLimiter {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            posted: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether `callsign` may get a card, recording it if so
+    pub fn should_post(&self, callsign: &str, now: Instant) -> bool {
+        let mut posted = self.posted.lock().expect("Card limiter lock poisoned");
+        posted.retain(|_, at| now.duration_since(*at) < self.window);
+        if posted.contains_key(callsign) {
+            return false;
+        }
+        posted.insert(callsign.to_string(), now);
+        true
+    }
+}
+
+/// "Jay Vana" from QRZ's first and last names, or the nickname alone
+fn full_name(info: &CallsignInfo) -> Option<String> {
+    let parts: Vec<&str> = [&info.fname, &info.name]
+        .into_iter()
+        .filter_map(|part| part.as_deref().map(str::trim).filter(|p| !p.is_empty()))
+        .collect();
+    if parts.is_empty() {
+        return info.nickname.clone().filter(|n| !n.trim().is_empty());
+    }
+    Some(parts.join(" "))
+}
+
+/// The card's text: the name, then the QTH unless the member hid their location
+pub fn description(info: &CallsignInfo, show_location: bool) -> String {
+    let mut lines = Vec::new();
+    if let Some(name) = full_name(info) {
+        lines.push(format!("**{}**", name));
+    }
+    if let Some(qth) = qth(info).filter(|_| show_location) {
+        lines.push(format!("📍 {}", qth));
+    }
+    lines.join("\n")
+}
+
+/// A compact QRZ card: the callsign linking to its QRZ page, with name and QTH
+pub fn card(callsign: &str, info: &CallsignInfo, show_location: bool) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .title(callsign)
+        .url(format!("https://www.qrz.com/db/{}", callsign));
+    let description = description(info, show_location);
+    if !description.is_empty() {
+        embed = embed.description(description);
+    }
+    embed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_post() {
+        let limiter = CardLimiter::new(Duration::from_secs(3600));
+        let now = Instant::now();
+        assert!(limiter.should_post("W6JSV", now));
+        assert!(!limiter.should_post("W6JSV", now + Duration::from_secs(60)));
+        assert!(limiter.should_post("KI7QCF", now));
+        assert!(limiter.should_post("W6JSV", now + Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_description() {
+        let info = CallsignInfo {
+            fname: Some("Jay".to_string()),
+            name: Some("Vana".to_string()),
+            city: Some("San Francisco".to_string()),
+            state: Some("CA".to_string()),
+            country: Some("United States".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            description(&info, true),
+            "**Jay Vana**\n📍 San Francisco, CA, United States"
+        );
+        assert_eq!(description(&info, false), "**Jay Vana**");
+
+        let nickname_only = CallsignInfo {
+            nickname: Some("Hiram".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(description(&nickname_only, true), "**Hiram**");
+        assert_eq!(description(&CallsignInfo::default(), true), "");
+    }
+}
@@ -0,0 +1,359 @@
+use crate::applications::{self, Application, Verification};
+use crate::config::{ApplicationsConfig, Override};
+use crate::output::{sanitize_field, MAX_NAME_CHARS};
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use chrono::Utc;
+use discord_callsign_bot::Error;
+use serenity::all::{
+    ActionRowComponent, ButtonStyle, ChannelId, CommandInteraction, ComponentInteraction,
+    CreateActionRow, CreateAllowedMentions, CreateButton, CreateCommand, CreateInputText,
+    CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, CreateModal,
+    EditInteractionResponse, GuildId, InputTextStyle, ModalInteraction, RoleId, UserId,
+};
+use serenity::prelude::*;
+use tracing::{info, warn};
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("apply").description("Apply for club membership with your callsign")
+}
+
+/// Open the application form, unless the member is already in or waiting
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    let guild_id = command
+        .guild_id
+        .context("/apply must be used in a server")?
+        .get();
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id)
+        .context("This server isn't configured")?;
+
+    let refusal = match &guild_config.applications {
+        None => Some("Membership applications aren't enabled for this server."),
+        Some(applications) => {
+            let is_member = applications.member_role_id.is_some_and(|role_id| {
+                command
+                    .member
+                    .as_ref()
+                    .is_some_and(|m| m.roles.contains(&RoleId::new(role_id)))
+            });
+            if is_member {
+                Some("You're already a member.")
+            } else if handler
+                .storage
+                .application(guild_id, command.user.id.get())
+                .is_some()
+            {
+                Some("Your application is waiting for an admin to review it.")
+            } else {
+                None
+            }
+        }
+    };
+    if let Some(refusal) = refusal {
+        command
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content(refusal)
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let modal =
+        CreateModal::new(applications::MODAL_ID, "Membership application").components(vec![
+            CreateActionRow::InputText(
+                CreateInputText::new(
+                    InputTextStyle::Short,
+                    "Callsign",
+                    applications::CALLSIGN_INPUT_ID,
+                )
+                .placeholder("W6JSV")
+                .max_length(10),
+            ),
+            CreateActionRow::InputText(
+                CreateInputText::new(
+                    InputTextStyle::Short,
+                    "Name as licensed",
+                    applications::NAME_INPUT_ID,
+                )
+                .placeholder("Jay Smith")
+                .max_length(64),
+            ),
+        ]);
+    command
+        .create_response(&ctx.http, CreateInteractionResponse::Modal(modal))
+        .await?;
+    Ok(())
+}
+
+/// Check a submitted application against QRZ and send it to the review channel
+pub async fn submit(handler: &Handler, ctx: &Context, modal: &ModalInteraction) -> Result<()> {
+    modal.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = modal
+        .guild_id
+        .context("Applications must be submitted in a server")?
+        .get();
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id)
+        .context("This server isn't configured")?;
+    let applications_config = guild_config
+        .applications
+        .as_ref()
+        .context("Applications aren't enabled for this server")?;
+    let user_id = modal.user.id.get();
+
+    let input = |id: &str| {
+        modal
+            .data
+            .components
+            .iter()
+            .flat_map(|row| &row.components)
+            .find_map(|component| match component {
+                ActionRowComponent::InputText(text) if text.custom_id == id => {
+                    text.value.as_deref().map(str::trim)
+                }
+                _ => None,
+            })
+            .unwrap_or_default()
+    };
+    let callsign = input(applications::CALLSIGN_INPUT_ID).to_uppercase();
+    let name = sanitize_field(input(applications::NAME_INPUT_ID), MAX_NAME_CHARS);
+    let reply = |content: &str| EditInteractionResponse::new().content(content);
+
+    if !handler.parser.is_callsign(&callsign) {
+        modal
+            .edit_response(
+                &ctx.http,
+                reply(&format!(
+                    "`{}` doesn't look like a callsign. Run `/apply` again to fix it.",
+                    callsign
+                )),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let verification = match &handler.qrz_client {
+        Some(qrz_client) => match qrz_client.lookup_callsign(&callsign).await {
+            Ok(info) => Verification::check(&name, &info),
+            Err(Error::NotFound(_)) => Verification::NotFound,
+            Err(e) => {
+                warn!("Failed to look up applicant {} in QRZ: {:?}", callsign, e);
+                Verification::Unchecked
+            }
+        },
+        None => Verification::Unchecked,
+    };
+    let application = Application {
+        callsign,
+        name,
+        verification,
+        submitted_at: Utc::now(),
+    };
+    handler
+        .storage
+        .save_application(guild_id, user_id, application.clone())?;
+
+    let buttons = vec![
+        CreateButton::new(applications::approve_id(user_id))
+            .label("Approve")
+            .style(ButtonStyle::Success),
+        CreateButton::new(applications::deny_id(user_id))
+            .label("Deny")
+            .style(ButtonStyle::Danger),
+    ];
+    let mut message = CreateMessage::new()
+        .embed(applications::review_embed(user_id, &application))
+        .components(vec![CreateActionRow::Buttons(buttons)]);
+    // Config validation requires admin_role_id with applications; ping only that role
+    if let Some(role_id) = guild_config.admin_role_id {
+        message = message
+            .content(format!("<@&{}> New membership application", role_id))
+            .allowed_mentions(CreateAllowedMentions::new().roles([RoleId::new(role_id)]));
+    }
+    let content = match ChannelId::new(applications_config.review_channel_id)
+        .send_message(&ctx.http, message)
+        .await
+    {
+        Ok(_) => {
+            info!(
+                "{} applied for membership in guild {} as {} ({})",
+                modal.user.name,
+                guild_id,
+                application.callsign,
+                application.verification.describe()
+            );
+            format!(
+                "Thanks! Your application as {} was sent to the admins. You'll get a DM once it's reviewed.",
+                application.callsign
+            )
+        }
+        Err(e) => {
+            handler.storage.take_application(guild_id, user_id)?;
+            warn!(
+                "Failed to post application to channel {}: {}",
+                applications_config.review_channel_id, e
+            );
+            "Sorry, your application couldn't be sent to the admins. Please try again later."
+                .to_string()
+        }
+    };
+    modal.edit_response(&ctx.http, reply(&content)).await?;
+    Ok(())
+}
+
+/// Approve or deny an application from its review card (admin role only)
+pub async fn review(
+    handler: &Handler,
+    ctx: &Context,
+    component: &ComponentInteraction,
+) -> Result<()> {
+    let guild_id = component
+        .guild_id
+        .context("Applications must be reviewed in a server")?
+        .get();
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id)
+        .context("This server isn't configured")?;
+    let applications_config = guild_config
+        .applications
+        .as_ref()
+        .context("Applications aren't enabled for this server")?;
+    let (approve, user_id) = applications::parse_review_id(&component.data.custom_id)
+        .with_context(|| format!("Malformed review button {}", component.data.custom_id))?;
+
+    let is_admin = guild_config.admin_role_id.is_some_and(|role_id| {
+        component
+            .member
+            .as_ref()
+            .is_some_and(|m| m.roles.contains(&RoleId::new(role_id)))
+    });
+    if !is_admin {
+        component
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content("Only admins can review applications.")
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+    component
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let Some(application) = handler.storage.take_application(guild_id, user_id)? else {
+        component
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content("This application was already reviewed.")
+                    .components(vec![]),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let (outcome, dm) = if approve {
+        approve_member(
+            handler,
+            ctx,
+            guild_id,
+            user_id,
+            &application,
+            applications_config,
+        )
+        .await?;
+        (
+            "✅ Approved",
+            format!(
+                "Welcome aboard! Your membership application was approved, and you'll be listed as {}.",
+                application.callsign
+            ),
+        )
+    } else {
+        (
+            "❌ Denied",
+            "Your membership application wasn't approved. Reach out to an admin with any questions."
+                .to_string(),
+        )
+    };
+    info!(
+        "{} {} the application from user {} ({}) in guild {}",
+        component.user.name,
+        if approve { "approved" } else { "denied" },
+        user_id,
+        application.callsign,
+        guild_id
+    );
+    component
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .content(format!("{} by <@{}>", outcome, component.user.id))
+                .components(vec![]),
+        )
+        .await?;
+
+    // Applicants may not accept DMs from server members
+    if let Err(e) = UserId::new(user_id)
+        .direct_message(&ctx.http, CreateMessage::new().content(dm))
+        .await
+    {
+        warn!("Failed to DM applicant {}: {}", user_id, e);
+    }
+    Ok(())
+}
+
+/// List the applicant under their callsign and grant the member role
+async fn approve_member(
+    handler: &Handler,
+    ctx: &Context,
+    guild_id: u64,
+    user_id: u64,
+    application: &Application,
+    applications_config: &ApplicationsConfig,
+) -> Result<()> {
+    let member_override = Override {
+        callsign: Some(application.callsign.clone()),
+        name: Some(application.name.clone()),
+        suffix: applications_config.suffix.clone(),
+        emoji: None,
+        skip_qrz: false,
+        hide_location: false,
+    };
+    handler
+        .storage
+        .set_member_override(guild_id, user_id, member_override)?;
+    if let Some(role_id) = applications_config.member_role_id {
+        if let Err(e) = ctx
+            .http
+            .add_member_role(
+                GuildId::new(guild_id),
+                UserId::new(user_id),
+                RoleId::new(role_id),
+                Some("Membership application approved"),
+            )
+            .await
+        {
+            warn!(
+                "Failed to grant member role to user {} in guild {}: {}",
+                user_id, guild_id, e
+            );
+        }
+    }
+    handler.enqueue(guild_id, user_id, "application");
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use super::edit_response;
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use serenity::all::{CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption};
+use serenity::prelude::*;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("aprs")
+        .description("Where and when an operator's APRS stations were last heard")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "callsign", "Callsign to look up")
+                .required(true),
+        )
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer(&ctx.http).await?;
+
+    let guild_id = command.guild_id.context("/aprs must be used in a server")?;
+    let callsign = command
+        .data
+        .options
+        .iter()
+        .find(|option| option.name == "callsign")
+        .and_then(|option| option.value.as_str())
+        .unwrap_or_default()
+        .trim()
+        .to_uppercase();
+
+    if !handler.parser.is_callsign(&callsign) {
+        return edit_response(ctx, command, "That doesn't look like a callsign.").await;
+    }
+    let Some(aprs_client) = &handler.aprs_client else {
+        return edit_response(ctx, command, "APRS lookups aren't configured.").await;
+    };
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id.get())
+        .context("This server isn't configured")?;
+    if handler
+        .hidden_location_callsigns(guild_config)
+        .contains(&callsign)
+    {
+        return edit_response(
+            ctx,
+            command,
+            &format!("{} has chosen not to share their location.", callsign),
+        )
+        .await;
+    }
+
+    let Some(position) = aprs_client.last_position(&callsign).await? else {
+        return edit_response(
+            ctx,
+            command,
+            &format!("aprs.fi hasn't heard any of {}'s stations.", callsign),
+        )
+        .await;
+    };
+
+    let mut lines = vec![format!(
+        "**{}** was last heard <t:{}:R> at {:.4}, {:.4}",
+        position.station,
+        position.last_heard.timestamp(),
+        position.lat,
+        position.lon
+    )];
+    if let Some(status) = &position.status {
+        lines.push(format!("Status: {}", status));
+    }
+    if let Some(comment) = &position.comment {
+        lines.push(format!("Comment: {}", comment));
+    }
+    lines.push(format!("<https://aprs.fi/#!call={}>", position.station));
+    edit_response(ctx, command, &lines.join("\n")).await
+}
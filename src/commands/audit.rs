@@ -0,0 +1,398 @@
+use super::edit_response;
+use super::suggest_overrides::{self, SuggestionSource};
+use crate::config::{default_nickname_format, GuildConfig, Override};
+use crate::discord;
+use crate::nickname;
+use crate::pipeline::Member;
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use serenity::all::{
+    ButtonStyle, CommandInteraction, CommandOptionType, ComponentInteraction, CreateActionRow,
+    CreateButton, CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    CreateInteractionResponseMessage, EditInteractionResponse, EditMember, GuildId, Permissions,
+    ResolvedOption, ResolvedValue, RoleId, UserId,
+};
+use serenity::http::StatusCode;
+use serenity::prelude::*;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+/// Discord allows five rows of five buttons per message
+const MAX_BUTTONS: usize = 25;
+
+/// Shown when Discord refuses a rename, which is almost always a permissions problem
+const FORBIDDEN_HINT: &str = "the bot needs Manage Nicknames, and can't rename the server owner or members whose highest role is above its own";
+
+/// A member whose display name doesn't parse, with the nickname that would fix it
+struct Finding {
+    user_id: u64,
+    current: String,
+    suggested: Option<String>,
+    /// Where the suggestion's callsign came from
+    source: &'static str,
+}
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("audit")
+        .description("Audit the server's members (admin role only)")
+        .default_member_permissions(Permissions::MANAGE_NICKNAMES)
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "nicknames",
+            "List members whose display name has no callsign, with suggested fixes",
+        ))
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/audit must be used in a server")?;
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id.get())
+        .context("This server isn't configured")?;
+
+    let Some(admin_role_id) = guild_config.admin_role_id else {
+        return edit_response(
+            ctx,
+            command,
+            "Admin commands aren't configured for this server.",
+        )
+        .await;
+    };
+    let is_admin = command
+        .member
+        .as_ref()
+        .is_some_and(|m| m.roles.contains(&RoleId::new(admin_role_id)));
+    if !is_admin {
+        return edit_response(ctx, command, "Only admins can run audits.").await;
+    }
+
+    let options = command.data.options();
+    match options.first() {
+        Some(ResolvedOption {
+            name: "nicknames",
+            value: ResolvedValue::SubCommand(_),
+            ..
+        }) => {}
+        _ => anyhow::bail!("/audit needs a subcommand"),
+    }
+
+    let members = handler
+        .rest_limits
+        .fetch_members(&ctx.http, guild_id)
+        .await
+        .context("Failed to fetch guild members")?;
+    let members: Vec<Member> = members.iter().map(discord::member).collect();
+    let findings = audit_nicknames(handler, ctx, guild_config, &members).await;
+    if findings.is_empty() {
+        return edit_response(
+            ctx,
+            command,
+            "Every member's display name has a callsign in it.",
+        )
+        .await;
+    }
+
+    let format = nickname_format(guild_config);
+    let can_rename = command
+        .app_permissions
+        .is_some_and(|p| p.contains(Permissions::MANAGE_NICKNAMES));
+    let fixable: Vec<&Finding> = findings.iter().filter(|f| f.suggested.is_some()).collect();
+
+    let mut content = format!(
+        "**{} members' display names have no callsign** ({} with a suggested `{}` nickname):",
+        findings.len(),
+        fixable.len(),
+        format
+    );
+    for finding in &findings {
+        match &finding.suggested {
+            Some(suggested) => content.push_str(&format!(
+                "\n{} → **{}** ({})",
+                finding.current, suggested, finding.source
+            )),
+            None => content.push_str(&format!(
+                "\n{}: no callsign found; set one with `/override set`",
+                finding.current
+            )),
+        }
+    }
+    if !fixable.is_empty() && !can_rename {
+        content.push_str(&format!(
+            "\n\nGive the bot the Manage Nicknames permission to apply these; {}.",
+            FORBIDDEN_HINT
+        ));
+    } else if fixable.len() > MAX_BUTTONS {
+        content.push_str(&format!(
+            "\n\nButtons cover the first {}; run the audit again after applying them.",
+            MAX_BUTTONS
+        ));
+    }
+
+    let buttons: Vec<CreateButton> = if can_rename {
+        fixable
+            .iter()
+            .take(MAX_BUTTONS)
+            .filter_map(|finding| {
+                let suggested = finding.suggested.as_deref()?;
+                Some(
+                    CreateButton::new(nickname::apply_id(finding.user_id, suggested))
+                        .label(suggested)
+                        .style(ButtonStyle::Primary),
+                )
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let rows = buttons
+        .chunks(5)
+        .map(|row| CreateActionRow::Buttons(row.to_vec()))
+        .collect();
+
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .content(super::truncate_message(&content))
+                .components(rows),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Rename a member to the nickname on the clicked button (admin role only)
+pub async fn apply(
+    handler: &Handler,
+    ctx: &Context,
+    component: &ComponentInteraction,
+) -> Result<()> {
+    let guild_id = component
+        .guild_id
+        .context("Nicknames must be applied in a server")?;
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id.get())
+        .context("This server isn't configured")?;
+    let (user_id, nickname) = nickname::parse_apply_id(&component.data.custom_id)
+        .with_context(|| format!("Malformed apply button {}", component.data.custom_id))?;
+
+    let is_admin = guild_config.admin_role_id.is_some_and(|role_id| {
+        component
+            .member
+            .as_ref()
+            .is_some_and(|m| m.roles.contains(&RoleId::new(role_id)))
+    });
+    if !is_admin {
+        component
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content("Only admins can change nicknames.")
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+    // Replies go in a new ephemeral message, so the audit's other buttons stay usable
+    component.defer_ephemeral(&ctx.http).await?;
+
+    let content = if handler.is_dry_run(guild_config) {
+        info!(
+            "[dry run] Would rename user {} to {:?} in guild {}",
+            user_id, nickname, guild_id
+        );
+        format!("Dry run: <@{}> would be renamed to {}.", user_id, nickname)
+    } else {
+        rename(handler, ctx, guild_id, user_id, &nickname, component).await
+    };
+
+    component
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+    Ok(())
+}
+
+/// Apply a nickname, describing the outcome for the admin who asked
+async fn rename(
+    handler: &Handler,
+    ctx: &Context,
+    guild_id: GuildId,
+    user_id: u64,
+    nickname: &str,
+    component: &ComponentInteraction,
+) -> String {
+    handler.rest_limits.before_edit(UserId::new(user_id)).await;
+    match guild_id
+        .edit_member(
+            &ctx.http,
+            UserId::new(user_id),
+            EditMember::new()
+                .nickname(nickname)
+                .audit_log_reason("Nickname audit"),
+        )
+        .await
+    {
+        Ok(_) => {
+            info!(
+                "{} renamed user {} to {:?} in guild {}",
+                component.user.name, user_id, nickname, guild_id
+            );
+            format!("Renamed <@{}> to {}.", user_id, nickname)
+        }
+        Err(serenity::Error::Http(e)) if e.status_code() == Some(StatusCode::FORBIDDEN) => {
+            warn!(
+                "Not allowed to rename user {} in guild {}: {}",
+                user_id, guild_id, e
+            );
+            format!(
+                "Discord wouldn't let the bot rename <@{}>: {}.",
+                user_id, FORBIDDEN_HINT
+            )
+        }
+        Err(e) => {
+            warn!(
+                "Failed to rename user {} to {:?} in guild {}: {}",
+                user_id, nickname, guild_id, e
+            );
+            format!("Renaming <@{}> failed: {}", user_id, e)
+        }
+    }
+}
+
+/// The `[guilds.nicknames]` format, or the default one when nicknames aren't configured
+fn nickname_format(guild_config: &GuildConfig) -> String {
+    guild_config
+        .nicknames
+        .as_ref()
+        .map(|n| n.format.clone())
+        .unwrap_or_else(default_nickname_format)
+}
+
+/// Find members whose display name doesn't parse and suggest a canonical nickname for
+/// each, from their override if they have one and `/suggest-overrides` otherwise
+async fn audit_nicknames(
+    handler: &Handler,
+    ctx: &Context,
+    guild_config: &GuildConfig,
+    members: &[Member],
+) -> Vec<Finding> {
+    let guild_id = guild_config.guild_id;
+    let builder = handler.roster_builder();
+    let bot_user = crate::bot_user_id(ctx);
+    let exclude_roles = guild_config
+        .nicknames
+        .as_ref()
+        .map(|n| n.exclude_roles.as_slice())
+        .unwrap_or_default();
+
+    let unparsed: Vec<Member> = members
+        .iter()
+        .filter(|m| {
+            m.user_id != bot_user.get()
+                && !builder.is_excluded(m)
+                && !m.roles.iter().any(|r| exclude_roles.contains(r))
+                && handler.parser.parse(m.display_name()).is_none()
+        })
+        .cloned()
+        .collect();
+
+    // Build the unparsed members' entries as if each suggestion were an override
+    let mut merged = handler.with_all_overrides(guild_config);
+    let mut sources = HashMap::new();
+    for member in &unparsed {
+        let user_id = member.user_id;
+        let has_callsign = merged
+            .get_override(&user_id.to_string())
+            .is_some_and(|o| o.callsign.is_some());
+        if has_callsign {
+            sources.insert(user_id, "override");
+            continue;
+        }
+        if builder.parse_member(member).0.is_some() {
+            sources.insert(user_id, "from their username");
+            continue;
+        }
+
+        let Some(suggestion) = suggest_overrides::suggest(
+            &handler.parser,
+            handler.qrz_client.as_deref(),
+            &handler.rosters,
+            guild_id,
+            member,
+        )
+        .await
+        else {
+            continue;
+        };
+        let name = suggestion.name.clone().or_else(|| {
+            handler.rosters.get(guild_id).and_then(|roster| {
+                roster
+                    .entries
+                    .into_iter()
+                    .find(|entry| entry.callsign == suggestion.callsign)
+                    .map(|entry| entry.name)
+            })
+        });
+        let member_override = merged
+            .overrides
+            .entry(user_id.to_string())
+            .or_insert(Override {
+                callsign: None,
+                name: None,
+                suffix: None,
+                emoji: None,
+                skip_qrz: false,
+                hide_location: false,
+            });
+        member_override.callsign = Some(suggestion.callsign);
+        member_override.name = member_override.name.take().or(name);
+        sources.insert(
+            user_id,
+            match suggestion.source {
+                SuggestionSource::PreviousRoster => "listed before",
+                SuggestionSource::QrzMatch => "confirmed on QRZ",
+                SuggestionSource::NameFragment => "unverified",
+            },
+        );
+    }
+
+    let roster = builder
+        .build(
+            &merged,
+            &unparsed,
+            Some(bot_user.get()),
+            &handler.storage.suffixes(guild_id),
+            None,
+        )
+        .await;
+    let format = nickname_format(guild_config);
+
+    let mut findings: Vec<Finding> = unparsed
+        .iter()
+        .map(|member| {
+            let user_id = member.user_id;
+            let source = sources.get(&user_id).copied();
+            let suggested = source
+                .and_then(|_| roster.by_user.get(&user_id))
+                .and_then(|entry| nickname::render(&format, entry));
+            Finding {
+                user_id,
+                current: member.display_name().to_string(),
+                suggested,
+                source: source.unwrap_or_default(),
+            }
+        })
+        .collect();
+    // Fixable members first, then by current name
+    findings.sort_by(|a, b| {
+        (a.suggested.is_none(), a.current.to_lowercase())
+            .cmp(&(b.suggested.is_none(), b.current.to_lowercase()))
+    });
+    findings
+}
@@ -0,0 +1,207 @@
+use super::edit_response;
+use crate::qrz::CallsignInfo;
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Duration, NaiveDateTime, Timelike, Utc};
+use chrono_tz::Tz;
+use serenity::all::{CommandInteraction, CreateCommand};
+use serenity::prelude::*;
+
+/// Local hours (inclusive start, exclusive end) considered "awake"
+const AWAKE_HOURS: std::ops::Range<u32> = 8..22;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("awake")
+        .description("Show roster members who are likely in daytime hours right now")
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/awake must be used in a server")?;
+
+    let Some(qrz_client) = &handler.qrz_client else {
+        return edit_response(
+            ctx,
+            command,
+            "QRZ lookups aren't configured, so locations are unknown.",
+        )
+        .await;
+    };
+
+    let Some(roster) = handler.rosters.get(guild_id.get()) else {
+        return edit_response(ctx, command, "The roster hasn't been generated yet.").await;
+    };
+
+    let opted_out = handler
+        .config
+        .get_guild_config(guild_id.get())
+        .map(|guild_config| handler.qrz_opt_out_callsigns(guild_config))
+        .unwrap_or_default();
+
+    let now = Utc::now();
+    let mut awake = Vec::new();
+    for entry in roster
+        .entries
+        .iter()
+        .filter(|e| !opted_out.contains(&e.callsign))
+    {
+        let Ok(info) = qrz_client.lookup_callsign(&entry.callsign).await else {
+            continue;
+        };
+        if let Some(local) = local_time(now, &info) {
+            if is_awake(&local) {
+                awake.push((local, format!("{} {}", entry.callsign, entry.name)));
+            }
+        }
+    }
+
+    if awake.is_empty() {
+        return edit_response(
+            ctx,
+            command,
+            "Nobody on the roster is likely awake right now.",
+        )
+        .await;
+    }
+
+    awake.sort_by(|a, b| a.1.cmp(&b.1));
+    let lines: Vec<String> = awake
+        .iter()
+        .map(|(local, who)| format!("{} — {} local", who, local.format("%H:%M")))
+        .collect();
+
+    let content = format!(
+        "**{} members likely awake:**\n{}",
+        awake.len(),
+        lines.join("\n")
+    );
+    edit_response(ctx, command, &content).await
+}
+
+/// Estimate an operator's local time from QRZ's UTC offset.
+///
+/// QRZ gives only the standard offset and whether the location observes DST, so
+/// observing locations are mapped to a representative IANA zone with that offset in
+/// the operator's hemisphere, and chrono-tz decides whether DST is in effect.
+pub fn local_time(now: DateTime<Utc>, info: &CallsignInfo) -> Option<NaiveDateTime> {
+    let offset_hours: f64 = info.gmt_offset.as_deref()?.trim().parse().ok()?;
+    let offset_minutes = (offset_hours * 60.0).round() as i32;
+
+    let observes_dst = info
+        .dst
+        .as_deref()
+        .is_some_and(|dst| dst.eq_ignore_ascii_case("y"));
+    let southern = info.lat.is_some_and(|lat| lat < 0.0);
+    if observes_dst {
+        if let Some(zone) = dst_zone(offset_minutes, southern) {
+            return Some(now.with_timezone(&zone).naive_local());
+        }
+    }
+
+    Some(now.naive_utc() + Duration::minutes(offset_minutes.into()))
+}
+
+/// A zone observing DST with the given standard offset, chosen so its rules match the
+/// region most operators at that offset live in
+fn dst_zone(offset_minutes: i32, southern: bool) -> Option<Tz> {
+    let zone = match (offset_minutes, southern) {
+        (-600, false) => Tz::America__Adak,
+        (-540, false) => Tz::America__Anchorage,
+        (-480, false) => Tz::America__Los_Angeles,
+        (-420, false) => Tz::America__Denver,
+        (-360, false) => Tz::America__Chicago,
+        (-300, false) => Tz::America__New_York,
+        (-240, false) => Tz::America__Halifax,
+        (-210, false) => Tz::America__St_Johns,
+        (-240, true) => Tz::America__Santiago,
+        (-60, false) => Tz::Atlantic__Azores,
+        (0, false) => Tz::Europe__London,
+        (60, false) => Tz::Europe__Berlin,
+        (120, false) => Tz::Europe__Helsinki,
+        (570, true) => Tz::Australia__Adelaide,
+        (600, true) => Tz::Australia__Sydney,
+        (720, true) => Tz::Pacific__Auckland,
+        _ => return None,
+    };
+    Some(zone)
+}
+
+pub fn is_awake(local: &NaiveDateTime) -> bool {
+    AWAKE_HOURS.contains(&local.hour())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn info(offset: &str, dst: &str) -> CallsignInfo {
+        CallsignInfo {
+            gmt_offset: Some(offset.to_string()),
+            dst: Some(dst.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_local_time_applies_offset() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 20, 0, 0).unwrap();
+        let local = local_time(now, &info("-8", "Y")).unwrap();
+        assert_eq!(local.hour(), 12);
+    }
+
+    #[test]
+    fn test_local_time_applies_dst_in_summer() {
+        let now = Utc.with_ymd_and_hms(2024, 7, 15, 20, 0, 0).unwrap();
+        assert_eq!(local_time(now, &info("-8", "Y")).unwrap().hour(), 13);
+        assert_eq!(local_time(now, &info("-7", "N")).unwrap().hour(), 13);
+    }
+
+    #[test]
+    fn test_local_time_follows_zone_transitions() {
+        // US DST starts on the second Sunday of March, the EU's on the last
+        let now = Utc.with_ymd_and_hms(2024, 3, 20, 20, 0, 0).unwrap();
+        assert_eq!(local_time(now, &info("-8", "Y")).unwrap().hour(), 13);
+        assert_eq!(local_time(now, &info("1", "Y")).unwrap().hour(), 21);
+
+        // It's summer time in Sydney in January
+        let sydney = CallsignInfo {
+            lat: Some(-33.9),
+            ..info("10", "Y")
+        };
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
+        assert_eq!(local_time(now, &sydney).unwrap().hour(), 11);
+        let now = Utc.with_ymd_and_hms(2024, 7, 15, 0, 0, 0).unwrap();
+        assert_eq!(local_time(now, &sydney).unwrap().hour(), 10);
+    }
+
+    #[test]
+    fn test_local_time_fractional_offset() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
+        let local = local_time(now, &info("5.5", "N")).unwrap();
+        assert_eq!((local.hour(), local.minute()), (5, 30));
+    }
+
+    #[test]
+    fn test_local_time_missing_offset() {
+        let now = Utc::now();
+        assert!(local_time(now, &CallsignInfo::default()).is_none());
+        assert!(local_time(now, &info("unknown", "N")).is_none());
+    }
+
+    #[test]
+    fn test_is_awake() {
+        let at = |hour| {
+            Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0)
+                .unwrap()
+                .naive_utc()
+        };
+        assert!(!is_awake(&at(3)));
+        assert!(is_awake(&at(8)));
+        assert!(is_awake(&at(21)));
+        assert!(!is_awake(&at(22)));
+    }
+}
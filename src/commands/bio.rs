@@ -0,0 +1,114 @@
+use super::edit_response;
+use crate::qrz::QrzClient;
+use crate::Handler;
+use anyhow::Result;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption, CreateEmbed,
+    EditInteractionResponse,
+};
+use serenity::prelude::*;
+
+/// Keep bios to a readable summary; Discord allows up to 4096 characters in an embed
+const MAX_BIO_CHARS: usize = 1000;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("bio")
+        .description("Show an operator's QRZ biography")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "callsign", "Callsign to look up")
+                .required(true),
+        )
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer(&ctx.http).await?;
+
+    let callsign = command
+        .data
+        .options
+        .iter()
+        .find(|option| option.name == "callsign")
+        .and_then(|option| option.value.as_str())
+        .unwrap_or_default()
+        .trim()
+        .to_uppercase();
+
+    if !handler.parser.is_callsign(&callsign) {
+        return edit_response(ctx, command, "That doesn't look like a callsign.").await;
+    }
+
+    let Some(qrz_client) = &handler.qrz_client else {
+        return edit_response(ctx, command, "QRZ lookups aren't configured.").await;
+    };
+
+    let Ok(info) = qrz_client.lookup_callsign(&callsign).await else {
+        return edit_response(ctx, command, &format!("{} wasn't found on QRZ.", callsign)).await;
+    };
+
+    // A missing bio is common; still show what we know
+    let bio = qrz_client
+        .lookup_biography(&callsign)
+        .await
+        .unwrap_or_default();
+
+    let title = match QrzClient::get_display_name(&info) {
+        Some(name) => format!("{} — {}", callsign, name),
+        None => callsign.clone(),
+    };
+    let description = if bio.is_empty() {
+        "No biography on QRZ.".to_string()
+    } else {
+        summarize(&bio, MAX_BIO_CHARS)
+    };
+
+    let mut embed = CreateEmbed::new()
+        .title(title)
+        .url(format!("https://www.qrz.com/db/{}", callsign))
+        .description(description);
+    if let Some(image) = info
+        .image
+        .as_deref()
+        .filter(|url| url.starts_with("https://"))
+    {
+        embed = embed.thumbnail(image);
+    }
+
+    command
+        .edit_response(&ctx.http, EditInteractionResponse::new().embed(embed))
+        .await?;
+    Ok(())
+}
+
+/// Truncate on a word boundary, marking the cut with an ellipsis
+pub fn summarize(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    // The ellipsis takes the last character, leaving max_chars - 1 for text. Taking one
+    // more than that means a cut right before a space keeps the last word.
+    let truncated: String = text.chars().take(max_chars).collect();
+    let cut = match truncated.rfind(char::is_whitespace) {
+        Some(end) => truncated[..end].to_string(),
+        None => truncated.chars().take(max_chars - 1).collect(),
+    };
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        assert_eq!(summarize("short bio", 100), "short bio");
+        assert_eq!(summarize("licensed in 2010 on HF", 12), "licensed in…");
+
+        assert_eq!(summarize("abcdefghij", 5), "abcd…");
+        // The cut falls right before a space, so "cd" is kept whole
+        assert_eq!(summarize("ab cd efg", 6), "ab cd…");
+
+        let long = "word ".repeat(500);
+        assert!(summarize(&long, MAX_BIO_CHARS).chars().count() <= MAX_BIO_CHARS);
+    }
+}
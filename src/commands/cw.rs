@@ -0,0 +1,192 @@
+use super::edit_response;
+use crate::config::CwConfig;
+use crate::morse::{self, Challenge};
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use rand::seq::IndexedRandom;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateAttachment, CreateCommand, CreateCommandOption,
+    EditInteractionResponse, ResolvedOption, ResolvedValue,
+};
+use serenity::prelude::*;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("cw")
+        .description("Morse code practice with callsigns from the roster")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "practice",
+                "Send a callsign in Morse to copy",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "wpm",
+                    "Speed in words per minute",
+                )
+                .min_int_value(CwConfig::MIN_WPM.into())
+                .max_int_value(CwConfig::MAX_WPM.into()),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "format", "How to send it")
+                    .add_string_choice("Audio", "audio")
+                    .add_string_choice("Dots and dashes", "text"),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "answer",
+                "Answer your last practice callsign",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "copy", "What you copied")
+                    .required(true),
+            ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "stats",
+            "Show your practice accuracy",
+        ))
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/cw must be used in a server")?
+        .get();
+    let user_id = command.user.id.get();
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id)
+        .context("This server isn't configured")?;
+    let Some(cw_config) = &guild_config.cw else {
+        return edit_response(
+            ctx,
+            command,
+            "Morse practice isn't enabled for this server.",
+        )
+        .await;
+    };
+
+    let options = command.data.options();
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(options),
+        ..
+    }) = options.first()
+    else {
+        anyhow::bail!("/cw needs a subcommand");
+    };
+    let option = |name: &str| options.iter().find(|option| option.name == name);
+
+    match *subcommand {
+        "practice" => {
+            let wpm = match option("wpm").map(|option| &option.value) {
+                Some(ResolvedValue::Integer(wpm)) => u32::try_from(*wpm)
+                    .unwrap_or(cw_config.wpm)
+                    .clamp(CwConfig::MIN_WPM, CwConfig::MAX_WPM),
+                _ => cw_config.wpm,
+            };
+            let as_text = matches!(
+                option("format").map(|option| &option.value),
+                Some(ResolvedValue::String("text"))
+            );
+
+            // Members don't get their own callsign, which they'd copy from memory
+            let own = handler.rosters.known_callsign(guild_id, user_id);
+            let callsigns: Vec<String> = handler
+                .rosters
+                .get(guild_id)
+                .map(|roster| roster.entries)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|entry| entry.callsign)
+                .filter(|callsign| Some(callsign) != own.as_ref())
+                .collect();
+            let Some(callsign) = callsigns.choose(&mut rand::rng()).cloned() else {
+                return edit_response(ctx, command, "The roster has no callsigns to send yet.")
+                    .await;
+            };
+
+            let prompt = format!(
+                "Copy this callsign at {} WPM, then send it with `/cw answer`.",
+                wpm
+            );
+            let response = if as_text {
+                EditInteractionResponse::new().content(format!(
+                    "{}\n`{}`",
+                    prompt,
+                    morse::to_text(&callsign)
+                ))
+            } else {
+                EditInteractionResponse::new()
+                    .content(prompt)
+                    .new_attachment(CreateAttachment::bytes(
+                        morse::to_wav(&callsign, wpm, cw_config.tone_hz),
+                        "cw.wav",
+                    ))
+            };
+            handler
+                .cw_practice
+                .start(guild_id, user_id, Challenge { callsign, wpm });
+            command.edit_response(&ctx.http, response).await?;
+            Ok(())
+        }
+        "answer" => {
+            let copied = match option("copy").map(|option| &option.value) {
+                Some(ResolvedValue::String(copy)) => copy.trim(),
+                _ => "",
+            };
+            let Some(challenge) = handler.cw_practice.take(guild_id, user_id) else {
+                return edit_response(
+                    ctx,
+                    command,
+                    "You don't have a callsign to copy; start with `/cw practice`.",
+                )
+                .await;
+            };
+            let score = handler.storage.record_cw_attempt(
+                guild_id,
+                user_id,
+                &challenge.callsign,
+                copied,
+                challenge.wpm,
+            )?;
+            let result = if challenge.callsign.eq_ignore_ascii_case(copied) {
+                format!(
+                    "✅ Correct: {} at {} WPM.",
+                    challenge.callsign, challenge.wpm
+                )
+            } else {
+                format!(
+                    "❌ It was {} at {} WPM; you copied {} ({}/{} characters).",
+                    challenge.callsign,
+                    challenge.wpm,
+                    if copied.is_empty() { "nothing" } else { copied },
+                    morse::grade(&challenge.callsign, copied),
+                    challenge.callsign.chars().count()
+                )
+            };
+            edit_response(
+                ctx,
+                command,
+                &format!("{}\nSo far: {}", result, score.describe()),
+            )
+            .await
+        }
+        "stats" => {
+            let score = handler.storage.cw_score(guild_id, user_id);
+            let content = match score.attempts {
+                0 => "You haven't practiced yet; start with `/cw practice`.".to_string(),
+                _ => format!("Your Morse practice: {}", score.describe()),
+            };
+            edit_response(ctx, command, &content).await
+        }
+        _ => anyhow::bail!("Unknown /cw subcommand {}", subcommand),
+    }
+}
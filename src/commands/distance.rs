@@ -0,0 +1,102 @@
+use super::edit_response;
+use crate::output::geo;
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use serenity::all::{CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption};
+use serenity::prelude::*;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("distance")
+        .description("How far another club member is from you, by grid square")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "callsign",
+                "Callsign of the other member",
+            )
+            .required(true),
+        )
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/distance must be used in a server")?;
+    let callsign = command
+        .data
+        .options
+        .iter()
+        .find(|option| option.name == "callsign")
+        .and_then(|option| option.value.as_str())
+        .unwrap_or_default()
+        .trim()
+        .to_uppercase();
+
+    if !handler.parser.is_callsign(&callsign) {
+        return edit_response(ctx, command, "That doesn't look like a callsign.").await;
+    }
+    let Some(own_callsign) = handler
+        .rosters
+        .known_callsign(guild_id.get(), command.user.id.get())
+    else {
+        return edit_response(
+            ctx,
+            command,
+            "Your callsign isn't on the roster yet, so I don't know where you are.",
+        )
+        .await;
+    };
+
+    let Some(own_grid) = grid(handler, guild_id.get(), &own_callsign).await else {
+        return edit_response(
+            ctx,
+            command,
+            &format!("I couldn't find a grid square for you ({}).", own_callsign),
+        )
+        .await;
+    };
+    let Some(their_grid) = grid(handler, guild_id.get(), &callsign).await else {
+        return edit_response(
+            ctx,
+            command,
+            &format!("I couldn't find a grid square for {}.", callsign),
+        )
+        .await;
+    };
+
+    let content = match geo::distance_bearing(&own_grid, &their_grid) {
+        Some((km, bearing)) => format!(
+            "{} ({}) is {:.0} km ({:.0} mi) from you ({}), bearing {:.0}° {}.",
+            callsign,
+            their_grid,
+            km,
+            geo::km_to_miles(km),
+            own_grid,
+            bearing,
+            geo::compass_point(bearing)
+        ),
+        None => format!(
+            "I couldn't work out the distance between {} and {}.",
+            own_grid, their_grid
+        ),
+    };
+    edit_response(ctx, command, &content).await
+}
+
+/// The grid square a callsign is listed with, or QRZ's when the roster has none
+async fn grid(handler: &Handler, guild_id: u64, callsign: &str) -> Option<String> {
+    let listed = handler.rosters.get(guild_id).and_then(|roster| {
+        roster
+            .entries
+            .into_iter()
+            .find(|e| e.callsign == callsign)
+            .and_then(|e| e.grid)
+    });
+    if listed.is_some() {
+        return listed;
+    }
+    let qrz_client = handler.qrz_client.as_ref()?;
+    qrz_client.lookup_callsign(callsign).await.ok()?.grid
+}
@@ -0,0 +1,175 @@
+use super::edit_response;
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption, ResolvedOption,
+    ResolvedValue, RoleId,
+};
+use serenity::prelude::*;
+use tracing::info;
+
+pub fn definition() -> CreateCommand {
+    let year = || {
+        CreateCommandOption::new(
+            CommandOptionType::Integer,
+            "year",
+            "Membership year (default: this year)",
+        )
+        .min_int_value(2000)
+        .max_int_value(2100)
+    };
+    CreateCommand::new("dues")
+        .description("Track membership dues (admin role only)")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "set",
+                "Mark a member's dues paid or unpaid",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::User, "user", "Member").required(true),
+            )
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "paid",
+                "Whether they've paid (default: true)",
+            ))
+            .add_sub_option(year()),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "List members who have paid",
+            )
+            .add_sub_option(year()),
+        )
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/dues must be used in a server")?
+        .get();
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id)
+        .context("This server isn't configured")?;
+
+    if guild_config.dues.is_none() {
+        return edit_response(ctx, command, "Dues tracking isn't enabled for this server.").await;
+    }
+    let Some(admin_role_id) = guild_config.admin_role_id else {
+        return edit_response(
+            ctx,
+            command,
+            "Admin commands aren't configured for this server.",
+        )
+        .await;
+    };
+    let is_admin = command
+        .member
+        .as_ref()
+        .is_some_and(|m| m.roles.contains(&RoleId::new(admin_role_id)));
+    if !is_admin {
+        return edit_response(ctx, command, "Only admins can manage dues.").await;
+    }
+
+    let options = command.data.options();
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(options),
+        ..
+    }) = options.first()
+    else {
+        anyhow::bail!("/dues needs a subcommand");
+    };
+
+    let year = options
+        .iter()
+        .find_map(|option| match option.value {
+            ResolvedValue::Integer(year) if option.name == "year" => i32::try_from(year).ok(),
+            _ => None,
+        })
+        .unwrap_or_else(crate::dues_year);
+
+    let content = match *subcommand {
+        "set" => {
+            let user_id = options
+                .iter()
+                .find_map(|option| match option.value {
+                    ResolvedValue::User(user, _) if option.name == "user" => Some(user.id.get()),
+                    _ => None,
+                })
+                .context("/dues set needs a user")?;
+            let paid = options
+                .iter()
+                .find_map(|option| match option.value {
+                    ResolvedValue::Boolean(paid) if option.name == "paid" => Some(paid),
+                    _ => None,
+                })
+                .unwrap_or(true);
+
+            let changed = handler
+                .storage
+                .set_dues_paid(guild_id, user_id, year, paid)?;
+            let status = if paid { "paid" } else { "unpaid" };
+            if !changed {
+                format!("<@{}> is already {} for {}.", user_id, status, year)
+            } else {
+                info!(
+                    "{} marked user {} {} for {} in guild {}",
+                    command.user.name, user_id, status, year, guild_id
+                );
+                // Only this year's dues show on the roster
+                if year == crate::dues_year() {
+                    handler.enqueue(guild_id, user_id, "dues_change");
+                }
+                format!("Marked <@{}> {} for {}.", user_id, status, year)
+            }
+        }
+        "list" => {
+            let mut paid: Vec<u64> = handler
+                .storage
+                .dues_paid(guild_id, year)
+                .into_iter()
+                .collect();
+            paid.sort_unstable();
+            list(year, &paid, |user_id| {
+                handler.rosters.known_callsign(guild_id, user_id)
+            })
+        }
+        other => anyhow::bail!("Unknown /dues subcommand: {}", other),
+    };
+
+    edit_response(ctx, command, &content).await
+}
+
+/// Paid members with their roster callsign when known
+fn list(year: i32, paid: &[u64], callsign: impl Fn(u64) -> Option<String>) -> String {
+    if paid.is_empty() {
+        return format!("Nobody has paid for {} yet.", year);
+    }
+    let mut lines = vec![format!("**Paid for {}** ({})", year, paid.len())];
+    lines.extend(paid.iter().map(|user_id| match callsign(*user_id) {
+        Some(callsign) => format!("<@{}> ({})", user_id, callsign),
+        None => format!("<@{}>", user_id),
+    }));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list() {
+        assert_eq!(list(2026, &[], |_| None), "Nobody has paid for 2026 yet.");
+        assert_eq!(
+            list(2026, &[1, 2], |id| (id == 1).then(|| "W6JSV".to_string())),
+            "**Paid for 2026** (2)\n<@1> (W6JSV)\n<@2>"
+        );
+    }
+}
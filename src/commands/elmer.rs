@@ -0,0 +1,144 @@
+use super::edit_response;
+use crate::elmers::{self, Mentor};
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption, ResolvedOption,
+    ResolvedValue,
+};
+use serenity::prelude::*;
+use tracing::info;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("elmer")
+        .description("Find a mentor, or offer to be one")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "register",
+                "Offer to mentor new hams, replacing any topics you gave before",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "topics",
+                    "Up to 10, comma-separated, e.g. \"antennas, HF, FT8\"",
+                )
+                .required(true),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "find",
+                "Find members who mentor a topic",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "topic", "e.g. \"antennas\"")
+                    .required(true),
+            ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "unregister",
+            "Stop being listed as a mentor",
+        ))
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/elmer must be used in a server")?
+        .get();
+    let user_id = command.user.id.get();
+
+    let options = command.data.options();
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(options),
+        ..
+    }) = options.first()
+    else {
+        anyhow::bail!("/elmer needs a subcommand");
+    };
+    let text = |name: &str| {
+        options.iter().find_map(|option| match option.value {
+            ResolvedValue::String(value) if option.name == name => Some(value),
+            _ => None,
+        })
+    };
+
+    let content = match *subcommand {
+        "register" => {
+            let topics = elmers::parse_topics(text("topics").unwrap_or_default());
+            if topics.is_empty() {
+                return edit_response(ctx, command, "Give at least one topic, e.g. `antennas`.")
+                    .await;
+            }
+            // Mentors are introduced by callsign, so they need to be on the roster
+            let Some(callsign) = handler.rosters.known_callsign(guild_id, user_id) else {
+                return edit_response(
+                    ctx,
+                    command,
+                    "Put your callsign in your server nickname first, so new hams know who you are.",
+                )
+                .await;
+            };
+            let topics_text = topics.iter().cloned().collect::<Vec<_>>().join(", ");
+            handler.storage.register_elmer(guild_id, user_id, topics)?;
+            info!(
+                "{} ({}) registered as an elmer in guild {}: {}",
+                command.user.name, callsign, guild_id, topics_text
+            );
+            format!(
+                "Thanks, {}! You're listed as an elmer for: {}.",
+                callsign, topics_text
+            )
+        }
+        "find" => {
+            let search = text("topic").context("/elmer find needs a topic")?;
+            let registered = handler.storage.elmers(guild_id);
+            let roster = handler.rosters.get(guild_id);
+            let mentors: Vec<Mentor> = elmers::find(&registered, search)
+                .into_iter()
+                .map(|(user_id, topics)| {
+                    let listing =
+                        handler
+                            .rosters
+                            .known_callsign(guild_id, user_id)
+                            .map(|callsign| {
+                                let name = roster
+                                    .iter()
+                                    .flat_map(|roster| &roster.entries)
+                                    .find(|entry| entry.callsign == callsign)
+                                    .map(|entry| entry.name.clone())
+                                    .unwrap_or_default();
+                                (callsign, name)
+                            });
+                    Mentor {
+                        user_id,
+                        listing,
+                        topics,
+                    }
+                })
+                .collect();
+            elmers::list(search, &mentors, &elmers::all_topics(&registered))
+        }
+        "unregister" => {
+            if handler.storage.unregister_elmer(guild_id, user_id)? {
+                info!(
+                    "{} unregistered as an elmer in guild {}",
+                    command.user.name, guild_id
+                );
+                "You're no longer listed as an elmer.".to_string()
+            } else {
+                "You weren't listed as an elmer.".to_string()
+            }
+        }
+        other => anyhow::bail!("Unknown /elmer subcommand: {}", other),
+    };
+
+    edit_response(ctx, command, &content).await
+}
@@ -0,0 +1,195 @@
+use super::edit_response;
+use crate::events::{self, ClubEvent};
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use chrono::Utc;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption, ResolvedOption,
+    ResolvedValue, RoleId,
+};
+use serenity::prelude::*;
+use tracing::info;
+
+/// Length of an event when `/event create` doesn't give one
+const DEFAULT_DURATION_MINS: u32 = 60;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("event")
+        .description("Schedule recurring nets and meetings with reminders")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "create",
+                "Create a recurring event",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "name", "Name of the event")
+                    .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "cron",
+                    "When it starts, as a UTC cron expression, e.g. \"0 3 * * WED\"",
+                )
+                .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "duration",
+                    "Length in minutes (default: 60)",
+                )
+                .min_int_value(1)
+                .max_int_value(24 * 60),
+            )
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "location",
+                "Where, e.g. a repeater frequency or address",
+            ))
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "description",
+                "Details for the reminder",
+            )),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "list",
+            "List scheduled events",
+        ))
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "delete", "Delete an event")
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "id",
+                        "Event ID from /event list",
+                    )
+                    .required(true),
+                ),
+        )
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/event must be used in a server")?
+        .get();
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id)
+        .context("This server isn't configured")?;
+    let Some(events_config) = &guild_config.events else {
+        return edit_response(ctx, command, "Events aren't configured for this server.").await;
+    };
+
+    let options = command.data.options();
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(options),
+        ..
+    }) = options.first()
+    else {
+        anyhow::bail!("/event needs a subcommand");
+    };
+    let text = |name: &str| {
+        options.iter().find_map(|option| match option.value {
+            ResolvedValue::String(value) if option.name == name => {
+                Some(value.trim()).filter(|v| !v.is_empty())
+            }
+            _ => None,
+        })
+    };
+    let integer = |name: &str| {
+        options.iter().find_map(|option| match option.value {
+            ResolvedValue::Integer(value) if option.name == name => Some(value),
+            _ => None,
+        })
+    };
+
+    if *subcommand == "list" {
+        let content = events::list(&handler.storage.events(guild_id), Utc::now());
+        return edit_response(ctx, command, &content).await;
+    }
+
+    let manager_role_id = events_config.manager_role_id.or(guild_config.admin_role_id);
+    let is_manager = manager_role_id.is_none_or(|role_id| {
+        command
+            .member
+            .as_ref()
+            .is_some_and(|m| m.roles.contains(&RoleId::new(role_id)))
+    });
+    if !is_manager {
+        return edit_response(ctx, command, "Only event managers can change events.").await;
+    }
+
+    let content = match *subcommand {
+        "create" => {
+            let name = text("name").context("/event create needs a name")?;
+            let cron = text("cron").context("/event create needs a cron expression")?;
+            let duration = integer("duration")
+                .and_then(|d| u32::try_from(d).ok())
+                .unwrap_or(DEFAULT_DURATION_MINS);
+            let event = match ClubEvent::new(
+                name,
+                cron,
+                duration,
+                text("description"),
+                text("location"),
+                command.user.id.get(),
+            ) {
+                Ok(event) => event,
+                Err(_) => {
+                    return edit_response(
+                        ctx,
+                        command,
+                        &format!(
+                            "`{}` isn't a cron expression. Use five fields in UTC: minute, hour, day of month, month, and day of week, e.g. `0 3 * * WED`.",
+                            cron
+                        ),
+                    )
+                    .await;
+                }
+            };
+            let Ok(next) = event.next_start(Utc::now()) else {
+                return edit_response(ctx, command, &format!("`{}` never comes around.", cron))
+                    .await;
+            };
+            let event = handler.storage.add_event(guild_id, event)?;
+            info!(
+                "{} created event {} ({}) in guild {}",
+                command.user.name, event.name, event.cron, guild_id
+            );
+            format!(
+                "Created event `{}` **{}**, next <t:{}:F>. Reminders post to <#{}> {} minutes before.",
+                event.id,
+                event.name,
+                next.timestamp(),
+                events_config.channel_id,
+                events_config.remind_mins
+            )
+        }
+        "delete" => {
+            let id = integer("id")
+                .and_then(|id| u32::try_from(id).ok())
+                .context("/event delete needs an ID")?;
+            match handler.storage.remove_event(guild_id, id)? {
+                Some(event) => {
+                    info!(
+                        "{} deleted event {} in guild {}",
+                        command.user.name, event.name, guild_id
+                    );
+                    format!("Deleted event `{}` **{}**.", event.id, event.name)
+                }
+                None => format!("No event has ID `{}`.", id),
+            }
+        }
+        other => anyhow::bail!("Unknown /event subcommand: {}", other),
+    };
+
+    edit_response(ctx, command, &content).await
+}
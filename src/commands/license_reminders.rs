@@ -0,0 +1,81 @@
+use super::edit_response;
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption, ResolvedValue,
+};
+use serenity::prelude::*;
+use tracing::info;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("license-reminders")
+        .description("Get a DM before your license expires")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "enabled",
+                "Whether to DM you before your license expires",
+            )
+            .required(true),
+        )
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/license-reminders must be used in a server")?;
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id.get())
+        .context("This server isn't configured")?;
+
+    let Some(dms) = &guild_config.license_dms else {
+        return edit_response(
+            ctx,
+            command,
+            "License reminders aren't configured for this server.",
+        )
+        .await;
+    };
+
+    let enabled = command
+        .data
+        .options()
+        .iter()
+        .find_map(|option| match option.value {
+            ResolvedValue::Boolean(value) if option.name == "enabled" => Some(value),
+            _ => None,
+        })
+        .context("/license-reminders needs enabled")?;
+
+    handler
+        .storage
+        .set_license_dm_opt_in(guild_id.get(), command.user.id.get(), enabled)?;
+    info!(
+        "{} turned license reminders {} in guild {}",
+        command.user.name,
+        if enabled { "on" } else { "off" },
+        guild_id
+    );
+
+    let content = if !enabled {
+        "You won't get license reminders.".to_string()
+    } else if handler
+        .rosters
+        .known_callsign(guild_id.get(), command.user.id.get())
+        .is_none()
+    {
+        format!(
+            "You'll get a DM {} days before your license expires, once your callsign is on the roster.",
+            dms.days_before
+        )
+    } else {
+        format!(
+            "You'll get a DM {} days before your license expires.",
+            dms.days_before
+        )
+    };
+    edit_response(ctx, command, &content).await
+}
@@ -0,0 +1,186 @@
+mod apply;
+mod aprs;
+mod audit;
+mod awake;
+mod bio;
+mod cw;
+mod distance;
+mod dues;
+mod elmer;
+mod event;
+mod license_reminders;
+mod net;
+mod nickname_preview;
+mod overrides;
+mod privacy;
+mod qsl;
+mod quiz;
+mod repeaters;
+mod roster;
+mod solar;
+mod stats;
+mod suffix;
+mod suggest_overrides;
+mod swap;
+
+use crate::Handler;
+use anyhow::Result;
+use serenity::all::{
+    CommandInteraction, ComponentInteraction, CreateCommand, EditInteractionResponse,
+    ModalInteraction,
+};
+use serenity::prelude::*;
+use tracing::{error, warn};
+
+pub use crate::output::truncate_message;
+
+/// Slash commands registered in every configured guild
+pub fn definitions() -> Vec<CreateCommand> {
+    vec![
+        apply::definition(),
+        aprs::definition(),
+        audit::definition(),
+        awake::definition(),
+        bio::definition(),
+        cw::definition(),
+        distance::definition(),
+        dues::definition(),
+        elmer::definition(),
+        event::definition(),
+        license_reminders::definition(),
+        net::checkin_definition(),
+        net::definition(),
+        nickname_preview::definition(),
+        overrides::definition(),
+        privacy::definition(),
+        qsl::definition(),
+        quiz::definition(),
+        repeaters::definition(),
+        roster::definition(),
+        solar::definition(),
+        stats::definition(),
+        suffix::definition(),
+        suggest_overrides::definition(),
+        swap::definition(),
+    ]
+}
+
+/// Dispatch a slash command to its implementation
+pub async fn handle(handler: &Handler, ctx: &Context, command: &CommandInteraction) {
+    let result = match command.data.name.as_str() {
+        "apply" => apply::run(handler, ctx, command).await,
+        "aprs" => aprs::run(handler, ctx, command).await,
+        "audit" => audit::run(handler, ctx, command).await,
+        "awake" => awake::run(handler, ctx, command).await,
+        "bio" => bio::run(handler, ctx, command).await,
+        "cw" => cw::run(handler, ctx, command).await,
+        "checkin" => net::checkin(handler, ctx, command).await,
+        "distance" => distance::run(handler, ctx, command).await,
+        "dues" => dues::run(handler, ctx, command).await,
+        "elmer" => elmer::run(handler, ctx, command).await,
+        "event" => event::run(handler, ctx, command).await,
+        "license-reminders" => license_reminders::run(handler, ctx, command).await,
+        "net" => net::run(handler, ctx, command).await,
+        "nickname-preview" => nickname_preview::run(handler, ctx, command).await,
+        "override" => overrides::run(handler, ctx, command).await,
+        "privacy" => privacy::run(handler, ctx, command).await,
+        "qsl" => qsl::run(handler, ctx, command).await,
+        "quiz" => quiz::run(handler, ctx, command).await,
+        "repeaters" => repeaters::run(handler, ctx, command).await,
+        "roster" => roster::run(handler, ctx, command).await,
+        "solar" => solar::run(handler, ctx, command).await,
+        "stats" => stats::run(handler, ctx, command).await,
+        "suffix" => suffix::run(handler, ctx, command).await,
+        "suggest-overrides" => suggest_overrides::run(handler, ctx, command).await,
+        "swap" => swap::run(handler, ctx, command).await,
+        other => {
+            warn!("Received unknown command: {}", other);
+            return;
+        }
+    };
+
+    if let Err(e) = result {
+        error!("Command /{} failed: {:?}", command.data.name, e);
+        let _ = edit_response(
+            ctx,
+            command,
+            "Sorry, something went wrong running that command.",
+        )
+        .await;
+    }
+}
+
+/// Dispatch a message component (button, select menu) by its custom ID
+pub async fn handle_component(handler: &Handler, ctx: &Context, component: &ComponentInteraction) {
+    let result = match component.data.custom_id.as_str() {
+        suffix::CHOOSE_MENU_ID => suffix::choose(handler, ctx, component).await,
+        suggest_overrides::APPROVE_MENU_ID => {
+            suggest_overrides::approve(handler, ctx, component).await
+        }
+        id if id.starts_with(crate::quiz::ANSWER_PREFIX) => {
+            quiz::answer(handler, ctx, component).await
+        }
+        id if id.starts_with(crate::quiz::NEXT_PREFIX) => quiz::next(handler, ctx, component).await,
+        id if id.starts_with(crate::applications::APPROVE_PREFIX)
+            || id.starts_with(crate::applications::DENY_PREFIX) =>
+        {
+            apply::review(handler, ctx, component).await
+        }
+        id if id.starts_with(crate::nickname::APPLY_PREFIX) => {
+            audit::apply(handler, ctx, component).await
+        }
+        other => {
+            warn!("Received unknown component: {}", other);
+            return;
+        }
+    };
+
+    if let Err(e) = result {
+        error!("Component {} failed: {:?}", component.data.custom_id, e);
+        let _ = component
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content("Sorry, something went wrong handling that.")
+                    .components(vec![]),
+            )
+            .await;
+    }
+}
+
+/// Dispatch a submitted modal by its custom ID
+pub async fn handle_modal(handler: &Handler, ctx: &Context, modal: &ModalInteraction) {
+    let result = match modal.data.custom_id.as_str() {
+        crate::applications::MODAL_ID => apply::submit(handler, ctx, modal).await,
+        other => {
+            warn!("Received unknown modal: {}", other);
+            return;
+        }
+    };
+
+    if let Err(e) = result {
+        error!("Modal {} failed: {:?}", modal.data.custom_id, e);
+        let _ = modal
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content("Sorry, something went wrong handling that."),
+            )
+            .await;
+    }
+}
+
+/// Replace a deferred response's content, truncated to Discord's message limit
+pub async fn edit_response(
+    ctx: &Context,
+    command: &CommandInteraction,
+    content: &str,
+) -> Result<()> {
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content(truncate_message(content)),
+        )
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,423 @@
+use super::edit_response;
+use crate::config::{GuildConfig, NetsConfig};
+use crate::nets::{self, CheckIn, Net, NetRecord};
+use crate::qrz::QrzClient;
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use chrono::Utc;
+use serenity::all::{
+    AutoArchiveDuration, ChannelId, ChannelType, CommandInteraction, CommandOptionType,
+    CreateAllowedMentions, CreateCommand, CreateCommandOption, CreateMessage, CreateThread,
+    EditThread, ResolvedOption, ResolvedValue, RoleId,
+};
+use serenity::prelude::*;
+use std::path::Path;
+use tracing::{info, warn};
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("net")
+        .description("Run a net or show attendance")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "start", "Start a net")
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "name", "Name of the net")
+                        .required(true),
+                ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "end",
+            "End the net and write its log",
+        ))
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "stats",
+                "Show check-in counts and streaks",
+            )
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "callsign",
+                "Station to show; defaults to the leaderboard",
+            )),
+        )
+}
+
+/// Stations listed by `/net stats` without a callsign
+const STATS_SIZE: usize = 10;
+
+pub fn checkin_definition() -> CreateCommand {
+    CreateCommand::new("checkin")
+        .description("Check in to the net in progress")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "callsign",
+            "Station to check in; defaults to you",
+        ))
+}
+
+/// The guild's net settings, or `None` after telling the user nets aren't set up
+async fn nets_config(
+    handler: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<Option<(GuildConfig, NetsConfig)>> {
+    let guild_id = command.guild_id.context("Nets must be run in a server")?;
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id.get())
+        .context("This server isn't configured")?;
+    let Some(nets_config) = guild_config.nets.clone() else {
+        edit_response(ctx, command, "Nets aren't configured for this server.").await?;
+        return Ok(None);
+    };
+    Ok(Some((guild_config.clone(), nets_config)))
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer(&ctx.http).await?;
+
+    let Some((guild_config, nets_config)) = nets_config(handler, ctx, command).await? else {
+        return Ok(());
+    };
+    let guild_id = guild_config.guild_id;
+
+    let options = command.data.options();
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(options),
+        ..
+    }) = options.first()
+    else {
+        anyhow::bail!("/net needs a subcommand");
+    };
+    let text = |name: &str| {
+        options.iter().find_map(|option| match option.value {
+            ResolvedValue::String(value) if option.name == name => {
+                Some(value.trim()).filter(|v| !v.is_empty())
+            }
+            _ => None,
+        })
+    };
+
+    if *subcommand == "stats" {
+        let history = handler.storage.net_history(guild_id);
+        let content = match text("callsign") {
+            Some(callsign) => nets::station_stats(&history, callsign),
+            None => nets::leaderboard(&history, STATS_SIZE),
+        };
+        return edit_response(ctx, command, &content).await;
+    }
+
+    let control_role_id = nets_config.control_role_id.or(guild_config.admin_role_id);
+    let is_control = control_role_id.is_none_or(|role_id| {
+        command
+            .member
+            .as_ref()
+            .is_some_and(|m| m.roles.contains(&RoleId::new(role_id)))
+    });
+    if !is_control {
+        return edit_response(ctx, command, "Only net control can start or end nets.").await;
+    }
+
+    let content = match *subcommand {
+        "start" => {
+            let name = text("name").context("/net start needs a name")?;
+            let net = Net::new(name, command.user.id.get(), Utc::now());
+            let started = handler.storage.update_active_net(guild_id, |active| {
+                if let Some(active) = active {
+                    return Err(active.name.clone());
+                }
+                *active = Some(net.clone());
+                Ok(())
+            })?;
+            match started {
+                Ok(()) => {
+                    info!(
+                        "{} started net {:?} in guild {}",
+                        command.user.name, net.name, guild_id
+                    );
+                    let from_voice = handler.check_in_voice_channel(ctx, &guild_config).await;
+                    let mut content = format!(
+                        "📻 **{}** is now running. Check in with `/checkin`.",
+                        net.name
+                    );
+                    if from_voice > 0 {
+                        content.push_str(&format!(
+                            " Checked in {} from the voice channel.",
+                            from_voice
+                        ));
+                    }
+                    if let Some(channel_id) = nets_config.thread_channel_id {
+                        match open_thread(handler, ctx, guild_id, channel_id, &net).await {
+                            Ok(thread_id) => content
+                                .push_str(&format!(" The roster is posted in <#{}>.", thread_id)),
+                            Err(e) => warn!(
+                                "Failed to open a thread for net {:?} in guild {}: {:?}",
+                                net.name, guild_id, e
+                            ),
+                        }
+                    }
+                    content
+                }
+                Err(active) => format!("**{}** is already running; end it first.", active),
+            }
+        }
+        "end" => {
+            let Some(net) = handler.storage.update_active_net(guild_id, Option::take)? else {
+                return edit_response(ctx, command, "No net is running.").await;
+            };
+            handler
+                .storage
+                .record_net(guild_id, NetRecord::from(&net))?;
+            let paths =
+                nets::write_logs(Path::new(&nets_config.log_dir), guild_id, &net, Utc::now())?;
+            info!(
+                "{} ended net {:?} in guild {} with {} check-ins; logs: {:?}",
+                command.user.name,
+                net.name,
+                guild_id,
+                net.checkins.len(),
+                paths
+            );
+            if let Some(thread_id) = net.thread_id {
+                close_thread(ctx, thread_id, &net).await;
+            }
+            summary(&net)
+        }
+        other => anyhow::bail!("Unknown /net subcommand {}", other),
+    };
+
+    edit_response(ctx, command, &content).await
+}
+
+pub async fn checkin(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer(&ctx.http).await?;
+
+    let Some((guild_config, _)) = nets_config(handler, ctx, command).await? else {
+        return Ok(());
+    };
+    let guild_id = guild_config.guild_id;
+
+    if handler.storage.active_net(guild_id).is_none() {
+        return edit_response(
+            ctx,
+            command,
+            "No net is running. Net control can start one with `/net start`.",
+        )
+        .await;
+    }
+
+    let callsign = command
+        .data
+        .options()
+        .iter()
+        .find_map(|option| match option.value {
+            ResolvedValue::String(value) if option.name == "callsign" => {
+                Some(value.trim().to_string()).filter(|v| !v.is_empty())
+            }
+            _ => None,
+        });
+
+    let checkin = match callsign {
+        Some(callsign) => match station_checkin(handler, &callsign).await {
+            Some(checkin) => checkin,
+            None => {
+                return edit_response(
+                    ctx,
+                    command,
+                    &format!("{} doesn't look like a callsign.", callsign),
+                )
+                .await;
+            }
+        },
+        None => {
+            let checkin = match command.member.as_deref() {
+                Some(member) => {
+                    let member = crate::discord::member(member);
+                    handler.member_checkin(&guild_config, &member, false).await
+                }
+                None => None,
+            };
+            match checkin {
+                Some(checkin) => checkin,
+                None => {
+                    return edit_response(
+                        ctx,
+                        command,
+                        "I couldn't find a callsign in your name; try `/checkin callsign:<yours>`.",
+                    )
+                    .await;
+                }
+            }
+        }
+    };
+
+    let label = if checkin.name.is_empty() {
+        checkin.callsign.clone()
+    } else {
+        format!("{} ({})", checkin.callsign, checkin.name)
+    };
+    let added = handler.storage.update_active_net(guild_id, |net| {
+        net.as_mut()
+            .map(|net| (net.check_in(checkin), net.name.clone(), net.checkins.len()))
+    })?;
+
+    let content = match added {
+        Some((true, net_name, count)) => {
+            format!("✅ {} checked in to **{}** (#{})", label, net_name, count)
+        }
+        Some((false, net_name, _)) => {
+            format!("{} is already checked in to **{}**.", label, net_name)
+        }
+        // The net ended between the check above and now
+        None => "No net is running.".to_string(),
+    };
+    edit_response(ctx, command, &content).await
+}
+
+/// Check in a station by callsign, naming it from QRZ when available
+async fn station_checkin(handler: &Handler, callsign: &str) -> Option<CheckIn> {
+    if !handler.parser.is_callsign(callsign) {
+        return None;
+    }
+    let info = handler.parser.parse(callsign)?;
+    let name = match &handler.qrz_client {
+        Some(qrz_client) => match qrz_client.lookup_callsign(&info.callsign).await {
+            Ok(qrz_info) => QrzClient::get_display_name(&qrz_info).unwrap_or_default(),
+            Err(e) => {
+                warn!("QRZ lookup for check-in {} failed: {:?}", info.callsign, e);
+                String::new()
+            }
+        },
+        None => String::new(),
+    };
+    Some(CheckIn {
+        callsign: info.callsign,
+        name: if name.is_empty() { info.name } else { name },
+        user_id: None,
+        at: Utc::now(),
+        voice: false,
+    })
+}
+
+/// Open a thread for a net that just started, post the roster for check-in reference,
+/// and remember the thread so `/net end` can archive it
+async fn open_thread(
+    handler: &Handler,
+    ctx: &Context,
+    guild_id: u64,
+    channel_id: u64,
+    net: &Net,
+) -> Result<u64> {
+    let thread = ChannelId::new(channel_id)
+        .create_thread(
+            &ctx.http,
+            CreateThread::new(nets::thread_name(net))
+                .kind(ChannelType::PublicThread)
+                .auto_archive_duration(AutoArchiveDuration::OneDay),
+        )
+        .await?;
+    handler.storage.update_active_net(guild_id, |active| {
+        if let Some(active) = active {
+            active.thread_id = Some(thread.id.get());
+        }
+    })?;
+
+    let stations: Vec<(String, String)> = handler
+        .rosters
+        .get(guild_id)
+        .map(|roster| {
+            roster
+                .entries
+                .into_iter()
+                .map(|entry| (entry.callsign, entry.name))
+                .collect()
+        })
+        .unwrap_or_default();
+    for content in nets::roster_messages(&stations) {
+        thread
+            .id
+            .send_message(
+                &ctx.http,
+                CreateMessage::new()
+                    .content(content)
+                    .allowed_mentions(CreateAllowedMentions::new()),
+            )
+            .await?;
+    }
+    Ok(thread.id.get())
+}
+
+/// Post the summary in an ended net's thread and archive it
+async fn close_thread(ctx: &Context, thread_id: u64, net: &Net) {
+    let thread = ChannelId::new(thread_id);
+    if let Err(e) = thread
+        .send_message(
+            &ctx.http,
+            CreateMessage::new().content(super::truncate_message(&summary(net))),
+        )
+        .await
+    {
+        warn!(
+            "Failed to post the summary to net thread {}: {}",
+            thread_id, e
+        );
+    }
+    // It may have been deleted, or archived by hand
+    if let Err(e) = thread
+        .edit_thread(&ctx.http, EditThread::new().archived(true))
+        .await
+    {
+        warn!("Failed to archive net thread {}: {}", thread_id, e);
+    }
+}
+
+/// Reply posted when a net ends
+fn summary(net: &Net) -> String {
+    if net.checkins.is_empty() {
+        return format!("📻 **{}** has ended with no check-ins.", net.name);
+    }
+    let callsigns = net
+        .checkins
+        .iter()
+        .map(|c| c.callsign.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "📻 **{}** has ended with {} check-in{}: {}",
+        net.name,
+        net.checkins.len(),
+        if net.checkins.len() == 1 { "" } else { "s" },
+        callsigns
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let started_at = chrono::DateTime::from_timestamp(1_760_000_000, 0).unwrap();
+        let mut net = Net::new("Sunday Net", 1, started_at);
+        assert_eq!(
+            summary(&net),
+            "📻 **Sunday Net** has ended with no check-ins."
+        );
+
+        for callsign in ["W6JSV", "KI7QCF"] {
+            net.check_in(CheckIn {
+                callsign: callsign.to_string(),
+                name: String::new(),
+                user_id: None,
+                at: started_at,
+                voice: false,
+            });
+        }
+        assert_eq!(
+            summary(&net),
+            "📻 **Sunday Net** has ended with 2 check-ins: W6JSV, KI7QCF"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use super::edit_response;
+use crate::discord::DiscordSource;
+use crate::nickname;
+use crate::pipeline::{Generation, Pipeline};
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use serenity::all::{CommandInteraction, CreateCommand, Permissions};
+use serenity::prelude::*;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("nickname-preview")
+        .description("Show which nicknames the canonical format would change")
+        .default_member_permissions(Permissions::MANAGE_NICKNAMES)
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/nickname-preview must be used in a server")?;
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id.get())
+        .context("This server isn't configured")?;
+    let Some(nickname_config) = &guild_config.nicknames else {
+        return edit_response(
+            ctx,
+            command,
+            "Nickname normalization isn't configured for this server.",
+        )
+        .await;
+    };
+
+    // Build the roster exactly as a regeneration would, so the preview matches enforcement
+    let guild_config = &handler.with_all_overrides(guild_config);
+    let pipeline = Pipeline {
+        source: &DiscordSource {
+            http: &ctx.http,
+            limits: &handler.rest_limits,
+        },
+        builder: handler.roster_builder(),
+    };
+    let Generation {
+        members, roster, ..
+    } = pipeline
+        .generate(
+            guild_config,
+            Some(crate::bot_user_id(ctx).get()),
+            handler.storage.suffixes(guild_id.get()),
+            handler.storage.departed(guild_id.get()),
+            Some(
+                handler
+                    .storage
+                    .dues_paid(guild_id.get(), crate::dues_year()),
+            ),
+        )
+        .await?;
+
+    let changes = nickname::plan(nickname_config, &members, &roster.by_user);
+    if changes.is_empty() {
+        return edit_response(
+            ctx,
+            command,
+            &format!(
+                "Every listed member already matches `{}`.",
+                nickname_config.format
+            ),
+        )
+        .await;
+    }
+
+    let mut content = format!(
+        "**{} nicknames would change** to `{}` ({}):",
+        changes.len(),
+        nickname_config.format,
+        if nickname_config.enforce {
+            "enforced on every regeneration"
+        } else {
+            "preview only; set `enforce = true` to apply"
+        }
+    );
+    for change in &changes {
+        content.push_str(&format!("\n{} → {}", change.current, change.desired));
+    }
+
+    edit_response(ctx, command, &super::truncate_message(&content)).await
+}
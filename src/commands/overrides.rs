@@ -0,0 +1,347 @@
+use super::edit_response;
+use crate::config::{GuildConfig, Override};
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption, ResolvedOption,
+    ResolvedValue, RoleId,
+};
+use serenity::prelude::*;
+use std::collections::HashMap;
+use tracing::info;
+
+pub fn definition() -> CreateCommand {
+    let user = || {
+        CreateCommandOption::new(CommandOptionType::User, "user", "Member to override")
+            .required(true)
+    };
+    CreateCommand::new("override")
+        .description("Manage roster overrides (admin role only)")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "set",
+                "Set a member's callsign, name, or suffix",
+            )
+            .add_sub_option(user())
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "callsign",
+                "Callsign to list",
+            ))
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "name",
+                "Name to list",
+            ))
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "suffix",
+                "Suffix to show",
+            )),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "remove",
+                "Remove a member's override",
+            )
+            .add_sub_option(user()),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "list",
+            "List this server's overrides",
+        ))
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/override must be used in a server")?;
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id.get())
+        .context("This server isn't configured")?;
+
+    let Some(admin_role_id) = guild_config.admin_role_id else {
+        return edit_response(
+            ctx,
+            command,
+            "Admin commands aren't configured for this server.",
+        )
+        .await;
+    };
+    let is_admin = command
+        .member
+        .as_ref()
+        .is_some_and(|m| m.roles.contains(&RoleId::new(admin_role_id)));
+    if !is_admin {
+        return edit_response(ctx, command, "Only admins can manage overrides.").await;
+    }
+
+    let options = command.data.options();
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(options),
+        ..
+    }) = options.first()
+    else {
+        anyhow::bail!("/override needs a subcommand");
+    };
+
+    let user_id = options.iter().find_map(|option| match option.value {
+        ResolvedValue::User(user, _) if option.name == "user" => Some(user.id.get()),
+        _ => None,
+    });
+    let text = |name: &str| {
+        options.iter().find_map(|option| match option.value {
+            ResolvedValue::String(value) if option.name == name => {
+                Some(value.trim().to_string()).filter(|v| !v.is_empty())
+            }
+            _ => None,
+        })
+    };
+
+    let content = match *subcommand {
+        "set" => {
+            let user_id = user_id.context("/override set needs a user")?;
+            let callsign = text("callsign").map(|c| c.to_uppercase());
+            if let Some(callsign) = &callsign {
+                if !handler.parser.is_callsign(callsign) {
+                    return edit_response(
+                        ctx,
+                        command,
+                        &format!("{} doesn't look like a callsign.", callsign),
+                    )
+                    .await;
+                }
+            }
+            let (name, suffix) = (text("name"), text("suffix"));
+            if callsign.is_none() && name.is_none() && suffix.is_none() {
+                return edit_response(
+                    ctx,
+                    command,
+                    "Give at least one of callsign, name, or suffix.",
+                )
+                .await;
+            }
+
+            let existing = handler
+                .storage
+                .member_overrides(guild_id.get())
+                .remove(&user_id.to_string());
+            let updated = merge(existing, callsign, name, suffix);
+            handler
+                .storage
+                .set_member_override(guild_id.get(), user_id, updated.clone())?;
+            info!(
+                "{} set override for user {} in guild {}: {:?}",
+                command.user.name, user_id, guild_id, updated
+            );
+            handler.enqueue(guild_id.get(), user_id, "override_change");
+            let mut content = format!(
+                "Set <@{}> to {}. The roster will update shortly.",
+                user_id,
+                describe(&updated)
+            );
+            let shadowed = shadowed(guild_config.overrides.get(&user_id.to_string()), &updated);
+            if !shadowed.is_empty() {
+                content.push_str(&format!(
+                    " The config file's override for them still sets {}, which takes precedence.",
+                    shadowed.join(", ")
+                ));
+            }
+            content
+        }
+        "remove" => {
+            let user_id = user_id.context("/override remove needs a user")?;
+            if !handler
+                .storage
+                .remove_member_override(guild_id.get(), user_id)?
+            {
+                return edit_response(
+                    ctx,
+                    command,
+                    &format!("<@{}> has no override set by command.", user_id),
+                )
+                .await;
+            }
+            info!(
+                "{} removed override for user {} in guild {}",
+                command.user.name, user_id, guild_id
+            );
+            handler.enqueue(guild_id.get(), user_id, "override_change");
+            format!(
+                "Removed <@{}>'s override. The roster will update shortly.",
+                user_id
+            )
+        }
+        "list" => list(
+            guild_config,
+            &handler.storage.member_overrides(guild_id.get()),
+        ),
+        other => anyhow::bail!("Unknown /override subcommand: {}", other),
+    };
+
+    edit_response(ctx, command, &content).await
+}
+
+/// Apply the given fields on top of a member's stored override, keeping the rest
+fn merge(
+    existing: Option<Override>,
+    callsign: Option<String>,
+    name: Option<String>,
+    suffix: Option<String>,
+) -> Override {
+    let existing = existing.unwrap_or(Override {
+        callsign: None,
+        name: None,
+        suffix: None,
+        emoji: None,
+        skip_qrz: false,
+        hide_location: false,
+    });
+    Override {
+        callsign: callsign.or(existing.callsign),
+        name: name.or(existing.name),
+        suffix: suffix.or(existing.suffix),
+        emoji: existing.emoji,
+        skip_qrz: existing.skip_qrz,
+        hide_location: existing.hide_location,
+    }
+}
+
+/// The fields of a stored override that a config file override for the same member hides
+fn shadowed(configured: Option<&Override>, stored: &Override) -> Vec<&'static str> {
+    let Some(configured) = configured else {
+        return Vec::new();
+    };
+    [
+        ("callsign", &configured.callsign, &stored.callsign),
+        ("name", &configured.name, &stored.name),
+        ("suffix", &configured.suffix, &stored.suffix),
+    ]
+    .into_iter()
+    .filter(|(_, configured, stored)| configured.is_some() && stored.is_some())
+    .map(|(label, _, _)| label)
+    .collect()
+}
+
+fn describe(o: &Override) -> String {
+    let fields: Vec<String> = [
+        ("callsign", &o.callsign),
+        ("name", &o.name),
+        ("suffix", &o.suffix),
+        ("emoji", &o.emoji),
+    ]
+    .into_iter()
+    .filter_map(|(label, value)| value.as_ref().map(|v| format!("{} `{}`", label, v)))
+    .collect();
+    fields.join(", ")
+}
+
+/// Stored overrides first, then config file ones, which can only be changed in the config
+fn list(guild_config: &GuildConfig, stored: &HashMap<String, Override>) -> String {
+    if stored.is_empty() && guild_config.overrides.is_empty() {
+        return "No overrides are set.".to_string();
+    }
+
+    let line = |user_id: &String, o: &Override, source: &str| {
+        format!("<@{}>: {}{}", user_id, describe(o), source)
+    };
+    let mut stored: Vec<_> = stored.iter().collect();
+    stored.sort_by(|a, b| a.0.cmp(b.0));
+    let mut configured: Vec<_> = guild_config.overrides.iter().collect();
+    configured.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut lines = vec!["**Overrides**".to_string()];
+    lines.extend(stored.into_iter().map(|(id, o)| line(id, o, "")));
+    lines.extend(
+        configured
+            .into_iter()
+            .map(|(id, o)| line(id, o, " (config)")),
+    );
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(callsign: Option<&str>, name: Option<&str>, suffix: Option<&str>) -> Override {
+        Override {
+            callsign: callsign.map(str::to_string),
+            name: name.map(str::to_string),
+            suffix: suffix.map(str::to_string),
+            emoji: None,
+            skip_qrz: false,
+            hide_location: false,
+        }
+    }
+
+    #[test]
+    fn test_merge_keeps_unset_fields() {
+        let existing = set(Some("W6JSV"), Some("Jay"), None);
+        let merged = merge(Some(existing), None, None, Some("73".to_string()));
+        assert_eq!(
+            describe(&merged),
+            "callsign `W6JSV`, name `Jay`, suffix `73`"
+        );
+
+        let merged = merge(None, Some("KI7QCF".to_string()), None, None);
+        assert_eq!(describe(&merged), "callsign `KI7QCF`");
+    }
+
+    #[test]
+    fn test_shadowed_by_config() {
+        let stored = set(Some("W6JSV"), Some("Jay"), Some("73"));
+        assert!(shadowed(None, &stored).is_empty());
+        let configured = set(Some("W1AW"), None, Some("QRT"));
+        assert_eq!(
+            shadowed(Some(&configured), &stored),
+            vec!["callsign", "suffix"]
+        );
+        assert!(shadowed(Some(&configured), &set(None, Some("Jay"), None)).is_empty());
+    }
+
+    #[test]
+    fn test_override_or_fills_unset_fields() {
+        let configured = set(Some("W1AW"), None, None);
+        let merged = configured.or(set(Some("W6JSV"), Some("Jay"), Some("73")));
+        assert_eq!(
+            describe(&merged),
+            "callsign `W1AW`, name `Jay`, suffix `73`"
+        );
+    }
+
+    #[test]
+    fn test_list() {
+        let mut guild_config: GuildConfig = toml::from_str(
+            r#"
+            guild_id = 1
+
+            [output]
+            repo = "jsvana/rosters"
+            path = "members.txt"
+            default_suffix = ""
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            list(&guild_config, &HashMap::new()),
+            "No overrides are set."
+        );
+
+        guild_config
+            .overrides
+            .insert("2".to_string(), set(Some("W1AW"), None, None));
+        let stored = HashMap::from([("3".to_string(), set(None, None, Some("QRT")))]);
+        assert_eq!(
+            list(&guild_config, &stored),
+            "**Overrides**\n<@3>: suffix `QRT`\n<@2>: callsign `W1AW` (config)"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use super::edit_response;
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption, ResolvedValue,
+};
+use serenity::prelude::*;
+use tracing::info;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("privacy")
+        .description("Choose whether the roster uses your QRZ.com data and shows your location")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "qrz",
+            "Whether to look you up on QRZ; when off, only your Discord name is used",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "map",
+            "Whether your grid square and location are published, e.g. on the club map",
+        ))
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/privacy must be used in a server")?;
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id.get())
+        .context("This server isn't configured")?;
+
+    let option = |name: &str| {
+        command
+            .data
+            .options()
+            .iter()
+            .find_map(|option| match option.value {
+                ResolvedValue::Boolean(value) if option.name == name => Some(value),
+                _ => None,
+            })
+    };
+    let (use_qrz, show_location) = (option("qrz"), option("map"));
+    if use_qrz.is_none() && show_location.is_none() {
+        return edit_response(ctx, command, "Choose qrz, map, or both.").await;
+    }
+
+    let user_id = command.user.id.get();
+    let configured = guild_config.get_override(&user_id.to_string());
+    let mut replies = Vec::new();
+
+    if let Some(use_qrz) = use_qrz {
+        handler
+            .storage
+            .set_qrz_opt_out(guild_id.get(), user_id, !use_qrz)?;
+        info!(
+            "{} turned QRZ lookups {} in guild {}",
+            command.user.name,
+            if use_qrz { "on" } else { "off" },
+            guild_id
+        );
+        replies.push(if !use_qrz {
+            "The roster will use only your Discord name, without QRZ lookups."
+        } else if configured.is_some_and(|o| o.skip_qrz) {
+            "QRZ lookups stay off for you because a server admin set that in the bot's config."
+        } else {
+            "The roster will use your QRZ.com data again."
+        });
+    }
+
+    if let Some(show_location) = show_location {
+        handler
+            .storage
+            .set_map_opt_out(guild_id.get(), user_id, !show_location)?;
+        info!(
+            "{} turned their published location {} in guild {}",
+            command.user.name,
+            if show_location { "on" } else { "off" },
+            guild_id
+        );
+        replies.push(if !show_location {
+            "Your grid square and location will be left off the roster and maps."
+        } else if configured.is_some_and(|o| o.hide_location) {
+            "Your location stays hidden because a server admin set that in the bot's config."
+        } else {
+            "Your grid square and location will be published again."
+        });
+    }
+
+    handler.enqueue(guild_id.get(), user_id, "privacy_change");
+    replies.push("It will update shortly.");
+    edit_response(ctx, command, &replies.join(" ")).await
+}
@@ -0,0 +1,54 @@
+use super::edit_response;
+use crate::Handler;
+use anyhow::Result;
+use chrono::Utc;
+use serenity::all::{CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption};
+use serenity::prelude::*;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("qsl")
+        .description("Whether an operator confirms QSOs on LoTW, and when they last uploaded")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "callsign", "Callsign to look up")
+                .required(true),
+        )
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer(&ctx.http).await?;
+
+    let callsign = command
+        .data
+        .options
+        .iter()
+        .find(|option| option.name == "callsign")
+        .and_then(|option| option.value.as_str())
+        .unwrap_or_default()
+        .trim()
+        .to_uppercase();
+
+    if !handler.parser.is_callsign(&callsign) {
+        return edit_response(ctx, command, "That doesn't look like a callsign.").await;
+    }
+    let Some(lotw) = &handler.lotw else {
+        return edit_response(ctx, command, "LoTW lookups aren't configured.").await;
+    };
+
+    let message = match lotw.current().last_upload(&callsign) {
+        Some(date) => {
+            let days = (Utc::now().date_naive() - date).num_days();
+            format!(
+                "**{}** uses LoTW; last upload {} ({})",
+                callsign,
+                date.format("%Y-%m-%d"),
+                match days {
+                    ..=0 => "today".to_string(),
+                    1 => "yesterday".to_string(),
+                    days => format!("{} days ago", days),
+                }
+            )
+        }
+        None => format!("{} isn't in the LoTW user list.", callsign),
+    };
+    edit_response(ctx, command, &message).await
+}
@@ -0,0 +1,164 @@
+use super::edit_response;
+use crate::quiz::{self, Pool, QuizPools};
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use rand::seq::IndexedRandom;
+use serenity::all::{
+    ButtonStyle, CommandInteraction, CommandOptionType, ComponentInteraction, CreateActionRow,
+    CreateButton, CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    EditInteractionResponse, ResolvedValue,
+};
+use serenity::prelude::*;
+
+pub fn definition() -> CreateCommand {
+    let mut pool =
+        CreateCommandOption::new(CommandOptionType::String, "pool", "Exam element").required(true);
+    for p in Pool::ALL {
+        pool = pool.add_string_choice(p.name(), p.id());
+    }
+    CreateCommand::new("quiz")
+        .description("Practice license exam questions from the NCVEC pools")
+        .add_option(pool)
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+    command.guild_id.context("/quiz must be used in a server")?;
+
+    let Some(pools) = &handler.quiz else {
+        return edit_response(ctx, command, "Exam questions aren't configured.").await;
+    };
+    let pool = command
+        .data
+        .options()
+        .iter()
+        .find_map(|option| match (option.name, &option.value) {
+            ("pool", ResolvedValue::String(pool)) => Pool::from_id(pool),
+            _ => None,
+        })
+        .context("Missing pool")?;
+
+    match ask(pools, pool) {
+        Some(response) => {
+            command.edit_response(&ctx.http, response).await?;
+            Ok(())
+        }
+        None => edit_response(ctx, command, &no_questions(pool)).await,
+    }
+}
+
+/// Grade an answer button, update the member's streak, and offer another question
+pub async fn answer(
+    handler: &Handler,
+    ctx: &Context,
+    component: &ComponentInteraction,
+) -> Result<()> {
+    component
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let guild_id = component
+        .guild_id
+        .context("Quiz answers must be given in a server")?;
+    let pools = handler.quiz.as_ref().context("Quiz isn't configured")?;
+    let (pool, question_id, choice) = quiz::parse_answer_id(&component.data.custom_id)
+        .with_context(|| format!("Malformed quiz answer {}", component.data.custom_id))?;
+    let Some(question) = pools.question(pool, question_id) else {
+        component
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content(format!(
+                        "Question {} is no longer in the pool.",
+                        question_id
+                    ))
+                    .components(vec![next_row(pool)]),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let correct = choice == question.answer;
+    let stats =
+        handler
+            .storage
+            .record_quiz_answer(guild_id.get(), component.user.id.get(), correct)?;
+    let result = if correct {
+        format!("✅ Correct, the answer is {}.", question.answer_letter())
+    } else {
+        format!(
+            "❌ The answer is {}; you chose {}.",
+            question.answer_letter(),
+            quiz::letter(choice)
+        )
+    };
+
+    component
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .content(format!(
+                    "{}\n\n{}\n{}",
+                    question.prompt(pool),
+                    result,
+                    stats.describe()
+                ))
+                .components(vec![next_row(pool)]),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Replace an answered question with a new one from the same pool
+pub async fn next(
+    handler: &Handler,
+    ctx: &Context,
+    component: &ComponentInteraction,
+) -> Result<()> {
+    component
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let pools = handler.quiz.as_ref().context("Quiz isn't configured")?;
+    let pool = component
+        .data
+        .custom_id
+        .strip_prefix(quiz::NEXT_PREFIX)
+        .and_then(Pool::from_id)
+        .with_context(|| format!("Malformed quiz button {}", component.data.custom_id))?;
+
+    let response = ask(pools, pool).unwrap_or_else(|| {
+        EditInteractionResponse::new()
+            .content(no_questions(pool))
+            .components(vec![])
+    });
+    component.edit_response(&ctx.http, response).await?;
+    Ok(())
+}
+
+/// A random question from the pool with a button per choice
+fn ask(pools: &QuizPools, pool: Pool) -> Option<EditInteractionResponse> {
+    let question = pools.questions(pool).choose(&mut rand::rng())?;
+    let buttons = (0..question.choices.len())
+        .map(|choice| {
+            CreateButton::new(quiz::answer_id(pool, question, choice))
+                .label(quiz::letter(choice).to_string())
+                .style(ButtonStyle::Primary)
+        })
+        .collect();
+    Some(
+        EditInteractionResponse::new()
+            .content(question.prompt(pool))
+            .components(vec![CreateActionRow::Buttons(buttons)]),
+    )
+}
+
+fn next_row(pool: Pool) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![CreateButton::new(quiz::next_id(pool))
+        .label("Next question")
+        .style(ButtonStyle::Secondary)])
+}
+
+fn no_questions(pool: Pool) -> String {
+    format!("No {} questions are loaded.", pool.name())
+}
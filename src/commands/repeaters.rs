@@ -0,0 +1,106 @@
+use super::edit_response;
+use crate::repeaters::{self, Repeater};
+use crate::Handler;
+use anyhow::Result;
+use serenity::all::{CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption};
+use serenity::prelude::*;
+
+/// Bands offered by the `band` option; repeaters elsewhere can still be listed without it
+const BANDS: [&str; 5] = ["10m", "6m", "2m", "1.25m", "70cm"];
+
+pub fn definition() -> CreateCommand {
+    let band = BANDS.iter().fold(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "band",
+            "Only list repeaters on this band",
+        ),
+        |option, band| option.add_string_choice(*band, *band),
+    );
+    CreateCommand::new("repeaters")
+        .description("Repeaters near a city, from RepeaterBook")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "location",
+                "City and state or province, e.g. \"San Francisco, CA\"",
+            )
+            .required(true),
+        )
+        .add_option(band)
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer(&ctx.http).await?;
+
+    let option = |name: &str| {
+        command
+            .data
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .and_then(|option| option.value.as_str())
+    };
+    let Some(client) = &handler.repeaterbook_client else {
+        return edit_response(ctx, command, "Repeater lookups aren't configured.").await;
+    };
+    let Some(location) = repeaters::parse_location(option("location").unwrap_or_default()) else {
+        return edit_response(
+            ctx,
+            command,
+            "Give a city and state or province, like \"San Francisco, CA\".",
+        )
+        .await;
+    };
+    let band = option("band");
+    let max_results = handler
+        .config
+        .repeaterbook
+        .as_ref()
+        .map_or(0, |config| config.max_results);
+
+    let mut found: Vec<Repeater> = client
+        .search(&location)
+        .await?
+        .into_iter()
+        .filter(|repeater| repeater.on_air)
+        .filter(|repeater| band.is_none() || repeater.band() == band)
+        .collect();
+    found.sort_by(|a, b| a.frequency.total_cmp(&b.frequency));
+
+    let place = match &location.city {
+        Some(city) => format!("{}, {}", city, location.state),
+        None => location.state.clone(),
+    };
+    let place = match band {
+        Some(band) => format!("{} on {}", place, band),
+        None => place,
+    };
+    if found.is_empty() {
+        return edit_response(
+            ctx,
+            command,
+            &format!("RepeaterBook lists no repeaters on the air in {}.", place),
+        )
+        .await;
+    }
+
+    let mut lines = vec![format!(
+        "{} {} on the air in {}:",
+        found.len(),
+        if found.len() == 1 {
+            "repeater"
+        } else {
+            "repeaters"
+        },
+        place
+    )];
+    lines.extend(found.iter().take(max_results).map(Repeater::describe));
+    if found.len() > max_results {
+        lines.push(format!(
+            "…and {} more on <https://www.repeaterbook.com>",
+            found.len() - max_results
+        ));
+    }
+    edit_response(ctx, command, &lines.join("\n")).await
+}
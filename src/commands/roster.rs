@@ -0,0 +1,51 @@
+use super::edit_response;
+use crate::snapshots;
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use serenity::all::{CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption};
+use serenity::prelude::*;
+
+/// Snapshots listed by `/roster history`
+const HISTORY_SIZE: usize = 10;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("roster")
+        .description("Published roster history")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "history",
+            "List recent roster snapshots and what changed in each",
+        ))
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/roster must be used in a server")?;
+
+    let Some(store) = &handler.snapshots else {
+        return edit_response(
+            ctx,
+            command,
+            "Roster snapshots aren't configured for this bot.",
+        )
+        .await;
+    };
+
+    let history = store.history(guild_id.get(), HISTORY_SIZE)?;
+    if history.is_empty() {
+        return edit_response(ctx, command, "No roster snapshots yet.").await;
+    }
+
+    let mut content = "🗂️ **Roster history** (newest first)".to_string();
+    for (snapshot, changes) in &history {
+        content.push_str(&format!("\n- {}", snapshots::describe(snapshot, changes)));
+    }
+    content.push_str(&format!(
+        "\nRestore one with `discord-callsign-bot rollback {} <id>`.",
+        guild_id
+    ));
+    edit_response(ctx, command, &content).await
+}
@@ -0,0 +1,26 @@
+use super::edit_response;
+use crate::propagation;
+use crate::Handler;
+use anyhow::Result;
+use serenity::all::{CommandInteraction, CreateCommand, EditInteractionResponse};
+use serenity::prelude::*;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("solar").description("Solar indices and HF band conditions")
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer(&ctx.http).await?;
+
+    let Some(propagation_client) = &handler.propagation_client else {
+        return edit_response(ctx, command, "Solar data isn't configured.").await;
+    };
+    let report = propagation_client.report().await?;
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().embed(propagation::embed(&report)),
+        )
+        .await?;
+    Ok(())
+}
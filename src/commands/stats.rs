@@ -0,0 +1,72 @@
+use super::edit_response;
+use crate::output::stats;
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use serenity::all::{CommandInteraction, CreateCommand, CreateEmbed, EditInteractionResponse};
+use serenity::prelude::*;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("stats").description("Roster statistics for this server")
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/stats must be used in a server")?;
+    let Some(roster) = handler.rosters.get(guild_id.get()) else {
+        return edit_response(ctx, command, "The roster hasn't been generated yet.").await;
+    };
+
+    let counts = roster.counts;
+    let percent = |n: usize| match counts.members {
+        0 => 0,
+        total => n * 100 / total,
+    };
+    let or_none = |text: String| {
+        if text.is_empty() {
+            "None".to_string()
+        } else {
+            text
+        }
+    };
+
+    let embed = CreateEmbed::new()
+        .title(format!(
+            "📊 {}",
+            roster.title.as_deref().unwrap_or("Roster statistics")
+        ))
+        .field("Members", counts.members.to_string(), true)
+        .field(
+            "Callsign parsed",
+            format!("{} ({}%)", counts.parsed, percent(counts.parsed)),
+            true,
+        )
+        .field(
+            "No callsign",
+            counts.members.saturating_sub(counts.parsed).to_string(),
+            true,
+        )
+        .field("Listed", roster.entries.len().to_string(), true)
+        .field(
+            "Last regenerated",
+            format!("<t:{}:R>", roster.generated_at),
+            true,
+        )
+        .field(
+            "License classes",
+            or_none(stats::describe(&stats::by_class(&roster.entries))),
+            false,
+        )
+        .field(
+            "Call districts",
+            or_none(stats::describe(&stats::by_district(&roster.entries))),
+            false,
+        );
+
+    command
+        .edit_response(&ctx.http, EditInteractionResponse::new().embed(embed))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use super::edit_response;
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use serenity::all::{
+    CommandInteraction, ComponentInteraction, ComponentInteractionDataKind, CreateActionRow,
+    CreateCommand, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu,
+    CreateSelectMenuKind, CreateSelectMenuOption, EditInteractionResponse,
+};
+use serenity::prelude::*;
+use tracing::info;
+
+/// Custom ID of the select menu members pick their suffix from
+pub const CHOOSE_MENU_ID: &str = "suffix:choose";
+
+/// Select menu value that clears a member's choice
+const DEFAULT_VALUE: &str = "__default__";
+
+/// Discord allows at most 25 options in a select menu, one of which is "Default"
+const MAX_CHOICES: usize = 24;
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("suffix")
+        .description("Choose the suffix shown after your name on the roster")
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/suffix must be used in a server")?;
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id.get())
+        .context("This server isn't configured")?;
+
+    if guild_config.suffix_choices.is_empty() {
+        return edit_response(ctx, command, "This server doesn't offer suffix choices.").await;
+    }
+
+    let current = handler
+        .storage
+        .suffixes(guild_id.get())
+        .remove(&command.user.id.get());
+
+    let mut options = vec![CreateSelectMenuOption::new(
+        format!(
+            "Default ({})",
+            display_suffix(&guild_config.output.default_suffix)
+        ),
+        DEFAULT_VALUE,
+    )
+    .default_selection(current.is_none())];
+    options.extend(
+        guild_config
+            .suffix_choices
+            .iter()
+            .take(MAX_CHOICES)
+            .map(|choice| {
+                CreateSelectMenuOption::new(choice, choice)
+                    .default_selection(current.as_deref() == Some(choice.as_str()))
+            }),
+    );
+
+    let menu = CreateSelectMenu::new(CHOOSE_MENU_ID, CreateSelectMenuKind::String { options })
+        .placeholder("Pick a suffix");
+
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .content("Choose the suffix shown after your name:")
+                .components(vec![CreateActionRow::SelectMenu(menu)]),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Save the member's pick and queue a regeneration
+pub async fn choose(
+    handler: &Handler,
+    ctx: &Context,
+    component: &ComponentInteraction,
+) -> Result<()> {
+    let guild_id = component
+        .guild_id
+        .context("Suffixes must be chosen in a server")?;
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id.get())
+        .context("This server isn't configured")?;
+
+    let ComponentInteractionDataKind::StringSelect { values } = &component.data.kind else {
+        anyhow::bail!("Unexpected component type for {}", CHOOSE_MENU_ID);
+    };
+    let value = values.first().context("No suffix selected")?;
+
+    let suffix = resolve_choice(&guild_config.suffix_choices, value)?;
+
+    let user_id = component.user.id.get();
+    handler
+        .storage
+        .set_suffix(guild_id.get(), user_id, suffix.clone())?;
+    info!(
+        "User {} in guild {} chose suffix {:?}",
+        user_id, guild_id, suffix
+    );
+    handler.enqueue(guild_id.get(), user_id, "suffix_change");
+
+    let content = match &suffix {
+        Some(suffix) => format!(
+            "Your suffix is now **{}**. The roster will update shortly.",
+            suffix
+        ),
+        None => {
+            "You'll use the server's default suffix. The roster will update shortly.".to_string()
+        }
+    };
+    component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Map a select menu value to the suffix to store, accepting only configured
+/// choices whatever the client sends
+fn resolve_choice(choices: &[String], value: &str) -> Result<Option<String>> {
+    match value {
+        DEFAULT_VALUE => Ok(None),
+        choice if choices.iter().any(|c| c == choice) => Ok(Some(choice.to_string())),
+        other => anyhow::bail!("Suffix {:?} isn't one of the configured choices", other),
+    }
+}
+
+fn display_suffix(suffix: &str) -> &str {
+    if suffix.is_empty() {
+        "none"
+    } else {
+        suffix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_choice() {
+        let choices = vec!["73".to_string(), "QRV".to_string()];
+        assert_eq!(
+            resolve_choice(&choices, "QRV").unwrap().as_deref(),
+            Some("QRV")
+        );
+        assert_eq!(resolve_choice(&choices, DEFAULT_VALUE).unwrap(), None);
+        assert!(resolve_choice(&choices, "QRT").is_err());
+    }
+}
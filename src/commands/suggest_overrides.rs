@@ -0,0 +1,398 @@
+use super::edit_response;
+use crate::config::Override;
+use crate::discord;
+use crate::parser::CallsignParser;
+use crate::pipeline::Member;
+use crate::qrz::QrzClient;
+use crate::state::RosterStore;
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use serenity::all::{
+    CommandInteraction, ComponentInteraction, ComponentInteractionDataKind, CreateActionRow,
+    CreateCommand, CreateInteractionResponse, CreateSelectMenu, CreateSelectMenuKind,
+    CreateSelectMenuOption, EditInteractionResponse, Permissions,
+};
+use serenity::prelude::*;
+use tracing::info;
+
+/// Custom ID of the select menu used to approve suggestions
+pub const APPROVE_MENU_ID: &str = "suggest_overrides:approve";
+
+/// Discord allows at most 25 options in a select menu
+const MAX_SUGGESTIONS: usize = 25;
+
+/// Discord caps select option labels, descriptions, and values at 100 characters
+const MAX_OPTION_CHARS: usize = 100;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SuggestionSource {
+    /// The member was listed under this callsign in an earlier roster
+    PreviousRoster,
+    /// A callsign-like fragment of a name field that QRZ recognizes
+    QrzMatch,
+    /// A callsign-like fragment of a name field, unverified because QRZ isn't configured
+    NameFragment,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub user_id: u64,
+    pub display_name: String,
+    pub callsign: String,
+    pub name: Option<String>,
+    pub source: SuggestionSource,
+}
+
+impl Suggestion {
+    fn label(&self) -> String {
+        format!("{} → {}", self.display_name, self.callsign)
+    }
+
+    fn description(&self) -> String {
+        let source = match self.source {
+            SuggestionSource::PreviousRoster => "listed under this callsign before",
+            SuggestionSource::QrzMatch => "found in name, confirmed on QRZ",
+            SuggestionSource::NameFragment => "found in name, unverified",
+        };
+        match &self.name {
+            Some(name) => format!("{} ({})", source, name),
+            None => source.to_string(),
+        }
+    }
+
+    /// Select menu values carry everything needed to apply the override on approval.
+    /// The name is cut short if needed to fit Discord's limit on option values.
+    fn encode(&self) -> String {
+        let prefix = format!("{}|{}|", self.user_id, self.callsign);
+        let room = MAX_OPTION_CHARS.saturating_sub(prefix.chars().count());
+        let name = truncate(self.name.as_deref().unwrap_or_default(), room);
+        format!("{}{}", prefix, name.trim_end())
+    }
+}
+
+pub fn definition() -> CreateCommand {
+    CreateCommand::new("suggest-overrides")
+        .description("Propose overrides for members whose callsign couldn't be parsed")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/suggest-overrides must be used in a server")?;
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id.get())
+        .context("This server isn't configured")?;
+
+    let members = handler
+        .rest_limits
+        .fetch_members(&ctx.http, guild_id)
+        .await
+        .context("Failed to fetch guild members")?;
+    let members: Vec<Member> = members.iter().map(discord::member).collect();
+
+    let mut suggestions = Vec::new();
+    let builder = handler.roster_builder();
+    let approved = handler.rosters.approved_overrides(guild_id.get());
+    let bot_user = crate::bot_user_id(ctx);
+
+    for member in &members {
+        let user_id = member.user_id.to_string();
+        if member.user_id == bot_user.get()
+            || builder.is_excluded(member)
+            || guild_config.get_override(&user_id).is_some()
+            || approved.contains_key(&user_id)
+            || builder.parse_member(member).0.is_some()
+        {
+            continue;
+        }
+
+        if let Some(suggestion) = suggest(
+            &handler.parser,
+            handler.qrz_client.as_deref(),
+            &handler.rosters,
+            guild_id.get(),
+            member,
+        )
+        .await
+        {
+            suggestions.push(suggestion);
+        }
+    }
+
+    if suggestions.is_empty() {
+        return edit_response(
+            ctx,
+            command,
+            "No suggestions: every unparsed member is either overridden or has no callsign-like name.",
+        )
+        .await;
+    }
+
+    let total = suggestions.len();
+    suggestions.truncate(MAX_SUGGESTIONS);
+
+    let options = suggestions
+        .iter()
+        .map(|s| {
+            CreateSelectMenuOption::new(truncate(&s.label(), MAX_OPTION_CHARS), s.encode())
+                .description(truncate(&s.description(), MAX_OPTION_CHARS))
+        })
+        .collect();
+    let menu = CreateSelectMenu::new(APPROVE_MENU_ID, CreateSelectMenuKind::String { options })
+        .placeholder("Select suggestions to approve")
+        .min_values(1)
+        .max_values(suggestions.len() as u8);
+
+    let mut content = format!(
+        "**{} suggested overrides.** Select the ones to apply; nothing changes until you approve.",
+        total
+    );
+    if total > MAX_SUGGESTIONS {
+        content.push_str(&format!(
+            "\nShowing the first {}; run the command again after approving.",
+            MAX_SUGGESTIONS
+        ));
+    }
+
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .content(content)
+                .components(vec![CreateActionRow::SelectMenu(menu)]),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Apply the overrides selected in the menu, regenerate, and reply with a config snippet
+pub async fn approve(
+    handler: &Handler,
+    ctx: &Context,
+    component: &ComponentInteraction,
+) -> Result<()> {
+    component
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let guild_id = component
+        .guild_id
+        .context("Suggestions must be approved in a server")?;
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id.get())
+        .context("This server isn't configured")?;
+
+    let ComponentInteractionDataKind::StringSelect { values } = &component.data.kind else {
+        anyhow::bail!("Unexpected component type for {}", APPROVE_MENU_ID);
+    };
+
+    let approved: Vec<(u64, Override)> = values.iter().filter_map(|v| decode(v)).collect();
+    for (user_id, override_config) in &approved {
+        info!(
+            "Approved override for user {} in guild {}: {:?}",
+            user_id, guild_id, override_config
+        );
+        handler
+            .rosters
+            .approve_override(guild_id.get(), *user_id, override_config.clone());
+    }
+
+    let regenerated = handler
+        .generate_member_list(
+            &ctx.http,
+            crate::bot_user_id(ctx),
+            guild_config,
+            "override_approval",
+        )
+        .await;
+
+    let mut content = format!(
+        "Approved {} overrides. They apply until the bot restarts; add this to your config to keep them:\n```toml\n{}```",
+        approved.len(),
+        toml_snippet(&approved)
+    );
+    if let Err(e) = regenerated {
+        content.push_str(&format!("\nRegenerating the member list failed: {}", e));
+    }
+
+    component
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .content(super::truncate_message(&content))
+                .components(vec![]),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Propose a callsign for an unparsed member, preferring earlier roster data
+pub async fn suggest(
+    parser: &CallsignParser,
+    qrz_client: Option<&QrzClient>,
+    rosters: &RosterStore,
+    guild_id: u64,
+    member: &Member,
+) -> Option<Suggestion> {
+    let display_name = member.display_name().to_string();
+
+    if let Some(callsign) = rosters.known_callsign(guild_id, member.user_id) {
+        return Some(Suggestion {
+            user_id: member.user_id,
+            display_name,
+            callsign,
+            name: None,
+            source: SuggestionSource::PreviousRoster,
+        });
+    }
+
+    let name_fields = [
+        member.nick.as_deref(),
+        member.global_name.as_deref(),
+        Some(member.username.as_str()),
+    ];
+    let callsign = name_fields
+        .into_iter()
+        .flatten()
+        .find_map(|field| loose_callsign(parser, field))?;
+
+    let (name, source) = match qrz_client {
+        Some(qrz_client) => {
+            // QRZ not knowing the callsign means the fragment was probably a coincidence
+            let info = qrz_client.lookup_callsign(&callsign).await.ok()?;
+            (
+                QrzClient::get_display_name(&info),
+                SuggestionSource::QrzMatch,
+            )
+        }
+        None => (None, SuggestionSource::NameFragment),
+    };
+
+    Some(Suggestion {
+        user_id: member.user_id,
+        display_name,
+        callsign,
+        name,
+        source,
+    })
+}
+
+/// Weaker than the parser: look for a callsign between any non-alphanumeric
+/// characters, so usernames like "jay_w6jsv" or "w6jsv.radio" match
+pub fn loose_callsign(parser: &CallsignParser, text: &str) -> Option<String> {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .find(|fragment| parser.is_callsign(fragment))
+        .map(|fragment| fragment.to_uppercase())
+}
+
+fn decode(value: &str) -> Option<(u64, Override)> {
+    let mut parts = value.splitn(3, '|');
+    let user_id = parts.next()?.parse().ok()?;
+    let callsign = parts.next()?.to_string();
+    let name = parts.next().filter(|n| !n.is_empty()).map(str::to_string);
+
+    Some((
+        user_id,
+        Override {
+            callsign: Some(callsign),
+            name,
+            suffix: None,
+            emoji: None,
+            skip_qrz: false,
+            hide_location: false,
+        },
+    ))
+}
+
+/// Render approved overrides as `[guilds.overrides."ID"]` tables
+fn toml_snippet(approved: &[(u64, Override)]) -> String {
+    let mut snippet = String::new();
+    for (user_id, override_config) in approved {
+        snippet.push_str(&format!("[guilds.overrides.\"{}\"]\n", user_id));
+        if let Some(callsign) = &override_config.callsign {
+            snippet.push_str(&format!(
+                "callsign = {}\n",
+                toml::Value::from(callsign.as_str())
+            ));
+        }
+        if let Some(name) = &override_config.name {
+            snippet.push_str(&format!("name = {}\n", toml::Value::from(name.as_str())));
+        }
+    }
+    snippet
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loose_callsign() {
+        let parser = CallsignParser::new();
+        assert_eq!(
+            loose_callsign(&parser, "jay_w6jsv").as_deref(),
+            Some("W6JSV")
+        );
+        assert_eq!(
+            loose_callsign(&parser, "w6jsv.radio").as_deref(),
+            Some("W6JSV")
+        );
+        assert_eq!(loose_callsign(&parser, "just_a_name"), None);
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let suggestion = Suggestion {
+            user_id: 42,
+            display_name: "jay_w6jsv".to_string(),
+            callsign: "W6JSV".to_string(),
+            name: Some("Jay | Radio".to_string()),
+            source: SuggestionSource::QrzMatch,
+        };
+
+        let (user_id, override_config) = decode(&suggestion.encode()).unwrap();
+        assert_eq!(user_id, 42);
+        assert_eq!(override_config.callsign.as_deref(), Some("W6JSV"));
+        assert_eq!(override_config.name.as_deref(), Some("Jay | Radio"));
+
+        let (_, without_name) = decode("42|W6JSV|").unwrap();
+        assert!(without_name.name.is_none());
+        assert!(decode("not-an-id|W6JSV|").is_none());
+    }
+
+    #[test]
+    fn test_encode_fits_option_value_limit() {
+        let suggestion = Suggestion {
+            user_id: 1_234_567_890_123_456_789,
+            display_name: "jay_w6jsv".to_string(),
+            callsign: "W6JSV".to_string(),
+            name: Some("Jay ".repeat(40)),
+            source: SuggestionSource::QrzMatch,
+        };
+
+        let value = suggestion.encode();
+        assert!(value.chars().count() <= MAX_OPTION_CHARS);
+        let (user_id, override_config) = decode(&value).unwrap();
+        assert_eq!(user_id, 1_234_567_890_123_456_789);
+        assert_eq!(override_config.callsign.as_deref(), Some("W6JSV"));
+        assert!(override_config.name.unwrap().starts_with("Jay Jay"));
+    }
+
+    #[test]
+    fn test_toml_snippet() {
+        let (user_id, override_config) = decode("42|W6JSV|Jay \"J\"").unwrap();
+        assert_eq!(
+            toml_snippet(&[(user_id, override_config)]),
+            "[guilds.overrides.\"42\"]\ncallsign = \"W6JSV\"\nname = 'Jay \"J\"'\n"
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use super::edit_response;
+use crate::swap::{self, Listing, ListingDetails, ListingKind};
+use crate::Handler;
+use anyhow::{Context as _, Result};
+use chrono::Utc;
+use serenity::all::{
+    ChannelId, CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption,
+    CreateMessage, ResolvedOption, ResolvedValue, RoleId,
+};
+use serenity::prelude::*;
+use tracing::{info, warn};
+
+pub fn definition() -> CreateCommand {
+    let mut kind = CreateCommandOption::new(CommandOptionType::String, "kind", "Kind of listing")
+        .required(true);
+    for k in ListingKind::ALL {
+        kind = kind.add_string_choice(k.name(), k.id());
+    }
+    CreateCommand::new("swap")
+        .description("Post and browse gear for sale, wanted, or for trade")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "post", "Post a listing")
+                .add_sub_option(kind)
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "item",
+                        "What it is, e.g. \"Yaesu FT-817\"",
+                    )
+                    .required(true),
+                )
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "price",
+                    "Asking price or what you'd trade for",
+                ))
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "condition",
+                    "e.g. \"Excellent\" or \"For parts\"",
+                ))
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "description",
+                    "Details, such as accessories and pickup or shipping",
+                )),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "list",
+            "List active listings",
+        ))
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "close",
+                "Take down a listing once it's sold or found",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "id",
+                    "Listing ID from /swap list",
+                )
+                .required(true),
+            ),
+        )
+}
+
+pub async fn run(handler: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guild_id = command
+        .guild_id
+        .context("/swap must be used in a server")?
+        .get();
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id)
+        .context("This server isn't configured")?;
+    let Some(swap_config) = &guild_config.swap else {
+        return edit_response(
+            ctx,
+            command,
+            "Swap listings aren't enabled for this server.",
+        )
+        .await;
+    };
+    let user_id = command.user.id.get();
+
+    let options = command.data.options();
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(options),
+        ..
+    }) = options.first()
+    else {
+        anyhow::bail!("/swap needs a subcommand");
+    };
+    let text = |name: &str| {
+        options.iter().find_map(|option| match option.value {
+            ResolvedValue::String(value) if option.name == name => {
+                Some(value.trim()).filter(|v| !v.is_empty())
+            }
+            _ => None,
+        })
+    };
+
+    let content = match *subcommand {
+        "post" => {
+            let kind = text("kind")
+                .and_then(ListingKind::from_id)
+                .context("/swap post needs a kind")?;
+            let item = text("item").context("/swap post needs an item")?;
+            // Listings carry the poster's callsign, so only members on the roster can post
+            let Some(callsign) = handler
+                .rosters
+                .known_callsign(guild_id, user_id)
+                .filter(|callsign| handler.rosters.lists_callsign(guild_id, callsign))
+            else {
+                return edit_response(
+                    ctx,
+                    command,
+                    "Only members on the roster can post. Put your callsign in your server nickname first.",
+                )
+                .await;
+            };
+            let name = handler
+                .rosters
+                .get(guild_id)
+                .and_then(|roster| {
+                    roster
+                        .entries
+                        .into_iter()
+                        .find(|entry| entry.callsign == callsign)
+                })
+                .map(|entry| entry.name)
+                .unwrap_or_default();
+
+            let details = ListingDetails {
+                kind,
+                item,
+                price: text("price"),
+                condition: text("condition"),
+                description: text("description"),
+            };
+            let listing = Listing::new(
+                details,
+                user_id,
+                &callsign,
+                &name,
+                Utc::now(),
+                swap_config.expire_days,
+            );
+            let listing = handler.storage.add_swap_listing(guild_id, listing)?;
+            let message = CreateMessage::new().embed(swap::embed(&listing));
+            match ChannelId::new(swap_config.channel_id)
+                .send_message(&ctx.http, message)
+                .await
+            {
+                Ok(message) => {
+                    handler
+                        .storage
+                        .set_swap_message(guild_id, listing.id, message.id.get())?;
+                    info!(
+                        "{} ({}) posted swap listing {} in guild {}: {}",
+                        command.user.name, callsign, listing.id, guild_id, listing.item
+                    );
+                    format!(
+                        "Posted listing `{}`: {}. It comes down <t:{}:R>, or sooner with `/swap close {}`.",
+                        listing.id,
+                        swap::message_link(guild_id, swap_config.channel_id, message.id.get()),
+                        listing.expires_at.timestamp(),
+                        listing.id
+                    )
+                }
+                Err(e) => {
+                    handler.storage.remove_swap_listing(guild_id, listing.id)?;
+                    warn!(
+                        "Failed to post swap listing to channel {}: {}",
+                        swap_config.channel_id, e
+                    );
+                    "Couldn't post to the swap channel; ask an admin to check the bot's permissions there.".to_string()
+                }
+            }
+        }
+        "list" => {
+            // Expired listings are taken down hourly; hide them in the meantime
+            let now = Utc::now();
+            let listings: Vec<Listing> = handler
+                .storage
+                .swap_listings(guild_id)
+                .into_iter()
+                .filter(|listing| !listing.is_expired(now))
+                .collect();
+            swap::list("Swap listings", &listings, guild_id, swap_config.channel_id)
+        }
+        "close" => {
+            let id = options
+                .iter()
+                .find_map(|option| match option.value {
+                    ResolvedValue::Integer(id) if option.name == "id" => u32::try_from(id).ok(),
+                    _ => None,
+                })
+                .context("/swap close needs an ID")?;
+            let Some(listing) = handler
+                .storage
+                .swap_listings(guild_id)
+                .into_iter()
+                .find(|l| l.id == id)
+            else {
+                return edit_response(ctx, command, &format!("No listing has ID `{}`.", id)).await;
+            };
+            let is_admin = guild_config.admin_role_id.is_some_and(|role_id| {
+                command
+                    .member
+                    .as_ref()
+                    .is_some_and(|m| m.roles.contains(&RoleId::new(role_id)))
+            });
+            if listing.user_id != user_id && !is_admin {
+                return edit_response(
+                    ctx,
+                    command,
+                    "Only the poster or an admin can close a listing.",
+                )
+                .await;
+            }
+
+            handler.storage.remove_swap_listing(guild_id, id)?;
+            if let Some(message_id) = listing.message_id {
+                // It may already have been deleted by hand
+                if let Err(e) = ChannelId::new(swap_config.channel_id)
+                    .delete_message(&ctx.http, message_id)
+                    .await
+                {
+                    warn!("Failed to delete swap listing {}'s message: {}", id, e);
+                }
+            }
+            info!(
+                "{} closed swap listing {} in guild {}",
+                command.user.name, id, guild_id
+            );
+            format!("Closed listing `{}`: {}.", id, listing.item)
+        }
+        other => anyhow::bail!("Unknown /swap subcommand: {}", other),
+    };
+
+    edit_response(ctx, command, &content).await
+}
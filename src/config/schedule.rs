@@ -0,0 +1,103 @@
+use crate::config::ScheduleConfig;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use croner::Cron;
+use std::time::Duration;
+
+/// When to run full regenerations independent of member events
+#[derive(Debug)]
+pub enum Schedule {
+    Interval(Duration),
+    /// Standard 5-field cron expression, evaluated in UTC
+    Cron(Box<Cron>),
+}
+
+impl Schedule {
+    pub fn from_config(config: &ScheduleConfig) -> Result<Self> {
+        match (config.interval_secs, config.cron.as_deref()) {
+            (Some(_), Some(_)) => anyhow::bail!("[schedule] sets both interval_secs and cron"),
+            (Some(0), None) => anyhow::bail!("[schedule] interval_secs must be positive"),
+            (Some(secs), None) => Ok(Self::Interval(Duration::from_secs(secs))),
+            (None, Some(expression)) => {
+                let cron = Cron::new(expression).parse().with_context(|| {
+                    format!("Invalid [schedule] cron expression: {}", expression)
+                })?;
+                Ok(Self::Cron(Box::new(cron)))
+            }
+            (None, None) => anyhow::bail!("[schedule] needs interval_secs or cron"),
+        }
+    }
+
+    /// How long to wait from `now` until the next run
+    pub fn next_delay(&self, now: DateTime<Utc>) -> Result<Duration> {
+        Ok((self.next_after(now)? - now).to_std().unwrap_or_default())
+    }
+
+    /// The first run strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Result<DateTime<Utc>> {
+        match self {
+            Self::Interval(interval) => Ok(after + *interval),
+            Self::Cron(cron) => cron
+                .find_next_occurrence(&after, false)
+                .context("Cron expression has no upcoming occurrence"),
+        }
+    }
+}
+
+impl std::fmt::Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Interval(interval) => write!(f, "every {}s", interval.as_secs()),
+            Self::Cron(cron) => write!(f, "cron \"{}\" (UTC)", cron.pattern),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn config(interval_secs: Option<u64>, cron: Option<&str>) -> ScheduleConfig {
+        ScheduleConfig {
+            interval_secs,
+            cron: cron.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_from_config_requires_exactly_one() {
+        assert!(Schedule::from_config(&config(None, None)).is_err());
+        assert!(Schedule::from_config(&config(Some(60), Some("0 * * * *"))).is_err());
+        assert!(Schedule::from_config(&config(Some(0), None)).is_err());
+        assert!(Schedule::from_config(&config(None, Some("not cron"))).is_err());
+    }
+
+    #[test]
+    fn test_next_delay() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 1, 12, 30, 0).unwrap();
+
+        let interval = Schedule::from_config(&config(Some(900), None)).unwrap();
+        assert_eq!(interval.next_delay(now).unwrap(), Duration::from_secs(900));
+
+        let hourly = Schedule::from_config(&config(None, Some("0 * * * *"))).unwrap();
+        assert_eq!(
+            hourly.next_delay(now).unwrap(),
+            Duration::from_secs(30 * 60)
+        );
+        assert_eq!(hourly.to_string(), "cron \"0 * * * *\" (UTC)");
+    }
+
+    #[test]
+    fn test_next_after() {
+        let weekly = Schedule::from_config(&config(None, Some("0 19 * * TUE"))).unwrap();
+        // 2024-06-01 is a Saturday
+        let now = Utc.with_ymd_and_hms(2024, 6, 1, 12, 30, 0).unwrap();
+        let next = weekly.next_after(now).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2024, 6, 4, 19, 0, 0).unwrap());
+        assert_eq!(
+            weekly.next_after(next).unwrap(),
+            Utc.with_ymd_and_hms(2024, 6, 11, 19, 0, 0).unwrap()
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use crate::output::html::html_to_text;
+use anyhow::{Context, Result};
+use regex::Regex;
+use reqwest::header::USER_AGENT;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Pause between callsign searches so a large roster doesn't hammer the score site
+const SEARCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// One station's claimed score
+#[derive(Debug, Clone, PartialEq)]
+pub struct Score {
+    pub callsign: String,
+    pub score: u64,
+    pub qsos: Option<u32>,
+    pub category: Option<String>,
+}
+
+/// Searches a claimed-score site such as 3830scores.com one callsign at a time
+pub struct ContestClient {
+    client: reqwest::Client,
+}
+
+impl ContestClient {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Each callsign's newest claimed score for `contest`. `search_url` has a
+    /// `{callsign}` placeholder; callsigns that aren't plain letters and digits are
+    /// skipped rather than escaped into the URL.
+    pub async fn fetch_scores(
+        &self,
+        search_url: &str,
+        contest: &str,
+        callsigns: &[String],
+    ) -> Result<Vec<Score>> {
+        let mut scores = Vec::new();
+        let searchable = callsigns
+            .iter()
+            .filter(|c| !c.is_empty() && c.chars().all(|c| c.is_ascii_alphanumeric()));
+        for (i, callsign) in searchable.enumerate() {
+            if i > 0 {
+                tokio::time::sleep(SEARCH_INTERVAL).await;
+            }
+            let html = self
+                .search(&search_url.replace("{callsign}", callsign))
+                .await
+                .with_context(|| format!("Failed to search scores for {}", callsign))?;
+            // Results can include other stations whose calls contain the one searched
+            let own = parse_scores(&html, contest)
+                .into_iter()
+                .find(|score| score.callsign.split('/').any(|part| part == callsign));
+            scores.extend(own);
+        }
+        Ok(scores)
+    }
+
+    async fn search(&self, url: &str) -> Result<String> {
+        let response = self
+            .client
+            .get(url)
+            .header(USER_AGENT, "discord-callsign-bot")
+            .send()
+            .await
+            .context("Failed to send request for contest scores")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Contest scores returned error {}", response.status());
+        }
+
+        response
+            .text()
+            .await
+            .context("Failed to read contest scores")
+    }
+}
+
+impl Default for ContestClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Column positions from a score table's header row
+struct Columns {
+    call: usize,
+    score: usize,
+    qsos: Option<usize>,
+    category: Option<usize>,
+}
+
+impl Columns {
+    /// `Call`/`Callsign` and `Score` columns are required; `QSOs` and `Category` are
+    /// used when present
+    fn from_header(cells: &[String]) -> Option<Self> {
+        let header: Vec<String> = cells.iter().map(|cell| cell.to_lowercase()).collect();
+        let column = |names: &[&str]| header.iter().position(|c| names.contains(&c.as_str()));
+        Some(Self {
+            call: column(&["call", "callsign"])?,
+            score: column(&["score", "claimed score"])?,
+            qsos: column(&["qsos", "qso"]),
+            category: column(&["category", "class"]),
+        })
+    }
+
+    /// Rows without a numeric score are skipped
+    fn score(&self, cells: &[String]) -> Option<Score> {
+        let field = |index: usize| cells.get(index).map(|f| f.trim());
+        let callsign = field(self.call)?.to_uppercase();
+        // Scores are often written with thousands separators
+        let score = field(self.score)?.replace(',', "").parse().ok()?;
+        Some(Score {
+            callsign,
+            score,
+            qsos: self
+                .qsos
+                .and_then(field)
+                .and_then(|q| q.replace(',', "").parse().ok()),
+            category: self
+                .category
+                .and_then(field)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string),
+        })
+    }
+}
+
+/// The scores in a search results page, in page order. Rows come from the first table
+/// row that reads as a header (see `Columns::from_header()`) onward, and only rows
+/// mentioning every word of `contest` are kept, so "CQ WW CW 2026" skips other
+/// contests and years.
+pub fn parse_scores(html: &str, contest: &str) -> Vec<Score> {
+    let row_regex = Regex::new(r"(?is)<tr\b[^>]*>(.*?)</tr>").expect("Failed to compile row regex");
+    let cell_regex =
+        Regex::new(r"(?is)<t[hd]\b[^>]*>(.*?)</t[hd]>").expect("Failed to compile cell regex");
+    let words: Vec<String> = contest.split_whitespace().map(str::to_lowercase).collect();
+
+    let mut columns: Option<Columns> = None;
+    let mut scores = Vec::new();
+    for row in row_regex.captures_iter(html) {
+        let cells: Vec<String> = cell_regex
+            .captures_iter(&row[1])
+            .map(|cell| html_to_text(&cell[1]))
+            .collect();
+        let Some(columns) = &columns else {
+            columns = Columns::from_header(&cells);
+            continue;
+        };
+        let text = cells.join(" ").to_lowercase();
+        if !words.iter().all(|word| text.contains(word.as_str())) {
+            continue;
+        }
+        scores.extend(columns.score(&cells));
+    }
+    scores
+}
+
+/// The club's entries, highest score first, with the club total; `None` when no
+/// member submitted a score
+pub fn club_summary(
+    contest: &str,
+    scores: &[Score],
+    members: &HashSet<String>,
+    top: usize,
+) -> Option<String> {
+    let mut club: Vec<&Score> = scores
+        .iter()
+        .filter(|score| members.contains(&score.callsign))
+        .collect();
+    if club.is_empty() {
+        return None;
+    }
+    club.sort_by(|a, b| b.score.cmp(&a.score).then(a.callsign.cmp(&b.callsign)));
+
+    let total: u64 = club.iter().map(|score| score.score).sum();
+    let mut lines = vec![format!(
+        "**{}**: {} club {} claimed {} points",
+        contest,
+        club.len(),
+        if club.len() == 1 { "entry" } else { "entries" },
+        thousands(total)
+    )];
+    for (rank, score) in club.iter().take(top).enumerate() {
+        let mut line = format!(
+            "{}. {} — {}",
+            rank + 1,
+            score.callsign,
+            thousands(score.score)
+        );
+        if let Some(qsos) = score.qsos {
+            line.push_str(&format!(" ({} QSOs)", qsos));
+        }
+        if let Some(category) = &score.category {
+            line.push_str(&format!(" · {}", category));
+        }
+        lines.push(line);
+    }
+    if club.len() > top {
+        lines.push(format!("…and {} more", club.len() - top));
+    }
+    Some(lines.join("\n"))
+}
+
+/// 1234567 -> "1,234,567"
+fn thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut output = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            output.push(',');
+        }
+        output.push(digit);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESULTS: &str = r#"<html><body>
+        <h1>Claimed scores for W6JSV</h1>
+        <table>
+        <tr><th>Contest</th><th>Year</th><th>Call</th><th>Category</th><th>QSOs</th><th>Score</th></tr>
+        <tr><td>CQ WW DX Contest, CW</td><td>2026</td><td><a href="/r?id=1">W6JSV</a></td>
+            <td>SOAB, LP</td><td>812</td><td>1,234,567</td></tr>
+        <tr><td>CQ WW DX Contest, SSB</td><td>2026</td><td>W6JSV</td>
+            <td>SOAB LP</td><td>300</td><td>99,000</td></tr>
+        <tr><td>CQ WW DX Contest, CW</td><td>2025</td><td>W6JSV/6</td>
+            <td>SO2R HP</td><td>1500</td><td>2500000</td></tr>
+        <tr><td>CQ WW DX Contest, CW</td><td>2026</td><td>W6JSVA</td>
+            <td>M/S</td><td></td><td>not claimed</td></tr>
+        </table></body></html>"#;
+
+    #[test]
+    fn test_parse_scores() {
+        let scores = parse_scores(RESULTS, "CQ WW CW 2026");
+        assert_eq!(
+            scores,
+            vec![Score {
+                callsign: "W6JSV".to_string(),
+                score: 1_234_567,
+                qsos: Some(812),
+                category: Some("SOAB, LP".to_string()),
+            }]
+        );
+
+        let scores = parse_scores(RESULTS, "cq ww cw");
+        assert_eq!(scores.len(), 2);
+        assert_eq!(scores[1].callsign, "W6JSV/6");
+
+        assert!(parse_scores("<table><tr><td>No results</td></tr></table>", "CQ WW").is_empty());
+        assert!(parse_scores("", "CQ WW").is_empty());
+    }
+
+    #[test]
+    fn test_club_summary() {
+        let score = |callsign: &str, score: u64, qsos: Option<u32>, category: &str| Score {
+            callsign: callsign.to_string(),
+            score,
+            qsos,
+            category: Some(category.to_string()),
+        };
+        let scores = vec![
+            score("W6JSV", 1_234_567, Some(812), "SOAB, LP"),
+            score("KI7QCF", 2_500_000, Some(1500), "SO2R HP"),
+            score("N0CALL", 1000, Some(10), "SOAB LP"),
+        ];
+        let members: HashSet<String> = ["W6JSV", "KI7QCF"].map(String::from).into();
+        assert_eq!(
+            club_summary("CQ WW DX CW", &scores, &members, 1).unwrap(),
+            "**CQ WW DX CW**: 2 club entries claimed 3,734,567 points\n\
+             1. KI7QCF — 2,500,000 (1500 QSOs) · SO2R HP\n\
+             …and 1 more"
+        );
+        assert_eq!(
+            club_summary("CQ WW DX CW", &scores, &HashSet::new(), 10),
+            None
+        );
+    }
+
+    #[test]
+    fn test_thousands() {
+        assert_eq!(thousands(0), "0");
+        assert_eq!(thousands(999), "999");
+        assert_eq!(thousands(1000), "1,000");
+        assert_eq!(thousands(1_234_567), "1,234,567");
+    }
+}
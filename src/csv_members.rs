@@ -0,0 +1,105 @@
+use crate::output::split_csv_line;
+use crate::pipeline::{Member, MemberSource, Role};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+/// Members read from a CSV of `display_name[,discord_id]` rows, for building a roster
+/// without Discord (`generate-from-csv`)
+pub struct CsvMembers {
+    /// Stands in for the guild's name in `{guild_name}`
+    pub name: String,
+    pub members: Vec<Member>,
+}
+
+impl CsvMembers {
+    pub fn from_file(path: &str, name: String) -> Result<Self> {
+        let csv = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read members from {}", path))?;
+        Ok(Self {
+            name,
+            members: parse(&csv).with_context(|| format!("Failed to parse {}", path))?,
+        })
+    }
+}
+
+/// Parse `display_name[,discord_id]` rows, skipping blank lines and an optional
+/// `display_name` header. Rows without an ID get their line number as one, so
+/// overrides, suffixes, and dues only apply to rows that have a real ID.
+pub fn parse(csv: &str) -> Result<Vec<Member>> {
+    let mut members = Vec::new();
+    for (index, line) in csv.lines().enumerate() {
+        let fields = split_csv_line(line);
+        let display_name = fields[0].trim();
+        if display_name.is_empty() || (index == 0 && display_name == "display_name") {
+            continue;
+        }
+
+        let line_number = index as u64 + 1;
+        let user_id = match fields
+            .get(1)
+            .map(|id| id.trim())
+            .filter(|id| !id.is_empty())
+        {
+            Some(id) => id
+                .parse::<u64>()
+                .ok()
+                .filter(|parsed| *parsed != 0)
+                .with_context(|| format!("Line {}: {:?} isn't a Discord ID", line_number, id))?,
+            None => line_number,
+        };
+        members.push(Member {
+            user_id,
+            username: display_name.to_string(),
+            nick: Some(display_name.to_string()),
+            ..Default::default()
+        });
+    }
+    Ok(members)
+}
+
+#[async_trait]
+impl MemberSource for CsvMembers {
+    async fn members(&self, _guild_id: u64) -> Result<Vec<Member>> {
+        Ok(self.members.clone())
+    }
+
+    /// A CSV has no roles, so `suffix_role_pattern` finds nothing
+    async fn roles(&self, _guild_id: u64) -> Result<HashMap<u64, Role>> {
+        Ok(HashMap::new())
+    }
+
+    async fn guild_name(&self, _guild_id: u64) -> Result<String> {
+        Ok(self.name.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let csv = "display_name,discord_id\n\
+                   Jay W6JSV,123456789012345678\n\
+                   \n\
+                   \"Forrest, KI7QCF\"\n\
+                   N0CALL Nobody, \n";
+        let members = parse(csv).unwrap();
+        let rows: Vec<(u64, &str)> = members
+            .iter()
+            .map(|m| (m.user_id, m.display_name()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (123456789012345678, "Jay W6JSV"),
+                (4, "Forrest, KI7QCF"),
+                (5, "N0CALL Nobody"),
+            ]
+        );
+
+        assert!(parse("Jay W6JSV,jay").is_err());
+        assert!(parse("Jay W6JSV,0").is_err());
+    }
+}
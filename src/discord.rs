@@ -0,0 +1,65 @@
+use crate::pipeline::{Member, MemberSource, Role};
+use crate::ratelimit::RestLimits;
+use anyhow::Result;
+use async_trait::async_trait;
+use serenity::all::{GuildId, Http};
+use std::collections::HashMap;
+
+/// A Discord member as the pipeline sees it
+pub fn member(member: &serenity::model::guild::Member) -> Member {
+    Member {
+        user_id: member.user.id.get(),
+        username: member.user.name.clone(),
+        global_name: member.user.global_name.clone(),
+        nick: member.nick.clone(),
+        bot: member.user.bot,
+        roles: member.roles.iter().map(|id| id.get()).collect(),
+        joined_at: member.joined_at.map(|t| t.unix_timestamp()),
+        // Server boosters have a premium_since timestamp
+        booster: member.premium_since.is_some(),
+    }
+}
+
+/// Discord's REST API, with members paged through `RestLimits`
+pub struct DiscordSource<'a> {
+    pub http: &'a Http,
+    pub limits: &'a RestLimits,
+}
+
+#[async_trait]
+impl MemberSource for DiscordSource<'_> {
+    async fn members(&self, guild_id: u64) -> Result<Vec<Member>> {
+        let members = self
+            .limits
+            .fetch_members(self.http, GuildId::new(guild_id))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to fetch guild members: {}", e))?;
+        Ok(members.iter().map(member).collect())
+    }
+
+    async fn roles(&self, guild_id: u64) -> Result<HashMap<u64, Role>> {
+        let roles = GuildId::new(guild_id)
+            .roles(self.http)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to fetch guild roles: {}", e))?;
+        Ok(roles
+            .into_values()
+            .map(|role| {
+                let id = role.id.get();
+                let role = Role {
+                    name: role.name,
+                    position: role.position,
+                };
+                (id, role)
+            })
+            .collect())
+    }
+
+    async fn guild_name(&self, guild_id: u64) -> Result<String> {
+        let guild = GuildId::new(guild_id)
+            .to_partial_guild(self.http)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to fetch guild: {}", e))?;
+        Ok(guild.name)
+    }
+}
@@ -0,0 +1,564 @@
+use crate::config::DxccConfig;
+use anyhow::{Context, Result};
+use reqwest::header::USER_AGENT;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Common entities in cty.dat format, for when no `[dxcc] cty_file` is configured.
+/// The full file from country-files.com covers every entity and exact-call exception.
+const BUILTIN: &str = "
+United States: 05: 08: NA: 37.53: 91.67: 5.0: K:
+    K,N,W,AA,AB,AC,AD,AE,AF,AG,AI,AJ,AK;
+Alaska: 01: 01: NA: 61.40: 148.87: 8.0: KL:
+    KL,AL,NL,WL;
+Hawaii: 31: 61: OC: 21.12: 157.48: 10.0: KH6:
+    KH6,KH7,AH6,AH7,NH6,NH7,WH6,WH7;
+Guam: 27: 64: OC: 13.37: -144.70: -10.0: KH2:
+    KH2,AH2,NH2,WH2;
+Puerto Rico: 08: 11: NA: 18.18: 66.55: 4.0: KP4:
+    KP3,KP4,NP3,NP4,WP3,WP4;
+US Virgin Islands: 08: 11: NA: 17.73: 64.80: 4.0: KP2:
+    KP2,NP2,WP2;
+Canada: 05: 09: NA: 44.35: 78.75: 5.0: VE:
+    VE,VA,VO,VY,VB,VC,VD,VG,VX,CF,CG,CH,CI,CJ,CK,CY,XJ,XK,XL,XM,XN,XO;
+Mexico: 06: 10: NA: 21.32: 100.23: 6.0: XE:
+    XE,XF,XA,XB,XC,XD,XG,XH,XI,4A,4B,4C,6D,6E,6F,6G,6H,6I,6J;
+Cuba: 08: 11: NA: 21.50: 80.00: 5.0: CO:
+    CO,CL,CM,T4;
+England: 14: 27: EU: 52.77: 1.47: 0.0: G:
+    G,M,2E;
+Scotland: 14: 27: EU: 56.82: 4.18: 0.0: GM:
+    GM,GS,MM,MS,2M;
+Wales: 14: 27: EU: 52.28: 3.73: 0.0: GW:
+    GW,GC,MW,MC,2W;
+Northern Ireland: 14: 27: EU: 54.73: 6.68: 0.0: GI:
+    GI,GN,MI,MN,2I;
+Isle of Man: 14: 27: EU: 54.20: 4.53: 0.0: GD:
+    GD,GT,MD,MT,2D;
+Jersey: 14: 27: EU: 49.22: 2.18: 0.0: GJ:
+    GJ,GH,MJ,MH,2J;
+Guernsey: 14: 27: EU: 49.45: 2.58: 0.0: GU:
+    GU,GP,MU,MP,2U;
+Ireland: 14: 27: EU: 53.13: 8.02: 0.0: EI:
+    EI,EJ;
+Germany: 14: 28: EU: 51.00: -10.00: -1.0: DL:
+    DL,DA,DB,DC,DD,DE,DF,DG,DH,DI,DJ,DK,DM,DN,DO,DP,DQ,DR;
+France: 14: 27: EU: 46.00: -2.00: -1.0: F:
+    F,TM;
+Spain: 14: 37: EU: 40.37: 4.88: -1.0: EA:
+    EA,EB,EC,ED,EE,EF,EG,EH,AM,AN,AO;
+Balearic Islands: 14: 37: EU: 39.60: -2.95: -1.0: EA6:
+    EA6,EB6,EC6,ED6,EE6,EF6,EG6,EH6;
+Canary Islands: 33: 36: AF: 28.32: 15.85: 0.0: EA8:
+    EA8,EB8,EC8,ED8,EE8,EF8,EG8,EH8;
+Portugal: 14: 37: EU: 39.50: 8.00: 0.0: CT:
+    CT,CQ,CR,CS;
+Italy: 15: 28: EU: 42.82: -12.58: -1.0: I:
+    I;
+Netherlands: 14: 27: EU: 52.28: -5.47: -1.0: PA:
+    PA,PB,PC,PD,PE,PF,PG,PH,PI;
+Belgium: 14: 27: EU: 50.70: -4.85: -1.0: ON:
+    ON,OO,OP,OQ,OR,OS,OT;
+Luxembourg: 14: 27: EU: 50.00: -6.00: -1.0: LX:
+    LX;
+Switzerland: 14: 28: EU: 46.87: -8.12: -1.0: HB:
+    HB;
+Liechtenstein: 14: 28: EU: 47.13: -9.57: -1.0: HB0:
+    HB0;
+Austria: 15: 28: EU: 47.33: -13.33: -1.0: OE:
+    OE;
+Denmark: 14: 18: EU: 56.00: -10.00: -1.0: OZ:
+    OZ,OU,OV,5P,5Q;
+Norway: 14: 18: EU: 61.00: -9.00: -1.0: LA:
+    LA,LB,LC,LD,LE,LF,LG,LH,LI,LJ,LK,LL,LM,LN;
+Sweden: 14: 18: EU: 61.20: -14.57: -1.0: SM:
+    SM,SA,SB,SC,SD,SE,SF,SG,SH,SI,SJ,SK,SL,7S,8S;
+Finland: 15: 18: EU: 63.78: -27.08: -2.0: OH:
+    OH,OF,OG,OI;
+Iceland: 40: 17: EU: 64.80: 18.73: 0.0: TF:
+    TF;
+Poland: 15: 28: EU: 52.28: -18.67: -1.0: SP:
+    SP,SN,SO,SQ,SR,3Z,HF;
+Czech Republic: 15: 28: EU: 50.00: -16.00: -1.0: OK:
+    OK,OL;
+Slovak Republic: 15: 28: EU: 49.00: -20.00: -1.0: OM:
+    OM;
+Hungary: 15: 28: EU: 47.12: -19.28: -1.0: HA:
+    HA,HG;
+Slovenia: 15: 28: EU: 46.00: -14.00: -1.0: S5:
+    S5;
+Croatia: 15: 28: EU: 45.18: -15.30: -1.0: 9A:
+    9A;
+Serbia: 15: 28: EU: 44.00: -21.00: -1.0: YU:
+    YU,YT;
+Romania: 20: 28: EU: 45.78: -24.70: -2.0: YO:
+    YO,YP,YQ,YR;
+Bulgaria: 20: 28: EU: 42.83: -25.08: -2.0: LZ:
+    LZ;
+Greece: 20: 28: EU: 39.78: -21.78: -2.0: SV:
+    SV,SW,SX,SY,SZ,J4;
+Lithuania: 15: 29: EU: 55.45: -23.63: -2.0: LY:
+    LY;
+Latvia: 15: 29: EU: 57.03: -24.65: -2.0: YL:
+    YL;
+Estonia: 15: 29: EU: 58.87: -25.55: -2.0: ES:
+    ES;
+Ukraine: 16: 29: EU: 50.00: -30.00: -2.0: UR:
+    UR,US,UT,UU,UV,UW,UX,UY,UZ,EM,EN,EO;
+European Russia: 16: 29: EU: 53.65: -41.37: -4.0: UA:
+    R,UA,UB,UC,UD,UE,UF,UG,UH,UI;
+Asiatic Russia: 17: 30: AS: 55.88: -84.08: -7.0: UA9:
+    R9,R0,UA9,UA0,RA9,RA0,RK9,RK0,RU9,RU0,RV9,RV0,RW9,RW0,RX9,RX0,RZ9,RZ0,UB9,UB0,UC9,UC0,UD9,UD0,UE9,UE0,UF9,UF0,UG9,UG0,UH9,UH0,UI9,UI0;
+Turkey: 20: 39: EU: 39.18: -35.65: -2.0: TA:
+    TA,TB,TC,YM;
+Israel: 20: 39: AS: 31.32: -34.82: -2.0: 4X:
+    4X,4Z;
+India: 22: 41: AS: 22.50: -77.58: -5.5: VU:
+    VU,AT,AU,AV,AW,8T,8U,8V,8W,8X,8Y;
+Thailand: 26: 49: AS: 12.60: -99.70: -7.0: HS:
+    HS,E2;
+Philippines: 27: 50: OC: 13.00: -122.00: -8.0: DU:
+    DU,DV,DW,DX,DY,DZ,4D,4E,4F,4G,4H,4I;
+Indonesia: 28: 51: OC: -7.30: -109.88: -7.0: YB:
+    YB,YC,YD,YE,YF,YG,YH,7A,7B,7C,7D,7E,7F,7G,7H,7I,8A,8B,8C,8D,8E,8F,8G,8H,8I;
+China: 24: 44: AS: 36.00: -102.00: -8.0: BY:
+    BY,BA,BD,BG,BH,BI,BJ,BL,BT,BZ;
+Taiwan: 24: 44: AS: 23.72: -120.88: -8.0: BV:
+    BV,BM,BN,BO,BP,BQ,BU,BW,BX;
+Republic of Korea: 25: 44: AS: 36.23: -127.90: -9.0: HL:
+    HL,DS,DT,D7,D8,D9,6K,6L,6M,6N;
+Japan: 25: 45: AS: 36.40: -138.38: -9.0: JA:
+    JA,JE,JF,JG,JH,JI,JJ,JK,JL,JM,JN,JO,JP,JQ,JR,JS,7J,7K,7L,7M,7N,8J,8K,8L,8M,8N;
+Australia: 30: 59: OC: -23.70: -132.33: -10.0: VK:
+    VK,AX,VH,VI,VJ,VL,VM,VN,VZ;
+New Zealand: 32: 60: OC: -41.83: -173.27: -12.0: ZL:
+    ZL,ZM;
+Brazil: 11: 15: SA: -10.00: 53.00: 3.0: PY:
+    PY,PP,PQ,PR,PS,PT,PU,PV,PW,PX,ZV,ZW,ZX,ZY,ZZ;
+Argentina: 13: 14: SA: -34.80: 65.92: 3.0: LU:
+    LU,AY,AZ,LO,LP,LQ,LR,LS,LT,LV,LW,L2,L3,L4,L5,L6,L7,L8,L9;
+Chile: 12: 14: SA: -30.00: 71.00: 4.0: CE:
+    CE,CA,CB,CC,CD,XQ,XR,3G;
+Colombia: 09: 12: SA: 5.00: 74.00: 5.0: HK:
+    HK,HJ,5J,5K;
+Peru: 10: 12: SA: -10.00: 76.00: 5.0: OA:
+    OA,OB,OC,4T;
+Venezuela: 09: 12: SA: 8.00: 66.00: 4.0: YV:
+    YV,YW,YX,YY,4M;
+South Africa: 38: 57: AF: -29.07: -22.63: -2.0: ZS:
+    ZS,ZR,ZT,ZU,S8;
+";
+
+/// ISO 3166 country codes by primary prefix, for flag emoji. Entities that aren't
+/// countries fly their country's flag (Hawaii the US, Scotland the UK, ...).
+const FLAGS: &[(&str, &str)] = &[
+    ("K", "US"),
+    ("KL", "US"),
+    ("KH6", "US"),
+    ("KH2", "GU"),
+    ("KP4", "PR"),
+    ("KP2", "VI"),
+    ("VE", "CA"),
+    ("XE", "MX"),
+    ("CO", "CU"),
+    ("G", "GB"),
+    ("GM", "GB"),
+    ("GW", "GB"),
+    ("GI", "GB"),
+    ("GD", "IM"),
+    ("GJ", "JE"),
+    ("GU", "GG"),
+    ("EI", "IE"),
+    ("DL", "DE"),
+    ("F", "FR"),
+    ("EA", "ES"),
+    ("EA6", "ES"),
+    ("EA8", "ES"),
+    ("CT", "PT"),
+    ("I", "IT"),
+    ("PA", "NL"),
+    ("ON", "BE"),
+    ("LX", "LU"),
+    ("HB", "CH"),
+    ("HB0", "LI"),
+    ("OE", "AT"),
+    ("OZ", "DK"),
+    ("LA", "NO"),
+    ("SM", "SE"),
+    ("OH", "FI"),
+    ("TF", "IS"),
+    ("SP", "PL"),
+    ("OK", "CZ"),
+    ("OM", "SK"),
+    ("HA", "HU"),
+    ("S5", "SI"),
+    ("9A", "HR"),
+    ("YU", "RS"),
+    ("YO", "RO"),
+    ("LZ", "BG"),
+    ("SV", "GR"),
+    ("LY", "LT"),
+    ("YL", "LV"),
+    ("ES", "EE"),
+    ("UR", "UA"),
+    ("UA", "RU"),
+    ("UA9", "RU"),
+    ("TA", "TR"),
+    ("4X", "IL"),
+    ("VU", "IN"),
+    ("HS", "TH"),
+    ("DU", "PH"),
+    ("YB", "ID"),
+    ("BY", "CN"),
+    ("BV", "TW"),
+    ("HL", "KR"),
+    ("JA", "JP"),
+    ("VK", "AU"),
+    ("ZL", "NZ"),
+    ("PY", "BR"),
+    ("LU", "AR"),
+    ("CE", "CL"),
+    ("HK", "CO"),
+    ("OA", "PE"),
+    ("YV", "VE"),
+    ("ZS", "ZA"),
+];
+
+/// A DXCC entity, as named in cty.dat
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+    pub name: String,
+    /// Primary prefix, e.g. "K" for the United States
+    pub prefix: String,
+    /// Two-letter continent: AF, AN, AS, EU, NA, OC, or SA
+    pub continent: String,
+}
+
+impl Entity {
+    /// Regional indicator flag for the entity's country, if it's in `FLAGS`
+    pub fn flag(&self) -> Option<String> {
+        let (_, code) = FLAGS.iter().find(|(prefix, _)| *prefix == self.prefix)?;
+        Some(
+            code.chars()
+                .filter_map(|c| char::from_u32(0x1F1E6 + (c as u32 - 'A' as u32)))
+                .collect(),
+        )
+    }
+}
+
+/// Resolves callsigns to DXCC entities by longest matching prefix, after
+/// cty.dat's exact-callsign exceptions
+#[derive(Debug, Default)]
+pub struct DxccTable {
+    entities: Vec<Entity>,
+    prefixes: HashMap<String, usize>,
+    exact: HashMap<String, usize>,
+}
+
+impl DxccTable {
+    /// The built-in table of common entities
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN).expect("built-in DXCC table is valid")
+    }
+
+    /// The configured cty.dat file, or the built-in table. Never downloads, so
+    /// replays stay offline; a `url` without a `cty_file` gets the built-in table.
+    pub fn from_config(config: Option<&DxccConfig>) -> Result<Self> {
+        match config.and_then(|config| config.cty_file.as_deref()) {
+            Some(path) => Self::from_file(path),
+            None => Ok(Self::builtin()),
+        }
+    }
+
+    pub fn from_file(path: &str) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read cty file: {}", path))?;
+        Self::parse(&contents).with_context(|| format!("Failed to parse cty file: {}", path))
+    }
+
+    /// Parse cty.dat: per entity, eight colon-terminated fields ending with the primary
+    /// prefix, then comma-separated prefixes ending with `;`. `=` marks an exact callsign;
+    /// zone and location overrides in `()`, `[]`, `<>`, `{}`, and `~~` are ignored.
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut table = Self::default();
+        for record in contents.split(';').map(str::trim).filter(|r| !r.is_empty()) {
+            let fields: Vec<&str> = record.splitn(9, ':').collect();
+            let [name, _, _, continent, _, _, _, primary, prefixes] = fields[..] else {
+                anyhow::bail!("Entity record has too few fields: {}", record);
+            };
+            let index = table.entities.len();
+            table.entities.push(Entity {
+                name: name.trim().to_string(),
+                // A leading * marks entities that only count for the WAE award
+                prefix: primary.trim().trim_start_matches('*').to_string(),
+                continent: continent.trim().to_string(),
+            });
+
+            for prefix in prefixes.split(',').map(str::trim) {
+                let prefix = prefix
+                    .split(['(', '[', '<', '{', '~'])
+                    .next()
+                    .unwrap_or_default()
+                    .to_uppercase();
+                match prefix.strip_prefix('=') {
+                    Some(call) => table.exact.insert(call.to_string(), index),
+                    None if !prefix.is_empty() => table.prefixes.insert(prefix, index),
+                    None => None,
+                };
+            }
+        }
+        if table.entities.is_empty() {
+            anyhow::bail!("No DXCC entities found");
+        }
+        Ok(table)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn lookup(&self, callsign: &str) -> Option<&Entity> {
+        let callsign = callsign.to_uppercase();
+        if let Some(index) = self.exact.get(&callsign) {
+            return Some(&self.entities[*index]);
+        }
+        (1..=callsign.len())
+            .rev()
+            .filter(|end| callsign.is_char_boundary(*end))
+            .find_map(|end| self.prefixes.get(&callsign[..end]))
+            .map(|index| &self.entities[*index])
+    }
+}
+
+/// The current DXCC table, swapped out whenever `[dxcc] url` is refreshed
+pub struct DxccStore {
+    client: reqwest::Client,
+    config: Option<DxccConfig>,
+    table: RwLock<Arc<DxccTable>>,
+}
+
+impl DxccStore {
+    /// Load the table from `url`, falling back to `cty_file` (where the last download
+    /// was saved) and then the built-in table if the download fails
+    pub async fn load(config: Option<&DxccConfig>) -> Result<Self> {
+        let store = Self {
+            client: reqwest::Client::new(),
+            config: config.cloned(),
+            table: RwLock::new(Arc::new(DxccTable::builtin())),
+        };
+        let Some(config) = config else {
+            return Ok(store);
+        };
+
+        let table = match &config.url {
+            Some(url) => match store.download(url).await {
+                Ok(table) => table,
+                Err(e) => {
+                    warn!("{:?}", e);
+                    match &config.cty_file {
+                        Some(path) if std::path::Path::new(path).exists() => {
+                            DxccTable::from_file(path)?
+                        }
+                        _ => {
+                            warn!("Using the built-in DXCC table until the next refresh");
+                            DxccTable::builtin()
+                        }
+                    }
+                }
+            },
+            None => DxccTable::from_config(Some(config))?,
+        };
+        store.replace(table);
+        Ok(store)
+    }
+
+    pub fn current(&self) -> Arc<DxccTable> {
+        Arc::clone(&self.table.read().expect("DXCC table lock poisoned"))
+    }
+
+    fn replace(&self, table: DxccTable) {
+        info!("Loaded {} DXCC entities", table.len());
+        *self.table.write().expect("DXCC table lock poisoned") = Arc::new(table);
+    }
+
+    /// Fetch and parse cty.dat from `url`, saving a copy to `cty_file` if set
+    async fn download(&self, url: &str) -> Result<DxccTable> {
+        let response = self
+            .client
+            .get(url)
+            .header(USER_AGENT, "discord-callsign-bot")
+            .send()
+            .await
+            .with_context(|| format!("Failed to download cty file from {}", url))?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Downloading cty file from {} returned {}",
+                url,
+                response.status()
+            );
+        }
+        let contents = response
+            .text()
+            .await
+            .with_context(|| format!("Failed to read cty file from {}", url))?;
+        let table = DxccTable::parse(&contents)
+            .with_context(|| format!("Failed to parse cty file from {}", url))?;
+
+        if let Some(path) = self.config.as_ref().and_then(|c| c.cty_file.as_deref()) {
+            if let Err(e) = std::fs::write(path, &contents) {
+                warn!("Failed to save cty file to {}: {}", path, e);
+            }
+        }
+        Ok(table)
+    }
+
+    /// Reload the table every `refresh_secs` from `url`, or from `cty_file` when there's
+    /// no URL, keeping the current table if that fails
+    pub fn spawn_refresh(self: &Arc<Self>) {
+        let Some(config) = &self.config else {
+            return;
+        };
+        if config.refresh_secs == 0 {
+            return;
+        }
+        info!(
+            "Refreshing the DXCC table every {}s from {}",
+            config.refresh_secs,
+            config
+                .url
+                .as_deref()
+                .or(config.cty_file.as_deref())
+                .unwrap_or_default()
+        );
+
+        let store = Arc::clone(self);
+        let period = Duration::from_secs(config.refresh_secs);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            // The first tick fires immediately, right after the initial load
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(config) = &store.config else {
+                    return;
+                };
+                let refreshed = match &config.url {
+                    Some(url) => store.download(url).await,
+                    None => DxccTable::from_config(Some(config)),
+                };
+                match refreshed {
+                    Ok(table) => store.replace(table),
+                    Err(e) => warn!("Keeping the current DXCC table: {:?}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_lookup() {
+        let table = DxccTable::builtin();
+        let name = |callsign: &str| table.lookup(callsign).map(|e| e.name.as_str());
+        assert_eq!(name("W6JSV"), Some("United States"));
+        assert_eq!(name("KH6ABC"), Some("Hawaii"));
+        assert_eq!(name("AL7AB"), Some("Alaska"));
+        assert_eq!(name("VE3ABC"), Some("Canada"));
+        assert_eq!(name("2E0ABC"), Some("England"));
+        assert_eq!(name("MM0ABC"), Some("Scotland"));
+        assert_eq!(name("ua9abc"), Some("Asiatic Russia"));
+        assert_eq!(name("UA3ABC"), Some("European Russia"));
+        assert_eq!(name("QQ1AA"), None);
+
+        // Every flag belongs to an entity in the built-in table
+        for (prefix, _) in FLAGS {
+            assert!(
+                table.entities.iter().any(|e| e.prefix == *prefix),
+                "{}",
+                prefix
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_cty_format() {
+        let table = DxccTable::parse(
+            "Sov Mil Order of Malta:   15:  28:  EU:   41.90:   -12.43:    -1.0:  1A:
+                1A;
+            Italy:                    15:  28:  EU:   42.82:   -12.58:    -1.0:  I:
+                I,=II0ABC(15)[28],IZ<42.1/12.2>{EU}~-1.0~;
+            *Sicily:                  15:  28:  EU:   37.50:   -14.00:    -1.0:  *IT9:
+                IT9,IB9;",
+        )
+        .unwrap();
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.lookup("IZ2ABC").unwrap().name, "Italy");
+        assert_eq!(table.lookup("II0ABC").unwrap().name, "Italy");
+        let sicily = table.lookup("IT9XYZ").unwrap();
+        assert_eq!(sicily.prefix, "IT9");
+        assert_eq!(sicily.continent, "EU");
+        assert_eq!(
+            table.lookup("1A0KM").unwrap().name,
+            "Sov Mil Order of Malta"
+        );
+
+        assert!(DxccTable::parse("Nowhere: 1: 2;").is_err());
+        assert!(DxccTable::parse("").is_err());
+    }
+
+    #[test]
+    fn test_flag() {
+        let table = DxccTable::builtin();
+        assert_eq!(table.lookup("W6JSV").unwrap().flag().as_deref(), Some("🇺🇸"));
+        assert_eq!(
+            table.lookup("GM4ABC").unwrap().flag().as_deref(),
+            Some("🇬🇧")
+        );
+        let unflagged = Entity {
+            name: "Sov Mil Order of Malta".to_string(),
+            prefix: "1A".to_string(),
+            continent: "EU".to_string(),
+        };
+        assert_eq!(unflagged.flag(), None);
+    }
+
+    #[tokio::test]
+    async fn test_store_falls_back_to_saved_file() {
+        let dir = std::env::temp_dir().join(format!("callsign-bot-dxcc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cty.dat");
+        std::fs::write(
+            &path,
+            "Monaco: 14: 27: EU: 43.73: -7.40: -1.0: 3A:\n    3A;",
+        )
+        .unwrap();
+
+        let config = |url: Option<&str>| DxccConfig {
+            cty_file: Some(path.to_string_lossy().to_string()),
+            url: url.map(str::to_string),
+            refresh_secs: 0,
+        };
+        // Nothing listens on port 1, so the download fails and the saved copy is used
+        let store = DxccStore::load(Some(&config(Some("http://127.0.0.1:1/cty.dat"))))
+            .await
+            .unwrap();
+        assert_eq!(store.current().len(), 1);
+        assert_eq!(store.current().lookup("3A2AB").unwrap().name, "Monaco");
+
+        let store = DxccStore::load(Some(&config(None))).await.unwrap();
+        assert_eq!(store.current().len(), 1);
+
+        let store = DxccStore::load(None).await.unwrap();
+        assert!(store.current().len() > 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
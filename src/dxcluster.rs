@@ -0,0 +1,333 @@
+use crate::config::DxClusterConfig;
+use anyhow::{Context, Result};
+use discord_callsign_bot::Backoff;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// Spots buffered for the announcer before the reader waits
+const SPOT_CAPACITY: usize = 256;
+
+/// Modes recognized in a spot's comment, checked word by word
+const MODES: [&str; 14] = [
+    "CW", "SSB", "USB", "LSB", "FT8", "FT4", "RTTY", "PSK31", "PSK", "JT65", "AM", "FM", "SSTV",
+    "MSK144",
+];
+
+/// Amateur bands by lower and upper edge in kHz, covering every region's allocation
+const BANDS: [(f64, f64, &str); 14] = [
+    (1800.0, 2000.0, "160m"),
+    (3500.0, 4000.0, "80m"),
+    (5330.0, 5410.0, "60m"),
+    (7000.0, 7300.0, "40m"),
+    (10100.0, 10150.0, "30m"),
+    (14000.0, 14350.0, "20m"),
+    (18068.0, 18168.0, "17m"),
+    (21000.0, 21450.0, "15m"),
+    (24890.0, 24990.0, "12m"),
+    (28000.0, 29700.0, "10m"),
+    (50000.0, 54000.0, "6m"),
+    (144000.0, 148000.0, "2m"),
+    (222000.0, 225000.0, "1.25m"),
+    (420000.0, 450000.0, "70cm"),
+];
+
+/// One `DX de` line from a cluster
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spot {
+    /// Who reported it, without the SSID some nodes append (e.g. "W6JSV-#")
+    pub spotter: String,
+    pub frequency_khz: f64,
+    /// The station heard
+    pub dx: String,
+    pub comment: String,
+    /// UTC time as sent by the cluster, e.g. "2359Z"
+    pub time: Option<String>,
+}
+
+impl Spot {
+    /// The mode named in the comment, e.g. "FT8"
+    pub fn mode(&self) -> Option<&'static str> {
+        self.comment.split_whitespace().find_map(|word| {
+            MODES
+                .iter()
+                .find(|mode| word.eq_ignore_ascii_case(mode))
+                .copied()
+        })
+    }
+
+    pub fn band(&self) -> Option<&'static str> {
+        band(self.frequency_khz)
+    }
+}
+
+pub fn band(frequency_khz: f64) -> Option<&'static str> {
+    BANDS
+        .iter()
+        .find(|(low, high, _)| (*low..=*high).contains(&frequency_khz))
+        .map(|(_, _, name)| *name)
+}
+
+/// Parse `DX de W6JSV:     14025.0  JA1ABC       CW 599                 0012Z`;
+/// any other line (announcements, talk, prompts) is `None`
+pub fn parse_spot(line: &str) -> Option<Spot> {
+    let rest = line.trim().strip_prefix("DX de ")?;
+    let (spotter, rest) = rest.split_once(':')?;
+    let spotter = base_call(spotter.trim());
+
+    let mut words = rest.split_whitespace();
+    let frequency_khz: f64 = words.next()?.parse().ok()?;
+    let dx = words.next()?.to_uppercase();
+    let mut comment: Vec<&str> = words.collect();
+
+    // The time is the last word, though some nodes follow it with the spotter's locator
+    let time_index = comment.iter().rposition(|word| is_spot_time(word));
+    let time = time_index.map(|index| comment[index].to_string());
+    if let Some(index) = time_index {
+        comment.truncate(index);
+    }
+
+    if spotter.is_empty() || dx.is_empty() {
+        return None;
+    }
+    Some(Spot {
+        spotter,
+        frequency_khz,
+        dx,
+        comment: comment.join(" "),
+        time,
+    })
+}
+
+/// "0012Z"
+fn is_spot_time(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    bytes.len() == 5 && bytes[4] == b'Z' && bytes[..4].iter().all(u8::is_ascii_digit)
+}
+
+/// Drop the SSID or node marker from a spotter, e.g. "W6JSV-#" or "W6JSV-2"
+fn base_call(callsign: &str) -> String {
+    callsign
+        .split('-')
+        .next()
+        .unwrap_or_default()
+        .to_uppercase()
+}
+
+/// "📡 **W6JSV** spotted on 14.025 MHz (20m, CW) by JA1ABC: 599 up 1 (0012Z)", or with
+/// `spotted` false, "📡 **W6JSV** spotted JA1ABC on ..."
+pub fn announcement(spot: &Spot, spotted: bool) -> String {
+    let details = [spot.band(), spot.mode()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut message = if spotted {
+        format!(
+            "📡 **{}** spotted on {:.3} MHz",
+            spot.dx,
+            spot.frequency_khz / 1000.0
+        )
+    } else {
+        format!(
+            "📡 **{}** spotted {} on {:.3} MHz",
+            spot.spotter,
+            spot.dx,
+            spot.frequency_khz / 1000.0
+        )
+    };
+    if !details.is_empty() {
+        message.push_str(&format!(" ({})", details));
+    }
+    if spotted {
+        message.push_str(&format!(" by {}", spot.spotter));
+    }
+    // The mode is already in the details, so only the rest of the comment follows
+    let mut remarks: Vec<&str> = spot.comment.split_whitespace().collect();
+    if let Some(index) = spot.mode().and_then(|mode| {
+        remarks
+            .iter()
+            .position(|word| word.eq_ignore_ascii_case(mode))
+    }) {
+        remarks.remove(index);
+    }
+    if !remarks.is_empty() {
+        message.push_str(&format!(": {}", remarks.join(" ")));
+    }
+    if let Some(time) = &spot.time {
+        message.push_str(&format!(" ({})", time));
+    }
+    message
+}
+
+/// Remembers what was announced so a station spotted by many people in a few
+/// minutes is posted once per band
+pub struct SpotDedup {
+    window: Duration,
+    seen: HashMap<(u64, String, String), Instant>,
+}
+
+impl SpotDedup {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Whether to announce `callsign` on `band` in a guild, recording it if so
+    pub fn should_announce(&mut self, guild_id: u64, callsign: &str, band: &str) -> bool {
+        let now = Instant::now();
+        self.seen
+            .retain(|_, announced| now.duration_since(*announced) < self.window);
+        let key = (guild_id, callsign.to_string(), band.to_string());
+        if self.seen.contains_key(&key) {
+            return false;
+        }
+        self.seen.insert(key, now);
+        true
+    }
+}
+
+/// Connect to the cluster in the background, reconnecting with backoff, and
+/// deliver every spot on the returned channel
+pub fn spawn(config: DxClusterConfig) -> mpsc::Receiver<Spot> {
+    let (tx, rx) = mpsc::channel(SPOT_CAPACITY);
+    tokio::spawn(async move {
+        let backoff = Backoff::new(
+            Duration::from_secs(config.reconnect_initial_secs),
+            Duration::from_secs(config.reconnect_max_secs),
+        );
+        let mut attempt = 0;
+        loop {
+            let started = Instant::now();
+            match read_spots(&config, &tx).await {
+                Ok(()) => info!("DX cluster {} closed the connection", config.host),
+                Err(e) => warn!("DX cluster {} connection failed: {:?}", config.host, e),
+            }
+            if tx.is_closed() {
+                return;
+            }
+            // A connection that stayed up a while starts the backoff over
+            if started.elapsed() > backoff.max {
+                attempt = 0;
+            }
+            attempt += 1;
+            tokio::time::sleep(backoff.jittered(attempt)).await;
+        }
+    });
+    rx
+}
+
+/// One connection: log in with the callsign, then forward spots until it closes
+async fn read_spots(config: &DxClusterConfig, tx: &mpsc::Sender<Spot>) -> Result<()> {
+    let stream = TcpStream::connect((config.host.as_str(), config.port))
+        .await
+        .with_context(|| format!("Failed to connect to {}:{}", config.host, config.port))?;
+    let (reader, mut writer) = stream.into_split();
+
+    // Clusters buffer input, so the login can go out before the prompt arrives
+    writer
+        .write_all(format!("{}\r\n", config.callsign).as_bytes())
+        .await
+        .context("Failed to log in to the DX cluster")?;
+    info!(
+        "Connected to DX cluster {}:{} as {}",
+        config.host, config.port, config.callsign
+    );
+
+    // Read bytes rather than lines of text, since nodes relay non-UTF-8 comments
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .await
+            .context("Failed to read from the DX cluster")?;
+        if read == 0 {
+            return Ok(());
+        }
+        let Some(spot) = parse_spot(&String::from_utf8_lossy(&line)) else {
+            continue;
+        };
+        debug!("Spot: {:?}", spot);
+        if tx.send(spot).await.is_err() {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spot() {
+        let spot =
+            parse_spot("DX de W6JSV-#:    14025.0  ja1abc       CW 599 up 1                 0012Z")
+                .unwrap();
+        assert_eq!(spot.spotter, "W6JSV");
+        assert_eq!(spot.frequency_khz, 14025.0);
+        assert_eq!(spot.dx, "JA1ABC");
+        assert_eq!(spot.comment, "CW 599 up 1");
+        assert_eq!(spot.time.as_deref(), Some("0012Z"));
+        assert_eq!(spot.mode(), Some("CW"));
+        assert_eq!(spot.band(), Some("20m"));
+
+        let spot = parse_spot("DX de KI7QCF:    50313.0  W6JSV        ft8 -12dB       2359Z CN87")
+            .unwrap();
+        assert_eq!(spot.comment, "ft8 -12dB");
+        assert_eq!(spot.mode(), Some("FT8"));
+        assert_eq!(spot.band(), Some("6m"));
+
+        let spot = parse_spot("DX de N0CALL:     7074.0  VE3ABC").unwrap();
+        assert_eq!(spot.comment, "");
+        assert_eq!(spot.time, None);
+        assert_eq!(spot.mode(), None);
+
+        assert_eq!(parse_spot("W6JSV de N0CALL: hello"), None);
+        assert_eq!(parse_spot("DX de W6JSV: not-a-frequency JA1ABC"), None);
+        assert_eq!(parse_spot("login: "), None);
+    }
+
+    #[test]
+    fn test_announcement() {
+        let spot =
+            parse_spot("DX de JA1ABC:    14025.0  W6JSV        CW 599 up 1      0012Z").unwrap();
+        assert_eq!(
+            announcement(&spot, true),
+            "📡 **W6JSV** spotted on 14.025 MHz (20m, CW) by JA1ABC: 599 up 1 (0012Z)"
+        );
+
+        let spot = parse_spot("DX de W6JSV:    15000.0  JA1ABC").unwrap();
+        assert_eq!(
+            announcement(&spot, false),
+            "📡 **W6JSV** spotted JA1ABC on 15.000 MHz"
+        );
+    }
+
+    #[test]
+    fn test_band() {
+        assert_eq!(band(1840.0), Some("160m"));
+        assert_eq!(band(14350.0), Some("20m"));
+        assert_eq!(band(146520.0), Some("2m"));
+        assert_eq!(band(15000.0), None);
+    }
+
+    #[test]
+    fn test_spot_dedup() {
+        let mut dedup = SpotDedup::new(Duration::from_secs(600));
+        assert!(dedup.should_announce(1, "W6JSV", "20m"));
+        assert!(!dedup.should_announce(1, "W6JSV", "20m"));
+        assert!(dedup.should_announce(1, "W6JSV", "40m"));
+        assert!(dedup.should_announce(2, "W6JSV", "20m"));
+
+        let mut dedup = SpotDedup::new(Duration::ZERO);
+        assert!(dedup.should_announce(1, "W6JSV", "20m"));
+        assert!(dedup.should_announce(1, "W6JSV", "20m"));
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Most topics one mentor can register
+const MAX_TOPICS: usize = 10;
+
+/// Longest topic kept, in characters
+const MAX_TOPIC_CHARS: usize = 32;
+
+/// A mentor as shown by `/elmer find`, with what the roster knows about them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mentor<'a> {
+    pub user_id: u64,
+    /// Their callsign and name from the latest roster, when they're listed
+    pub listing: Option<(String, String)>,
+    pub topics: &'a BTreeSet<String>,
+}
+
+/// Lowercase with single spaces and no leading `#`, so "#HF  Antennas" and
+/// "hf antennas" are the same topic
+pub fn normalize(topic: &str) -> String {
+    topic
+        .trim()
+        .trim_start_matches('#')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .take(MAX_TOPIC_CHARS)
+        .collect()
+}
+
+/// Comma-separated topics from `/elmer register`, up to `MAX_TOPICS`
+pub fn parse_topics(text: &str) -> BTreeSet<String> {
+    text.split(',')
+        .map(normalize)
+        .filter(|topic| !topic.is_empty())
+        .take(MAX_TOPICS)
+        .collect()
+}
+
+/// Whether a registered topic shares a word with a search, allowing either to be a
+/// prefix of the other: "antenna" finds "antennas", but "hf" doesn't find "vhf"
+fn covers(topic: &str, search: &str) -> bool {
+    topic.split(' ').any(|word| {
+        search
+            .split(' ')
+            .any(|term| word.starts_with(term) || term.starts_with(word))
+    })
+}
+
+/// Members with a topic covering `search`, in user ID order
+pub fn find<'a>(
+    elmers: &'a BTreeMap<u64, BTreeSet<String>>,
+    search: &str,
+) -> Vec<(u64, &'a BTreeSet<String>)> {
+    let search = normalize(search);
+    if search.is_empty() {
+        return Vec::new();
+    }
+    elmers
+        .iter()
+        .filter(|(_, topics)| topics.iter().any(|topic| covers(topic, &search)))
+        .map(|(user_id, topics)| (*user_id, topics))
+        .collect()
+}
+
+/// Every topic someone has registered, for when a search finds nobody
+pub fn all_topics(elmers: &BTreeMap<u64, BTreeSet<String>>) -> BTreeSet<&str> {
+    elmers.values().flatten().map(String::as_str).collect()
+}
+
+/// `/elmer find` results: one line per mentor, or the topics that do have mentors
+pub fn list(search: &str, mentors: &[Mentor], topics: &BTreeSet<&str>) -> String {
+    let search = normalize(search);
+    if mentors.is_empty() {
+        let mut text = format!("Nobody has offered to mentor `{}` yet.", search);
+        if !topics.is_empty() {
+            let topics: Vec<&str> = topics.iter().copied().collect();
+            text.push_str(&format!(" Topics with elmers: {}.", topics.join(", ")));
+        }
+        return text;
+    }
+    let mut lines = vec![format!("**Elmers for {}** ({})", search, mentors.len())];
+    lines.extend(mentors.iter().map(|mentor| {
+        let who = match &mentor.listing {
+            Some((callsign, name)) if !name.is_empty() => {
+                format!("<@{}> {} ({})", mentor.user_id, callsign, name)
+            }
+            Some((callsign, _)) => format!("<@{}> {}", mentor.user_id, callsign),
+            None => format!("<@{}>", mentor.user_id),
+        };
+        let topics: Vec<&str> = mentor.topics.iter().map(String::as_str).collect();
+        format!("{}: {}", who, topics.join(", "))
+    }));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topics(list: &[&str]) -> BTreeSet<String> {
+        list.iter().map(|topic| topic.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_topics() {
+        assert_eq!(
+            parse_topics("#HF  Antennas, ft8,, , FT8 "),
+            topics(&["ft8", "hf antennas"])
+        );
+        assert!(parse_topics(" , ").is_empty());
+        let many: Vec<String> = (0..20).map(|i| format!("topic {}", i)).collect();
+        assert_eq!(parse_topics(&many.join(",")).len(), MAX_TOPICS);
+        assert_eq!(normalize(&"x".repeat(50)).len(), MAX_TOPIC_CHARS);
+    }
+
+    #[test]
+    fn test_find() {
+        let elmers = BTreeMap::from([
+            (1, topics(&["antennas", "hf"])),
+            (2, topics(&["ft8", "digital modes"])),
+            (3, topics(&["vhf contesting"])),
+        ]);
+        let ids = |search| {
+            find(&elmers, search)
+                .iter()
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids("Antenna"), vec![1]);
+        assert_eq!(ids("HF antennas"), vec![1]);
+        assert_eq!(ids("hf"), vec![1]);
+        assert_eq!(ids("contest"), vec![3]);
+        assert_eq!(ids("digital"), vec![2]);
+        assert!(ids("satellites").is_empty());
+        assert!(ids(" ").is_empty());
+        assert_eq!(all_topics(&elmers).len(), 5);
+    }
+
+    #[test]
+    fn test_list() {
+        let antennas = topics(&["antennas", "hf"]);
+        let mentors = [
+            Mentor {
+                user_id: 1,
+                listing: Some(("W6JSV".to_string(), "Jay".to_string())),
+                topics: &antennas,
+            },
+            Mentor {
+                user_id: 2,
+                listing: None,
+                topics: &antennas,
+            },
+        ];
+        assert_eq!(
+            list("Antennas", &mentors, &BTreeSet::new()),
+            "**Elmers for antennas** (2)\n<@1> W6JSV (Jay): antennas, hf\n<@2>: antennas, hf"
+        );
+        assert_eq!(
+            list("satellites", &[], &BTreeSet::from(["ft8", "hf"])),
+            "Nobody has offered to mentor `satellites` yet. Topics with elmers: ft8, hf."
+        );
+        assert_eq!(
+            list("satellites", &[], &BTreeSet::new()),
+            "Nobody has offered to mentor `satellites` yet."
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use crate::qrz::is_session_error;
+use qrz_xml::QrzXmlError;
+use reqwest::StatusCode;
+use std::fmt::Display;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Failures from QRZ, GitHub, and output files, classified so callers can pick a
+/// retry and alert policy per class
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Credentials are missing or were rejected; retrying won't help until they're fixed
+    #[error("{0}")]
+    Auth(String),
+    /// The service is throttling requests
+    #[error("{0}")]
+    RateLimited(String),
+    /// The callsign, repository, file, or gist doesn't exist
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+    /// Network failures, server errors, and unexpected responses
+    #[error("{0}")]
+    Service(String),
+}
+
+impl Error {
+    /// The class of the first `Error` in an `anyhow` chain, if any
+    pub fn classify(error: &anyhow::Error) -> Option<&Error> {
+        error.chain().find_map(|e| e.downcast_ref::<Error>())
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Auth(_) => "auth",
+            Self::RateLimited(_) => "rate-limited",
+            Self::NotFound(_) => "not-found",
+            Self::Io { .. } => "io",
+            Self::Service(_) => "service",
+        }
+    }
+
+    /// Whether the same request may succeed later without anyone fixing anything
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::RateLimited(_) | Self::Service(_))
+    }
+
+    /// Prefix the message with what was being attempted, keeping the class
+    pub fn context(self, context: impl Display) -> Self {
+        match self {
+            Self::Auth(message) => Self::Auth(format!("{}: {}", context, message)),
+            Self::RateLimited(message) => Self::RateLimited(format!("{}: {}", context, message)),
+            Self::NotFound(message) => Self::NotFound(format!("{}: {}", context, message)),
+            Self::Io {
+                context: inner,
+                source,
+            } => Self::Io {
+                context: format!("{}: {}", context, inner),
+                source,
+            },
+            Self::Service(message) => Self::Service(format!("{}: {}", context, message)),
+        }
+    }
+
+    /// Classify an unsuccessful GitHub API response
+    pub fn from_github(status: StatusCode, rate_limit_remaining: Option<&str>, body: &str) -> Self {
+        let message = format!("GitHub API returned error {}: {}", status, body);
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited(message),
+            // GitHub reports an exhausted rate limit as 403 with no requests remaining
+            StatusCode::FORBIDDEN if rate_limit_remaining == Some("0") => {
+                Self::RateLimited(message)
+            }
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Self::Auth(message),
+            StatusCode::NOT_FOUND => Self::NotFound(message),
+            _ => Self::Service(message),
+        }
+    }
+}
+
+impl From<QrzXmlError> for Error {
+    fn from(error: QrzXmlError) -> Self {
+        let message = error.to_string();
+        match error {
+            QrzXmlError::CallsignNotFound { .. } | QrzXmlError::DxccNotFound { .. } => {
+                Self::NotFound(message)
+            }
+            QrzXmlError::RateLimitExceeded | QrzXmlError::ConnectionRefused => {
+                Self::RateLimited(message)
+            }
+            QrzXmlError::AuthenticationFailed { .. } => Self::Auth(message),
+            ref error if is_session_error(error) || error.is_permission_error() => {
+                Self::Auth(message)
+            }
+            _ => Self::Service(message),
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        Self::Service(error.to_string())
+    }
+}
+
+/// `anyhow`-style context for results whose error converts into [`Error`]
+pub trait ResultExt<T> {
+    fn context(self, context: impl Display) -> Result<T>;
+    fn with_context<C: Display>(self, context: impl FnOnce() -> C) -> Result<T>;
+}
+
+impl<T, E: Into<Error>> ResultExt<T> for std::result::Result<T, E> {
+    fn context(self, context: impl Display) -> Result<T> {
+        self.map_err(|e| e.into().context(context))
+    }
+
+    fn with_context<C: Display>(self, context: impl FnOnce() -> C) -> Result<T> {
+        self.map_err(|e| e.into().context(context()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_github() {
+        let class = |status, remaining| Error::from_github(status, remaining, "").label();
+        assert_eq!(class(StatusCode::UNAUTHORIZED, None), "auth");
+        assert_eq!(class(StatusCode::FORBIDDEN, Some("12")), "auth");
+        assert_eq!(class(StatusCode::FORBIDDEN, Some("0")), "rate-limited");
+        assert_eq!(class(StatusCode::TOO_MANY_REQUESTS, None), "rate-limited");
+        assert_eq!(class(StatusCode::NOT_FOUND, None), "not-found");
+        assert_eq!(class(StatusCode::BAD_GATEWAY, None), "service");
+    }
+
+    #[test]
+    fn test_from_qrz() {
+        let class = |error: QrzXmlError| Error::from(error).label();
+        assert_eq!(
+            class(QrzXmlError::callsign_not_found("N0CALL")),
+            "not-found"
+        );
+        assert_eq!(class(QrzXmlError::SessionExpired), "auth");
+        assert_eq!(class(QrzXmlError::SubscriptionRequired), "auth");
+        assert_eq!(class(QrzXmlError::RateLimitExceeded), "rate-limited");
+        assert_eq!(class(QrzXmlError::api_error("Unknown")), "service");
+    }
+
+    #[test]
+    fn test_context_keeps_class() {
+        let error = Error::NotFound("no such gist".to_string()).context("Failed to fetch gist 1");
+        assert_eq!(error.to_string(), "Failed to fetch gist 1: no such gist");
+        assert_eq!(error.label(), "not-found");
+
+        let wrapped = anyhow::Error::new(error).context("Failed to publish");
+        assert_eq!(
+            Error::classify(&wrapped).map(Error::label),
+            Some("not-found")
+        );
+        assert!(Error::classify(&anyhow::anyhow!("other")).is_none());
+    }
+}
@@ -0,0 +1,310 @@
+use crate::config::schedule::Schedule;
+use crate::config::ScheduleConfig;
+use crate::output::{sanitize_field, MAX_NAME_CHARS};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest event description kept, in characters; Discord's scheduled event limit
+pub const MAX_DESCRIPTION_CHARS: usize = 1000;
+
+/// How far ahead the iCalendar export lists occurrences
+const CALENDAR_DAYS: i64 = 90;
+
+/// Most occurrences exported per event, for crons that fire every few minutes
+const MAX_CALENDAR_OCCURRENCES: usize = 200;
+
+/// A recurring club event created with `/event create`; kept in `Storage`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClubEvent {
+    /// Unique within the guild, for `/event delete`
+    pub id: u32,
+    pub name: String,
+    /// Standard 5-field cron expression for each start, evaluated in UTC
+    pub cron: String,
+    pub duration_mins: u32,
+    pub description: Option<String>,
+    /// Where it happens, e.g. a repeater frequency or meeting address
+    pub location: Option<String>,
+    pub created_by: u64,
+    /// Start of the latest occurrence a Discord scheduled event was created for
+    #[serde(default)]
+    pub discord_event_start: Option<DateTime<Utc>>,
+}
+
+impl ClubEvent {
+    /// `id` is filled in by `Storage::add_event()`
+    pub fn new(
+        name: &str,
+        cron: &str,
+        duration_mins: u32,
+        description: Option<&str>,
+        location: Option<&str>,
+        created_by: u64,
+    ) -> Result<Self> {
+        let event = Self {
+            id: 0,
+            name: sanitize_field(name, MAX_NAME_CHARS),
+            cron: cron.trim().to_string(),
+            duration_mins,
+            description: description.map(|d| sanitize_field(d, MAX_DESCRIPTION_CHARS)),
+            location: location.map(|l| sanitize_field(l, MAX_NAME_CHARS)),
+            created_by,
+            discord_event_start: None,
+        };
+        event.schedule()?;
+        Ok(event)
+    }
+
+    pub fn schedule(&self) -> Result<Schedule> {
+        Schedule::from_config(&ScheduleConfig {
+            interval_secs: None,
+            cron: Some(self.cron.clone()),
+        })
+    }
+
+    /// The first start strictly after `after`
+    pub fn next_start(&self, after: DateTime<Utc>) -> Result<DateTime<Utc>> {
+        self.schedule()?.next_after(after)
+    }
+
+    pub fn end(&self, start: DateTime<Utc>) -> DateTime<Utc> {
+        start + Duration::minutes(self.duration_mins.into())
+    }
+
+    /// The start whose reminder, `lead` before it, falls in `(since, now]`
+    pub fn reminder_due(
+        &self,
+        lead: Duration,
+        since: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let start = self.next_start(since + lead)?;
+        Ok((start - lead <= now).then_some(start))
+    }
+}
+
+/// "⏰ **Weekly Net** starts <t:..:R> (<t:..:F>) at 146.520 MHz", then the description
+pub fn reminder(event: &ClubEvent, start: DateTime<Utc>) -> String {
+    let mut text = format!(
+        "⏰ **{}** starts <t:{}:R> (<t:{}:F>)",
+        event.name,
+        start.timestamp(),
+        start.timestamp()
+    );
+    if let Some(location) = &event.location {
+        text.push_str(&format!(" at {}", location));
+    }
+    if let Some(description) = &event.description {
+        text.push_str(&format!("\n{}", description));
+    }
+    text
+}
+
+/// One line per event for `/event list`
+pub fn list(events: &[ClubEvent], now: DateTime<Utc>) -> String {
+    if events.is_empty() {
+        return "No events are scheduled.".to_string();
+    }
+    let mut lines = vec!["**Events**".to_string()];
+    lines.extend(events.iter().map(|event| {
+        let next = match event.next_start(now) {
+            Ok(start) => format!("next <t:{}:F>", start.timestamp()),
+            Err(_) => "no upcoming dates".to_string(),
+        };
+        format!(
+            "`{}` **{}**: `{}` UTC, {} min, {}",
+            event.id, event.name, event.cron, event.duration_mins, next
+        )
+    }));
+    lines.join("\n")
+}
+
+/// An iCalendar (RFC 5545) feed of every event's occurrences over the next
+/// `CALENDAR_DAYS`, including any in progress, for phones to subscribe to
+pub fn calendar(guild_id: u64, name: &str, events: &[ClubEvent], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//discord-callsign-bot//events//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ];
+    let until = now + Duration::days(CALENDAR_DAYS);
+    for event in events {
+        let Ok(schedule) = event.schedule() else {
+            continue;
+        };
+        let mut after = now - Duration::minutes(event.duration_mins.into());
+        for _ in 0..MAX_CALENDAR_OCCURRENCES {
+            let Ok(start) = schedule.next_after(after) else {
+                break;
+            };
+            if start > until {
+                break;
+            }
+            lines.extend([
+                "BEGIN:VEVENT".to_string(),
+                format!(
+                    "UID:{}-{}-{}@discord-callsign-bot",
+                    guild_id,
+                    event.id,
+                    start.timestamp()
+                ),
+                // The start rather than the export time, so the feed only changes
+                // when the events do
+                format!("DTSTAMP:{}", ics_time(start)),
+                format!("DTSTART:{}", ics_time(start)),
+                format!("DTEND:{}", ics_time(event.end(start))),
+                format!("SUMMARY:{}", escape(&event.name)),
+            ]);
+            if let Some(location) = &event.location {
+                lines.push(format!("LOCATION:{}", escape(location)));
+            }
+            if let Some(description) = &event.description {
+                lines.push(format!("DESCRIPTION:{}", escape(description)));
+            }
+            lines.push("END:VEVENT".to_string());
+            after = start;
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+/// "20261014T030000Z"
+fn ics_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape a TEXT value: backslashes, commas, semicolons, and newlines
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+        .replace('\n', "\\n")
+}
+
+/// Break a content line into 75-octet pieces, continuing each with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        // Continuation lines lose one octet to their leading space
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn weekly_net() -> ClubEvent {
+        ClubEvent::new(
+            "Weekly Net",
+            "0 3 * * WED",
+            60,
+            Some("Check in on the club repeater"),
+            Some("146.520 MHz"),
+            42,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_new_checks_cron() {
+        assert!(ClubEvent::new("Meeting", "every tuesday", 60, None, None, 42).is_err());
+        assert_eq!(weekly_net().name, "Weekly Net");
+    }
+
+    #[test]
+    fn test_reminder_due() {
+        let event = weekly_net();
+        let lead = Duration::minutes(60);
+        // 2026-10-14 is a Wednesday; the net starts at 03:00 UTC
+        let at = |h, m| Utc.with_ymd_and_hms(2026, 10, 14, h, m, 0).unwrap();
+        let start = at(3, 0);
+
+        assert_eq!(
+            event.reminder_due(lead, at(1, 58), at(1, 59)).unwrap(),
+            None
+        );
+        assert_eq!(
+            event.reminder_due(lead, at(1, 59), at(2, 0)).unwrap(),
+            Some(start)
+        );
+        // Already reminded
+        assert_eq!(event.reminder_due(lead, at(2, 0), at(2, 1)).unwrap(), None);
+        assert_eq!(event.end(start), at(4, 0));
+    }
+
+    #[test]
+    fn test_reminder() {
+        let event = weekly_net();
+        let start = Utc.with_ymd_and_hms(2026, 10, 14, 3, 0, 0).unwrap();
+        assert_eq!(
+            reminder(&event, start),
+            "⏰ **Weekly Net** starts <t:1791946800:R> (<t:1791946800:F>) at 146.520 MHz\n\
+             Check in on the club repeater"
+        );
+    }
+
+    #[test]
+    fn test_list() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 13, 0, 0, 0).unwrap();
+        assert_eq!(list(&[], now), "No events are scheduled.");
+        let mut event = weekly_net();
+        event.id = 1;
+        assert_eq!(
+            list(&[event], now),
+            "**Events**\n`1` **Weekly Net**: `0 3 * * WED` UTC, 60 min, next <t:1791946800:F>"
+        );
+    }
+
+    #[test]
+    fn test_calendar() {
+        let mut event = weekly_net();
+        event.id = 1;
+        event.description = Some("Check in, then rag chew; bring a friend".to_string());
+        // During the 2026-10-14 net, so it's still listed
+        let now = Utc.with_ymd_and_hms(2026, 10, 14, 3, 30, 0).unwrap();
+        let feed = calendar(7, "Test Club", &[event], now);
+
+        assert!(feed.starts_with(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//discord-callsign-bot//events//EN\r\n"
+        ));
+        assert!(feed.contains("X-WR-CALNAME:Test Club\r\n"));
+        assert!(feed.contains(
+            "BEGIN:VEVENT\r\n\
+             UID:7-1-1791946800@discord-callsign-bot\r\n\
+             DTSTAMP:20261014T030000Z\r\n\
+             DTSTART:20261014T030000Z\r\n\
+             DTEND:20261014T040000Z\r\n\
+             SUMMARY:Weekly Net\r\n\
+             LOCATION:146.520 MHz\r\n\
+             DESCRIPTION:Check in\\, then rag chew\\; bring a friend\r\n\
+             END:VEVENT\r\n"
+        ));
+        // 90 days of Wednesdays
+        assert_eq!(feed.matches("BEGIN:VEVENT").count(), 13);
+        assert!(feed.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn test_fold() {
+        let line = format!("SUMMARY:{}", "x".repeat(100));
+        let folded = fold(&line);
+        assert_eq!(folded.len(), line.len() + 3);
+        assert!(folded.split("\r\n").all(|piece| piece.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+        assert_eq!(fold("SUMMARY:Net"), "SUMMARY:Net");
+    }
+}
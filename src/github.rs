@@ -1,18 +1,69 @@
-use anyhow::{Context, Result};
+use crate::config::{CommitIdentity, GitHubConfig};
 use base64::{engine::general_purpose::STANDARD, Engine};
+use discord_callsign_bot::{Error, Result, ResultExt};
 use reqwest::header::{ACCEPT, AUTHORIZATION, USER_AGENT};
+use reqwest::Method;
 use serde::{Deserialize, Serialize};
-use std::env;
+use std::collections::HashMap;
 use tracing::{info, warn};
 
 pub struct GitHubClient {
     client: reqwest::Client,
     token: String,
+    author: Option<CommitIdentity>,
+    committer: Option<CommitIdentity>,
+    git_data_api: bool,
+}
+
+/// One file to write in a commit
+pub struct FileChange<'a> {
+    pub path: &'a str,
+    pub content: &'a str,
 }
 
 #[derive(Deserialize)]
 struct ContentResponse {
     sha: String,
+    #[serde(default)]
+    content: Option<String>,
+}
+
+impl ContentResponse {
+    /// The file's content, decoded from the API's base64
+    fn decoded(&self) -> Result<String> {
+        let encoded: String = self
+            .content
+            .as_deref()
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let bytes = STANDARD
+            .decode(encoded)
+            .map_err(|e| Error::Service(format!("Failed to decode GitHub file content: {}", e)))?;
+
+        String::from_utf8(bytes)
+            .map_err(|_| Error::Service("GitHub file content is not UTF-8".to_string()))
+    }
+}
+
+/// Whether `commit_file` made a commit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommitOutcome {
+    Committed,
+    /// The file already had this content, so no commit was made
+    Unchanged,
+}
+
+#[derive(Deserialize)]
+struct RepoResponse {
+    #[serde(default)]
+    permissions: Option<RepoPermissions>,
+}
+
+#[derive(Deserialize)]
+struct RepoPermissions {
+    push: bool,
 }
 
 #[derive(Serialize)]
@@ -22,31 +73,161 @@ struct UpdateFileRequest<'a> {
     branch: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     sha: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<&'a CommitIdentity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    committer: Option<&'a CommitIdentity>,
+}
+
+#[derive(Deserialize)]
+struct GistResponse {
+    id: String,
+    #[serde(default)]
+    files: HashMap<String, GistFile>,
+}
+
+#[derive(Deserialize)]
+struct GistFile {
+    #[serde(default)]
+    content: Option<String>,
+    /// Large files are truncated in API responses
+    #[serde(default)]
+    truncated: bool,
+}
+
+#[derive(Serialize)]
+struct GistRequest<'a> {
+    description: &'a str,
+    /// Only accepted when creating a gist
+    #[serde(skip_serializing_if = "Option::is_none")]
+    public: Option<bool>,
+    files: HashMap<&'a str, GistFileContent<'a>>,
+}
+
+#[derive(Serialize)]
+struct GistFileContent<'a> {
+    content: &'a str,
+}
+
+/// Git data API objects (refs, commits, trees, blobs) all answer with at least a SHA
+#[derive(Deserialize)]
+struct GitObject {
+    sha: String,
+}
+
+#[derive(Deserialize)]
+struct RefResponse {
+    object: GitObject,
+}
+
+#[derive(Deserialize)]
+struct CommitResponse {
+    tree: GitObject,
+}
+
+#[derive(Serialize)]
+struct CreateBlobRequest<'a> {
+    content: &'a str,
+    encoding: &'static str,
+}
+
+#[derive(Serialize)]
+struct CreateTreeRequest<'a> {
+    base_tree: &'a str,
+    tree: Vec<TreeEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct TreeEntry<'a> {
+    path: &'a str,
+    mode: &'static str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    sha: String,
+}
+
+#[derive(Serialize)]
+struct CreateCommitRequest<'a> {
+    message: &'a str,
+    tree: &'a str,
+    parents: [&'a str; 1],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<&'a CommitIdentity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    committer: Option<&'a CommitIdentity>,
+}
+
+#[derive(Serialize)]
+struct UpdateRefRequest<'a> {
+    sha: &'a str,
+    force: bool,
 }
 
 impl GitHubClient {
-    pub fn new() -> Result<Self> {
-        let token =
-            env::var("GITHUB_TOKEN").context("GITHUB_TOKEN environment variable not set")?;
+    pub fn new(config: &GitHubConfig) -> Result<Self> {
+        let token = config.token.clone().ok_or_else(|| {
+            Error::Auth("Set github.token, github.token_file, or GITHUB_TOKEN".to_string())
+        })?;
 
         let client = reqwest::Client::new();
 
-        Ok(Self { client, token })
+        Ok(Self {
+            client,
+            token,
+            author: config.author.clone(),
+            // GitHub would otherwise attribute the commit to the token's user
+            committer: config.committer.clone().or_else(|| config.author.clone()),
+            git_data_api: config.git_data_api,
+        })
     }
 
-    pub async fn commit_file(
+    /// Write files to a branch, skipping any whose content is unchanged. With the
+    /// git data API this is a single commit; otherwise each file is its own commit.
+    pub async fn commit_files(
+        &self,
+        repo: &str,
+        branch: &str,
+        files: &[FileChange<'_>],
+        message: &str,
+    ) -> Result<CommitOutcome> {
+        if self.git_data_api {
+            return self.commit_tree(repo, branch, files, message).await;
+        }
+
+        let mut outcome = CommitOutcome::Unchanged;
+        for file in files {
+            if self
+                .commit_file(repo, file.path, branch, file.content, message)
+                .await?
+                == CommitOutcome::Committed
+            {
+                outcome = CommitOutcome::Committed;
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Commit through the contents API, one file per commit
+    async fn commit_file(
         &self,
         repo: &str,
         path: &str,
         branch: &str,
         content: &str,
         message: &str,
-    ) -> Result<()> {
+    ) -> Result<CommitOutcome> {
         let url = format!("https://api.github.com/repos/{}/contents/{}", repo, path);
 
-        // Try to get existing file SHA (404 is expected for new files)
-        let sha = match self.get_file_sha(repo, path, branch).await {
-            Ok(sha) => Some(sha),
+        // Try to get the existing file (404 is expected for new files)
+        let sha = match self.get_file(repo, path, branch).await {
+            Ok(file) => {
+                // Identical content would only produce an empty commit
+                if file.decoded().is_ok_and(|existing| existing == content) {
+                    info!("{}/{} on branch {} is unchanged", repo, path, branch);
+                    return Ok(CommitOutcome::Unchanged);
+                }
+                Some(file.sha)
+            }
             Err(e) => {
                 warn!(
                     "Could not get file SHA for {}/{}: {} (file may not exist yet)",
@@ -66,56 +247,347 @@ impl GitHubClient {
             content: &encoded_content,
             branch,
             sha: sha.as_deref(),
+            author: self.author.as_ref(),
+            committer: self.committer.as_ref(),
         };
 
         let response = self
-            .client
-            .put(&url)
+            .request(Method::PUT, &url)
+            .json(&request_body)
+            .send()
+            .await
+            .context("Failed to send request to GitHub API")?;
+
+        if !response.status().is_success() {
+            return Err(error_response(response).await);
+        }
+
+        Ok(CommitOutcome::Committed)
+    }
+
+    /// Commit every changed file at once through the git data API: create blobs, a
+    /// tree on top of the branch head, and a commit, then fast-forward the branch
+    async fn commit_tree(
+        &self,
+        repo: &str,
+        branch: &str,
+        files: &[FileChange<'_>],
+        message: &str,
+    ) -> Result<CommitOutcome> {
+        let mut changed = Vec::new();
+        for file in files {
+            match self.get_file_content(repo, file.path, branch).await {
+                Ok(existing) if existing == file.content => {
+                    info!("{}/{} on branch {} is unchanged", repo, file.path, branch);
+                }
+                _ => changed.push(file),
+            }
+        }
+        if changed.is_empty() {
+            return Ok(CommitOutcome::Unchanged);
+        }
+
+        let api = format!("https://api.github.com/repos/{}/git", repo);
+        let head: RefResponse = self
+            .send_json(
+                Method::GET,
+                &format!("{}/ref/heads/{}", api, branch),
+                None::<&()>,
+            )
+            .await
+            .with_context(|| format!("Failed to read branch {}", branch))?;
+        let parent = head.object.sha;
+        let parent_commit: CommitResponse = self
+            .send_json(
+                Method::GET,
+                &format!("{}/commits/{}", api, parent),
+                None::<&()>,
+            )
+            .await
+            .context("Failed to read the branch head commit")?;
+
+        let mut tree = Vec::new();
+        for file in &changed {
+            let blob: GitObject = self
+                .send_json(
+                    Method::POST,
+                    &format!("{}/blobs", api),
+                    Some(&CreateBlobRequest {
+                        content: file.content,
+                        encoding: "utf-8",
+                    }),
+                )
+                .await
+                .with_context(|| format!("Failed to create blob for {}", file.path))?;
+            tree.push(TreeEntry {
+                path: file.path,
+                mode: "100644",
+                kind: "blob",
+                sha: blob.sha,
+            });
+        }
+
+        let tree: GitObject = self
+            .send_json(
+                Method::POST,
+                &format!("{}/trees", api),
+                Some(&CreateTreeRequest {
+                    base_tree: &parent_commit.tree.sha,
+                    tree,
+                }),
+            )
+            .await
+            .context("Failed to create tree")?;
+        let commit: GitObject = self
+            .send_json(
+                Method::POST,
+                &format!("{}/commits", api),
+                Some(&CreateCommitRequest {
+                    message,
+                    tree: &tree.sha,
+                    parents: [&parent],
+                    author: self.author.as_ref(),
+                    committer: self.committer.as_ref(),
+                }),
+            )
+            .await
+            .context("Failed to create commit")?;
+
+        info!(
+            "Committing {} file(s) to {} on branch {}",
+            changed.len(),
+            repo,
+            branch
+        );
+        // Not forced: if the branch moved since we read it, fail and retry next time
+        let _: GitObject = self
+            .send_json(
+                Method::PATCH,
+                &format!("{}/refs/heads/{}", api, branch),
+                Some(&UpdateRefRequest {
+                    sha: &commit.sha,
+                    force: false,
+                }),
+            )
+            .await
+            .with_context(|| format!("Failed to update branch {}", branch))?;
+
+        Ok(CommitOutcome::Committed)
+    }
+
+    /// Update a gist's file, or create a gist when `id` is `None`. Returns the gist's
+    /// ID (new or existing) and whether anything changed.
+    pub async fn publish_gist(
+        &self,
+        id: Option<&str>,
+        filename: &str,
+        content: &str,
+        description: &str,
+        public: bool,
+    ) -> Result<(String, CommitOutcome)> {
+        let files = HashMap::from([(filename, GistFileContent { content })]);
+
+        let Some(id) = id else {
+            info!(
+                "Creating {} gist {}",
+                if public { "public" } else { "secret" },
+                filename
+            );
+            let gist: GistResponse = self
+                .send_json(
+                    Method::POST,
+                    "https://api.github.com/gists",
+                    Some(&GistRequest {
+                        description,
+                        public: Some(public),
+                        files,
+                    }),
+                )
+                .await
+                .context("Failed to create gist")?;
+            return Ok((gist.id, CommitOutcome::Committed));
+        };
+
+        if self
+            .get_gist_content(id, filename)
+            .await
+            .is_ok_and(|existing| existing == content)
+        {
+            info!("Gist {} ({}) is unchanged", id, filename);
+            return Ok((id.to_string(), CommitOutcome::Unchanged));
+        }
+
+        info!("Updating gist {} ({})", id, filename);
+        let _: GistResponse = self
+            .send_json(
+                Method::PATCH,
+                &format!("https://api.github.com/gists/{}", id),
+                Some(&GistRequest {
+                    description,
+                    public: None,
+                    files,
+                }),
+            )
+            .await
+            .with_context(|| format!("Failed to update gist {}", id))?;
+        Ok((id.to_string(), CommitOutcome::Committed))
+    }
+
+    /// Fetch the current content of one file in a gist
+    pub async fn get_gist_content(&self, id: &str, filename: &str) -> Result<String> {
+        let mut gist: GistResponse = self
+            .send_json(
+                Method::GET,
+                &format!("https://api.github.com/gists/{}", id),
+                None::<&()>,
+            )
+            .await
+            .with_context(|| format!("Failed to fetch gist {}", id))?;
+        match gist.files.remove(filename) {
+            Some(GistFile {
+                content: Some(content),
+                truncated: false,
+            }) => Ok(content),
+            Some(_) => Err(Error::Service(format!(
+                "{} in gist {} is too large to read back",
+                filename, id
+            ))),
+            None => Err(Error::NotFound(format!(
+                "gist {} has no file {}",
+                id, filename
+            ))),
+        }
+    }
+
+    fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
             .header(AUTHORIZATION, format!("Bearer {}", self.token))
             .header(USER_AGENT, "discord-callsign-bot")
             .header(ACCEPT, "application/vnd.github+json")
-            .json(&request_body)
+    }
+
+    /// Send a request with an optional JSON body and parse the JSON response
+    async fn send_json<B: Serialize, T: for<'de> Deserialize<'de>>(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&B>,
+    ) -> Result<T> {
+        let mut request = self.request(method, url);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request
+            .send()
+            .await
+            .context("Failed to send request to GitHub API")?;
+
+        if !response.status().is_success() {
+            return Err(error_response(response).await);
+        }
+
+        response
+            .json()
+            .await
+            .context("Failed to parse GitHub response")
+    }
+
+    /// Confirm the token can see the repository and push to it
+    pub async fn check_repo_access(&self, repo: &str) -> Result<()> {
+        let url = format!("https://api.github.com/repos/{}", repo);
+
+        let response = self
+            .request(Method::GET, &url)
             .send()
             .await
             .context("Failed to send request to GitHub API")?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("GitHub API returned error {}: {}", status, body);
+            return Err(error_response(response)
+                .await
+                .context("Repository not found or access denied"));
+        }
+
+        let repo_info: RepoResponse = response
+            .json()
+            .await
+            .context("Failed to parse GitHub response")?;
+
+        if !repo_info.permissions.is_some_and(|p| p.push) {
+            return Err(Error::Auth("token does not have push access".to_string()));
         }
 
         Ok(())
     }
 
-    async fn get_file_sha(&self, repo: &str, path: &str, branch: &str) -> Result<String> {
+    /// Fetch the current content of a file, decoded from the API's base64
+    pub async fn get_file_content(&self, repo: &str, path: &str, branch: &str) -> Result<String> {
+        self.get_file(repo, path, branch).await?.decoded()
+    }
+
+    async fn get_file(&self, repo: &str, path: &str, branch: &str) -> Result<ContentResponse> {
         let url = format!(
             "https://api.github.com/repos/{}/contents/{}?ref={}",
             repo, path, branch
         );
 
         let response = self
-            .client
-            .get(&url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.token))
-            .header(USER_AGENT, "discord-callsign-bot")
-            .header(ACCEPT, "application/vnd.github+json")
+            .request(Method::GET, &url)
             .send()
             .await
             .context("Failed to fetch file from GitHub")?;
 
         if !response.status().is_success() {
-            anyhow::bail!(
-                "GitHub API error (status {}): file not found or access denied",
-                response.status()
-            );
+            return Err(error_response(response)
+                .await
+                .context("File not found or access denied"));
         }
 
-        let content: ContentResponse = response
+        response
             .json()
             .await
-            .context("Failed to parse GitHub response")?;
+            .context("Failed to parse GitHub response")
+    }
+}
+
+/// Classify an unsuccessful response, keeping its body for the message
+async fn error_response(response: reqwest::Response) -> Error {
+    let status = response.status();
+    let remaining = response
+        .headers()
+        .get("x-ratelimit-remaining")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let body = response.text().await.unwrap_or_default();
+    Error::from_github(status, remaining.as_deref(), &body)
+}
 
-        Ok(content.sha)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_request_identity() {
+        let author = CommitIdentity {
+            name: "Roster Bot".to_string(),
+            email: "roster@example.com".to_string(),
+        };
+        let request = CreateCommitRequest {
+            message: "Update member list",
+            tree: "abc",
+            parents: ["def"],
+            author: Some(&author),
+            committer: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "message": "Update member list",
+                "tree": "abc",
+                "parents": ["def"],
+                "author": {"name": "Roster Bot", "email": "roster@example.com"},
+            })
+        );
     }
 }
@@ -0,0 +1,200 @@
+use crate::config::Config;
+use anyhow::{Context, Result};
+use serenity::all::{GuildId, Http};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// Answers collected by `init`, rendered into a starter config
+#[derive(Debug, Clone, PartialEq)]
+struct Answers {
+    token: String,
+    guild_id: u64,
+    repo: String,
+    path: String,
+    branch: String,
+    title: String,
+}
+
+/// Prompt for the essentials, check them against Discord, and write a starter config to `path`
+pub async fn init(path: &str, force: bool) -> Result<()> {
+    if Path::new(path).exists() && !force {
+        anyhow::bail!("{} already exists; pass --force to overwrite it", path);
+    }
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut output = io::stdout();
+    let mut ask =
+        |question: &str, default: Option<&str>| ask(&mut input, &mut output, question, default);
+
+    println!(
+        "Writing a starter config to {}. Press Ctrl-C to stop at any time.\n",
+        path
+    );
+    println!("Bot token: Discord Developer Portal > your application > Bot > Reset Token");
+
+    let (token, http) = loop {
+        let token = ask("Discord bot token", None)?;
+        let http = Http::new(&token);
+        match http.get_current_user().await {
+            Ok(user) => {
+                println!("✓ Token works (bot user {})\n", user.name);
+                break (token, http);
+            }
+            Err(e) => println!("✗ Discord rejected the token: {}\n", e),
+        }
+    };
+
+    let guilds = http.get_guilds(None, None).await.unwrap_or_default();
+    if guilds.is_empty() {
+        println!(
+            "The bot isn't in any servers yet; invite it with the OAuth2 URL generator first."
+        );
+    } else {
+        println!("The bot is in:");
+        for guild in &guilds {
+            println!("  {}  {}", guild.id, guild.name);
+        }
+    }
+    let only_guild = (guilds.len() == 1).then(|| guilds[0].id.to_string());
+
+    let (guild_id, guild_name) = loop {
+        let answer = ask("Server (guild) ID", only_guild.as_deref())?;
+        let Some(guild_id) = answer.parse::<u64>().ok().filter(|id| *id != 0) else {
+            println!(
+                "✗ {} isn't a server ID; enable Developer Mode and use Copy Server ID\n",
+                answer
+            );
+            continue;
+        };
+        match http.get_guild(GuildId::new(guild_id)).await {
+            Ok(guild) => {
+                println!("✓ Bot can access {}\n", guild.name);
+                break (guild_id, guild.name);
+            }
+            Err(e) => println!("✗ Bot cannot access guild {}: {}\n", guild_id, e),
+        }
+    };
+
+    let repo = loop {
+        let repo = ask("GitHub repository for the member list (owner/name)", None)?;
+        if repo.split('/').filter(|part| !part.is_empty()).count() == 2 {
+            break repo;
+        }
+        println!("✗ Use the owner/name form, e.g. jsvana/rosters\n");
+    };
+    let answers = Answers {
+        token,
+        guild_id,
+        repo,
+        path: ask("Path in the repository", Some("members.txt"))?,
+        branch: ask("Branch", Some("main"))?,
+        title: ask("List title", Some(&format!("{} Member List", guild_name)))?,
+    };
+
+    std::fs::write(path, render(&answers))
+        .with_context(|| format!("Failed to write config file: {}", path))?;
+    Config::from_file(path).context("The written config doesn't parse; please report this")?;
+
+    println!("\n✓ Wrote {}", path);
+    println!("Next steps:");
+    println!(
+        "  1. Export GITHUB_TOKEN with push access to {}",
+        answers.repo
+    );
+    println!("  2. Run `discord-callsign-bot validate-config` to check everything end to end");
+    println!("  3. See config.example.toml for optional features such as QRZ lookups");
+    Ok(())
+}
+
+/// Ask one question; a blank answer takes `default`, and questions without one repeat until answered
+fn ask(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+    default: Option<&str>,
+) -> Result<String> {
+    loop {
+        match default {
+            Some(default) => write!(output, "{} [{}]: ", question, default)?,
+            None => write!(output, "{}: ", question)?,
+        }
+        output.flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            anyhow::bail!("Input ended before {} was answered", question);
+        }
+        match (line.trim(), default) {
+            ("", Some(default)) => return Ok(default.to_string()),
+            ("", None) => continue,
+            (answer, _) => return Ok(answer.to_string()),
+        }
+    }
+}
+
+fn render(answers: &Answers) -> String {
+    let quote = |s: &str| toml::Value::String(s.to_string()).to_string();
+    format!(
+        "# Written by `discord-callsign-bot init`; see config.example.toml for every option\n\
+         [discord]\n\
+         token = {}\n\
+         \n\
+         [[guilds]]\n\
+         guild_id = {}\n\
+         \n\
+         [guilds.output]\n\
+         repo = {}\n\
+         path = {}\n\
+         branch = {}\n\
+         default_suffix = \"\"\n\
+         title = {}\n",
+        quote(&answers.token),
+        answers.guild_id,
+        quote(&answers.repo),
+        quote(&answers.path),
+        quote(&answers.branch),
+        quote(&answers.title),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_ask_defaults_and_repeats() {
+        let mut input = Cursor::new("\n\n  W6JSV \n");
+        let mut output = Vec::new();
+        assert_eq!(
+            ask(&mut input, &mut output, "Branch", Some("main")).unwrap(),
+            "main"
+        );
+        // No default, so the blank line is asked again
+        assert_eq!(ask(&mut input, &mut output, "Call", None).unwrap(), "W6JSV");
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Branch [main]: Call: Call: "
+        );
+        assert!(ask(&mut input, &mut Vec::new(), "Token", None).is_err());
+    }
+
+    #[test]
+    fn test_render_parses() {
+        let answers = Answers {
+            token: "abc\"def".to_string(),
+            guild_id: 123456789012345678,
+            repo: "jsvana/rosters".to_string(),
+            path: "members.txt".to_string(),
+            branch: "main".to_string(),
+            title: "Club \"73\" List".to_string(),
+        };
+        let config: Config = toml::from_str(&render(&answers)).unwrap();
+        assert_eq!(config.discord.token, "abc\"def");
+        let guild = &config.guilds[0];
+        assert_eq!(guild.guild_id, 123456789012345678);
+        assert_eq!(guild.output.repo.as_deref(), Some("jsvana/rosters"));
+        assert_eq!(guild.output.title.as_deref(), Some("Club \"73\" List"));
+    }
+}
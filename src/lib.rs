@@ -0,0 +1,25 @@
+//! Callsign parsing, QRZ enrichment, and roster rendering behind discord-callsign-bot.
+//!
+//! [`parser::CallsignParser`] pulls callsigns out of display names, [`qrz::QrzClient`]
+//! looks them up, [`pipeline::Pipeline`] turns any [`pipeline::MemberSource`]'s members
+//! into entries, and [`output`] renders them. [`config`] holds the settings they share.
+//! Build without the default `bot` feature to leave out serenity and the bot's other
+//! dependencies.
+
+pub mod config;
+pub mod output;
+pub mod parser;
+pub mod pipeline;
+pub mod qrz;
+
+mod aprs;
+mod backoff;
+mod dxcc;
+mod error;
+mod lotw;
+mod metrics;
+mod radioid;
+mod roster;
+
+pub use backoff::Backoff;
+pub use error::{Error, Result, ResultExt};
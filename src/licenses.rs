@@ -0,0 +1,232 @@
+use chrono::{Datelike, NaiveDate};
+
+/// A roster member's license dates, from QRZ
+#[derive(Debug, Clone, PartialEq)]
+pub struct License {
+    pub callsign: String,
+    pub name: String,
+    pub effective: Option<NaiveDate>,
+    pub expires: Option<NaiveDate>,
+}
+
+/// Licenses expiring from `today` through `within_days` later, soonest first
+pub fn expiring(licenses: &[License], today: NaiveDate, within_days: u32) -> Vec<&License> {
+    let last = today + chrono::Days::new(within_days.into());
+    let mut expiring = licenses
+        .iter()
+        .filter(|license| license.expires.is_some_and(|d| d >= today && d <= last))
+        .collect::<Vec<_>>();
+    expiring.sort_by(|a, b| a.expires.cmp(&b.expires).then(a.callsign.cmp(&b.callsign)));
+    expiring
+}
+
+/// Anniversaries of licenses' effective dates in the same window, with the
+/// date and years each marks, soonest first
+pub fn anniversaries(
+    licenses: &[License],
+    today: NaiveDate,
+    within_days: u32,
+) -> Vec<(&License, NaiveDate, i32)> {
+    let last = today + chrono::Days::new(within_days.into());
+    let mut anniversaries = licenses
+        .iter()
+        .filter_map(|license| {
+            let effective = license.effective?;
+            let next = (today.year()..=today.year() + 1)
+                .filter_map(|year| anniversary_in(effective, year))
+                .find(|date| *date >= today)?;
+            let years = next.year() - effective.year();
+            (years >= 1 && next <= last).then_some((license, next, years))
+        })
+        .collect::<Vec<_>>();
+    anniversaries.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.callsign.cmp(&b.0.callsign)));
+    anniversaries
+}
+
+/// The anniversary of `date` in `year`; February 29 falls on March 1 in common years
+fn anniversary_in(date: NaiveDate, year: i32) -> Option<NaiveDate> {
+    date.with_year(year)
+        .or_else(|| NaiveDate::from_ymd_opt(year, 3, 1))
+}
+
+/// The reminder post, or `None` when there's nothing to announce
+pub fn announcement(
+    licenses: &[License],
+    today: NaiveDate,
+    within_days: u32,
+    include_anniversaries: bool,
+) -> Option<String> {
+    let expiring = expiring(licenses, today, within_days);
+    let anniversaries = if include_anniversaries {
+        anniversaries(licenses, today, within_days)
+    } else {
+        Vec::new()
+    };
+    if expiring.is_empty() && anniversaries.is_empty() {
+        return None;
+    }
+
+    let mut post = "📅 **License reminders**".to_string();
+    if !expiring.is_empty() {
+        post.push_str(&format!("\nExpiring in the next {} days:", within_days));
+        for license in expiring {
+            let expires = license.expires.expect("filtered on expiry");
+            post.push_str(&format!(
+                "\n- {} expires {} ({})",
+                label(license),
+                expires,
+                days_away((expires - today).num_days())
+            ));
+        }
+    }
+    if !anniversaries.is_empty() {
+        post.push_str("\nLicense anniversaries:");
+        for (license, date, years) in anniversaries {
+            post.push_str(&format!(
+                "\n- {}: {} year{} on {}",
+                label(license),
+                years,
+                if years == 1 { "" } else { "s" },
+                date
+            ));
+        }
+    }
+    Some(post)
+}
+
+/// Whether to DM a member about a license expiring on `expires`: once it's within
+/// `days_before`, and only once per expiration date
+pub fn dm_due(
+    expires: NaiveDate,
+    today: NaiveDate,
+    days_before: u32,
+    last_warned: Option<NaiveDate>,
+) -> bool {
+    let days = (expires - today).num_days();
+    (0..=i64::from(days_before)).contains(&days) && last_warned != Some(expires)
+}
+
+/// The DM sent to a member whose license expires soon
+pub fn dm_message(callsign: &str, expires: NaiveDate, today: NaiveDate) -> String {
+    format!(
+        "📅 Heads up: QRZ shows the license for {} expiring {} ({}). Renew it before then to keep your callsign. \
+         Use `/license-reminders enabled:false` in the server to stop these messages.",
+        callsign,
+        expires,
+        days_away((expires - today).num_days())
+    )
+}
+
+fn label(license: &License) -> String {
+    if license.name.is_empty() {
+        license.callsign.clone()
+    } else {
+        format!("{} ({})", license.callsign, license.name)
+    }
+}
+
+fn days_away(days: i64) -> String {
+    match days {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        days => format!("in {} days", days),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn license(
+        callsign: &str,
+        effective: Option<NaiveDate>,
+        expires: Option<NaiveDate>,
+    ) -> License {
+        License {
+            callsign: callsign.to_string(),
+            name: String::new(),
+            effective,
+            expires,
+        }
+    }
+
+    #[test]
+    fn test_expiring_window() {
+        let today = date(2026, 10, 16);
+        let licenses = [
+            license("W1LATE", None, Some(date(2026, 11, 30))),
+            license("W1SOON", None, Some(date(2026, 10, 20))),
+            license("W1GONE", None, Some(date(2026, 10, 1))),
+            license("W1EDGE", None, Some(date(2026, 11, 15))),
+            license("W1NONE", None, None),
+        ];
+        let callsigns = expiring(&licenses, today, 30)
+            .iter()
+            .map(|l| l.callsign.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(callsigns, vec!["W1SOON", "W1EDGE"]);
+    }
+
+    #[test]
+    fn test_anniversaries() {
+        let today = date(2026, 12, 20);
+        let licenses = [
+            license("W1NEW", Some(date(2026, 12, 25)), None),
+            license("W1FIVE", Some(date(2022, 1, 5)), None),
+            license("W1LEAP", Some(date(2020, 2, 29)), None),
+            license("W1PAST", Some(date(2020, 12, 1)), None),
+        ];
+        let found = anniversaries(&licenses, today, 30)
+            .into_iter()
+            .map(|(l, d, years)| (l.callsign.as_str(), d, years))
+            .collect::<Vec<_>>();
+        // W1NEW's license is too new for an anniversary; W1PAST's was earlier this month
+        assert_eq!(found, vec![("W1FIVE", date(2027, 1, 5), 5)]);
+
+        let leap = anniversaries(&licenses, date(2027, 2, 20), 30);
+        assert_eq!(leap[0].0.callsign, "W1LEAP");
+        assert_eq!(leap[0].1, date(2027, 3, 1));
+    }
+
+    #[test]
+    fn test_dm_due() {
+        let today = date(2026, 10, 16);
+        let expires = date(2026, 11, 1);
+        assert!(dm_due(expires, today, 30, None));
+        assert!(!dm_due(expires, today, 7, None));
+        assert!(!dm_due(expires, today, 30, Some(expires)));
+        // A renewal moves the expiration, so the next one is warned about too
+        assert!(dm_due(expires, today, 30, Some(date(2016, 11, 1))));
+        assert!(!dm_due(date(2026, 10, 1), today, 30, None));
+    }
+
+    #[test]
+    fn test_announcement() {
+        let today = date(2026, 10, 16);
+        let licenses = [
+            License {
+                name: "Jay".to_string(),
+                ..license("W6JSV", None, Some(date(2026, 10, 17)))
+            },
+            license("KI7QCF", Some(date(2016, 10, 20)), Some(date(2026, 10, 26))),
+        ];
+        assert_eq!(
+            announcement(&licenses, today, 30, true).unwrap(),
+            "📅 **License reminders**\nExpiring in the next 30 days:\n\
+             - W6JSV (Jay) expires 2026-10-17 (tomorrow)\n\
+             - KI7QCF expires 2026-10-26 (in 10 days)\n\
+             License anniversaries:\n- KI7QCF: 10 years on 2026-10-20"
+        );
+        assert!(!announcement(&licenses, today, 30, false)
+            .unwrap()
+            .contains("anniversaries"));
+        assert_eq!(
+            announcement(&licenses[..1], date(2027, 1, 1), 30, true),
+            None
+        );
+    }
+}
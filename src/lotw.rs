@@ -0,0 +1,179 @@
+use crate::config::LotwConfig;
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use reqwest::header::USER_AGENT;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Callsigns in ARRL's LoTW user activity list, with the date each last uploaded
+#[derive(Debug, Default)]
+pub struct LotwUsers {
+    last_upload: HashMap<String, NaiveDate>,
+}
+
+impl LotwUsers {
+    /// Parse `lotw-user-activity.csv`: `CALLSIGN,YYYY-MM-DD,HH:MM:SS` per line.
+    /// Lines that don't fit are skipped; a file with no users at all is an error.
+    pub fn parse(contents: &str) -> Result<Self> {
+        let last_upload: HashMap<String, NaiveDate> = contents
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split(',');
+                let callsign = fields.next()?.trim();
+                let date = NaiveDate::parse_from_str(fields.next()?.trim(), "%Y-%m-%d").ok()?;
+                (!callsign.is_empty()).then(|| (callsign.to_uppercase(), date))
+            })
+            .collect();
+        if last_upload.is_empty() {
+            anyhow::bail!("No LoTW users found");
+        }
+        Ok(Self { last_upload })
+    }
+
+    pub fn from_file(path: &str) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read LoTW user list: {}", path))?;
+        Self::parse(&contents).with_context(|| format!("Failed to parse LoTW user list: {}", path))
+    }
+
+    /// When the callsign last uploaded to LoTW, or `None` if it isn't a user
+    pub fn last_upload(&self, callsign: &str) -> Option<NaiveDate> {
+        self.last_upload.get(&callsign.to_uppercase()).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.last_upload.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.last_upload.is_empty()
+    }
+}
+
+/// The current LoTW user list, swapped out on each refresh
+pub struct LotwStore {
+    client: reqwest::Client,
+    config: LotwConfig,
+    users: RwLock<Arc<LotwUsers>>,
+}
+
+impl LotwStore {
+    /// Download the list, falling back to `file` (where the last download was saved)
+    /// and then an empty list until the next refresh
+    pub async fn load(config: &LotwConfig) -> Self {
+        let store = Self {
+            client: reqwest::Client::new(),
+            config: config.clone(),
+            users: RwLock::new(Arc::new(LotwUsers::default())),
+        };
+        match store.download().await {
+            Ok(users) => store.replace(users),
+            Err(e) => {
+                warn!("{:?}", e);
+                match config.file.as_deref().map(LotwUsers::from_file) {
+                    Some(Ok(users)) => store.replace(users),
+                    Some(Err(e)) => warn!("{:?}", e),
+                    None => {}
+                }
+            }
+        }
+        store
+    }
+
+    pub fn current(&self) -> Arc<LotwUsers> {
+        Arc::clone(&self.users.read().expect("LoTW list lock poisoned"))
+    }
+
+    fn replace(&self, users: LotwUsers) {
+        info!("Loaded {} LoTW users", users.len());
+        *self.users.write().expect("LoTW list lock poisoned") = Arc::new(users);
+    }
+
+    /// Fetch and parse the list from `url`, saving a copy to `file` if set
+    async fn download(&self) -> Result<LotwUsers> {
+        let url = &self.config.url;
+        let response = self
+            .client
+            .get(url)
+            .header(USER_AGENT, "discord-callsign-bot")
+            .send()
+            .await
+            .with_context(|| format!("Failed to download LoTW user list from {}", url))?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Downloading LoTW user list from {} returned {}",
+                url,
+                response.status()
+            );
+        }
+        let contents = response
+            .text()
+            .await
+            .with_context(|| format!("Failed to read LoTW user list from {}", url))?;
+        let users = LotwUsers::parse(&contents)
+            .with_context(|| format!("Failed to parse LoTW user list from {}", url))?;
+
+        if let Some(path) = &self.config.file {
+            if let Err(e) = std::fs::write(path, &contents) {
+                warn!("Failed to save LoTW user list to {}: {}", path, e);
+            }
+        }
+        Ok(users)
+    }
+
+    /// Download the list again every `refresh_secs`, keeping the current one on failure
+    pub fn spawn_refresh(self: &Arc<Self>) {
+        if self.config.refresh_secs == 0 {
+            return;
+        }
+        info!(
+            "Refreshing the LoTW user list every {}s",
+            self.config.refresh_secs
+        );
+
+        let store = Arc::clone(self);
+        let period = Duration::from_secs(self.config.refresh_secs);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            // The first tick fires immediately, right after the initial load
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match store.download().await {
+                    Ok(users) => store.replace(users),
+                    Err(e) => warn!("Keeping the current LoTW user list: {:?}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let users = LotwUsers::parse(
+            "W6JSV,2026-10-01,18:22:05\n\
+             ki7qcf,2019-03-14,01:00:00\n\
+             garbage line\n\
+             N0CALL,not-a-date,00:00:00\n",
+        )
+        .unwrap();
+        assert_eq!(users.len(), 2);
+        assert_eq!(
+            users.last_upload("w6jsv"),
+            NaiveDate::from_ymd_opt(2026, 10, 1)
+        );
+        assert_eq!(
+            users.last_upload("KI7QCF"),
+            NaiveDate::from_ymd_opt(2019, 3, 14)
+        );
+        assert_eq!(users.last_upload("N0CALL"), None);
+
+        assert!(LotwUsers::parse("<html>Service unavailable</html>").is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::config::{MailingListConfig, MailingListFormat};
+use crate::output::{fill_placeholders, sanitize_field, OutputEntry, MAX_NAME_CHARS};
+
+/// Stamped on every mbox message, so an unchanged roster renders identically and
+/// isn't committed again
+const MBOX_DATE: &str = "Thu Jan  1 00:00:00 1970";
+
+/// Characters that can't appear unquoted in an address's display name (RFC 5322)
+const SPECIALS: &[char] = &[
+    '(', ')', '<', '>', '[', ']', ':', ';', '@', '\\', ',', '.', '"',
+];
+
+/// A name as an address's display name, quoted when it has specials: `"Smith, Jay"`
+fn display_name(name: &str) -> String {
+    let name = sanitize_field(name, MAX_NAME_CHARS);
+    if !name.contains(SPECIALS) {
+        return name;
+    }
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// An entry's address from `template`, such as "Jay Vana <W6JSV@winlink.org>"
+pub fn address(template: &str, entry: &OutputEntry) -> String {
+    let address = fill_placeholders(template, |placeholder| match placeholder {
+        "callsign" => Some(sanitize_field(&entry.callsign, MAX_NAME_CHARS)),
+        "name" => Some(display_name(&entry.name)),
+        _ => None,
+    });
+    address.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The bare `user@host` part of "Name <user@host>"
+fn addr_spec(address: &str) -> &str {
+    address
+        .rsplit_once('<')
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map_or(address, |(spec, _)| spec)
+}
+
+/// Render every entry's address in the configured format
+pub fn generate(entries: &[OutputEntry], config: &MailingListConfig) -> String {
+    let mut output = String::new();
+    for entry in entries {
+        let address = address(&config.template, entry);
+        match config.format {
+            MailingListFormat::Plain => {
+                output.push_str(&address);
+                output.push('\n');
+            }
+            MailingListFormat::Mbox => {
+                output.push_str(&format!(
+                    "From {} {}\nFrom: {}\nSubject: {}\n\n",
+                    addr_spec(&address),
+                    MBOX_DATE,
+                    address,
+                    sanitize_field(&entry.callsign, MAX_NAME_CHARS)
+                ));
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(callsign: &str, name: &str) -> OutputEntry {
+        OutputEntry {
+            callsign: callsign.to_string(),
+            name: name.to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn config(format: MailingListFormat, template: &str) -> MailingListConfig {
+        MailingListConfig {
+            path: "addresses.txt".to_string(),
+            format,
+            template: template.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_address() {
+        let template = "{name} <{callsign}@winlink.org>";
+        assert_eq!(
+            address(template, &entry("W6JSV", "Jay Vana")),
+            "Jay Vana <W6JSV@winlink.org>"
+        );
+        assert_eq!(
+            address(template, &entry("KI7QCF", "Forrest \"Woody\" Jr.")),
+            "\"Forrest \\\"Woody\\\" Jr.\" <KI7QCF@winlink.org>"
+        );
+        assert_eq!(
+            address(template, &entry("N0CALL", "")),
+            "<N0CALL@winlink.org>"
+        );
+        assert_eq!(
+            address("{callsign}@arrl.net", &entry("W6JSV", "Jay")),
+            "W6JSV@arrl.net"
+        );
+    }
+
+    #[test]
+    fn test_generate() {
+        let entries = vec![entry("W6JSV", "Jay"), entry("KI7QCF", "Forrest")];
+        let template = "{name} <{callsign}@winlink.org>";
+        assert_eq!(
+            generate(&entries, &config(MailingListFormat::Plain, template)),
+            "Jay <W6JSV@winlink.org>\nForrest <KI7QCF@winlink.org>\n"
+        );
+        assert_eq!(
+            generate(&entries[..1], &config(MailingListFormat::Mbox, template)),
+            "From W6JSV@winlink.org Thu Jan  1 00:00:00 1970\n\
+             From: Jay <W6JSV@winlink.org>\n\
+             Subject: W6JSV\n\n"
+        );
+        assert_eq!(addr_spec("W6JSV@arrl.net"), "W6JSV@arrl.net");
+    }
+}
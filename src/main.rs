@@ -1,22 +1,91 @@
-mod config;
+mod alerts;
+mod applications;
+mod bulletins;
+mod callsign_cards;
+mod commands;
+mod contests;
+mod csv_members;
+mod discord;
+mod dxcluster;
+mod elmers;
+mod events;
 mod github;
-mod output;
-mod parser;
-mod qrz;
+mod init;
+mod licenses;
+mod mailing_list;
+mod morse;
+mod mqtt;
+mod nets;
+mod nickname;
+mod onboarding;
+mod propagation;
+mod queue;
+mod quiz;
+mod ratelimit;
+mod repeaters;
+mod roles;
+mod s3;
+mod server;
+mod sheets;
+mod shutdown;
+mod simulation;
+mod snapshots;
+mod state;
+mod storage;
+mod swap;
+mod telemetry;
+mod validate;
+mod webhook;
+mod welcome;
 
+use alerts::{AlertSource, Alerts};
 use anyhow::Result;
-use clap::Parser;
+use bulletins::BulletinClient;
+use callsign_cards::CardLimiter;
+use clap::{Parser, Subcommand};
+use config::schedule::Schedule;
 use config::Config;
-use github::GitHubClient;
-use output::{generate_output_content, OutputEntry};
+use contests::ContestClient;
+use csv_members::CsvMembers;
+use discord::DiscordSource;
+use discord_callsign_bot::{config, output, parser, pipeline, qrz, Backoff, Error};
+use dxcluster::{Spot, SpotDedup};
+use github::{CommitOutcome, FileChange, GitHubClient};
+use mqtt::MqttClient;
+use onboarding::{Onboarding, Reply};
+use output::diff_lines;
 use parser::CallsignParser;
-use qrz::QrzClient;
-use serenity::all::GuildId;
+use pipeline::{
+    AprsClient, CallsignConflict, CallsignLookup, DxccStore, Generation, LookupAlerts, LotwStore,
+    Member, Metrics, Pipeline, QrzStatus, RadioIdClient, RosterBuilder,
+};
+use propagation::PropagationClient;
+use qrz::{QrzClient, UsageStore};
+use queue::{Enqueued, EventQueue};
+use quiz::QuizPools;
+use ratelimit::RestLimits;
+use repeaters::RepeaterBookClient;
+use s3::S3Client;
+use serenity::all::{
+    ActivityData, ChannelId, ConnectionStage, CreateAllowedMentions, CreateMessage,
+    CreateScheduledEvent, EditMember, GuildId, Http, Interaction, RatelimitInfo, ReactionType,
+    ResumedEvent, RoleId, ScheduledEventType, ShardMessenger, ShardStageUpdateEvent, Timestamp,
+    UserId, VoiceState,
+};
 use serenity::async_trait;
 use serenity::prelude::*;
-use std::collections::HashMap;
-use std::sync::Arc;
-use tracing::{error, info, warn};
+use sheets::SheetsClient;
+use shutdown::InFlight;
+use simulation::{EntryChange, EventRecorder, RecordedEvent};
+use snapshots::{Snapshot, SnapshotStore};
+use state::{GatewayState, MemberCounts, RosterStore};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use storage::Storage;
+use tracing::{debug, error, info, info_span, instrument, warn, Instrument};
+use webhook::WebhookClient;
 
 /// Discord bot that generates member lists of amateur radio operators from callsigns
 #[derive(Parser, Debug)]
@@ -24,221 +93,2593 @@ use tracing::{error, info, warn};
 #[command(version, about, long_about = None)]
 struct Args {
     /// Path to the configuration file
-    #[arg(short, long, default_value = "config.toml", env = "CONFIG_PATH")]
+    #[arg(
+        short,
+        long,
+        global = true,
+        default_value = "config.toml",
+        env = "CONFIG_PATH"
+    )]
     config: String,
+
+    /// Append member events to this JSONL file for later replay
+    #[arg(long, global = true, env = "RECORD_EVENTS_PATH")]
+    record_events: Option<String>,
+
+    /// Run the full pipeline but only log what would be written; nothing is
+    /// committed, posted, or changed in Discord
+    #[arg(long, global = true, env = "DRY_RUN")]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Connect to Discord and keep the member lists up to date (the default)
+    Run,
+    /// Generate every guild's member list once and exit (useful from cron)
+    GenerateOnce,
+    /// Build a guild's member list from a CSV of `display_name[,discord_id]` rows
+    /// instead of Discord, publish it to the guild's sinks, and exit
+    GenerateFromCsv {
+        /// Guild whose overrides and output to use
+        guild_id: u64,
+        /// CSV file of members
+        path: String,
+    },
+    /// Parse the config, verify Discord, QRZ, and GitHub credentials, and exit
+    ValidateConfig,
+    /// Replay a recorded JSONL event file offline, print roster changes, and exit
+    Replay {
+        /// Recording written by --record-events
+        path: String,
+    },
+    /// Prompt for a bot token, server, and output repository, verify them, and
+    /// write a starter config to --config
+    Init {
+        /// Overwrite an existing config file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Republish a saved `[snapshots]` roster to a guild's sinks; without a snapshot
+    /// ID, list the guild's snapshots
+    Rollback {
+        /// Guild whose roster to restore
+        guild_id: u64,
+        /// Snapshot ID from the listing or `/roster history`
+        snapshot: Option<String>,
+    },
 }
 
 struct Handler {
     config: Config,
     parser: CallsignParser,
     qrz_client: Option<Arc<QrzClient>>,
+    radioid_client: Option<RadioIdClient>,
+    aprs_client: Option<AprsClient>,
+    dxcc: Arc<DxccStore>,
+    lotw: Option<Arc<LotwStore>>,
+    repeaterbook_client: Option<RepeaterBookClient>,
+    propagation_client: Option<PropagationClient>,
+    quiz: Option<QuizPools>,
+    mqtt_client: Option<MqttClient>,
     github_client: GitHubClient,
+    webhook_client: WebhookClient,
+    s3_client: S3Client,
+    sheets_client: SheetsClient,
+    contest_client: ContestClient,
+    bulletin_client: BulletinClient,
+    rosters: RosterStore,
+    metrics: Metrics,
+    recorder: Option<Arc<EventRecorder>>,
+    in_flight: InFlight,
+    queue: EventQueue,
+    /// Shared with the HTTP server for the events calendar
+    storage: Arc<Storage>,
+    /// DM conversations with new members whose name has no callsign
+    onboarding: Onboarding,
+    /// Callsigns sent by /cw practice, awaiting /cw answer
+    cw_practice: morse::Practice,
+    /// Callsigns recently answered with a card, per guild with `[guilds.callsign_cards]`
+    card_limiters: HashMap<u64, CardLimiter>,
+    dry_run: bool,
+    /// Set once the startup summary has been posted, so reconnects don't repeat it
+    announced: AtomicBool,
+    /// `[alerts]` for repeated QRZ and GitHub failures
+    alerts: Option<Alerts>,
+    /// `[snapshots]` of published rosters
+    snapshots: Option<SnapshotStore>,
+    /// Paces member fetches and nickname/role edits (`[discord.rest]`)
+    rest_limits: RestLimits,
+    /// Gateway connection state, shared with `/healthz`
+    gateway: GatewayState,
+    /// Set on the first `ready`, for updating the bot's presence
+    shard: OnceLock<ShardMessenger>,
 }
 
+/// Pause between QRZ backfill lookups, so the backfill never competes with roster builds
+const QRZ_BACKFILL_PAUSE: Duration = Duration::from_secs(2);
+
 impl Handler {
-    fn new(
-        config: Config,
-        qrz_client: Option<Arc<QrzClient>>,
-        github_client: GitHubClient,
-    ) -> Self {
-        Self {
-            config,
-            parser: CallsignParser::new(),
-            qrz_client,
-            github_client,
+    /// Post the effective configuration to the ops channel, once per process
+    async fn announce_startup(&self, http: &Http) {
+        let Some(channel_id) = self.config.discord.ops_channel_id else {
+            return;
+        };
+        if self.announced.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        if self.dry_run {
+            info!(
+                "[dry run] Would post the startup summary to channel {}",
+                channel_id
+            );
+            return;
+        }
+
+        let summary = self
+            .config
+            .summarize()
+            .lines()
+            .map(|line| format!("- {}", line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let content = commands::truncate_message(&format!(
+            "**Callsign bot v{} started**\n{}",
+            env!("CARGO_PKG_VERSION"),
+            summary
+        ));
+        if let Err(e) = ChannelId::new(channel_id).say(http, content).await {
+            warn!(
+                "Failed to post startup summary to channel {}: {}",
+                channel_id, e
+            );
         }
     }
 
-    async fn generate_member_list(
+    /// Tell the guild's report channel about duplicate callsigns, only when they change
+    async fn report_conflicts(
         &self,
-        ctx: &Context,
+        http: &Http,
         guild_config: &config::GuildConfig,
-    ) -> Result<()> {
-        let guild_id = GuildId::new(guild_config.guild_id);
+        conflicts: &[CallsignConflict],
+    ) {
+        if !self
+            .rosters
+            .update_conflicts(guild_config.guild_id, conflicts.to_vec())
+            || conflicts.is_empty()
+        {
+            return;
+        }
+        let Some(channel_id) = guild_config.report_channel_id else {
+            return;
+        };
 
-        info!("Fetching members from guild {}", guild_id);
+        let mut content = format!(
+            "**Duplicate callsigns in the roster** (policy: {})",
+            guild_config.output.dedup.label()
+        );
+        for conflict in conflicts {
+            let mention = |ids: &[u64]| {
+                ids.iter()
+                    .map(|id| format!("<@{}>", id))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            content.push_str(&format!(
+                "\n- {}: {} (listed: {})",
+                conflict.callsign,
+                mention(&conflict.user_ids),
+                mention(&conflict.kept)
+            ));
+        }
 
-        // Get all members from the guild
-        let members = guild_id
-            .members(&ctx.http, None, None)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to fetch guild members: {}", e))?;
+        if self.is_dry_run(guild_config) {
+            info!(
+                "[dry run] Would post to report channel {}:\n{}",
+                channel_id, content
+            );
+            return;
+        }
+
+        // Mention members for context without pinging them
+        let message = CreateMessage::new()
+            .content(commands::truncate_message(&content))
+            .allowed_mentions(CreateAllowedMentions::new());
+        if let Err(e) = ChannelId::new(channel_id).send_message(http, message).await {
+            warn!(
+                "Failed to post duplicate callsign report to channel {}: {}",
+                channel_id, e
+            );
+        }
+    }
+
+    /// Rewrite nicknames that don't match `[guilds.nicknames]` when enforcement is on
+    async fn enforce_nicknames(
+        &self,
+        http: &Http,
+        guild_config: &config::GuildConfig,
+        members: &[Member],
+        by_user: &HashMap<u64, output::OutputEntry>,
+    ) {
+        let Some(nickname_config) = guild_config.nicknames.as_ref().filter(|n| n.enforce) else {
+            return;
+        };
 
-        info!("Found {} members", members.len());
+        let guild_id = GuildId::new(guild_config.guild_id);
+        for change in nickname::plan(nickname_config, members, by_user) {
+            if self.is_dry_run(guild_config) {
+                info!(
+                    "[dry run] Would rename {} to {:?} in guild {}",
+                    change.current, change.desired, guild_id
+                );
+                continue;
+            }
+
+            self.rest_limits
+                .before_edit(UserId::new(change.user_id))
+                .await;
+            // Fails for the server owner and members above the bot's highest role
+            match guild_id
+                .edit_member(
+                    http,
+                    UserId::new(change.user_id),
+                    EditMember::new().nickname(&change.desired),
+                )
+                .await
+            {
+                Ok(_) => info!(
+                    "Renamed {} to {:?} in guild {}",
+                    change.current, change.desired, guild_id
+                ),
+                Err(e) => warn!(
+                    "Failed to rename {} to {:?} in guild {}: {}",
+                    change.current, change.desired, guild_id, e
+                ),
+            }
+        }
+    }
 
-        // Get the bot's own user ID to filter it out
-        let bot_user_id = ctx.cache.current_user().id;
+    /// Grant or revoke `[guilds.licensed_role]` to match who parses to a callsign
+    async fn sync_licensed_role(
+        &self,
+        http: &Http,
+        guild_config: &config::GuildConfig,
+        bot_user_id: UserId,
+        members: &[Member],
+        listed: &HashMap<u64, output::OutputEntry>,
+        licensed: &HashMap<u64, QrzStatus>,
+    ) {
+        let Some(role_config) = &guild_config.licensed_role else {
+            return;
+        };
 
-        let mut entries = Vec::new();
+        // Excluded members and those listed only through an override have no parsed
+        // callsign to go by, so their role is left alone
+        let builder = self.roster_builder();
+        let exempt: HashSet<u64> = members
+            .iter()
+            .filter(|m| {
+                builder.is_excluded(m)
+                    || (listed.contains_key(&m.user_id) && !licensed.contains_key(&m.user_id))
+            })
+            .map(|m| m.user_id)
+            .collect();
 
-        for member in members {
-            // Skip the bot itself
-            if member.user.id == bot_user_id {
-                info!("Skipping bot user: {}", member.user.name);
+        let guild_id = GuildId::new(guild_config.guild_id);
+        let role_id = RoleId::new(role_config.role_id);
+        for change in roles::plan(role_config, members, licensed, &exempt) {
+            let user_id = UserId::new(change.user_id);
+            // The bot is never listed, but shouldn't strip its own roles either
+            if user_id == bot_user_id {
+                continue;
+            }
+            let action = if change.grant { "grant" } else { "revoke" };
+            if self.is_dry_run(guild_config) {
+                info!(
+                    "[dry run] Would {} licensed role for user {} in guild {}",
+                    action, user_id, guild_id
+                );
                 continue;
             }
 
-            // Try to find a valid callsign in multiple name fields
-            // Priority: nick -> global_name -> user.name
-            let name_fields = [
-                member.nick.as_ref(),
-                member.user.global_name.as_ref(),
-                Some(&member.user.name),
-            ];
+            self.rest_limits.before_edit(user_id).await;
+            let result = if change.grant {
+                http.add_member_role(guild_id, user_id, role_id, Some("Callsign parsed"))
+                    .await
+            } else {
+                http.remove_member_role(guild_id, user_id, role_id, Some("No callsign"))
+                    .await
+            };
+            match result {
+                Ok(()) => info!(
+                    "Licensed role {} for user {} in guild {}",
+                    if change.grant { "granted" } else { "revoked" },
+                    user_id,
+                    guild_id
+                ),
+                Err(e) => warn!(
+                    "Failed to {} licensed role for user {} in guild {}: {}",
+                    action, user_id, guild_id, e
+                ),
+            }
+        }
+    }
 
-            let (parsed, display_name) = name_fields
-                .iter()
-                .filter_map(|field| {
-                    field.map(|name| {
-                        let parsed = self.parser.parse(name);
-                        (parsed, name.clone())
-                    })
-                })
-                .find(|(parsed, _)| parsed.is_some())
-                .unwrap_or((None, member.user.name.clone()));
+    /// Post a summary of roster changes to the guild's configured channel and webhook
+    async fn notify_changes(
+        &self,
+        http: &Http,
+        guild_config: &config::GuildConfig,
+        changes: &[EntryChange],
+    ) {
+        let Some(notifications) = &guild_config.change_notifications else {
+            return;
+        };
+        if changes.is_empty() {
+            return;
+        }
+
+        let title = guild_config.output.title.as_deref().unwrap_or("Roster");
+        let mut content = format!("**{} changes**", title);
+        for change in changes {
+            content.push_str(&format!("\n{}", change));
+        }
+        let content = commands::truncate_message(&content);
 
+        if self.is_dry_run(guild_config) {
+            info!("[dry run] Would post roster changes:\n{}", content);
+            return;
+        }
+
+        if let Some(channel_id) = notifications.channel_id {
+            let message = CreateMessage::new()
+                .content(&content)
+                .allowed_mentions(CreateAllowedMentions::new());
+            if let Err(e) = ChannelId::new(channel_id).send_message(http, message).await {
+                warn!(
+                    "Failed to post roster changes to channel {}: {}",
+                    channel_id, e
+                );
+            }
+        }
+        if let Some(url) = &notifications.webhook_url {
+            if let Err(e) = self.webhook_client.post_message(url, &content).await {
+                warn!("Failed to post roster changes to webhook: {:?}", e);
+            }
+        }
+    }
+
+    /// Show `discord.presence` with the number of listed members across all guilds
+    fn update_presence(&self) {
+        let (Some(template), Some(shard)) = (&self.config.discord.presence, self.shard.get())
+        else {
+            return;
+        };
+        if self.dry_run {
+            return;
+        }
+        let count: usize = self
+            .rosters
+            .all()
+            .values()
+            .map(|roster| roster.entries.len())
+            .sum();
+        let status = template.replace("{count}", &count.to_string());
+        debug!("Setting presence to {:?}", status);
+        shard.set_activity(Some(ActivityData::custom(status)));
+    }
+
+    /// Publish a joined/left/changed MQTT event per roster change
+    async fn publish_member_events(
+        &self,
+        guild_config: &config::GuildConfig,
+        changes: &[EntryChange],
+    ) {
+        let Some(mqtt) = &self.mqtt_client else {
+            return;
+        };
+        if changes.is_empty() {
+            return;
+        }
+        if self.is_dry_run(guild_config) {
             info!(
-                "Processing member: {} (parsed: {})",
-                display_name,
-                if parsed.is_some() { "✓" } else { "✗" }
-            );
-
-            // Check if there's a manual override for this user
-            let user_id = member.user.id.to_string();
-            if let Some(override_config) = guild_config.get_override(&user_id) {
-                info!("Using override for user {}", user_id);
-
-                // Use the parsed callsign if available
-
-                let callsign = override_config
-                    .callsign
-                    .clone()
-                    .or_else(|| parsed.as_ref().map(|p| p.callsign.clone()))
-                    .unwrap_or_else(|| "UNKNOWN".to_string());
-
-                let name = override_config
-                    .name
-                    .clone()
-                    .or_else(|| parsed.as_ref().map(|p| p.name.clone()))
-                    .unwrap_or_else(|| display_name.clone());
-
-                let suffix = override_config
-                    .suffix
-                    .clone()
-                    .unwrap_or_else(|| guild_config.output.default_suffix.clone());
-
-                let emoji_separator = override_config
-                    .emoji
-                    .clone()
-                    .unwrap_or_else(|| guild_config.output.emoji_separator.clone());
-
-                entries.push(OutputEntry {
-                    callsign,
-                    name,
-                    suffix,
-                    emoji_separator,
-                });
-            } else if let Some(parsed) = parsed {
-                // Successfully parsed callsign from one of the name fields
-                let mut name = parsed.name.clone();
-
-                // Try to get name from QRZ if client is available
-                if let Some(qrz_client) = &self.qrz_client {
-                    match qrz_client.lookup_callsign(&parsed.callsign).await {
-                        Ok(qrz_info) => {
-                            if let Some(qrz_name) = QrzClient::get_display_name(&qrz_info) {
-                                info!(
-                                    "Using QRZ name '{}' for callsign {}",
-                                    qrz_name, parsed.callsign
-                                );
-                                name = qrz_name;
-                            } else {
-                                info!(
-                                    "No name found in QRZ for {}, using Discord name: {}",
-                                    parsed.callsign, name
-                                );
-                            }
-                        }
-                        Err(e) => {
-                            warn!(
-                                "Failed to lookup callsign {} in QRZ: {:?}. Using Discord name: {}",
-                                parsed.callsign, e, name
-                            );
-                        }
+                "[dry run] Would publish {} member events to MQTT",
+                changes.len()
+            );
+            return;
+        }
+        if let Err(e) = mqtt.publish_changes(guild_config.guild_id, changes) {
+            warn!("Failed to publish member events to MQTT: {:?}", e);
+        }
+    }
+
+    /// Post a QRZ profile card for a new member whose name parses to a callsign
+    async fn welcome_member(
+        &self,
+        http: &Http,
+        guild_config: &config::GuildConfig,
+        member: &Member,
+    ) {
+        let Some(welcome_config) = &guild_config.welcome else {
+            return;
+        };
+        let builder = self.roster_builder();
+        if builder.is_excluded(member) {
+            return;
+        }
+        let Some(info) = builder.parse_member(member).0 else {
+            return;
+        };
+
+        // A failed lookup still gets a card with what the display name had
+        let qrz_client = self
+            .qrz_client
+            .as_ref()
+            .filter(|_| !self.skips_qrz(guild_config, member.user_id));
+        let qrz_info = match qrz_client {
+            Some(qrz_client) => match qrz_client.lookup_callsign(&info.callsign).await {
+                Ok(qrz_info) => Some(qrz_info),
+                Err(e) => {
+                    warn!("QRZ lookup for welcome card failed: {:?}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        if self.is_dry_run(guild_config) {
+            info!(
+                "[dry run] Would welcome {} to channel {}",
+                info.callsign, welcome_config.channel_id
+            );
+            return;
+        }
+
+        let user_id = UserId::new(member.user_id);
+        let content = welcome_config
+            .message
+            .replace("{mention}", &user_id.mention().to_string());
+        let message = CreateMessage::new()
+            .content(content)
+            .embed(welcome::card(&info, qrz_info.as_ref()))
+            .allowed_mentions(CreateAllowedMentions::new().users([user_id]));
+        if let Err(e) = ChannelId::new(welcome_config.channel_id)
+            .send_message(http, message)
+            .await
+        {
+            warn!(
+                "Failed to post welcome card to channel {}: {}",
+                welcome_config.channel_id, e
+            );
+        }
+    }
+
+    /// DM a new member whose name has no callsign, asking them for it
+    async fn start_onboarding(
+        &self,
+        http: &Http,
+        guild_config: &config::GuildConfig,
+        member: &Member,
+    ) {
+        let Some(onboarding_config) = &guild_config.onboarding else {
+            return;
+        };
+        let builder = self.roster_builder();
+        if builder.is_excluded(member) || builder.parse_member(member).0.is_some() {
+            return;
+        }
+        if self.is_dry_run(guild_config) {
+            info!(
+                "[dry run] Would DM {} asking for their callsign",
+                member.username
+            );
+            return;
+        }
+
+        let user_id = member.user_id;
+        self.onboarding.start(
+            guild_config.guild_id,
+            user_id,
+            Duration::from_secs(onboarding_config.timeout_secs),
+        );
+        let content = format!(
+            "Welcome! Your display name doesn't include a callsign, so you aren't on the {} yet. \
+             Reply with your callsign and name (e.g. `W1AW Hiram`) and I'll add you.",
+            guild_config.output.title.as_deref().unwrap_or("roster")
+        );
+        if let Err(e) = UserId::new(user_id)
+            .direct_message(http, CreateMessage::new().content(content))
+            .await
+        {
+            // Members can turn off DMs from server members
+            warn!("Failed to DM {} for onboarding: {}", member.username, e);
+            self.onboarding.cancel(guild_config.guild_id, user_id);
+        }
+    }
+
+    /// Answer callsigns mentioned in a guild message with a QRZ card or reaction, per
+    /// `[guilds.callsign_cards]`. Members who opted out of QRZ are skipped, and each
+    /// callsign is answered at most once per `repeat_after_secs`
+    async fn answer_callsigns(
+        &self,
+        http: &Http,
+        guild_id: u64,
+        msg: &serenity::model::channel::Message,
+    ) {
+        let Some(guild_config) = self.config.get_guild_config(guild_id) else {
+            return;
+        };
+        let Some(cards) = &guild_config.callsign_cards else {
+            return;
+        };
+        if !cards.channel_ids.is_empty() && !cards.channel_ids.contains(&msg.channel_id.get()) {
+            return;
+        }
+        // Checked when the config was loaded
+        let (Some(qrz_client), Some(limiter)) =
+            (&self.qrz_client, self.card_limiters.get(&guild_id))
+        else {
+            return;
+        };
+
+        let opted_out = self.qrz_opt_out_callsigns(guild_config);
+        let now = Instant::now();
+        let candidates: Vec<String> = self
+            .parser
+            .find_callsigns(&msg.content)
+            .into_iter()
+            .filter(|callsign| !opted_out.contains(callsign))
+            .take(cards.max_per_message)
+            .filter(|callsign| limiter.should_post(callsign, now))
+            .collect();
+        let mut found = Vec::new();
+        for callsign in candidates {
+            // Words shaped like callsigns, such as rig models, usually aren't on QRZ
+            match qrz_client.lookup_callsign(&callsign).await {
+                Ok(info) => found.push((callsign, info)),
+                Err(e) => debug!("No callsign card for {}: {:?}", callsign, e),
+            }
+        }
+        if found.is_empty() {
+            return;
+        }
+        if self.dry_run {
+            info!(
+                "[dry run] Would answer {} callsigns in channel {}",
+                found.len(),
+                msg.channel_id
+            );
+            return;
+        }
+
+        let result = match cards.mode {
+            config::CardMode::Reply => {
+                let hidden = self.hidden_location_callsigns(guild_config);
+                let embeds = found
+                    .iter()
+                    .map(|(callsign, info)| {
+                        callsign_cards::card(callsign, info, !hidden.contains(callsign))
+                    })
+                    .collect();
+                let message = CreateMessage::new()
+                    .embeds(embeds)
+                    .reference_message(msg)
+                    .allowed_mentions(CreateAllowedMentions::new().replied_user(false));
+                msg.channel_id.send_message(http, message).await.map(|_| ())
+            }
+            config::CardMode::React => msg
+                .react(http, ReactionType::Unicode(cards.emoji.clone()))
+                .await
+                .map(|_| ()),
+        };
+        if let Err(e) = result {
+            warn!(
+                "Failed to answer callsigns in channel {}: {}",
+                msg.channel_id, e
+            );
+        }
+    }
+
+    /// Continue an onboarding conversation with a member's DM reply
+    async fn handle_onboarding_reply(&self, http: &Http, msg: &serenity::model::channel::Message) {
+        let user_id = msg.author.id.get();
+        let reply =
+            match self
+                .onboarding
+                .handle_reply(&self.config, &self.parser, user_id, &msg.content)
+            {
+                Reply::Ignored => return,
+                Reply::Retry { remaining } => {
+                    format!("{} ({}).", ONBOARDING_RETRY, tries_left(remaining))
+                }
+                Reply::GaveUp => ONBOARDING_GAVE_UP.to_string(),
+                Reply::Parsed {
+                    guild_ids,
+                    mut info,
+                } => {
+                    if info.name.is_empty() {
+                        info.name = msg.author.display_name().to_string();
                     }
+                    self.verify_onboarding(http, user_id, &guild_ids, &info)
+                        .await
+                }
+            };
+
+        if let Err(e) = msg.channel_id.say(http, reply).await {
+            warn!(
+                "Failed to reply to {}'s onboarding DM: {}",
+                msg.author.name, e
+            );
+        }
+    }
+
+    /// Check a DMed callsign against QRZ, listing the member under it in each guild
+    /// they're being onboarded in once QRZ confirms it
+    async fn verify_onboarding(
+        &self,
+        http: &Http,
+        user_id: u64,
+        guild_ids: &[u64],
+        info: &parser::MemberInfo,
+    ) -> String {
+        // Config validation requires [qrz] with [guilds.onboarding]
+        let Some(qrz_client) = &self.qrz_client else {
+            return ONBOARDING_GAVE_UP.to_string();
+        };
+        match qrz_client.lookup_callsign(&info.callsign).await {
+            Ok(_) => {}
+            Err(Error::NotFound(_)) => {
+                info!(
+                    "User {} replied to onboarding with {}, which QRZ doesn't know",
+                    user_id, info.callsign
+                );
+                return match self.onboarding.reject(&self.config, user_id) {
+                    Reply::Retry { remaining } => format!(
+                        "QRZ has no record of {}. Check it and try again ({}).",
+                        info.callsign,
+                        tries_left(remaining)
+                    ),
+                    _ => ONBOARDING_GAVE_UP.to_string(),
+                };
+            }
+            Err(e) => {
+                // The conversation stays open, so the member can reply again
+                warn!(
+                    "Failed to verify onboarding callsign {} on QRZ: {:?}",
+                    info.callsign, e
+                );
+                return format!(
+                    "I couldn't check {} on QRZ just now. Please reply again in a few minutes.",
+                    info.callsign
+                );
+            }
+        }
+
+        self.onboarding.finish(user_id);
+        let mut replies = Vec::new();
+        for guild_config in guild_ids
+            .iter()
+            .filter_map(|guild_id| self.config.get_guild_config(*guild_id))
+        {
+            replies.push(
+                self.complete_onboarding(http, guild_config, user_id, info)
+                    .await,
+            );
+        }
+        replies.join("\n")
+    }
+
+    /// Store a member's DMed callsign as an override and suggest or set their nickname
+    async fn complete_onboarding(
+        &self,
+        http: &Http,
+        guild_config: &config::GuildConfig,
+        user_id: u64,
+        info: &parser::MemberInfo,
+    ) -> String {
+        let guild_id = guild_config.guild_id;
+        let member_override = config::Override {
+            callsign: Some(info.callsign.clone()),
+            name: Some(info.name.clone()),
+            suffix: None,
+            emoji: None,
+            skip_qrz: false,
+            hide_location: false,
+        };
+        if let Err(e) = self
+            .storage
+            .set_member_override(guild_id, user_id, member_override)
+        {
+            warn!("{:?}", e);
+        }
+        info!(
+            "Onboarded user {} in guild {} as {}",
+            user_id, guild_id, info.callsign
+        );
+        self.enqueue(guild_id, user_id, "onboarding");
+
+        let mut reply = format!(
+            "Thanks! You'll be listed as {} {}.",
+            info.callsign, info.name
+        );
+        let Some(nickname) = onboarding::suggested_nickname(guild_config, info) else {
+            return reply;
+        };
+        let set_nickname = guild_config
+            .onboarding
+            .as_ref()
+            .is_some_and(|o| o.set_nickname);
+        if !set_nickname {
+            reply.push_str(&format!(
+                " Consider changing your server nickname to `{}`.",
+                nickname
+            ));
+            return reply;
+        }
+
+        // Fails for the server owner and members above the bot's highest role
+        match GuildId::new(guild_id)
+            .edit_member(
+                http,
+                UserId::new(user_id),
+                EditMember::new().nickname(&nickname),
+            )
+            .await
+        {
+            Ok(_) => reply.push_str(&format!(" I've set your nickname to `{}`.", nickname)),
+            Err(e) => {
+                warn!(
+                    "Failed to rename user {} to {:?} in guild {}: {}",
+                    user_id, nickname, guild_id, e
+                );
+                reply.push_str(&format!(
+                    " I couldn't set your nickname; consider changing it to `{}`.",
+                    nickname
+                ));
+            }
+        }
+        reply
+    }
+
+    /// Record how a departing member was listed, for the alumni section
+    fn remember_departure(
+        &self,
+        guild_config: &config::GuildConfig,
+        user: &serenity::model::user::User,
+        member: Option<&Member>,
+    ) {
+        let guild_id = guild_config.guild_id;
+        let user_id = user.id.get();
+        let override_config = guild_config.get_override(&user_id.to_string());
+        let parsed = member.and_then(|m| self.roster_builder().parse_member(m).0);
+
+        // Members who were never listed under a callsign have nothing to remember
+        let Some(callsign) = override_config
+            .and_then(|o| o.callsign.clone())
+            .or_else(|| parsed.as_ref().map(|p| p.callsign.clone()))
+            .or_else(|| self.rosters.known_callsign(guild_id, user_id))
+        else {
+            return;
+        };
+
+        // Prefer the name as last published, which may have come from QRZ
+        let listed_name = self.rosters.get(guild_id).and_then(|roster| {
+            roster
+                .entries
+                .into_iter()
+                .find(|e| e.callsign == callsign)
+                .map(|e| e.name)
+        });
+        let name = override_config
+            .and_then(|o| o.name.clone())
+            .or(listed_name)
+            .or_else(|| parsed.map(|p| p.name))
+            .unwrap_or_else(|| user.name.clone());
+
+        info!(
+            "Recording departure of {} ({}) from guild {}",
+            callsign, name, guild_id
+        );
+        let departed = pipeline::DepartedMember {
+            callsign,
+            name,
+            departed_at: state::unix_now(),
+        };
+        if let Err(e) = self.storage.record_departure(guild_id, user_id, departed) {
+            warn!("{:?}", e);
+        }
+    }
+
+    fn is_dry_run(&self, guild_config: &config::GuildConfig) -> bool {
+        self.dry_run || guild_config.output.dry_run
+    }
+
+    /// Log the diff between the published file and what would be committed
+    async fn log_dry_run(&self, guild_config: &config::GuildConfig, content: &str) {
+        let output = &guild_config.output;
+        let gist_id = output
+            .gist
+            .as_ref()
+            .and_then(|g| g.id.clone())
+            .or_else(|| self.storage.gist_id(guild_config.guild_id));
+        // The repo is the source of truth when both targets are configured
+        let (target, current) = match (&output.repo, gist_id) {
+            (Some(repo), _) => (
+                format!("{}/{} on branch {}", repo, output.path, output.branch),
+                self.github_client
+                    .get_file_content(repo, &output.path, &output.branch)
+                    .await
+                    .map_err(anyhow::Error::from),
+            ),
+            (None, Some(id)) => (
+                format!("gist {}", id),
+                self.github_client
+                    .get_gist_content(&id, output.gist_filename())
+                    .await
+                    .map_err(anyhow::Error::from),
+            ),
+            (None, None) => match &output.s3 {
+                Some(s3) if output.gist.is_none() => (
+                    format!("s3://{}/{}", s3.bucket, s3.key),
+                    Err(anyhow::anyhow!("objects aren't read back")),
+                ),
+                _ => (
+                    "a new gist".to_string(),
+                    Err(anyhow::anyhow!("not created yet")),
+                ),
+            },
+        };
+        let current = match current {
+            Ok(current) => current,
+            Err(e) => {
+                info!(
+                    "[dry run] Could not read {} ({}); treating it as empty",
+                    target, e
+                );
+                String::new()
+            }
+        };
+
+        let changes = diff_lines(&current, content);
+        if changes.is_empty() {
+            info!("[dry run] {} is already up to date", target);
+            return;
+        }
+
+        info!(
+            "[dry run] Would write {} changed lines to {}:",
+            changes.len(),
+            target
+        );
+        for change in changes {
+            info!("[dry run]   {}", change);
+        }
+    }
+
+    /// Generate with exponential backoff per `[startup]`, so a transient outage
+    /// (Discord, QRZ, GitHub) at connect time doesn't leave the list stale
+    async fn generate_with_retry(
+        &self,
+        http: &Http,
+        bot_user_id: UserId,
+        guild_config: &config::GuildConfig,
+        cause: &str,
+    ) -> Result<()> {
+        let startup = &self.config.startup;
+        let backoff = Backoff::new(
+            Duration::from_secs(startup.initial_backoff_secs),
+            Duration::from_secs(startup.max_backoff_secs),
+        );
+
+        let mut attempt = 1;
+        loop {
+            match self
+                .generate_member_list(http, bot_user_id, guild_config, cause)
+                .await
+            {
+                Ok(()) => return Ok(()),
+                Err(e) if startup.max_attempts != 0 && attempt >= startup.max_attempts => {
+                    return Err(e.context(format!("Giving up after {} attempts", attempt)));
+                }
+                // Bad credentials or a missing repo won't fix themselves between attempts
+                Err(e) if Error::classify(&e).is_some_and(|class| !class.is_retryable()) => {
+                    let label = Error::classify(&e).map_or("unknown", Error::label);
+                    return Err(e.context(format!("Not retrying a {} error", label)));
+                }
+                Err(e) => {
+                    let delay = backoff.delay(attempt);
+                    warn!(
+                        "Attempt {} to generate member list for guild {} failed ({}): {:?}. Retrying in {:?}",
+                        attempt,
+                        guild_config.guild_id,
+                        Error::classify(&e).map_or("unknown", Error::label),
+                        e,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Look up every guild's roster callsigns per `[qrz] prewarm`, several at a time, so
+    /// the first regeneration finds them cached; then save the cache
+    async fn prewarm_qrz(&self, http: &Http) {
+        let (Some(qrz_client), Some(qrz_config)) = (
+            &self.qrz_client,
+            self.config.qrz.as_ref().filter(|qrz| qrz.prewarm),
+        ) else {
+            return;
+        };
+
+        let builder = self.roster_builder();
+        let mut callsigns = BTreeSet::new();
+        for guild_config in &self.config.guilds {
+            let guild_id = GuildId::new(guild_config.guild_id);
+            match self.rest_limits.fetch_members(http, guild_id).await {
+                Ok(members) => {
+                    let members: Vec<Member> = members.iter().map(discord::member).collect();
+                    callsigns.extend(
+                        builder.qrz_callsigns(&self.with_all_overrides(guild_config), &members),
+                    );
+                }
+                Err(e) => warn!(
+                    "Failed to fetch members of guild {} to pre-warm QRZ: {}",
+                    guild_id, e
+                ),
+            }
+        }
+
+        info!(
+            "Pre-warming the QRZ cache with {} callsigns, {} at a time",
+            callsigns.len(),
+            qrz_config.prewarm_concurrency
+        );
+        let started = Instant::now();
+        let permits = Arc::new(tokio::sync::Semaphore::new(qrz_config.prewarm_concurrency));
+        let mut lookups = tokio::task::JoinSet::new();
+        for callsign in callsigns {
+            let (qrz_client, permits) = (qrz_client.clone(), permits.clone());
+            lookups.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                // Lookups are cached by the client; only the outcome matters here
+                qrz_client.lookup_callsign(&callsign).await.map(|_| ())
+            });
+        }
+        let (mut found, mut not_found, mut failed) = (0, 0, 0);
+        while let Some(result) = lookups.join_next().await {
+            match result {
+                Ok(Ok(())) => found += 1,
+                Ok(Err(Error::NotFound(_))) => not_found += 1,
+                _ => failed += 1,
+            }
+        }
+        info!(
+            "Pre-warmed the QRZ cache in {:.1}s: {} found, {} not found, {} failed",
+            started.elapsed().as_secs_f64(),
+            found,
+            not_found,
+            failed
+        );
+
+        self.save_qrz_cache();
+    }
+
+    /// Save the QRZ cache to `[qrz] cache_file`, if set
+    fn save_qrz_cache(&self) {
+        let (Some(qrz_client), Some(path)) = (
+            &self.qrz_client,
+            self.config
+                .qrz
+                .as_ref()
+                .and_then(|qrz| qrz.cache_file.as_ref()),
+        ) else {
+            return;
+        };
+        match qrz_client.save_cache(path) {
+            Ok(saved) => info!("Saved {} QRZ lookups to {}", saved, path),
+            Err(e) => warn!("{:?}", e),
+        }
+    }
+
+    /// Queue a member event; the queue worker regenerates the guild
+    fn enqueue(&self, guild_id: u64, user_id: u64, cause: &'static str) {
+        let result = self.queue.push(guild_id, user_id, cause);
+        self.metrics
+            .queued_events
+            .with_label_values(&[result.label()])
+            .inc();
+        self.metrics
+            .event_queue_depth
+            .set(self.queue.depth() as i64);
+
+        if result == Enqueued::Dropped {
+            warn!(
+                "Event queue full; dropped {} event for user {} (guild {} will still regenerate)",
+                cause, user_id, guild_id
+            );
+        }
+    }
+
+    /// Post a guild's net attendance leaderboard on its `[guilds.nets.leaderboard]` schedule
+    async fn run_leaderboard(
+        self: Arc<Self>,
+        http: Arc<Http>,
+        guild_id: u64,
+        leaderboard: config::LeaderboardConfig,
+        schedule: Schedule,
+    ) {
+        loop {
+            let delay = match schedule.next_delay(chrono::Utc::now()) {
+                Ok(delay) => delay,
+                Err(e) => {
+                    error!(
+                        "Stopping net leaderboard posts for guild {}: {:?}",
+                        guild_id, e
+                    );
+                    return;
                 }
+            };
+            tokio::time::sleep(delay).await;
+
+            let history = self.storage.net_history(guild_id);
+            if history.is_empty() {
+                continue;
+            }
+            let content = nets::leaderboard(&history, leaderboard.size);
+            if self.dry_run {
+                info!(
+                    "[dry run] Would post net leaderboard for guild {} to channel {}",
+                    guild_id, leaderboard.channel_id
+                );
+                continue;
+            }
+            let message = CreateMessage::new()
+                .content(commands::truncate_message(&content))
+                .allowed_mentions(CreateAllowedMentions::new());
+            if let Err(e) = ChannelId::new(leaderboard.channel_id)
+                .send_message(&http, message)
+                .await
+            {
+                warn!(
+                    "Failed to post net leaderboard to channel {}: {}",
+                    leaderboard.channel_id, e
+                );
+            }
+        }
+    }
+
+    /// Announce DX cluster spots of (and by) listed members in each guild with
+    /// `[guilds.dx_spots]`
+    async fn run_dx_spots(
+        self: Arc<Self>,
+        http: Arc<Http>,
+        mut spots: tokio::sync::mpsc::Receiver<Spot>,
+        repeat_after: Duration,
+    ) {
+        let mut dedup = SpotDedup::new(repeat_after);
+        while let Some(spot) = spots.recv().await {
+            // Portable and operating prefixes (W6JSV/P, VE3/W6JSV) still match the member
+            let dx = self
+                .parser
+                .parse(&spot.dx)
+                .map_or(spot.dx.clone(), |info| info.callsign);
+            let band = spot.band().unwrap_or("other");
+
+            for guild_config in &self.config.guilds {
+                let Some(dx_spots) = &guild_config.dx_spots else {
+                    continue;
+                };
+                let guild_id = guild_config.guild_id;
+                let (member, spotted) = if dx_spots.spotted
+                    && self.rosters.lists_callsign(guild_id, &dx)
+                {
+                    (&dx, true)
+                } else if dx_spots.spotter && self.rosters.lists_callsign(guild_id, &spot.spotter) {
+                    (&spot.spotter, false)
+                } else {
+                    continue;
+                };
+                if !dedup.should_announce(guild_id, member, band) {
+                    continue;
+                }
+
+                let content = dxcluster::announcement(&spot, spotted);
+                if self.dry_run {
+                    info!(
+                        "[dry run] Would post to channel {}: {}",
+                        dx_spots.channel_id, content
+                    );
+                    continue;
+                }
+                let message = CreateMessage::new()
+                    .content(commands::truncate_message(&content))
+                    .allowed_mentions(CreateAllowedMentions::new());
+                if let Err(e) = ChannelId::new(dx_spots.channel_id)
+                    .send_message(&http, message)
+                    .await
+                {
+                    warn!(
+                        "Failed to post DX spot to channel {}: {}",
+                        dx_spots.channel_id, e
+                    );
+                }
+            }
+        }
+    }
+
+    /// Post the club's claimed scores for one `[[guilds.contest_scores]]` contest on
+    /// its schedule, skipping runs where nothing changed
+    async fn run_contest_scores(
+        self: Arc<Self>,
+        http: Arc<Http>,
+        guild_id: u64,
+        contest: config::ContestScoresConfig,
+        schedule: Schedule,
+    ) {
+        loop {
+            let delay = match schedule.next_delay(chrono::Utc::now()) {
+                Ok(delay) => delay,
+                Err(e) => {
+                    error!(
+                        "Stopping {} score posts for guild {}: {:?}",
+                        contest.name, guild_id, e
+                    );
+                    return;
+                }
+            };
+            tokio::time::sleep(delay).await;
+
+            let Some(roster) = self.rosters.get(guild_id) else {
+                continue;
+            };
+            let members: HashSet<String> = roster.entries.into_iter().map(|e| e.callsign).collect();
+            let mut callsigns: Vec<String> = members.iter().cloned().collect();
+            callsigns.sort();
+            let matching = contest.contest.as_deref().unwrap_or(&contest.name);
+            let scores = self
+                .contest_client
+                .fetch_scores(&contest.search_url, matching, &callsigns)
+                .await;
+            let mut scores = match scores {
+                Ok(scores) => scores,
+                Err(e) => {
+                    warn!("Failed to fetch {} scores: {:?}", contest.name, e);
+                    continue;
+                }
+            };
+            // Portable entries (W6JSV/6) count for the member
+            for score in &mut scores {
+                if let Some(info) = self.parser.parse(&score.callsign) {
+                    score.callsign = info.callsign;
+                }
+            }
+
+            let Some(content) =
+                contests::club_summary(&contest.name, &scores, &members, contest.top)
+            else {
+                continue;
+            };
+            let posted = self.storage.contest_summary(guild_id, &contest.name);
+            if posted.is_some_and(|posted| posted == content) {
+                continue;
+            }
+            if self.dry_run {
+                info!(
+                    "[dry run] Would post {} scores for guild {} to channel {}",
+                    contest.name, guild_id, contest.channel_id
+                );
+                continue;
+            }
+            let message = CreateMessage::new()
+                .content(commands::truncate_message(&content))
+                .allowed_mentions(CreateAllowedMentions::new());
+            match ChannelId::new(contest.channel_id)
+                .send_message(&http, message)
+                .await
+            {
+                Ok(_) => {
+                    if let Err(e) =
+                        self.storage
+                            .set_contest_summary(guild_id, &contest.name, content)
+                    {
+                        warn!("Failed to save the {} summary: {:?}", contest.name, e);
+                    }
+                }
+                Err(e) => warn!(
+                    "Failed to post {} scores to channel {}: {}",
+                    contest.name, contest.channel_id, e
+                ),
+            }
+        }
+    }
+
+    /// Post solar indices and band conditions on a guild's `[guilds.solar_post]` schedule
+    async fn run_solar_posts(
+        self: Arc<Self>,
+        http: Arc<Http>,
+        guild_id: u64,
+        solar_post: config::SolarPostConfig,
+        schedule: Schedule,
+    ) {
+        loop {
+            let delay = match schedule.next_delay(chrono::Utc::now()) {
+                Ok(delay) => delay,
+                Err(e) => {
+                    error!("Stopping solar posts for guild {}: {:?}", guild_id, e);
+                    return;
+                }
+            };
+            tokio::time::sleep(delay).await;
+
+            // Checked when the config was loaded
+            let Some(propagation_client) = &self.propagation_client else {
+                return;
+            };
+            let report = match propagation_client.report().await {
+                Ok(report) => report,
+                Err(e) => {
+                    warn!("Failed to fetch solar data: {:?}", e);
+                    continue;
+                }
+            };
+            if self.dry_run {
+                info!(
+                    "[dry run] Would post solar data for guild {} to channel {}",
+                    guild_id, solar_post.channel_id
+                );
+                continue;
+            }
+            let message = CreateMessage::new().embed(propagation::embed(&report));
+            if let Err(e) = ChannelId::new(solar_post.channel_id)
+                .send_message(&http, message)
+                .await
+            {
+                warn!(
+                    "Failed to post solar data to channel {}: {}",
+                    solar_post.channel_id, e
+                );
+            }
+        }
+    }
+
+    /// Post a reminder before each start of a guild's `/event` events, checking once a
+    /// minute since events can be added at any time. With `discord_events`, also keep a
+    /// Discord scheduled event for each one's next occurrence, and with `ics_path`, keep
+    /// the committed calendar current
+    async fn run_event_reminders(
+        self: Arc<Self>,
+        http: Arc<Http>,
+        guild_id: u64,
+        events_config: config::EventsConfig,
+    ) {
+        let lead = chrono::Duration::minutes(events_config.remind_mins.into());
+        let mut since = chrono::Utc::now();
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        // Last calendar committed, so unchanged ones aren't sent to GitHub every minute
+        let mut committed_calendar = None;
+        loop {
+            interval.tick().await;
+            let now = chrono::Utc::now();
+            if let Some(path) = &events_config.ics_path {
+                self.commit_calendar(guild_id, path, now, &mut committed_calendar)
+                    .await;
+            }
+            for event in self.storage.events(guild_id) {
+                match event.reminder_due(lead, since, now) {
+                    Ok(Some(start)) => {
+                        self.post_event_reminder(&http, &events_config, &event, start)
+                            .await
+                    }
+                    Ok(None) => {}
+                    // Checked when the event was created; some cron dates never come
+                    Err(e) => debug!("No upcoming start for event {}: {:?}", event.name, e),
+                }
+                if events_config.discord_events {
+                    self.create_discord_event(&http, guild_id, &event, now)
+                        .await;
+                }
+            }
+            since = now;
+        }
+    }
+
+    /// Commit the guild's iCalendar feed to `output.repo` if it changed
+    async fn commit_calendar(
+        &self,
+        guild_id: u64,
+        path: &str,
+        now: chrono::DateTime<chrono::Utc>,
+        committed: &mut Option<String>,
+    ) {
+        let Some(guild_config) = self.config.get_guild_config(guild_id) else {
+            return;
+        };
+        // Checked when the config was loaded
+        let Some(repo) = &guild_config.output.repo else {
+            return;
+        };
+        let feed = events::calendar(
+            guild_id,
+            &guild_config.calendar_name(),
+            &self.storage.events(guild_id),
+            now,
+        );
+        if committed.as_ref() == Some(&feed) {
+            return;
+        }
+        if self.is_dry_run(guild_config) {
+            info!(
+                "[dry run] Would commit the events calendar to {}/{}",
+                repo, path
+            );
+            *committed = Some(feed);
+            return;
+        }
+
+        let files = [FileChange {
+            path,
+            content: &feed,
+        }];
+        let result = self
+            .github_client
+            .commit_files(
+                repo,
+                &guild_config.output.branch,
+                &files,
+                "Update events calendar",
+            )
+            .await;
+        self.record_github(&result);
+        match result {
+            Ok(outcome) => {
+                if outcome == CommitOutcome::Committed {
+                    info!("Committed events calendar to {}/{}", repo, path);
+                }
+                *committed = Some(feed);
+            }
+            Err(e) => warn!(
+                "Failed to commit events calendar to {}/{}: {:?}",
+                repo, path, e
+            ),
+        }
+    }
+
+    async fn post_event_reminder(
+        &self,
+        http: &Http,
+        events_config: &config::EventsConfig,
+        event: &events::ClubEvent,
+        start: chrono::DateTime<chrono::Utc>,
+    ) {
+        if self.dry_run {
+            info!(
+                "[dry run] Would remind channel {} about {} at {}",
+                events_config.channel_id, event.name, start
+            );
+            return;
+        }
+        let message = CreateMessage::new().content(events::reminder(event, start));
+        match ChannelId::new(events_config.channel_id)
+            .send_message(http, message)
+            .await
+        {
+            Ok(_) => info!("Posted reminder for {} at {}", event.name, start),
+            Err(e) => warn!(
+                "Failed to post reminder for {} to channel {}: {}",
+                event.name, events_config.channel_id, e
+            ),
+        }
+    }
+
+    /// Create a Discord scheduled event for the event's next occurrence, unless one
+    /// was already created for it
+    async fn create_discord_event(
+        &self,
+        http: &Http,
+        guild_id: u64,
+        event: &events::ClubEvent,
+        now: chrono::DateTime<chrono::Utc>,
+    ) {
+        let Ok(start) = event.next_start(now) else {
+            return;
+        };
+        if event.discord_event_start == Some(start) {
+            return;
+        }
+        // Recorded first, so a failure is logged once rather than retried every minute
+        if let Err(e) = self
+            .storage
+            .set_discord_event_start(guild_id, event.id, start)
+        {
+            error!("Failed to save Discord event for {}: {:?}", event.name, e);
+            return;
+        }
+        if self.dry_run {
+            info!(
+                "[dry run] Would create a Discord event for {} at {}",
+                event.name, start
+            );
+            return;
+        }
+
+        let (Ok(start_time), Ok(end_time)) = (
+            Timestamp::from_unix_timestamp(start.timestamp()),
+            Timestamp::from_unix_timestamp(event.end(start).timestamp()),
+        ) else {
+            return;
+        };
+        let location = event
+            .location
+            .clone()
+            .unwrap_or_else(|| "See the club's Discord".to_string());
+        let mut builder =
+            CreateScheduledEvent::new(ScheduledEventType::External, &event.name, start_time)
+                .end_time(end_time)
+                .location(location);
+        if let Some(description) = &event.description {
+            builder = builder.description(description);
+        }
+        match GuildId::new(guild_id)
+            .create_scheduled_event(http, builder)
+            .await
+        {
+            Ok(_) => info!("Created Discord event for {} at {}", event.name, start),
+            Err(e) => warn!(
+                "Failed to create Discord event for {} in guild {}: {}",
+                event.name, guild_id, e
+            ),
+        }
+    }
+
+    /// Take down a guild's expired `/swap` listings, checking hourly
+    async fn run_swap_expiry(self: Arc<Self>, http: Arc<Http>, guild_id: u64, channel_id: u64) {
+        let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+        loop {
+            interval.tick().await;
+            let expired = match self
+                .storage
+                .remove_expired_swap_listings(guild_id, chrono::Utc::now())
+            {
+                Ok(expired) => expired,
+                Err(e) => {
+                    error!(
+                        "Failed to expire swap listings for guild {}: {:?}",
+                        guild_id, e
+                    );
+                    continue;
+                }
+            };
+            for listing in expired {
+                info!(
+                    "Swap listing {} ({}) expired in guild {}",
+                    listing.id, listing.item, guild_id
+                );
+                let Some(message_id) = listing.message_id else {
+                    continue;
+                };
+                if self.dry_run {
+                    info!(
+                        "[dry run] Would delete swap listing {}'s message in channel {}",
+                        listing.id, channel_id
+                    );
+                    continue;
+                }
+                // It may already have been deleted by hand
+                if let Err(e) = ChannelId::new(channel_id)
+                    .delete_message(&http, message_id)
+                    .await
+                {
+                    warn!(
+                        "Failed to delete swap listing {}'s message: {}",
+                        listing.id, e
+                    );
+                }
+            }
+        }
+    }
+
+    /// Post a digest of a guild's active `/swap` listings on its `swap.digest` schedule
+    async fn run_swap_digest(
+        self: Arc<Self>,
+        http: Arc<Http>,
+        guild_id: u64,
+        channel_id: u64,
+        schedule: Schedule,
+    ) {
+        loop {
+            let delay = match schedule.next_delay(chrono::Utc::now()) {
+                Ok(delay) => delay,
+                Err(e) => {
+                    error!("Stopping swap digests for guild {}: {:?}", guild_id, e);
+                    return;
+                }
+            };
+            tokio::time::sleep(delay).await;
+
+            let now = chrono::Utc::now();
+            let listings: Vec<swap::Listing> = self
+                .storage
+                .swap_listings(guild_id)
+                .into_iter()
+                .filter(|listing| !listing.is_expired(now))
+                .collect();
+            if listings.is_empty() {
+                continue;
+            }
+            if self.dry_run {
+                info!(
+                    "[dry run] Would post a digest of {} swap listings to channel {}",
+                    listings.len(),
+                    channel_id
+                );
+                continue;
+            }
+            let content = swap::list("Swap digest", &listings, guild_id, channel_id);
+            let message = CreateMessage::new().content(commands::truncate_message(&content));
+            if let Err(e) = ChannelId::new(channel_id)
+                .send_message(&http, message)
+                .await
+            {
+                warn!(
+                    "Failed to post swap digest to channel {}: {}",
+                    channel_id, e
+                );
+            }
+        }
+    }
+
+    /// Relay new items from a guild's `[guilds.bulletins]` feeds on its schedule
+    async fn run_bulletins(
+        self: Arc<Self>,
+        http: Arc<Http>,
+        guild_id: u64,
+        bulletins: config::BulletinsConfig,
+        schedule: Schedule,
+    ) {
+        loop {
+            let delay = match schedule.next_delay(chrono::Utc::now()) {
+                Ok(delay) => delay,
+                Err(e) => {
+                    error!("Stopping bulletins for guild {}: {:?}", guild_id, e);
+                    return;
+                }
+            };
+            tokio::time::sleep(delay).await;
+
+            for feed_url in &bulletins.feeds {
+                if let Err(e) = self
+                    .relay_bulletins(&http, guild_id, &bulletins, feed_url)
+                    .await
+                {
+                    warn!("Failed to relay bulletins from {}: {:?}", feed_url, e);
+                }
+            }
+        }
+    }
+
+    /// Post one feed's unseen items, recording each in `Storage` once it's posted so a
+    /// failed post is retried on the next check
+    async fn relay_bulletins(
+        &self,
+        http: &Http,
+        guild_id: u64,
+        bulletins: &config::BulletinsConfig,
+        feed_url: &str,
+    ) -> Result<()> {
+        let feed = self.bulletin_client.fetch(feed_url).await?;
+        let Some(seen) = self.storage.seen_bulletins(guild_id, feed_url) else {
+            // A newly added feed's backlog isn't news; relay what's published from now on
+            info!(
+                "Found {} existing bulletins in {} for guild {}; relaying new ones from now on",
+                feed.items.len(),
+                feed_url,
+                guild_id
+            );
+            if !self.dry_run {
+                self.storage.mark_bulletins_seen(
+                    guild_id,
+                    feed_url,
+                    feed.items.into_iter().map(|b| b.id),
+                )?;
+            }
+            return Ok(());
+        };
+
+        let (post, skipped) = bulletins::select(&feed.items, &seen, bulletins.max_posts);
+        if self.dry_run {
+            for bulletin in &post {
+                info!(
+                    "[dry run] Would post bulletin \"{}\" to channel {}",
+                    bulletin.title, bulletins.channel_id
+                );
+            }
+            return Ok(());
+        }
+        if !skipped.is_empty() {
+            info!(
+                "Skipping {} older bulletins from {} for guild {}",
+                skipped.len(),
+                feed_url,
+                guild_id
+            );
+            self.storage.mark_bulletins_seen(
+                guild_id,
+                feed_url,
+                skipped.into_iter().map(|b| b.id.clone()),
+            )?;
+        }
+        for bulletin in post {
+            let message =
+                CreateMessage::new().embed(bulletins::embed(bulletin, feed.title.as_deref()));
+            ChannelId::new(bulletins.channel_id)
+                .send_message(http, message)
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to post bulletin to channel {}: {}",
+                        bulletins.channel_id,
+                        e
+                    )
+                })?;
+            self.storage
+                .mark_bulletins_seen(guild_id, feed_url, [bulletin.id.clone()])?;
+        }
+        Ok(())
+    }
+
+    /// Post upcoming license expirations for a guild's latest roster on its
+    /// `[guilds.license_reminders]` schedule
+    async fn run_license_reminders(
+        self: Arc<Self>,
+        http: Arc<Http>,
+        guild_id: u64,
+        reminders: config::LicenseRemindersConfig,
+        schedule: Schedule,
+    ) {
+        loop {
+            let delay = match schedule.next_delay(chrono::Utc::now()) {
+                Ok(delay) => delay,
+                Err(e) => {
+                    error!("Stopping license reminders for guild {}: {:?}", guild_id, e);
+                    return;
+                }
+            };
+            tokio::time::sleep(delay).await;
+
+            let (Some(qrz_client), Some(roster)) = (&self.qrz_client, self.rosters.get(guild_id))
+            else {
+                continue;
+            };
+            let opted_out = self
+                .config
+                .get_guild_config(guild_id)
+                .map(|guild_config| self.qrz_opt_out_callsigns(guild_config))
+                .unwrap_or_default();
+            let mut licenses = Vec::new();
+            for entry in roster.entries {
+                if opted_out.contains(&entry.callsign) {
+                    continue;
+                }
+                match qrz_client.lookup_callsign(&entry.callsign).await {
+                    Ok(info) => licenses.push(licenses::License {
+                        callsign: entry.callsign,
+                        name: entry.name,
+                        effective: info.license_effective,
+                        expires: info.license_expires,
+                    }),
+                    Err(e) => debug!("No QRZ license dates for {}: {:?}", entry.callsign, e),
+                }
+            }
+
+            let today = chrono::Utc::now().date_naive();
+            let Some(content) = licenses::announcement(
+                &licenses,
+                today,
+                reminders.within_days,
+                reminders.anniversaries,
+            ) else {
+                info!("No license reminders for guild {}", guild_id);
+                continue;
+            };
+            if self.dry_run {
+                info!(
+                    "[dry run] Would post license reminders for guild {} to channel {}",
+                    guild_id, reminders.channel_id
+                );
+                continue;
+            }
+            let message = CreateMessage::new()
+                .content(commands::truncate_message(&content))
+                .allowed_mentions(CreateAllowedMentions::new());
+            if let Err(e) = ChannelId::new(reminders.channel_id)
+                .send_message(&http, message)
+                .await
+            {
+                warn!(
+                    "Failed to post license reminders to channel {}: {}",
+                    reminders.channel_id, e
+                );
+            }
+        }
+    }
+
+    /// DM opted-in members of a guild whose licenses expire soon, on its
+    /// `[guilds.license_dms]` schedule
+    async fn run_license_dms(
+        self: Arc<Self>,
+        http: Arc<Http>,
+        guild_id: u64,
+        dms: config::LicenseDmsConfig,
+        schedule: Schedule,
+    ) {
+        loop {
+            let delay = match schedule.next_delay(chrono::Utc::now()) {
+                Ok(delay) => delay,
+                Err(e) => {
+                    error!("Stopping license DMs for guild {}: {:?}", guild_id, e);
+                    return;
+                }
+            };
+            tokio::time::sleep(delay).await;
+
+            let Some(qrz_client) = &self.qrz_client else {
+                continue;
+            };
+            let today = chrono::Utc::now().date_naive();
+            for user_id in self.storage.license_dm_opt_ins(guild_id) {
+                let skips_qrz = self
+                    .config
+                    .get_guild_config(guild_id)
+                    .is_some_and(|guild_config| self.skips_qrz(guild_config, user_id));
+                if skips_qrz {
+                    continue;
+                }
+                // Members are matched to callsigns by the latest roster build
+                let Some(callsign) = self.rosters.known_callsign(guild_id, user_id) else {
+                    continue;
+                };
+                let expires = match qrz_client.lookup_callsign(&callsign).await {
+                    Ok(info) => info.license_expires,
+                    Err(e) => {
+                        debug!("No QRZ license dates for {}: {:?}", callsign, e);
+                        None
+                    }
+                };
+                let Some(expires) = expires else {
+                    continue;
+                };
+                let last_warned = self.storage.license_dm_sent(guild_id, user_id);
+                if !licenses::dm_due(expires, today, dms.days_before, last_warned) {
+                    continue;
+                }
+
+                if self.dry_run {
+                    info!(
+                        "[dry run] Would DM user {} about {} expiring {}",
+                        user_id, callsign, expires
+                    );
+                    continue;
+                }
+                let content = licenses::dm_message(&callsign, expires, today);
+                if let Err(e) = UserId::new(user_id)
+                    .direct_message(&http, CreateMessage::new().content(content))
+                    .await
+                {
+                    // Members can turn off DMs from server members
+                    warn!("Failed to DM user {} about license expiry: {}", user_id, e);
+                    continue;
+                }
+                info!(
+                    "Warned user {} that {} expires {}",
+                    user_id, callsign, expires
+                );
+                if let Err(e) = self.storage.record_license_dm(guild_id, user_id, expires) {
+                    error!("Failed to save license DM for user {}: {:?}", user_id, e);
+                }
+            }
+        }
+    }
+
+    /// Queue a regeneration of every guild on the `[schedule]`, independent of member events
+    async fn run_schedule(self: Arc<Self>, schedule: Schedule) {
+        loop {
+            let delay = match schedule.next_delay(chrono::Utc::now()) {
+                Ok(delay) => delay,
+                Err(e) => {
+                    error!("Stopping scheduled regenerations: {:?}", e);
+                    return;
+                }
+            };
+            tokio::time::sleep(delay).await;
+
+            // The queue worker runs it, so it never overlaps an event-driven regeneration
+            for guild_config in &self.config.guilds {
+                info!(
+                    "Queueing scheduled regeneration for guild {}",
+                    guild_config.guild_id
+                );
+                self.queue.push_guild(guild_config.guild_id, "schedule");
+            }
+        }
+    }
+
+    /// Retry the QRZ lookups that failed or found nothing in each guild's latest build,
+    /// regenerating the guild once any resolves rather than waiting for a member event.
+    /// Lookups are paced so roster builds and commands keep priority.
+    async fn run_qrz_backfill(self: Arc<Self>, http: Arc<Http>, interval: Duration) {
+        let Some(qrz_client) = self.qrz_client.clone() else {
+            return;
+        };
+        let bot_user_id = match http.get_current_user().await {
+            Ok(user) => user.id,
+            Err(e) => {
+                error!("Failed to look up bot user; QRZ backfill won't run: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            for guild_config in &self.config.guilds {
+                let pending = self.rosters.unresolved(guild_config.guild_id);
+                let mut resolved = Vec::new();
+                for callsign in &pending {
+                    // Successful lookups are cached, so the regeneration below reuses them
+                    match qrz_client.lookup_callsign(callsign).await {
+                        Ok(_) => resolved.push(callsign.as_str()),
+                        Err(e) => debug!("QRZ backfill for {} still failing: {}", callsign, e),
+                    }
+                    tokio::time::sleep(QRZ_BACKFILL_PAUSE).await;
+                }
+                if resolved.is_empty() {
+                    continue;
+                }
+
+                resolved.sort();
+                info!(
+                    "QRZ backfill resolved {} in guild {}; regenerating",
+                    resolved.join(", "),
+                    guild_config.guild_id
+                );
+                if let Err(e) = self
+                    .generate_member_list(&http, bot_user_id, guild_config, "qrz_backfill")
+                    .await
+                {
+                    error!(
+                        "Failed to regenerate guild {} after QRZ backfill: {:?}",
+                        guild_config.guild_id, e
+                    );
+                }
+            }
+        }
+    }
+
+    /// Regenerate guilds with pending member events, one batch at a time.
+    /// Bursts of events for a guild collapse into a single regeneration.
+    async fn process_queue(self: Arc<Self>, http: Arc<Http>) {
+        let bot_user_id = match http.get_current_user().await {
+            Ok(user) => user.id,
+            Err(e) => {
+                error!(
+                    "Failed to look up bot user; member events won't be processed: {}",
+                    e
+                );
+                return;
+            }
+        };
+        let debounce = Duration::from_millis(self.config.event_queue.debounce_ms);
+
+        loop {
+            // Wait for the first event, then let the burst settle before draining
+            self.queue.wait_for_event().await;
+            tokio::time::sleep(debounce).await;
+            let batch = self.queue.take_all();
+            self.metrics
+                .event_queue_depth
+                .set(self.queue.depth() as i64);
+
+            for (guild_id, pending) in batch {
+                let Some(guild_config) = self.config.get_guild_config(guild_id) else {
+                    continue;
+                };
+
+                info!(
+                    "Regenerating guild {} ({}) for {} member(s) with pending events",
+                    guild_id,
+                    pending.cause(),
+                    pending.users.len()
+                );
+                if let Err(e) = self
+                    .generate_member_list(&http, bot_user_id, guild_config, pending.cause())
+                    .await
+                {
+                    error!(
+                        "Failed to regenerate member list for guild {} ({}): {:?}",
+                        guild_id,
+                        pending.cause(),
+                        e
+                    );
+                } else {
+                    info!(
+                        "Member list updated for guild {} ({})",
+                        guild_id,
+                        pending.cause()
+                    );
+                }
+            }
+        }
+    }
+
+    fn roster_builder(&self) -> RosterBuilder<'_> {
+        RosterBuilder {
+            parser: &self.parser,
+            qrz_client: self
+                .qrz_client
+                .as_deref()
+                .map(|qrz| qrz as &dyn CallsignLookup),
+            radioid_client: self.radioid_client.as_ref(),
+            aprs_client: self.aprs_client.as_ref(),
+            dxcc: Some(self.dxcc.current()),
+            lotw: self.lotw.as_ref().map(|lotw| lotw.current()),
+            metrics: &self.metrics,
+            excluded_user_ids: &self.config.discord.exclude_user_ids,
+            alerts: self
+                .alerts
+                .as_ref()
+                .map(|alerts| alerts as &dyn LookupAlerts),
+        }
+    }
+
+    /// The guild config with stored and approved overrides merged in field by field.
+    /// Stored overrides (onboarding DMs, /override) yield to configured ones; overrides
+    /// approved via /suggest-overrides take precedence and apply until restart
+    fn with_all_overrides(&self, guild_config: &config::GuildConfig) -> config::GuildConfig {
+        let approved = self.rosters.approved_overrides(guild_config.guild_id);
+        let mut guild_config = guild_config.clone();
+        let mut overrides = self.storage.member_overrides(guild_config.guild_id);
+        for (user_id, o) in std::mem::take(&mut guild_config.overrides)
+            .into_iter()
+            .chain(approved)
+        {
+            let merged = match overrides.remove(&user_id) {
+                Some(below) => o.or(below),
+                None => o,
+            };
+            overrides.insert(user_id, merged);
+        }
+        let guild_id = guild_config.guild_id;
+        let opt_outs = (self
+            .storage
+            .qrz_opt_outs(guild_id)
+            .into_iter()
+            .map(|id| (id, true)))
+        .chain(
+            self.storage
+                .map_opt_outs(guild_id)
+                .into_iter()
+                .map(|id| (id, false)),
+        );
+        for (user_id, qrz) in opt_outs {
+            let privacy = overrides
+                .entry(user_id.to_string())
+                .or_insert(config::Override {
+                    callsign: None,
+                    name: None,
+                    suffix: None,
+                    emoji: None,
+                    skip_qrz: false,
+                    hide_location: false,
+                });
+            if qrz {
+                privacy.skip_qrz = true;
+            } else {
+                privacy.hide_location = true;
+            }
+        }
+        guild_config.overrides = overrides;
+        guild_config
+    }
+
+    /// Whether a member opted out of QRZ lookups, by config override or /privacy
+    fn skips_qrz(&self, guild_config: &config::GuildConfig, user_id: u64) -> bool {
+        guild_config
+            .get_override(&user_id.to_string())
+            .is_some_and(|o| o.skip_qrz)
+            || self
+                .storage
+                .qrz_opt_outs(guild_config.guild_id)
+                .contains(&user_id)
+    }
+
+    /// Roster callsigns of members who opted out of QRZ lookups, for features that
+    /// look up every entry
+    pub fn qrz_opt_out_callsigns(&self, guild_config: &config::GuildConfig) -> HashSet<String> {
+        let guild_id = guild_config.guild_id;
+        guild_config
+            .overrides
+            .iter()
+            .filter(|(_, o)| o.skip_qrz)
+            .filter_map(|(user_id, _)| user_id.parse().ok())
+            .chain(self.storage.qrz_opt_outs(guild_id))
+            .filter_map(|user_id| self.rosters.known_callsign(guild_id, user_id))
+            .collect()
+    }
+
+    /// Roster callsigns of members who hid their location, for features that show
+    /// where a callsign is
+    pub fn hidden_location_callsigns(&self, guild_config: &config::GuildConfig) -> HashSet<String> {
+        let guild_id = guild_config.guild_id;
+        guild_config
+            .overrides
+            .iter()
+            .filter(|(_, o)| o.hide_location)
+            .filter_map(|(user_id, _)| user_id.parse().ok())
+            .chain(self.storage.map_opt_outs(guild_id))
+            .filter_map(|user_id| self.rosters.known_callsign(guild_id, user_id))
+            .collect()
+    }
+
+    /// A check-in for a member, with the callsign and name the roster would list
+    async fn member_checkin(
+        &self,
+        guild_config: &config::GuildConfig,
+        member: &Member,
+        voice: bool,
+    ) -> Option<nets::CheckIn> {
+        let guild_config = self.with_all_overrides(guild_config);
+        let roster = self
+            .roster_builder()
+            .build(
+                &guild_config,
+                std::slice::from_ref(member),
+                None,
+                &HashMap::new(),
+                None,
+            )
+            .await;
+        let entry = roster.entries.into_iter().next()?;
+        Some(nets::CheckIn {
+            callsign: entry.callsign,
+            name: entry.name,
+            user_id: Some(member.user_id),
+            at: chrono::Utc::now(),
+            voice,
+        })
+    }
+
+    /// Check in a member who is in the net's voice channel, if a net is running;
+    /// returns whether they were added
+    async fn check_in_voice_member(
+        &self,
+        guild_config: &config::GuildConfig,
+        member: &Member,
+    ) -> bool {
+        let guild_id = guild_config.guild_id;
+        if self.storage.active_net(guild_id).is_none() {
+            return false;
+        }
+        let Some(checkin) = self.member_checkin(guild_config, member, true).await else {
+            info!(
+                "No callsign for {} in the net voice channel of guild {}",
+                member.username, guild_id
+            );
+            return false;
+        };
+        let callsign = checkin.callsign.clone();
+        match self.storage.update_active_net(guild_id, |net| {
+            net.as_mut().is_some_and(|net| net.check_in(checkin))
+        }) {
+            Ok(added) => {
+                if added {
+                    info!("Checked in {} from voice in guild {}", callsign, guild_id);
+                }
+                added
+            }
+            Err(e) => {
+                error!("Failed to save voice check-in for {}: {:?}", callsign, e);
+                false
+            }
+        }
+    }
+
+    /// Check in everyone already in the guild's net voice channel; returns how many were added
+    async fn check_in_voice_channel(
+        &self,
+        ctx: &Context,
+        guild_config: &config::GuildConfig,
+    ) -> usize {
+        let Some(channel_id) = guild_config.nets.as_ref().and_then(|n| n.voice_channel_id) else {
+            return 0;
+        };
+        let guild_id = GuildId::new(guild_config.guild_id);
+        let user_ids = ctx
+            .cache
+            .guild(guild_id)
+            .map(|guild| {
+                guild
+                    .voice_states
+                    .values()
+                    .filter(|state| state.channel_id == Some(ChannelId::new(channel_id)))
+                    .map(|state| state.user_id)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let mut added = 0;
+        for user_id in user_ids {
+            match guild_id.member(ctx, user_id).await {
+                Ok(member) => {
+                    let member = discord::member(&member);
+                    if self.check_in_voice_member(guild_config, &member).await {
+                        added += 1;
+                    }
+                }
+                Err(e) => warn!("Failed to fetch voice member {}: {}", user_id, e),
+            }
+        }
+        added
+    }
+
+    fn record(&self, event: RecordedEvent) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(&event);
+        }
+    }
+
+    #[instrument(skip_all, fields(guild_id = guild_config.guild_id, cause = cause))]
+    async fn generate_member_list(
+        &self,
+        http: &Http,
+        bot_user_id: UserId,
+        guild_config: &config::GuildConfig,
+        cause: &str,
+    ) -> Result<()> {
+        let guild_id = GuildId::new(guild_config.guild_id);
+        let guild_label = guild_config.guild_id.to_string();
+
+        // Held until this regeneration finishes so shutdown never interrupts a write
+        let Some(_in_flight) = self.in_flight.begin().await else {
+            info!(
+                "Shutting down; skipping {} regeneration for guild {}",
+                cause, guild_id
+            );
+            return Ok(());
+        };
+
+        self.metrics
+            .regenerations
+            .with_label_values(&[guild_label.as_str(), cause])
+            .inc();
+
+        let guild_config = &self.with_all_overrides(guild_config);
+        let builder = self.roster_builder();
+        let pipeline = Pipeline {
+            source: &DiscordSource {
+                http,
+                limits: &self.rest_limits,
+            },
+            builder: self.roster_builder(),
+        };
+        let Generation {
+            members,
+            roster,
+            stable_content,
+            content,
+        } = pipeline
+            .generate(
+                guild_config,
+                Some(bot_user_id.get()),
+                self.storage.suffixes(guild_config.guild_id),
+                self.storage.departed(guild_config.guild_id),
+                Some(self.storage.dues_paid(guild_config.guild_id, dues_year())),
+            )
+            .await?;
+        let unique_entries = roster.entries;
+
+        self.metrics
+            .members
+            .with_label_values(&[guild_label.as_str()])
+            .set(members.len() as i64);
+
+        // Snapshots give replays a baseline to apply later events to, including
+        // after reconnects that may have missed events
+        if matches!(cause, "startup" | "reconnect" | "resync") {
+            self.record(RecordedEvent::Snapshot {
+                at: chrono::Utc::now(),
+                guild_id: guild_config.guild_id,
+                members: members.clone(),
+            });
+        }
+
+        self.report_conflicts(http, guild_config, &roster.conflicts)
+            .await;
+        self.enforce_nicknames(http, guild_config, &members, &roster.by_user)
+            .await;
+        self.sync_licensed_role(
+            http,
+            guild_config,
+            bot_user_id,
+            &members,
+            &roster.by_user,
+            &roster.licensed,
+        )
+        .await;
+
+        // Remember who was listed under which callsign for later override suggestions
+        for member in &members {
+            if let (Some(parsed), _) = builder.parse_member(member) {
+                self.rosters.remember_callsign(
+                    guild_config.guild_id,
+                    member.user_id,
+                    parsed.callsign,
+                );
+            }
+        }
+        self.rosters
+            .set_unresolved(guild_config.guild_id, roster.unresolved.clone());
+
+        info!("Built {} unique entries", unique_entries.len());
+
+        // A restart has no previous roster, and listing everyone as added isn't useful
+        let changes = self
+            .rosters
+            .get(guild_config.guild_id)
+            .map(|previous| simulation::diff_entries(&previous.entries, &unique_entries))
+            .unwrap_or_default();
+
+        let counts = MemberCounts {
+            members: members
+                .iter()
+                .filter(|m| m.user_id != bot_user_id.get() && !builder.is_excluded(m))
+                .count(),
+            parsed: roster.licensed.len(),
+        };
+        self.rosters.update(
+            guild_config.guild_id,
+            guild_config.output.title.clone(),
+            unique_entries.clone(),
+            counts,
+        );
+        self.update_presence();
+        self.metrics
+            .roster_entries
+            .with_label_values(&[guild_label.as_str()])
+            .set(unique_entries.len() as i64);
+        self.metrics
+            .roster_generated_at
+            .with_label_values(&[guild_label.as_str()])
+            .set(state::unix_now() as i64);
+
+        self.publish_member_events(guild_config, &changes).await;
+
+        if self.is_dry_run(guild_config) {
+            self.notify_changes(http, guild_config, &changes).await;
+            if self.mqtt_client.is_some() {
+                info!("[dry run] Would publish roster to MQTT");
+            }
+            if let Some(webhook) = &guild_config.output.webhook {
+                info!("[dry run] Would post roster to webhook {}", webhook.url);
+            }
+            if let Some(sheets) = &guild_config.output.sheets {
+                info!(
+                    "[dry run] Would write {} entries to sheet {} tab {}",
+                    unique_entries.len(),
+                    sheets.spreadsheet_id,
+                    sheets.tab
+                );
+            }
+            self.log_dry_run(guild_config, &content).await;
+            return Ok(());
+        }
+
+        // Presence flaps and irrelevant member updates often render identically
+        let fingerprint = output::fingerprint(&stable_content, &unique_entries);
+        if self.rosters.published_fingerprint(guild_config.guild_id) == Some(fingerprint) {
+            info!(
+                "No changes for guild {}; skipping sinks and commit",
+                guild_id
+            );
+            self.metrics
+                .github_commits
+                .with_label_values(&[guild_label.as_str(), "unchanged"])
+                .inc();
+            return Ok(());
+        }
+
+        let delivered = self
+            .publish(guild_config, &content, &unique_entries)
+            .await?;
+        // Announce changes only once they're live, not for a commit that failed
+        self.notify_changes(http, guild_config, &changes).await;
+
+        // A sink that missed this roster gets it again on the next regeneration
+        if delivered {
+            self.rosters
+                .set_published_fingerprint(guild_config.guild_id, fingerprint);
+        }
+        self.save_snapshot(guild_config.guild_id, content, unique_entries);
+        Ok(())
+    }
+
+    /// Send a rendered roster to every configured sink: webhook, MQTT, sheet, repo, gist,
+    /// and S3. Webhook, MQTT, and sheet failures are only logged; the result says whether
+    /// they all went through.
+    async fn publish(
+        &self,
+        guild_config: &config::GuildConfig,
+        content: &str,
+        entries: &[output::OutputEntry],
+    ) -> Result<bool> {
+        let mut delivered = true;
+        // Feed the webhook, MQTT, and sheet first so a GitHub failure doesn't block it
+        if let Some(webhook) = &guild_config.output.webhook {
+            if let Err(e) = self
+                .webhook_client
+                .post_roster(
+                    webhook,
+                    guild_config.guild_id,
+                    guild_config.output.title.as_deref(),
+                    entries,
+                )
+                .instrument(info_span!("publish", sink = "webhook"))
+                .await
+            {
+                error!(
+                    "Failed to post member list to webhook {}: {:?}",
+                    webhook.url, e
+                );
+                delivered = false;
+            }
+        }
+        if let Some(mqtt) = &self.mqtt_client {
+            if let Err(e) = info_span!("publish", sink = "mqtt").in_scope(|| {
+                mqtt.publish_roster(
+                    guild_config.guild_id,
+                    guild_config.output.title.as_deref(),
+                    entries,
+                )
+            }) {
+                error!("Failed to publish member list to MQTT: {:?}", e);
+                delivered = false;
+            }
+        }
+        if let Some(sheets) = &guild_config.output.sheets {
+            if let Err(e) = self
+                .sheets_client
+                .write_roster(sheets, entries)
+                .instrument(info_span!("publish", sink = "sheets"))
+                .await
+            {
+                error!(
+                    "Failed to write member list to sheet {}: {:?}",
+                    sheets.spreadsheet_id, e
+                );
+                delivered = false;
+            }
+        }
+
+        // Try every target before failing, so a repo outage doesn't hold back the gist
+        let mut result = Ok(());
+        if let Some(repo) = &guild_config.output.repo {
+            result = self
+                .commit_to_repo(guild_config, repo, content, entries)
+                .await;
+        }
+        if let Some(gist) = &guild_config.output.gist {
+            if let Err(e) = self
+                .publish_gist(guild_config, gist, content)
+                .instrument(info_span!("publish", sink = "gist"))
+                .await
+            {
+                // Only one error can be returned; log the gist's if the repo also failed
+                match result {
+                    Ok(()) => result = Err(e),
+                    Err(_) => error!("{:?}", e),
+                }
+            }
+        }
+        if let Some(s3) = &guild_config.output.s3 {
+            let content_type = s3
+                .content_type
+                .as_deref()
+                .unwrap_or(guild_config.output.content_type());
+            if let Err(e) = self
+                .s3_client
+                .put_object(s3, content_type, content)
+                .instrument(info_span!("publish", sink = "s3"))
+                .await
+                .map_err(|e| {
+                    e.context(format!("Failed to upload to s3://{}/{}", s3.bucket, s3.key))
+                })
+            {
+                match result {
+                    Ok(()) => result = Err(e),
+                    Err(_) => error!("{:?}", e),
+                }
+            }
+        }
+        result.map(|()| delivered)
+    }
+
+    /// Keep a published roster for `/roster history` and `rollback`
+    fn save_snapshot(&self, guild_id: u64, content: String, entries: Vec<output::OutputEntry>) {
+        let Some(snapshots) = &self.snapshots else {
+            return;
+        };
+        let snapshot = Snapshot {
+            guild_id,
+            taken_at: chrono::Utc::now(),
+            content,
+            entries,
+        };
+        match snapshots.save(&snapshot) {
+            Ok(id) => debug!("Saved snapshot {} for guild {}", id, guild_id),
+            Err(e) => warn!("Failed to save snapshot for guild {}: {:?}", guild_id, e),
+        }
+    }
 
-                entries.push(OutputEntry {
-                    callsign: parsed.callsign,
-                    name,
-                    suffix: guild_config.output.default_suffix.clone(),
-                    emoji_separator: guild_config.output.emoji_separator.clone(),
-                });
-            } else {
-                info!(
-                    "Could not parse callsign from display name: {}",
-                    display_name
-                );
-            }
+    /// Commit the rendered output (and the JSON entries and mailing list, if configured)
+    /// to `output.repo`
+    async fn commit_to_repo(
+        &self,
+        guild_config: &config::GuildConfig,
+        repo: &str,
+        content: &str,
+        entries: &[output::OutputEntry],
+    ) -> Result<()> {
+        let output = &guild_config.output;
+        let json = serde_json::to_string_pretty(entries)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize roster JSON: {}", e))?;
+        let addresses = output
+            .mailing_list
+            .as_ref()
+            .map(|list| (list, mailing_list::generate(entries, list)));
+        let mut files = vec![FileChange {
+            path: &output.path,
+            content,
+        }];
+        if let Some(path) = &output.json_path {
+            files.push(FileChange {
+                path,
+                content: &json,
+            });
+        }
+        if let Some((list, addresses)) = &addresses {
+            files.push(FileChange {
+                path: &list.path,
+                content: addresses,
+            });
         }
 
-        // Deduplicate entries by callsign (keep first occurrence)
-        let mut seen_callsigns = HashMap::new();
-        let mut unique_entries = Vec::new();
+        let commit_result = self
+            .github_client
+            .commit_files(repo, &output.branch, &files, "Update member list")
+            .instrument(info_span!("write", repo = %repo))
+            .await;
+        self.metrics
+            .github_commits
+            .with_label_values(&[
+                guild_config.guild_id.to_string().as_str(),
+                match commit_result {
+                    Ok(CommitOutcome::Committed) => "ok",
+                    Ok(CommitOutcome::Unchanged) => "unchanged",
+                    Err(_) => "error",
+                },
+            ])
+            .inc();
 
-        for entry in entries {
-            if !seen_callsigns.contains_key(&entry.callsign) {
-                seen_callsigns.insert(entry.callsign.clone(), true);
-                unique_entries.push(entry);
-            } else {
-                warn!(
-                    "Skipping duplicate callsign: {} (already processed)",
-                    entry.callsign
-                );
-            }
+        self.record_github(&commit_result);
+        let outcome = commit_result.map_err(|e| {
+            anyhow::Error::new(e).context(format!(
+                "Failed to commit to {}/{} on branch {}",
+                repo, output.path, output.branch
+            ))
+        })?;
+        if outcome == CommitOutcome::Committed {
+            info!(
+                "Successfully committed member list to {}/{}",
+                repo, output.path
+            );
         }
+        Ok(())
+    }
 
-        info!(
-            "Committing {} unique entries to GitHub (filtered {} duplicates)",
-            unique_entries.len(),
-            seen_callsigns.len() - unique_entries.len()
-        );
+    /// Count GitHub publishes toward `[alerts]`
+    fn record_github<T>(&self, result: &std::result::Result<T, Error>) {
+        if let Some(alerts) = &self.alerts {
+            match result {
+                Ok(_) => alerts.success(AlertSource::GitHub),
+                Err(e) => alerts.failure(AlertSource::GitHub, e.label(), e),
+            }
+        }
+    }
 
-        // Generate content and commit to GitHub
-        let content = generate_output_content(unique_entries, guild_config.output.title.as_deref());
+    /// Update the guild's gist, creating it (and remembering its ID) on first publish
+    async fn publish_gist(
+        &self,
+        guild_config: &config::GuildConfig,
+        gist: &config::GistConfig,
+        content: &str,
+    ) -> Result<()> {
+        let guild_id = guild_config.guild_id;
+        let known_id = gist.id.clone().or_else(|| self.storage.gist_id(guild_id));
+        let description = gist
+            .description
+            .as_deref()
+            .or(guild_config.output.title.as_deref())
+            .unwrap_or("Member list");
 
-        self.github_client
-            .commit_file(
-                &guild_config.output.repo,
-                &guild_config.output.path,
-                &guild_config.output.branch,
-                &content,
-                "Update member list",
+        let result = self
+            .github_client
+            .publish_gist(
+                known_id.as_deref(),
+                guild_config.output.gist_filename(),
+                content,
+                description,
+                gist.public,
             )
-            .await
-            .map_err(|e| {
-                anyhow::anyhow!(
-                    "Failed to commit to {}/{} on branch {}: {}",
-                    guild_config.output.repo,
-                    guild_config.output.path,
-                    guild_config.output.branch,
-                    e
-                )
-            })?;
-
-        info!(
-            "Successfully committed member list to {}/{}",
-            guild_config.output.repo, guild_config.output.path
-        );
+            .await;
+        self.record_github(&result);
+        let (id, outcome) = result.map_err(|e| {
+            anyhow::Error::new(e).context(format!("Failed to publish gist for guild {}", guild_id))
+        })?;
 
+        if known_id.is_none() {
+            info!("Created gist {} for guild {}", id, guild_id);
+            // Without this the next regeneration would create another gist
+            self.storage.set_gist_id(guild_id, id)?;
+        } else if outcome == CommitOutcome::Committed {
+            info!("Successfully updated gist {} for guild {}", id, guild_id);
+        }
         Ok(())
     }
 }
 
+/// The bot's own user ID, used to filter it out of the member list
+fn bot_user_id(ctx: &Context) -> UserId {
+    ctx.cache.current_user().id
+}
+
+/// The membership year dues are tracked for: the current calendar year in UTC
+fn dues_year() -> i32 {
+    use chrono::Datelike;
+    chrono::Utc::now().year()
+}
+
 #[async_trait]
 impl EventHandler for Handler {
     async fn ready(&self, ctx: Context, ready: serenity::model::gateway::Ready) {
         info!("{} is connected and ready!", ready.user.name);
+        // A later ready means the old session couldn't be resumed, so events may be missing
+        let cause = if self.gateway.status().since == 0 {
+            "startup"
+        } else {
+            "reconnect"
+        };
+        if let Some(down) = self.gateway.connected() {
+            warn!(
+                "Gateway reconnected with a new session after {}s; regenerating all guilds",
+                down
+            );
+        }
+        if let Some(alerts) = &self.alerts {
+            alerts.set_http(ctx.http.clone());
+        }
+        // Regenerations update the presence once the shard is known
+        if self.shard.set(ctx.shard.clone()).is_err() {
+            self.update_presence();
+        }
+        self.announce_startup(&ctx.http).await;
+        // Reconnects keep the cache warmed by the first startup
+        if cause == "startup" {
+            self.prewarm_qrz(&ctx.http).await;
+        }
 
         // Process each configured guild
         for guild_config in &self.config.guilds {
@@ -247,7 +2688,12 @@ impl EventHandler for Handler {
 
             // Set bot nickname if configured for this guild
             if let Some(nickname) = &guild_config.bot_nickname {
-                if let Err(e) = guild_id.edit_nickname(&ctx.http, Some(nickname)).await {
+                if self.is_dry_run(guild_config) {
+                    info!(
+                        "[dry run] Would set bot nickname to '{}' in guild {}",
+                        nickname, guild_id
+                    );
+                } else if let Err(e) = guild_id.edit_nickname(&ctx.http, Some(nickname)).await {
                     warn!(
                         "Failed to set bot nickname to '{}' in guild {}: {}",
                         nickname, guild_id, e
@@ -257,19 +2703,91 @@ impl EventHandler for Handler {
                 }
             }
 
+            if self.is_dry_run(guild_config) {
+                info!(
+                    "[dry run] Skipping slash command registration in guild {}",
+                    guild_id
+                );
+            } else if let Err(e) = guild_id
+                .set_commands(&ctx.http, commands::definitions())
+                .await
+            {
+                warn!(
+                    "Failed to register slash commands in guild {}: {}",
+                    guild_id, e
+                );
+            }
+
             // Generate the member list when the bot starts
-            if let Err(e) = self.generate_member_list(&ctx, guild_config).await {
+            if let Err(e) = self
+                .generate_with_retry(&ctx.http, bot_user_id(&ctx), guild_config, cause)
+                .await
+            {
                 error!(
                     "Failed to generate member list for guild {}: {:?}",
                     guild_id, e
                 );
-                // Continue with other guilds instead of crashing
+                if self.config.startup.exit_on_failure {
+                    error!("startup.exit_on_failure is set; exiting");
+                    std::process::exit(1);
+                }
+                // Stay connected; the next member event retries generation
             }
         }
 
         info!("Member list generation complete for all guilds. Bot is now listening for member changes.");
     }
 
+    async fn resume(&self, ctx: Context, _event: ResumedEvent) {
+        let Some(down) = self.gateway.connected() else {
+            return;
+        };
+        if down < self.config.discord.resync_after_secs {
+            info!("Gateway resumed after {}s", down);
+            return;
+        }
+
+        warn!(
+            "Gateway resumed after {}s; regenerating all guilds in case events were missed",
+            down
+        );
+        for guild_config in &self.config.guilds {
+            if let Err(e) = self
+                .generate_member_list(&ctx.http, bot_user_id(&ctx), guild_config, "resync")
+                .await
+            {
+                error!("Resync failed for guild {}: {:?}", guild_config.guild_id, e);
+            }
+        }
+    }
+
+    async fn shard_stage_update(&self, _ctx: Context, event: ShardStageUpdateEvent) {
+        if matches!(event.old, ConnectionStage::Connected)
+            && !matches!(event.new, ConnectionStage::Connected)
+        {
+            warn!(
+                "Gateway shard {} disconnected ({})",
+                event.shard_id, event.new
+            );
+            self.gateway.disconnected();
+        }
+    }
+
+    async fn ratelimit(&self, data: RatelimitInfo) {
+        self.rest_limits.rate_limited(&data);
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        match interaction {
+            Interaction::Command(command) => commands::handle(self, &ctx, &command).await,
+            Interaction::Component(component) => {
+                commands::handle_component(self, &ctx, &component).await
+            }
+            Interaction::Modal(modal) => commands::handle_modal(self, &ctx, &modal).await,
+            _ => {}
+        }
+    }
+
     async fn guild_member_addition(
         &self,
         ctx: Context,
@@ -280,54 +2798,93 @@ impl EventHandler for Handler {
         // Check if this guild is configured
         if let Some(guild_config) = self.config.get_guild_config(guild_id) {
             info!(
+                guild_id,
+                event = "member_addition",
                 "New member joined guild {}: {}",
-                guild_id, new_member.user.name
+                guild_id,
+                new_member.user.name
             );
-
-            if let Err(e) = self.generate_member_list(&ctx, guild_config).await {
-                error!(
-                    "Failed to regenerate member list for guild {} after member addition: {:?}",
-                    guild_id, e
-                );
-            } else {
-                info!(
-                    "Member list updated for guild {} after new member joined",
-                    guild_id
-                );
+            let member = discord::member(&new_member);
+            self.record(RecordedEvent::MemberAddition {
+                at: chrono::Utc::now(),
+                guild_id,
+                member: member.clone(),
+            });
+            if let Err(e) = self
+                .storage
+                .forget_departure(guild_id, new_member.user.id.get())
+            {
+                warn!("{:?}", e);
             }
+            self.enqueue(guild_id, new_member.user.id.get(), "member_addition");
+            self.welcome_member(&ctx.http, guild_config, &member).await;
+            self.start_onboarding(&ctx.http, guild_config, &member)
+                .await;
+        }
+    }
+
+    async fn voice_state_update(&self, _ctx: Context, old: Option<VoiceState>, new: VoiceState) {
+        let Some(guild_config) = new
+            .guild_id
+            .and_then(|id| self.config.get_guild_config(id.get()))
+        else {
+            return;
+        };
+        let Some(channel_id) = guild_config.nets.as_ref().and_then(|n| n.voice_channel_id) else {
+            return;
+        };
+        // Only joins count; mute and deafen changes repeat the same channel
+        let joined = new.channel_id == Some(ChannelId::new(channel_id))
+            && old.and_then(|old| old.channel_id) != new.channel_id;
+        if let (true, Some(member)) = (joined, &new.member) {
+            self.check_in_voice_member(guild_config, &discord::member(member))
+                .await;
+        }
+    }
+
+    async fn message(&self, ctx: Context, msg: serenity::model::channel::Message) {
+        if msg.author.bot {
+            return;
+        }
+        match msg.guild_id {
+            // Onboarding replies arrive as DMs, which have no guild
+            None => self.handle_onboarding_reply(&ctx.http, &msg).await,
+            Some(guild_id) => self.answer_callsigns(&ctx.http, guild_id.get(), &msg).await,
         }
     }
 
     async fn guild_member_removal(
         &self,
-        ctx: Context,
+        _ctx: Context,
         guild_id: GuildId,
         user: serenity::model::user::User,
-        _member_data_if_available: Option<serenity::model::guild::Member>,
+        member_data_if_available: Option<serenity::model::guild::Member>,
     ) {
         let guild_id_u64 = guild_id.get();
 
         // Check if this guild is configured
         if let Some(guild_config) = self.config.get_guild_config(guild_id_u64) {
-            info!("Member left guild {}: {}", guild_id_u64, user.name);
-
-            if let Err(e) = self.generate_member_list(&ctx, guild_config).await {
-                error!(
-                    "Failed to regenerate member list for guild {} after member removal: {:?}",
-                    guild_id_u64, e
-                );
-            } else {
-                info!(
-                    "Member list updated for guild {} after member left",
-                    guild_id_u64
-                );
-            }
+            info!(
+                guild_id = guild_id_u64,
+                event = "member_removal",
+                "Member left guild {}: {}",
+                guild_id_u64,
+                user.name
+            );
+            let member = member_data_if_available.as_ref().map(discord::member);
+            self.remember_departure(guild_config, &user, member.as_ref());
+            self.record(RecordedEvent::MemberRemoval {
+                at: chrono::Utc::now(),
+                guild_id: guild_id_u64,
+                user_id: user.id.get(),
+            });
+            self.enqueue(guild_id_u64, user.id.get(), "member_removal");
         }
     }
 
     async fn guild_member_update(
         &self,
-        ctx: Context,
+        _ctx: Context,
         _old_if_available: Option<serenity::model::guild::Member>,
         new: Option<serenity::model::guild::Member>,
         event: serenity::model::event::GuildMemberUpdateEvent,
@@ -335,21 +2892,21 @@ impl EventHandler for Handler {
         let guild_id = event.guild_id.get();
 
         // Check if this guild is configured
-        if let Some(guild_config) = self.config.get_guild_config(guild_id) {
+        if self.config.get_guild_config(guild_id).is_some() {
             if let Some(member) = new {
-                info!("Member updated in guild {}: {}", guild_id, member.user.name);
-
-                if let Err(e) = self.generate_member_list(&ctx, guild_config).await {
-                    error!(
-                        "Failed to regenerate member list for guild {} after member update: {:?}",
-                        guild_id, e
-                    );
-                } else {
-                    info!(
-                        "Member list updated for guild {} after member info changed",
-                        guild_id
-                    );
-                }
+                info!(
+                    guild_id,
+                    event = "member_update",
+                    "Member updated in guild {}: {}",
+                    guild_id,
+                    member.user.name
+                );
+                self.record(RecordedEvent::MemberUpdate {
+                    at: chrono::Utc::now(),
+                    guild_id,
+                    member: discord::member(&member),
+                });
+                self.enqueue(guild_id, member.user.id.get(), "member_update");
             }
         }
     }
@@ -360,54 +2917,458 @@ async fn main() -> Result<()> {
     // Parse command-line arguments
     let args = Args::parse();
 
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive(tracing::Level::INFO.into())
-                .add_directive("qrz_xml=off".parse().unwrap()),
-        )
-        .init();
+    // There's no config to load yet; init writes one
+    if let Some(Command::Init { force }) = args.command {
+        return init::init(&args.config, force).await;
+    }
 
-    // Load configuration
+    // Load configuration first; it decides where traces are exported
     let config = Config::from_file(&args.config)?;
 
+    // Initialize logging and tracing
+    let telemetry = telemetry::init(config.telemetry.as_ref(), &config.logging)?;
+
     info!("Configuration loaded from: {}", args.config);
+    for line in config.summarize().lines() {
+        info!("  {}", line);
+    }
 
-    // Initialize QRZ client if credentials are configured
-    let qrz_client = if let Some(qrz_config) = &config.qrz {
-        info!("QRZ credentials found, initializing QRZ client...");
-        match QrzClient::new(qrz_config).await {
-            Ok(client) => {
-                info!("QRZ client initialized successfully");
-                Some(Arc::new(client))
-            }
-            Err(e) => {
-                warn!(
-                    "Failed to initialize QRZ client: {:?}. Continuing without QRZ lookups.",
-                    e
-                );
-                None
-            }
+    if args.dry_run {
+        info!("Dry run enabled: no commits, webhook posts, or Discord changes will be made");
+    }
+
+    let result = match args.command {
+        None | Some(Command::Run) => run(config, args.record_events, args.dry_run).await,
+        Some(Command::GenerateOnce) => generate_once(config, args.dry_run).await,
+        Some(Command::GenerateFromCsv { guild_id, path }) => {
+            generate_from_csv(config, guild_id, &path, args.dry_run).await
+        }
+        Some(Command::ValidateConfig) => validate::validate_config(&config).await,
+        Some(Command::Replay { path }) => {
+            info!("Replaying recorded events from {}", path);
+            simulation::replay(&path, &config).await
         }
-    } else {
+        Some(Command::Rollback { guild_id, snapshot }) => {
+            rollback(config, guild_id, snapshot.as_deref(), args.dry_run).await
+        }
+        Some(Command::Init { .. }) => unreachable!("handled before loading the config"),
+    };
+
+    telemetry.shutdown();
+    result
+}
+
+/// Initialize the QRZ client if credentials are configured, persisting its usage in `storage`
+async fn init_qrz(config: &Config, storage: Arc<Storage>) -> Option<Arc<QrzClient>> {
+    let Some(qrz_config) = &config.qrz else {
         info!("No QRZ credentials configured, skipping QRZ lookups");
-        None
+        return None;
     };
 
+    info!("QRZ credentials found, initializing QRZ client...");
+    match QrzClient::new(qrz_config, Some(storage as Arc<dyn UsageStore>)).await {
+        Ok(client) => {
+            info!("QRZ client initialized successfully");
+            // The first run has no cache file yet
+            if let Some(path) = qrz_config
+                .cache_file
+                .as_deref()
+                .filter(|path| std::path::Path::new(path).exists())
+            {
+                match client.load_cache(path) {
+                    Ok(restored) => info!("Restored {} QRZ lookups from {}", restored, path),
+                    Err(e) => warn!("{:?}", e),
+                }
+            }
+            Some(Arc::new(client))
+        }
+        Err(e) => {
+            warn!(
+                "Failed to initialize QRZ client: {:?}. Continuing without QRZ lookups.",
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Build a handler with everything it needs for generation
+async fn build_handler(
+    config: Config,
+    rosters: RosterStore,
+    metrics: Metrics,
+    gateway: GatewayState,
+    recorder: Option<Arc<EventRecorder>>,
+    in_flight: InFlight,
+    dry_run: bool,
+) -> Result<Handler> {
+    let storage = Arc::new(Storage::open(config.storage.path.as_deref())?);
+    let qrz_client = init_qrz(&config, storage.clone()).await;
+
     // Initialize GitHub client
     info!("Initializing GitHub client...");
-    let github_client = GitHubClient::new()?;
+    let github_client = GitHubClient::new(&config.github)?;
     info!("GitHub client initialized successfully");
 
+    let alerts = config
+        .alerts
+        .as_ref()
+        .and_then(|alerts| Alerts::new(alerts, config.discord.ops_channel_id, dry_run));
+    let snapshots = config
+        .snapshots
+        .as_ref()
+        .map(|snapshots| SnapshotStore::new(&snapshots.dir, snapshots.keep));
+
+    let rest_limits = RestLimits::new(&config.discord.rest, metrics.clone());
+    let dxcc = Arc::new(DxccStore::load(config.dxcc.as_ref()).await?);
+    let lotw = match &config.lotw {
+        Some(lotw) => Some(Arc::new(LotwStore::load(lotw).await)),
+        None => None,
+    };
+    let quiz = match &config.quiz {
+        Some(quiz) => Some(QuizPools::load(quiz).await),
+        None => None,
+    };
+
+    Ok(Handler {
+        parser: CallsignParser::from_config(&config.parser)?,
+        qrz_client,
+        radioid_client: config.radioid.as_ref().map(RadioIdClient::new),
+        aprs_client: config.aprs.as_ref().map(AprsClient::new),
+        dxcc,
+        lotw,
+        repeaterbook_client: config.repeaterbook.as_ref().map(RepeaterBookClient::new),
+        propagation_client: config.propagation.as_ref().map(PropagationClient::new),
+        quiz,
+        mqtt_client: config.mqtt.as_ref().map(MqttClient::connect),
+        github_client,
+        webhook_client: WebhookClient::new(),
+        s3_client: S3Client::new(),
+        sheets_client: SheetsClient::new(),
+        contest_client: ContestClient::new(),
+        bulletin_client: BulletinClient::new(),
+        rosters,
+        metrics,
+        recorder,
+        queue: EventQueue::new(config.event_queue.capacity),
+        storage,
+        onboarding: Onboarding::new(),
+        cw_practice: morse::Practice::new(),
+        card_limiters: config
+            .guilds
+            .iter()
+            .filter_map(|g| {
+                let cards = g.callsign_cards.as_ref()?;
+                let window = Duration::from_secs(cards.repeat_after_secs);
+                Some((g.guild_id, CardLimiter::new(window)))
+            })
+            .collect(),
+        config,
+        in_flight,
+        dry_run,
+        announced: AtomicBool::new(false),
+        alerts,
+        snapshots,
+        rest_limits,
+        gateway,
+        shard: OnceLock::new(),
+    })
+}
+
+/// Connect to the gateway and keep member lists up to date until stopped
+async fn run(config: Config, record_events: Option<String>, dry_run: bool) -> Result<()> {
+    let recorder = match &record_events {
+        Some(path) => {
+            info!("Recording member events to {}", path);
+            Some(Arc::new(EventRecorder::open(path)?))
+        }
+        None => None,
+    };
+
+    let rosters = RosterStore::new();
+    let metrics = Metrics::new();
+    let gateway = GatewayState::new();
+
+    if let Some(push_gateway) = &config.metrics.push_gateway {
+        info!("Pushing metrics to {}", push_gateway.url);
+        metrics.spawn_pusher(push_gateway.clone());
+    }
+
+    // Fail fast on a bad schedule rather than after connecting
+    let schedule = config
+        .schedule
+        .as_ref()
+        .map(Schedule::from_config)
+        .transpose()?;
+
+    let token = config.discord.token.clone();
+    let push_gateway = config.metrics.push_gateway.clone();
+    let qrz_config = config.qrz.clone();
+    let in_flight = InFlight::new();
+    let handler = build_handler(
+        config,
+        rosters.clone(),
+        metrics.clone(),
+        gateway.clone(),
+        recorder.clone(),
+        in_flight.clone(),
+        dry_run,
+    )
+    .await?;
+
+    // Start the HTTP server if configured
+    if let Some(server_config) = &handler.config.server {
+        let bind = server_config.bind.clone();
+        let metrics = metrics.clone();
+        let calendars = server::Calendars::new(&handler.config, handler.storage.clone());
+        tokio::spawn(async move {
+            if let Err(e) = server::serve(&bind, rosters, metrics, gateway, calendars).await {
+                error!("HTTP server failed: {:?}", e);
+            }
+        });
+    }
+
+    if let (Some(qrz_client), Some(qrz_config)) = (&handler.qrz_client, &qrz_config) {
+        if let Some(keepalive_secs) = qrz_config.keepalive_secs {
+            info!(
+                "Pinging QRZ every {}s to keep the session alive",
+                keepalive_secs
+            );
+            qrz_client.spawn_keepalive(
+                Duration::from_secs(keepalive_secs),
+                qrz_config.username.clone(),
+            );
+        }
+    }
+
+    handler.dxcc.spawn_refresh();
+    if let Some(lotw) = &handler.lotw {
+        lotw.spawn_refresh();
+    }
+
     // Set up Discord client
-    let intents = GatewayIntents::GUILDS | GatewayIntents::GUILD_MEMBERS;
+    let mut intents = GatewayIntents::GUILDS | GatewayIntents::GUILD_MEMBERS;
+    if handler.config.guilds.iter().any(|g| g.onboarding.is_some()) {
+        // Onboarding reads members' replies to its DMs
+        intents |= GatewayIntents::DIRECT_MESSAGES | GatewayIntents::MESSAGE_CONTENT;
+    }
+    if handler
+        .config
+        .guilds
+        .iter()
+        .any(|g| g.callsign_cards.is_some())
+    {
+        // Callsign cards read messages in server channels
+        intents |= GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT;
+    }
+    if handler.config.guilds.iter().any(|g| {
+        g.nets
+            .as_ref()
+            .is_some_and(|n| n.voice_channel_id.is_some())
+    }) {
+        // Voice check-ins follow who is in each net's voice channel
+        intents |= GatewayIntents::GUILD_VOICE_STATES;
+    }
 
-    let mut client = Client::builder(&config.discord.token, intents)
-        .event_handler(Handler::new(config, qrz_client, github_client))
+    let handler = Arc::new(handler);
+    let mut client = Client::builder(&token, intents)
+        .event_handler_arc(handler.clone())
         .await
         .map_err(|e| anyhow::anyhow!("Failed to create Discord client: {}", e))?;
 
+    // Member events are queued and regenerated in batches by this worker
+    tokio::spawn(handler.clone().process_queue(client.http.clone()));
+
+    if let Some(qrz_config) = qrz_config.as_ref().filter(|q| q.backfill_interval_secs > 0) {
+        tokio::spawn(handler.clone().run_qrz_backfill(
+            client.http.clone(),
+            Duration::from_secs(qrz_config.backfill_interval_secs),
+        ));
+    }
+
+    if let Some(schedule) = schedule {
+        info!("Regenerating all guilds on a schedule: {}", schedule);
+        tokio::spawn(handler.clone().run_schedule(schedule));
+    }
+
+    for guild_config in &handler.config.guilds {
+        let Some(reminders) = guild_config.license_reminders.clone() else {
+            continue;
+        };
+        // Checked when the config was loaded
+        let schedule = Schedule::from_config(&reminders.schedule)?;
+        info!(
+            "Posting license reminders for guild {} {}",
+            guild_config.guild_id, schedule
+        );
+        tokio::spawn(handler.clone().run_license_reminders(
+            client.http.clone(),
+            guild_config.guild_id,
+            reminders,
+            schedule,
+        ));
+    }
+
+    for guild_config in &handler.config.guilds {
+        let Some(dms) = guild_config.license_dms.clone() else {
+            continue;
+        };
+        // Checked when the config was loaded
+        let schedule = Schedule::from_config(&dms.schedule)?;
+        info!(
+            "Checking license expirations to DM for guild {} {}",
+            guild_config.guild_id, schedule
+        );
+        tokio::spawn(handler.clone().run_license_dms(
+            client.http.clone(),
+            guild_config.guild_id,
+            dms,
+            schedule,
+        ));
+    }
+
+    for guild_config in &handler.config.guilds {
+        let Some(leaderboard) = guild_config
+            .nets
+            .as_ref()
+            .and_then(|n| n.leaderboard.clone())
+        else {
+            continue;
+        };
+        // Checked when the config was loaded
+        let schedule = Schedule::from_config(&leaderboard.schedule)?;
+        info!(
+            "Posting the net leaderboard for guild {} {}",
+            guild_config.guild_id, schedule
+        );
+        tokio::spawn(handler.clone().run_leaderboard(
+            client.http.clone(),
+            guild_config.guild_id,
+            leaderboard,
+            schedule,
+        ));
+    }
+
+    for guild_config in &handler.config.guilds {
+        for contest in &guild_config.contest_scores {
+            // Checked when the config was loaded
+            let schedule = Schedule::from_config(&contest.schedule)?;
+            info!(
+                "Posting {} scores for guild {} {}",
+                contest.name, guild_config.guild_id, schedule
+            );
+            tokio::spawn(handler.clone().run_contest_scores(
+                client.http.clone(),
+                guild_config.guild_id,
+                contest.clone(),
+                schedule,
+            ));
+        }
+    }
+
+    for guild_config in &handler.config.guilds {
+        let Some(solar_post) = guild_config.solar_post.clone() else {
+            continue;
+        };
+        // Checked when the config was loaded
+        let schedule = Schedule::from_config(&solar_post.schedule)?;
+        info!(
+            "Posting solar data for guild {} {}",
+            guild_config.guild_id, schedule
+        );
+        tokio::spawn(handler.clone().run_solar_posts(
+            client.http.clone(),
+            guild_config.guild_id,
+            solar_post,
+            schedule,
+        ));
+    }
+
+    for guild_config in &handler.config.guilds {
+        let Some(events_config) = guild_config.events.clone() else {
+            continue;
+        };
+        info!(
+            "Posting event reminders for guild {} to channel {}",
+            guild_config.guild_id, events_config.channel_id
+        );
+        tokio::spawn(handler.clone().run_event_reminders(
+            client.http.clone(),
+            guild_config.guild_id,
+            events_config,
+        ));
+    }
+
+    for guild_config in &handler.config.guilds {
+        let Some(swap_config) = guild_config.swap.clone() else {
+            continue;
+        };
+        info!(
+            "Expiring swap listings for guild {} after {} days",
+            guild_config.guild_id, swap_config.expire_days
+        );
+        tokio::spawn(handler.clone().run_swap_expiry(
+            client.http.clone(),
+            guild_config.guild_id,
+            swap_config.channel_id,
+        ));
+        if let Some(digest) = &swap_config.digest {
+            // Checked when the config was loaded
+            let schedule = Schedule::from_config(digest)?;
+            info!(
+                "Posting swap digests for guild {} {}",
+                guild_config.guild_id, schedule
+            );
+            tokio::spawn(handler.clone().run_swap_digest(
+                client.http.clone(),
+                guild_config.guild_id,
+                swap_config.channel_id,
+                schedule,
+            ));
+        }
+    }
+
+    for guild_config in &handler.config.guilds {
+        let Some(bulletins) = guild_config.bulletins.clone() else {
+            continue;
+        };
+        // Checked when the config was loaded
+        let schedule = Schedule::from_config(&bulletins.schedule)?;
+        info!(
+            "Relaying {} bulletin feeds for guild {} {}",
+            bulletins.feeds.len(),
+            guild_config.guild_id,
+            schedule
+        );
+        tokio::spawn(handler.clone().run_bulletins(
+            client.http.clone(),
+            guild_config.guild_id,
+            bulletins,
+            schedule,
+        ));
+    }
+
+    if let Some(cluster) = handler.config.dx_cluster.clone() {
+        if handler.config.guilds.iter().any(|g| g.dx_spots.is_some()) {
+            let repeat_after = Duration::from_secs(cluster.repeat_after_secs);
+            tokio::spawn(handler.clone().run_dx_spots(
+                client.http.clone(),
+                dxcluster::spawn(cluster),
+                repeat_after,
+            ));
+        }
+    }
+
+    // On SIGINT/SIGTERM, let in-flight regenerations finish, then disconnect
+    let shard_manager = client.shard_manager.clone();
+    tokio::spawn(async move {
+        shutdown::wait_for_signal().await;
+        info!("Shutting down: waiting for in-flight regenerations to finish...");
+        in_flight.drain().await;
+        info!("Disconnecting from Discord...");
+        shard_manager.shutdown_all().await;
+    });
+
     // Start the bot
     info!("Starting Discord bot...");
     client
@@ -415,5 +3376,208 @@ async fn main() -> Result<()> {
         .await
         .map_err(|e| anyhow::anyhow!("Failed to start Discord client: {}", e))?;
 
+    // Flush sinks so nothing recorded before shutdown is lost
+    if let Some(recorder) = &recorder {
+        if let Err(e) = recorder.flush() {
+            warn!("{:?}", e);
+        }
+    }
+    // Keep lookups made since the pre-warm for the next start
+    handler.save_qrz_cache();
+    if let Some(push_gateway) = &push_gateway {
+        if let Err(e) = metrics.push(&reqwest::Client::new(), push_gateway).await {
+            warn!(
+                "Failed to push final metrics to {}: {:?}",
+                push_gateway.url, e
+            );
+        }
+    }
+
+    info!("Shutdown complete");
+    Ok(())
+}
+
+/// Generate every configured guild's member list over REST, then exit
+async fn generate_once(config: Config, dry_run: bool) -> Result<()> {
+    let http = Http::new(&config.discord.token);
+    let bot_user = http
+        .get_current_user()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to authenticate with Discord: {}", e))?;
+
+    let handler = build_handler(
+        config,
+        RosterStore::new(),
+        Metrics::new(),
+        GatewayState::new(),
+        None,
+        InFlight::new(),
+        dry_run,
+    )
+    .await?;
+    handler.prewarm_qrz(&http).await;
+
+    let mut failures = 0;
+    for guild_config in &handler.config.guilds {
+        if let Err(e) = handler
+            .generate_with_retry(&http, bot_user.id, guild_config, "generate_once")
+            .await
+        {
+            error!(
+                "Failed to generate member list for guild {}: {:?}",
+                guild_config.guild_id, e
+            );
+            failures += 1;
+        }
+    }
+    handler.save_qrz_cache();
+
+    if failures > 0 {
+        anyhow::bail!("{} guild(s) failed to generate", failures);
+    }
+
+    info!("Generated member lists for all guilds");
+    Ok(())
+}
+
+/// Run a CSV of members through the roster pipeline and publish it like a Discord roster
+async fn generate_from_csv(config: Config, guild_id: u64, path: &str, dry_run: bool) -> Result<()> {
+    let handler = build_handler(
+        config,
+        RosterStore::new(),
+        Metrics::new(),
+        GatewayState::new(),
+        None,
+        InFlight::new(),
+        dry_run,
+    )
+    .await?;
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id)
+        .ok_or_else(|| anyhow::anyhow!("Guild {} isn't configured", guild_id))?;
+    let guild_config = &handler.with_all_overrides(guild_config);
+
+    let name = guild_config
+        .output
+        .title
+        .clone()
+        .unwrap_or_else(|| guild_id.to_string());
+    let source = CsvMembers::from_file(path, name)?;
+    info!("Read {} members from {}", source.members.len(), path);
+
+    let pipeline = Pipeline {
+        source: &source,
+        builder: handler.roster_builder(),
+    };
+    // Alumni are members who left Discord, which a CSV knows nothing about
+    let Generation {
+        roster, content, ..
+    } = pipeline
+        .generate(
+            guild_config,
+            None,
+            handler.storage.suffixes(guild_id),
+            HashMap::new(),
+            Some(handler.storage.dues_paid(guild_id, dues_year())),
+        )
+        .await?;
+    info!("Built {} unique entries", roster.entries.len());
+
+    if handler.is_dry_run(guild_config) {
+        handler.log_dry_run(guild_config, &content).await;
+        return Ok(());
+    }
+    handler
+        .publish(guild_config, &content, &roster.entries)
+        .await?;
+    // Keeps the published roster available to `rollback`
+    handler.save_snapshot(guild_id, content, roster.entries);
+    info!(
+        "Published the member list for guild {} from {}",
+        guild_id, path
+    );
+    Ok(())
+}
+
+/// Sent when an onboarding reply has no callsign
+const ONBOARDING_RETRY: &str =
+    "I couldn't find a callsign in that. Try again with just your callsign and name";
+
+/// Sent when a member runs out of onboarding attempts
+const ONBOARDING_GAVE_UP: &str = "I still couldn't find a callsign, so I'll stop asking. \
+     An admin can add you, or update your nickname to include your callsign.";
+
+/// "1 try left" or "2 tries left"
+fn tries_left(remaining: u32) -> String {
+    format!(
+        "{} {} left",
+        remaining,
+        if remaining == 1 { "try" } else { "tries" }
+    )
+}
+
+/// Snapshots listed by `rollback` without a snapshot ID
+const ROLLBACK_LISTING: usize = 20;
+
+/// Republish a snapshot to a guild's sinks, or list the guild's snapshots
+async fn rollback(
+    config: Config,
+    guild_id: u64,
+    snapshot_id: Option<&str>,
+    dry_run: bool,
+) -> Result<()> {
+    let snapshots_config = config
+        .snapshots
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("rollback needs [snapshots] in the config"))?;
+    let store = SnapshotStore::new(&snapshots_config.dir, snapshots_config.keep);
+
+    let Some(snapshot_id) = snapshot_id else {
+        let history = store.history(guild_id, ROLLBACK_LISTING)?;
+        if history.is_empty() {
+            info!(
+                "No snapshots for guild {} in {}",
+                guild_id, snapshots_config.dir
+            );
+        }
+        for (snapshot, changes) in &history {
+            info!("{}", snapshots::describe(snapshot, changes));
+        }
+        return Ok(());
+    };
+
+    let snapshot = store.load(guild_id, snapshot_id)?;
+    let handler = build_handler(
+        config,
+        RosterStore::new(),
+        Metrics::new(),
+        GatewayState::new(),
+        None,
+        InFlight::new(),
+        dry_run,
+    )
+    .await?;
+    let guild_config = handler
+        .config
+        .get_guild_config(guild_id)
+        .ok_or_else(|| anyhow::anyhow!("Guild {} isn't configured", guild_id))?;
+
+    info!(
+        "Rolling guild {} back to snapshot {} ({} entries)",
+        guild_id,
+        snapshot_id,
+        snapshot.entries.len()
+    );
+    if handler.is_dry_run(guild_config) {
+        handler.log_dry_run(guild_config, &snapshot.content).await;
+        return Ok(());
+    }
+    handler
+        .publish(guild_config, &snapshot.content, &snapshot.entries)
+        .await?;
+    // The restored roster is now the latest published one
+    handler.save_snapshot(guild_id, snapshot.content, snapshot.entries);
+    info!("Restored snapshot {} for guild {}", snapshot_id, guild_id);
     Ok(())
 }
@@ -0,0 +1,228 @@
+use crate::config::PushGatewayConfig;
+use anyhow::{Context, Result};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::time::Duration;
+use tracing::warn;
+
+/// Prometheus metrics for the bot, cheap to clone and share between tasks
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    pub regenerations: IntCounterVec,
+    pub members: IntGaugeVec,
+    pub roster_entries: IntGaugeVec,
+    pub roster_generated_at: IntGaugeVec,
+    pub parse_failures: IntCounterVec,
+    pub qrz_lookup_seconds: HistogramVec,
+    pub qrz_lookup_errors: IntCounter,
+    pub github_commits: IntCounterVec,
+    pub event_queue_depth: IntGauge,
+    pub queued_events: IntCounterVec,
+    pub discord_rate_limits: IntCounterVec,
+    pub discord_rate_limit_wait_seconds: Histogram,
+    pub discord_rate_limit_remaining: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("callsign_bot".to_string()), None)
+            .expect("Failed to create metrics registry");
+
+        let regenerations = IntCounterVec::new(
+            Opts::new("regenerations_total", "Member list regenerations by cause"),
+            &["guild_id", "cause"],
+        )
+        .expect("Failed to create regenerations metric");
+        let members = IntGaugeVec::new(
+            Opts::new("members", "Members fetched from the guild"),
+            &["guild_id"],
+        )
+        .expect("Failed to create members metric");
+        let roster_entries = IntGaugeVec::new(
+            Opts::new("roster_entries", "Entries in the latest roster"),
+            &["guild_id"],
+        )
+        .expect("Failed to create roster_entries metric");
+        let roster_generated_at = IntGaugeVec::new(
+            Opts::new(
+                "roster_generated_timestamp_seconds",
+                "Unix time of the latest roster",
+            ),
+            &["guild_id"],
+        )
+        .expect("Failed to create roster_generated_timestamp_seconds metric");
+        let parse_failures = IntCounterVec::new(
+            Opts::new(
+                "parse_failures_total",
+                "Members whose names had no parseable callsign",
+            ),
+            &["guild_id"],
+        )
+        .expect("Failed to create parse_failures metric");
+        let qrz_lookup_seconds = HistogramVec::new(
+            HistogramOpts::new("qrz_lookup_duration_seconds", "QRZ lookup latency"),
+            &["result"],
+        )
+        .expect("Failed to create qrz_lookup_duration_seconds metric");
+        let qrz_lookup_errors = IntCounter::new("qrz_lookup_errors_total", "Failed QRZ lookups")
+            .expect("Failed to create qrz_lookup_errors metric");
+        let github_commits = IntCounterVec::new(
+            Opts::new("github_commits_total", "GitHub commit attempts by result"),
+            &["guild_id", "result"],
+        )
+        .expect("Failed to create github_commits metric");
+
+        let event_queue_depth = IntGauge::new(
+            "event_queue_depth",
+            "Members with events waiting for regeneration",
+        )
+        .expect("Failed to create event_queue_depth metric");
+        let queued_events = IntCounterVec::new(
+            Opts::new(
+                "queued_events_total",
+                "Member events offered to the queue by result (new, coalesced, dropped)",
+            ),
+            &["result"],
+        )
+        .expect("Failed to create queued_events metric");
+
+        let discord_rate_limits = IntCounterVec::new(
+            Opts::new(
+                "discord_rate_limits_total",
+                "Discord REST requests that had to wait out a rate limit",
+            ),
+            &["global"],
+        )
+        .expect("Failed to create discord_rate_limits metric");
+        let discord_rate_limit_wait_seconds = Histogram::with_opts(HistogramOpts::new(
+            "discord_rate_limit_wait_seconds",
+            "Time spent waiting out Discord rate limits",
+        ))
+        .expect("Failed to create discord_rate_limit_wait_seconds metric");
+        let discord_rate_limit_remaining = IntGaugeVec::new(
+            Opts::new(
+                "discord_rate_limit_remaining",
+                "Requests left in a Discord rate limit bucket when last checked",
+            ),
+            &["route"],
+        )
+        .expect("Failed to create discord_rate_limit_remaining metric");
+
+        for collector in [
+            Box::new(regenerations.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(members.clone()),
+            Box::new(roster_entries.clone()),
+            Box::new(roster_generated_at.clone()),
+            Box::new(parse_failures.clone()),
+            Box::new(qrz_lookup_seconds.clone()),
+            Box::new(qrz_lookup_errors.clone()),
+            Box::new(github_commits.clone()),
+            Box::new(event_queue_depth.clone()),
+            Box::new(queued_events.clone()),
+            Box::new(discord_rate_limits.clone()),
+            Box::new(discord_rate_limit_wait_seconds.clone()),
+            Box::new(discord_rate_limit_remaining.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("Failed to register metric");
+        }
+
+        Self {
+            registry,
+            regenerations,
+            members,
+            roster_entries,
+            roster_generated_at,
+            parse_failures,
+            qrz_lookup_seconds,
+            qrz_lookup_errors,
+            github_commits,
+            event_queue_depth,
+            queued_events,
+            discord_rate_limits,
+            discord_rate_limit_wait_seconds,
+            discord_rate_limit_remaining,
+        }
+    }
+
+    /// Render all metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("Failed to encode metrics");
+        String::from_utf8(buffer).expect("Metrics output is not UTF-8")
+    }
+
+    /// Push the current metrics to a Prometheus push gateway
+    pub async fn push(&self, client: &reqwest::Client, config: &PushGatewayConfig) -> Result<()> {
+        let url = format!(
+            "{}/metrics/job/{}",
+            config.url.trim_end_matches('/'),
+            config.job
+        );
+
+        let response = client
+            .put(&url)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                TextEncoder::new().format_type(),
+            )
+            .body(self.render())
+            .send()
+            .await
+            .context("Failed to send metrics to push gateway")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Push gateway returned error {}", response.status());
+        }
+
+        Ok(())
+    }
+
+    /// Push metrics on a fixed interval until the process exits
+    pub fn spawn_pusher(&self, config: PushGatewayConfig) {
+        let metrics = self.clone();
+        tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs));
+            loop {
+                interval.tick().await;
+                if let Err(e) = metrics.push(&client, &config).await {
+                    warn!("Failed to push metrics to {}: {:?}", config.url, e);
+                }
+            }
+        });
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_includes_prefixed_metrics() {
+        let metrics = Metrics::new();
+        metrics
+            .regenerations
+            .with_label_values(&["1", "startup"])
+            .inc();
+        metrics.roster_entries.with_label_values(&["1"]).set(42);
+
+        let output = metrics.render();
+        assert!(
+            output.contains("callsign_bot_regenerations_total{cause=\"startup\",guild_id=\"1\"} 1")
+        );
+        assert!(output.contains("callsign_bot_roster_entries{guild_id=\"1\"} 42"));
+    }
+}
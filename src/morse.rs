@@ -0,0 +1,298 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Samples per second of generated audio; plenty for a sidetone
+const SAMPLE_RATE: u32 = 8000;
+
+/// Rise and fall time of each element, so tones start and stop without clicks
+const RAMP_SECS: f64 = 0.005;
+
+/// Silence before and after the code, so players don't clip the first element
+const PADDING_SECS: f64 = 0.3;
+
+const CODES: [(char, &str); 41] = [
+    ('A', ".-"),
+    ('B', "-..."),
+    ('C', "-.-."),
+    ('D', "-.."),
+    ('E', "."),
+    ('F', "..-."),
+    ('G', "--."),
+    ('H', "...."),
+    ('I', ".."),
+    ('J', ".---"),
+    ('K', "-.-"),
+    ('L', ".-.."),
+    ('M', "--"),
+    ('N', "-."),
+    ('O', "---"),
+    ('P', ".--."),
+    ('Q', "--.-"),
+    ('R', ".-."),
+    ('S', "..."),
+    ('T', "-"),
+    ('U', "..-"),
+    ('V', "...-"),
+    ('W', ".--"),
+    ('X', "-..-"),
+    ('Y', "-.--"),
+    ('Z', "--.."),
+    ('0', "-----"),
+    ('1', ".----"),
+    ('2', "..---"),
+    ('3', "...--"),
+    ('4', "....-"),
+    ('5', "....."),
+    ('6', "-...."),
+    ('7', "--..."),
+    ('8', "---.."),
+    ('9', "----."),
+    ('/', "-..-."),
+    ('?', "..--.."),
+    ('.', ".-.-.-"),
+    (',', "--..--"),
+    ('=', "-...-"),
+];
+
+fn code(c: char) -> Option<&'static str> {
+    let c = c.to_ascii_uppercase();
+    CODES
+        .iter()
+        .find(|(letter, _)| *letter == c)
+        .map(|(_, code)| *code)
+}
+
+/// "W6JSV" -> ".-- -.... .--- ... ...-"; words are separated by " / " and
+/// characters without a code are dropped
+pub fn to_text(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| word.chars().filter_map(code).collect::<Vec<_>>().join(" "))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+/// 16-bit mono WAV of the text sent at `wpm`, using PARIS timing (a dit is 1.2 / wpm seconds)
+pub fn to_wav(text: &str, wpm: u32, tone_hz: u32) -> Vec<u8> {
+    let unit = 1.2 / f64::from(wpm.max(1));
+    let mut samples: Vec<i16> = Vec::new();
+    silence(&mut samples, PADDING_SECS);
+    for (w, word) in text.split_whitespace().enumerate() {
+        if w > 0 {
+            // 7 units between words, 3 of which follow the last character
+            silence(&mut samples, unit * 4.0);
+        }
+        for (c, code) in word.chars().filter_map(code).enumerate() {
+            if c > 0 {
+                silence(&mut samples, unit * 2.0);
+            }
+            for (e, element) in code.chars().enumerate() {
+                if e > 0 {
+                    silence(&mut samples, unit);
+                }
+                let units = if element == '-' { 3.0 } else { 1.0 };
+                tone(&mut samples, unit * units, tone_hz);
+            }
+            silence(&mut samples, unit);
+        }
+    }
+    silence(&mut samples, PADDING_SECS);
+
+    wav(&samples)
+}
+
+fn silence(samples: &mut Vec<i16>, secs: f64) {
+    let count = (secs * f64::from(SAMPLE_RATE)) as usize;
+    samples.resize(samples.len() + count, 0);
+}
+
+fn tone(samples: &mut Vec<i16>, secs: f64, tone_hz: u32) {
+    let count = (secs * f64::from(SAMPLE_RATE)) as usize;
+    let ramp = (RAMP_SECS * f64::from(SAMPLE_RATE)) as usize;
+    for i in 0..count {
+        let envelope = (i.min(count - i) as f64 / ramp as f64).min(1.0);
+        let phase =
+            2.0 * std::f64::consts::PI * f64::from(tone_hz) * i as f64 / f64::from(SAMPLE_RATE);
+        samples.push((phase.sin() * envelope * f64::from(i16::MAX) * 0.5) as i16);
+    }
+}
+
+/// Wrap PCM samples in a RIFF header
+fn wav(samples: &[i16]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // mono
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    bytes
+}
+
+/// Characters copied correctly, position by position
+pub fn grade(sent: &str, copied: &str) -> usize {
+    sent.chars()
+        .zip(copied.trim().chars())
+        .filter(|(s, c)| s.eq_ignore_ascii_case(c))
+        .count()
+}
+
+/// A member's practice results over time; kept in `Storage`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CwScore {
+    pub attempts: u32,
+    /// Callsigns copied exactly
+    pub correct: u32,
+    pub characters: u32,
+    pub characters_correct: u32,
+    /// Fastest speed of an exact copy
+    pub best_wpm: Option<u32>,
+}
+
+impl CwScore {
+    pub fn record(&mut self, sent: &str, copied: &str, wpm: u32) {
+        let characters_correct = grade(sent, copied);
+        let exact = sent.eq_ignore_ascii_case(copied.trim());
+        self.attempts += 1;
+        self.characters += sent.chars().count() as u32;
+        self.characters_correct += characters_correct as u32;
+        if exact {
+            self.correct += 1;
+            self.best_wpm = Some(self.best_wpm.map_or(wpm, |best| best.max(wpm)));
+        }
+    }
+
+    /// "12/15 callsigns (80%), 91% of characters, best 25 WPM"
+    pub fn describe(&self) -> String {
+        let percent = |n: u32, total: u32| match total {
+            0 => 0,
+            total => n * 100 / total,
+        };
+        let mut text = format!(
+            "{}/{} callsigns ({}%), {}% of characters",
+            self.correct,
+            self.attempts,
+            percent(self.correct, self.attempts),
+            percent(self.characters_correct, self.characters)
+        );
+        if let Some(wpm) = self.best_wpm {
+            text.push_str(&format!(", best {} WPM", wpm));
+        }
+        text
+    }
+}
+
+/// The callsign a member was last sent, waiting for `/cw answer`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Challenge {
+    pub callsign: String,
+    pub wpm: u32,
+}
+
+/// Open challenges, keyed by guild and user ID; a new one replaces the last
+#[derive(Default)]
+pub struct Practice {
+    challenges: Mutex<HashMap<(u64, u64), Challenge>>,
+}
+
+impl Practice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&self, guild_id: u64, user_id: u64, challenge: Challenge) {
+        self.challenges
+            .lock()
+            .expect("practice lock poisoned")
+            .insert((guild_id, user_id), challenge);
+    }
+
+    /// The member's open challenge, closing it
+    pub fn take(&self, guild_id: u64, user_id: u64) -> Option<Challenge> {
+        self.challenges
+            .lock()
+            .expect("practice lock poisoned")
+            .remove(&(guild_id, user_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_text() {
+        assert_eq!(to_text("W6JSV"), ".-- -.... .--- ... ...-");
+        assert_eq!(to_text("w6jsv/p"), ".-- -.... .--- ... ...- -..-. .--.");
+        assert_eq!(to_text("CQ DE"), "-.-. --.- / -.. .");
+        assert_eq!(to_text("é !"), "");
+    }
+
+    #[test]
+    fn test_to_wav() {
+        let wav = to_wav("E", 20, 600);
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
+        // A 60 ms dit and a one-unit gap between 300 ms of padding on each side
+        let samples = (wav.len() - 44) / 2;
+        let expected = ((0.3 + 0.06 + 0.06 + 0.3) * f64::from(SAMPLE_RATE)) as usize;
+        assert!(samples.abs_diff(expected) <= 2, "{}", samples);
+
+        // Twice the speed, half the code
+        assert!(to_wav("PARIS", 40, 600).len() < to_wav("PARIS", 20, 600).len());
+    }
+
+    #[test]
+    fn test_grade() {
+        assert_eq!(grade("W6JSV", "w6jsv"), 5);
+        assert_eq!(grade("W6JSV", "W6JBV"), 4);
+        assert_eq!(grade("W6JSV", "W6"), 2);
+        assert_eq!(grade("W6JSV", ""), 0);
+    }
+
+    #[test]
+    fn test_cw_score() {
+        let mut score = CwScore::default();
+        score.record("W6JSV", "w6jsv ", 20);
+        score.record("KI7QCF", "KI7QCE", 25);
+        score.record("N0CALL", "N0CALL", 15);
+        assert_eq!(score.attempts, 3);
+        assert_eq!(score.correct, 2);
+        assert_eq!(score.characters, 17);
+        assert_eq!(score.characters_correct, 16);
+        assert_eq!(score.best_wpm, Some(20));
+        assert_eq!(
+            score.describe(),
+            "2/3 callsigns (66%), 94% of characters, best 20 WPM"
+        );
+        assert_eq!(
+            CwScore::default().describe(),
+            "0/0 callsigns (0%), 0% of characters"
+        );
+    }
+
+    #[test]
+    fn test_practice() {
+        let practice = Practice::new();
+        let challenge = Challenge {
+            callsign: "W6JSV".to_string(),
+            wpm: 20,
+        };
+        practice.start(1, 42, challenge.clone());
+        assert_eq!(practice.take(1, 43), None);
+        assert_eq!(practice.take(1, 42), Some(challenge));
+        assert_eq!(practice.take(1, 42), None);
+    }
+}
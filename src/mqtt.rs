@@ -0,0 +1,175 @@
+use crate::config::MqttConfig;
+use crate::output::OutputEntry;
+use crate::simulation::EntryChange;
+use crate::webhook::RosterPayload;
+use anyhow::{Context, Result};
+use rumqttc::{AsyncClient, EventLoop, MqttOptions, QoS};
+use serde::Serialize;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Messages queued while the broker is unreachable; once full, publishes fail
+/// immediately instead of waiting for the broker to come back
+const QUEUE_CAPACITY: usize = 64;
+
+/// Publishes rosters and member events to an MQTT broker
+pub struct MqttClient {
+    client: AsyncClient,
+    roster_topic: String,
+    events_topic: String,
+    retain_roster: bool,
+}
+
+/// A roster change tagged with its guild, published to the events topic
+#[derive(Serialize)]
+struct MemberEvent<'a> {
+    guild_id: u64,
+    #[serde(flatten)]
+    change: &'a EntryChange,
+}
+
+impl MqttClient {
+    /// Start a client whose event loop runs in the background, reconnecting as needed
+    pub fn connect(config: &MqttConfig) -> Self {
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        let password = config
+            .password
+            .clone()
+            .or_else(|| std::env::var("MQTT_PASSWORD").ok());
+        if let Some(username) = &config.username {
+            options.set_credentials(username, password.unwrap_or_default());
+        }
+
+        let (client, event_loop) = AsyncClient::new(options, QUEUE_CAPACITY);
+        info!("Connecting to MQTT broker {}:{}", config.host, config.port);
+        tokio::spawn(run_event_loop(event_loop));
+
+        Self {
+            client,
+            roster_topic: config.roster_topic.clone(),
+            events_topic: config.events_topic.clone(),
+            retain_roster: config.retain_roster,
+        }
+    }
+
+    /// Queue the full roster JSON, retained by default so new subscribers get it at once
+    pub fn publish_roster(
+        &self,
+        guild_id: u64,
+        title: Option<&str>,
+        entries: &[OutputEntry],
+    ) -> Result<()> {
+        let payload = serde_json::to_vec(&RosterPayload::new(guild_id, title, entries))?;
+        self.client
+            .try_publish(
+                topic(&self.roster_topic, guild_id),
+                QoS::AtLeastOnce,
+                self.retain_roster,
+                payload,
+            )
+            .context("Failed to queue MQTT roster message; is the broker reachable?")
+    }
+
+    /// Queue one message per joined, left, or changed member
+    pub fn publish_changes(&self, guild_id: u64, changes: &[EntryChange]) -> Result<()> {
+        let topic = topic(&self.events_topic, guild_id);
+        for change in changes {
+            let payload = serde_json::to_vec(&MemberEvent { guild_id, change })?;
+            self.client
+                .try_publish(&topic, QoS::AtLeastOnce, false, payload)
+                .context("Failed to queue MQTT member event; is the broker reachable?")?;
+        }
+        Ok(())
+    }
+}
+
+/// Drive the connection; rumqttc reconnects on the next poll after an error
+async fn run_event_loop(mut event_loop: EventLoop) {
+    let mut connected = false;
+    loop {
+        match event_loop.poll().await {
+            Ok(event) => {
+                if !connected {
+                    info!("Connected to MQTT broker");
+                    connected = true;
+                }
+                debug!("MQTT event: {:?}", event);
+            }
+            Err(e) => {
+                if connected {
+                    warn!("MQTT connection lost: {}", e);
+                    connected = false;
+                } else {
+                    debug!("MQTT connection failed: {}", e);
+                }
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        }
+    }
+}
+
+/// Fill the `{guild_id}` placeholder of a topic template
+fn topic(template: &str, guild_id: u64) -> String {
+    template.replace("{guild_id}", &guild_id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic() {
+        assert_eq!(
+            topic("callsign-bot/{guild_id}/roster", 42),
+            "callsign-bot/42/roster"
+        );
+        assert_eq!(topic("shack/members", 42), "shack/members");
+    }
+
+    #[tokio::test]
+    async fn test_publish_fails_fast_when_broker_unreachable() {
+        let config: MqttConfig = toml::from_str(
+            r#"
+            host = "127.0.0.1"
+            port = 1
+            "#,
+        )
+        .unwrap();
+        let client = MqttClient::connect(&config);
+        let results: Vec<bool> = (0..=QUEUE_CAPACITY)
+            .map(|_| client.publish_roster(42, None, &[]).is_ok())
+            .collect();
+        assert!(results[..QUEUE_CAPACITY].iter().all(|ok| *ok));
+        assert!(!results[QUEUE_CAPACITY]);
+    }
+
+    #[test]
+    fn test_member_event_serialization() {
+        let change = EntryChange::Renamed {
+            callsign: "W6JSV".to_string(),
+            old: "Jay".to_string(),
+            new: "Jay S".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_string(&MemberEvent {
+                guild_id: 42,
+                change: &change,
+            })
+            .unwrap(),
+            r#"{"guild_id":42,"event":"changed","callsign":"W6JSV","old":"Jay","new":"Jay S"}"#
+        );
+
+        let change = EntryChange::Removed {
+            callsign: "N0CALL".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_string(&MemberEvent {
+                guild_id: 42,
+                change: &change,
+            })
+            .unwrap(),
+            r#"{"guild_id":42,"event":"left","callsign":"N0CALL"}"#
+        );
+    }
+}
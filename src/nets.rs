@@ -0,0 +1,482 @@
+use crate::output::{adif_field, sanitize_field, MAX_NAME_CHARS};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Discord caps thread names at 100 characters
+const MAX_THREAD_NAME_CHARS: usize = 100;
+
+/// Discord caps message content at 2000 characters
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+/// A net in progress; kept in `Storage` so a restart doesn't lose check-ins
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Net {
+    pub name: String,
+    /// User ID of the net control operator who started it
+    pub started_by: u64,
+    pub started_at: DateTime<Utc>,
+    #[serde(default)]
+    pub checkins: Vec<CheckIn>,
+    /// The net's thread under `[guilds.nets] thread_channel_id`, archived when it ends
+    #[serde(default)]
+    pub thread_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckIn {
+    pub callsign: String,
+    pub name: String,
+    /// The Discord member checked in, if the station is a member
+    pub user_id: Option<u64>,
+    pub at: DateTime<Utc>,
+    /// Checked in automatically for being in the net's voice channel
+    #[serde(default)]
+    pub voice: bool,
+}
+
+impl Net {
+    pub fn new(name: &str, started_by: u64, started_at: DateTime<Utc>) -> Self {
+        Self {
+            name: sanitize_field(name, MAX_NAME_CHARS),
+            started_by,
+            started_at,
+            checkins: Vec::new(),
+            thread_id: None,
+        }
+    }
+
+    /// Record a check-in unless the callsign already checked in; returns whether it was added
+    pub fn check_in(&mut self, checkin: CheckIn) -> bool {
+        if self
+            .checkins
+            .iter()
+            .any(|c| c.callsign.eq_ignore_ascii_case(&checkin.callsign))
+        {
+            return false;
+        }
+        self.checkins.push(checkin);
+        true
+    }
+}
+
+/// "Sunday Net 2026-10-18", from the UTC start date
+pub fn thread_name(net: &Net) -> String {
+    let date = net.started_at.format("%Y-%m-%d").to_string();
+    let name = sanitize_field(&net.name, MAX_THREAD_NAME_CHARS - date.len() - 1);
+    format!("{} {}", name, date)
+}
+
+/// The roster's callsigns and names, posted to a net's thread for check-in reference,
+/// split into messages that fit Discord's limit
+pub fn roster_messages(stations: &[(String, String)]) -> Vec<String> {
+    let mut messages = vec![format!(
+        "📋 **Roster** for check-in reference ({} stations):",
+        stations.len()
+    )];
+    for (callsign, name) in stations {
+        let line = if name.is_empty() || name == callsign {
+            callsign.clone()
+        } else {
+            format!("{} {}", callsign, name)
+        };
+        let last = messages.last_mut().expect("messages start with a heading");
+        if last.chars().count() + 1 + line.chars().count() > MAX_MESSAGE_CHARS {
+            messages.push(line);
+        } else {
+            last.push('\n');
+            last.push_str(&line);
+        }
+    }
+    messages
+}
+
+/// Plain-text net log: a header, then one timestamped line per check-in
+pub fn log_text(net: &Net, ended_at: DateTime<Utc>) -> String {
+    let mut log = format!(
+        "# NET: {}\n# Started: {}\n# Ended: {}\n# Check-ins: {}\n",
+        net.name,
+        net.started_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        ended_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        net.checkins.len()
+    );
+    for (i, checkin) in net.checkins.iter().enumerate() {
+        let line = format!(
+            "{:>3}. {} {} {}",
+            i + 1,
+            checkin.at.format("%H:%M:%S"),
+            checkin.callsign,
+            sanitize_field(&checkin.name, MAX_NAME_CHARS)
+        );
+        // No trailing space when the name is unknown
+        log.push_str(line.trim_end());
+        if checkin.voice {
+            log.push_str(" (voice)");
+        }
+        log.push('\n');
+    }
+    log
+}
+
+/// ADIF log with one record per check-in, timestamped, for importing into a logger
+pub fn log_adif(net: &Net) -> String {
+    // The header must not start with '<'
+    let mut log = format!(
+        "Net log: {}\n{}{}<EOH>\n",
+        net.name,
+        adif_field("ADIF_VER", "3.1.4"),
+        adif_field("PROGRAMID", "discord-callsign-bot"),
+    );
+    for checkin in &net.checkins {
+        log.push_str(&adif_field("CALL", &checkin.callsign));
+        log.push_str(&adif_field("NAME", &checkin.name));
+        log.push_str(&adif_field(
+            "QSO_DATE",
+            &checkin.at.format("%Y%m%d").to_string(),
+        ));
+        log.push_str(&adif_field(
+            "TIME_ON",
+            &checkin.at.format("%H%M%S").to_string(),
+        ));
+        log.push_str(&adif_field("COMMENT", &net.name));
+        log.push_str("<EOR>\n");
+    }
+    log
+}
+
+/// File name stem for a net's logs, e.g. `2026-10-16-0100-sunday-night-net`
+pub fn log_stem(net: &Net) -> String {
+    let mut slug = String::new();
+    for c in net.name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    let stamp = net.started_at.format("%Y-%m-%d-%H%M");
+    if slug.is_empty() {
+        stamp.to_string()
+    } else {
+        format!("{}-{}", stamp, slug)
+    }
+}
+
+/// Write the text and ADIF logs under `dir/<guild_id>/`, returning their paths
+pub fn write_logs(
+    dir: &Path,
+    guild_id: u64,
+    net: &Net,
+    ended_at: DateTime<Utc>,
+) -> Result<Vec<PathBuf>> {
+    let dir = dir.join(guild_id.to_string());
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create net log directory {}", dir.display()))?;
+
+    let stem = log_stem(net);
+    let files = [
+        (dir.join(format!("{}.txt", stem)), log_text(net, ended_at)),
+        (dir.join(format!("{}.adi", stem)), log_adif(net)),
+    ];
+    for (path, contents) in &files {
+        fs::write(path, contents)
+            .with_context(|| format!("Failed to write net log {}", path.display()))?;
+    }
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+/// A finished net, kept for attendance stats
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetRecord {
+    pub name: String,
+    pub started_at: DateTime<Utc>,
+    /// Callsigns that checked in, in check-in order
+    pub callsigns: Vec<String>,
+}
+
+impl From<&Net> for NetRecord {
+    fn from(net: &Net) -> Self {
+        Self {
+            name: net.name.clone(),
+            started_at: net.started_at,
+            callsigns: net.checkins.iter().map(|c| c.callsign.clone()).collect(),
+        }
+    }
+}
+
+/// One station's attendance across a guild's nets
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attendance {
+    pub callsign: String,
+    pub checkins: usize,
+    /// Consecutive nets attended, counting back from the most recent
+    pub streak: usize,
+    pub best_streak: usize,
+}
+
+/// Attendance per callsign, most check-ins first (ties: longer streak, then callsign).
+/// Streaks count consecutive nets in start order, whatever their name.
+pub fn attendance(history: &[NetRecord]) -> Vec<Attendance> {
+    let mut history = history.iter().collect::<Vec<_>>();
+    history.sort_by_key(|net| net.started_at);
+
+    let mut stats: Vec<Attendance> = Vec::new();
+    for (index, net) in history.iter().enumerate() {
+        for callsign in &net.callsigns {
+            let position = stats
+                .iter()
+                .position(|a| a.callsign.eq_ignore_ascii_case(callsign));
+            let entry = match position {
+                Some(position) => &mut stats[position],
+                None => {
+                    stats.push(Attendance {
+                        callsign: callsign.to_uppercase(),
+                        checkins: 0,
+                        streak: 0,
+                        best_streak: 0,
+                    });
+                    stats.last_mut().expect("just pushed")
+                }
+            };
+            entry.checkins += 1;
+            entry.streak = if index > 0 && attended(history[index - 1], callsign) {
+                entry.streak + 1
+            } else {
+                1
+            };
+            entry.best_streak = entry.best_streak.max(entry.streak);
+        }
+    }
+
+    // Streaks broken by the most recent nets no longer count
+    if let Some(latest) = history.last() {
+        for entry in &mut stats {
+            if !attended(latest, &entry.callsign) {
+                entry.streak = 0;
+            }
+        }
+    }
+
+    stats.sort_by(|a, b| {
+        b.checkins
+            .cmp(&a.checkins)
+            .then(b.streak.cmp(&a.streak))
+            .then(a.callsign.cmp(&b.callsign))
+    });
+    stats
+}
+
+fn attended(net: &NetRecord, callsign: &str) -> bool {
+    net.callsigns
+        .iter()
+        .any(|c| c.eq_ignore_ascii_case(callsign))
+}
+
+/// One station's line for `/net stats <callsign>`
+pub fn station_stats(history: &[NetRecord], callsign: &str) -> String {
+    let callsign = callsign.to_uppercase();
+    match attendance(history)
+        .into_iter()
+        .find(|entry| entry.callsign == callsign)
+    {
+        Some(entry) => format!(
+            "{} has checked in to {} of {} nets; current streak {}, best {}.",
+            callsign,
+            entry.checkins,
+            history.len(),
+            entry.streak,
+            entry.best_streak
+        ),
+        None => format!("{} hasn't checked in to any nets yet.", callsign),
+    }
+}
+
+/// The top `limit` stations by check-ins, one numbered line each
+pub fn leaderboard(history: &[NetRecord], limit: usize) -> String {
+    let stats = attendance(history);
+    let mut board = format!(
+        "📊 **Net attendance** ({} net{})",
+        history.len(),
+        if history.len() == 1 { "" } else { "s" }
+    );
+    if stats.is_empty() {
+        board.push_str("\nNo check-ins yet.");
+    }
+    for (rank, entry) in stats.iter().take(limit).enumerate() {
+        board.push_str(&format!(
+            "\n{}. {} — {} check-in{}, streak {} (best {})",
+            rank + 1,
+            entry.callsign,
+            entry.checkins,
+            if entry.checkins == 1 { "" } else { "s" },
+            entry.streak,
+            entry.best_streak
+        ));
+    }
+    board
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    fn checkin(callsign: &str, name: &str, secs: i64) -> CheckIn {
+        CheckIn {
+            callsign: callsign.to_string(),
+            name: name.to_string(),
+            user_id: None,
+            at: at(secs),
+            voice: false,
+        }
+    }
+
+    #[test]
+    fn test_check_in_skips_duplicates() {
+        let mut net = Net::new("Sunday Net", 1, at(1_760_000_000));
+        assert!(net.check_in(checkin("W6JSV", "Jay", 1_760_000_060)));
+        assert!(!net.check_in(checkin("w6jsv", "Jay", 1_760_000_120)));
+        assert!(net.check_in(checkin("KI7QCF", "Forrest", 1_760_000_180)));
+        assert_eq!(net.checkins.len(), 2);
+    }
+
+    #[test]
+    fn test_thread_name() {
+        let net = Net::new("Sunday Net", 1, at(1_760_000_000));
+        assert_eq!(thread_name(&net), "Sunday Net 2025-10-09");
+        let net = Net::new(&"x".repeat(MAX_NAME_CHARS), 1, at(1_760_000_000));
+        assert!(thread_name(&net).chars().count() <= MAX_THREAD_NAME_CHARS);
+    }
+
+    #[test]
+    fn test_roster_messages() {
+        let station = |callsign: &str, name: &str| (callsign.to_string(), name.to_string());
+        assert_eq!(
+            roster_messages(&[station("W6JSV", "Jay"), station("KI7QCF", "KI7QCF")]),
+            vec!["📋 **Roster** for check-in reference (2 stations):\nW6JSV Jay\nKI7QCF"]
+        );
+
+        let stations: Vec<(String, String)> = (0..400)
+            .map(|i| station(&format!("W{}ABC", i), "Operator"))
+            .collect();
+        let messages = roster_messages(&stations);
+        assert!(messages.len() > 1);
+        assert!(messages
+            .iter()
+            .all(|m| m.chars().count() <= MAX_MESSAGE_CHARS));
+        assert_eq!(
+            messages.iter().map(|m| m.lines().count()).sum::<usize>(),
+            401
+        );
+    }
+
+    #[test]
+    fn test_logs() {
+        let mut net = Net::new("Sunday Night Net!", 1, at(1_760_000_000));
+        net.check_in(checkin("W6JSV", "Jay", 1_760_000_060));
+        net.check_in(CheckIn {
+            voice: true,
+            ..checkin("KI7QCF", "", 1_760_000_185)
+        });
+
+        assert_eq!(
+            log_text(&net, at(1_760_001_800)),
+            "# NET: Sunday Night Net!\n# Started: 2025-10-09 08:53:20 UTC\n\
+             # Ended: 2025-10-09 09:23:20 UTC\n# Check-ins: 2\n\
+             \x20 1. 08:54:20 W6JSV Jay\n\x20 2. 08:56:25 KI7QCF (voice)\n"
+        );
+        assert_eq!(
+            log_adif(&net),
+            "Net log: Sunday Night Net!\n<ADIF_VER:5>3.1.4 <PROGRAMID:20>discord-callsign-bot <EOH>\n\
+             <CALL:5>W6JSV <NAME:3>Jay <QSO_DATE:8>20251009 <TIME_ON:6>085420 <COMMENT:17>Sunday Night Net! <EOR>\n\
+             <CALL:6>KI7QCF <QSO_DATE:8>20251009 <TIME_ON:6>085625 <COMMENT:17>Sunday Night Net! <EOR>\n"
+        );
+        assert_eq!(log_stem(&net), "2025-10-09-0853-sunday-night-net");
+        assert_eq!(
+            log_stem(&Net::new("📻", 1, at(1_760_000_000))),
+            "2025-10-09-0853"
+        );
+    }
+
+    fn record(secs: i64, callsigns: &[&str]) -> NetRecord {
+        NetRecord {
+            name: "Sunday Net".to_string(),
+            started_at: at(secs),
+            callsigns: callsigns.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_attendance() {
+        // Out of order on purpose; streaks follow start times
+        let history = [
+            record(300, &["W6JSV", "KI7QCF"]),
+            record(100, &["W6JSV", "KI7QCF"]),
+            record(200, &["w6jsv"]),
+            record(400, &["KI7QCF"]),
+            record(0, &["KI7QCF"]),
+        ];
+        let stats = attendance(&history);
+        assert_eq!(
+            stats,
+            vec![
+                Attendance {
+                    callsign: "KI7QCF".to_string(),
+                    checkins: 4,
+                    streak: 2,
+                    best_streak: 2,
+                },
+                Attendance {
+                    callsign: "W6JSV".to_string(),
+                    checkins: 3,
+                    streak: 0,
+                    best_streak: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_leaderboard_and_station_stats() {
+        assert_eq!(
+            leaderboard(&[], 10),
+            "📊 **Net attendance** (0 nets)\nNo check-ins yet."
+        );
+
+        let history = [record(0, &["W6JSV", "KI7QCF"]), record(100, &["W6JSV"])];
+        assert_eq!(
+            leaderboard(&history, 1),
+            "📊 **Net attendance** (2 nets)\n1. W6JSV — 2 check-ins, streak 2 (best 2)"
+        );
+        assert_eq!(
+            station_stats(&history, "ki7qcf"),
+            "KI7QCF has checked in to 1 of 2 nets; current streak 0, best 1."
+        );
+        assert_eq!(
+            station_stats(&history, "N0CALL"),
+            "N0CALL hasn't checked in to any nets yet."
+        );
+    }
+
+    #[test]
+    fn test_write_logs() {
+        let dir = std::env::temp_dir().join(format!("callsign-bot-nets-{}", std::process::id()));
+        let mut net = Net::new("Test", 1, at(1_760_000_000));
+        net.check_in(checkin("W6JSV", "Jay", 1_760_000_060));
+
+        let paths = write_logs(&dir, 42, &net, at(1_760_000_600)).unwrap();
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].ends_with("42/2025-10-09-0853-test.txt"));
+        assert!(fs::read_to_string(&paths[1])
+            .unwrap()
+            .contains("<CALL:5>W6JSV"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
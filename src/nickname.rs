@@ -0,0 +1,164 @@
+use crate::config::NicknameConfig;
+use crate::output::{render_template, OutputEntry};
+use crate::pipeline::Member;
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Discord caps nicknames at 32 characters
+pub const MAX_NICKNAME_CHARS: usize = 32;
+
+/// Prefix of `/audit nicknames` apply buttons' custom IDs: `nickname:apply:<user ID>:<nickname>`.
+/// The nickname fits, since Discord allows 100-character custom IDs.
+pub const APPLY_PREFIX: &str = "nickname:apply:";
+
+/// A nickname that differs from the canonical format
+#[derive(Debug, Clone, PartialEq)]
+pub struct NicknameChange {
+    pub user_id: u64,
+    /// The name the member shows as now
+    pub current: String,
+    pub desired: String,
+}
+
+/// Work out which listed members' nicknames don't match the configured format.
+/// Excluded roles, unlisted members, and nicknames too long for Discord are skipped.
+pub fn plan(
+    config: &NicknameConfig,
+    members: &[Member],
+    by_user: &HashMap<u64, OutputEntry>,
+) -> Vec<NicknameChange> {
+    let mut changes: Vec<NicknameChange> = members
+        .iter()
+        .filter(|m| {
+            !m.roles
+                .iter()
+                .any(|role| config.exclude_roles.contains(role))
+        })
+        .filter_map(|member| {
+            let entry = by_user.get(&member.user_id)?;
+            let Some(desired) = render(&config.format, entry) else {
+                debug!(
+                    "Nickname for {} would be too long; leaving it alone",
+                    member.username
+                );
+                return None;
+            };
+
+            let current = member.display_name().to_string();
+            if member.nick.as_deref() == Some(desired.as_str()) {
+                return None;
+            }
+            Some(NicknameChange {
+                user_id: member.user_id,
+                current,
+                desired,
+            })
+        })
+        .collect();
+
+    changes.sort_by(|a, b| a.desired.cmp(&b.desired));
+    changes
+}
+
+/// The canonical nickname for an entry, or `None` if it's empty or too long for Discord
+pub fn render(format: &str, entry: &OutputEntry) -> Option<String> {
+    let nickname = render_template(format, entry).trim().to_string();
+    let fits = !nickname.is_empty() && nickname.chars().count() <= MAX_NICKNAME_CHARS;
+    fits.then_some(nickname)
+}
+
+pub fn apply_id(user_id: u64, nickname: &str) -> String {
+    format!("{}{}:{}", APPLY_PREFIX, user_id, nickname)
+}
+
+/// The member and nickname an apply button is for
+pub fn parse_apply_id(custom_id: &str) -> Option<(u64, String)> {
+    let (user_id, nickname) = custom_id.strip_prefix(APPLY_PREFIX)?.split_once(':')?;
+    Some((user_id.parse().ok()?, nickname.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(user_id: u64, nick: Option<&str>, roles: &[u64]) -> Member {
+        Member {
+            user_id,
+            username: format!("user{}", user_id),
+            nick: nick.map(str::to_string),
+            roles: roles.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn entry(callsign: &str, name: &str) -> OutputEntry {
+        OutputEntry {
+            callsign: callsign.to_string(),
+            name: name.to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        let config = NicknameConfig {
+            enforce: true,
+            format: "{name} ({callsign})".to_string(),
+            exclude_roles: vec![99],
+        };
+        let members = vec![
+            member(1, Some("w6jsv - jay"), &[]),
+            member(2, Some("Forrest (KI7QCF)"), &[]),
+            member(3, Some("n0call"), &[99]),
+            member(4, None, &[]),
+            member(5, None, &[]),
+        ];
+        let by_user = HashMap::from([
+            (1, entry("W6JSV", "Jay")),
+            (2, entry("KI7QCF", "Forrest")),
+            (3, entry("N0CALL", "Nobody")),
+            (5, entry("W1AW", "A name far too long to fit in a nickname")),
+        ]);
+
+        assert_eq!(
+            plan(&config, &members, &by_user),
+            vec![NicknameChange {
+                user_id: 1,
+                current: "w6jsv - jay".to_string(),
+                desired: "Jay (W6JSV)".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let format = "{name} ({callsign})";
+        assert_eq!(
+            render(format, &entry("W6JSV", "Jay")).as_deref(),
+            Some("Jay (W6JSV)")
+        );
+        assert_eq!(
+            render(
+                format,
+                &entry("W1AW", "A name far too long to fit in a nickname")
+            ),
+            None
+        );
+        assert_eq!(render("{grid}", &entry("W6JSV", "Jay")), None);
+    }
+
+    #[test]
+    fn test_apply_ids() {
+        let id = apply_id(42, "Jay (W6JSV)");
+        assert_eq!(id, "nickname:apply:42:Jay (W6JSV)");
+        assert_eq!(parse_apply_id(&id), Some((42, "Jay (W6JSV)".to_string())));
+        // Nicknames may contain colons
+        assert_eq!(
+            parse_apply_id(&apply_id(42, "W6JSV: Jay")),
+            Some((42, "W6JSV: Jay".to_string()))
+        );
+        assert_eq!(parse_apply_id("nickname:apply:x:Jay"), None);
+        assert_eq!(parse_apply_id("application:approve:42"), None);
+    }
+}
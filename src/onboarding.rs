@@ -0,0 +1,292 @@
+use crate::config::{self, Config, GuildConfig};
+use crate::nickname::MAX_NICKNAME_CHARS;
+use crate::output::{render_template, OutputEntry};
+use crate::parser::{CallsignParser, MemberInfo};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// A new member we've DMed and are waiting to hear back from
+#[derive(Debug)]
+struct Pending {
+    attempts: u32,
+    expires: Instant,
+}
+
+/// What to do with a DM from a member
+#[derive(Debug, PartialEq)]
+pub enum Reply {
+    /// The member isn't being onboarded (or their conversation expired)
+    Ignored,
+    /// The reply contained a callsign for the member's conversations in these guilds.
+    /// They stay open until the callsign is checked: `finish()` or `reject()` them.
+    Parsed {
+        guild_ids: Vec<u64>,
+        info: MemberInfo,
+    },
+    /// No callsign found; ask again
+    Retry { remaining: u32 },
+    /// Out of attempts; the conversation is over
+    GaveUp,
+}
+
+/// Conversations with new members whose display name had no callsign, keyed by guild
+/// and user ID, so joining a second server doesn't end the first conversation.
+///
+/// Each member is asked for their callsign by DM and may reply up to `max_attempts`
+/// times within `timeout_secs` of joining. DMs have no guild, so a reply answers every
+/// open conversation with its author. Expired conversations are dropped on each call.
+#[derive(Default)]
+pub struct Onboarding {
+    pending: Mutex<HashMap<(u64, u64), Pending>>,
+}
+
+impl Onboarding {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start waiting for a member's reply, replacing any earlier conversation in the guild
+    pub fn start(&self, guild_id: u64, user_id: u64, timeout: Duration) {
+        let mut pending = self.lock();
+        pending.insert(
+            (guild_id, user_id),
+            Pending {
+                attempts: 0,
+                expires: Instant::now() + timeout,
+            },
+        );
+    }
+
+    /// Stop waiting for a member, e.g. when the first DM couldn't be delivered
+    pub fn cancel(&self, guild_id: u64, user_id: u64) {
+        self.lock().remove(&(guild_id, user_id));
+    }
+
+    /// Advance a member's conversations with the text of their DM
+    pub fn handle_reply(
+        &self,
+        config: &Config,
+        parser: &CallsignParser,
+        user_id: u64,
+        text: &str,
+    ) -> Reply {
+        let mut pending = self.lock();
+        let mut guild_ids: Vec<u64> = pending
+            .keys()
+            .filter(|(_, user)| *user == user_id)
+            .map(|(guild_id, _)| *guild_id)
+            .collect();
+        if guild_ids.is_empty() {
+            return Reply::Ignored;
+        }
+        guild_ids.sort_unstable();
+
+        match parser.parse(text) {
+            Some(info) => Reply::Parsed { guild_ids, info },
+            None => Self::miss(&mut pending, config, user_id),
+        }
+    }
+
+    /// End a member's conversations once their callsign is accepted
+    pub fn finish(&self, user_id: u64) {
+        self.lock().retain(|(_, user), _| *user != user_id);
+    }
+
+    /// Count a parsed callsign that didn't check out as a failed attempt
+    pub fn reject(&self, config: &Config, user_id: u64) -> Reply {
+        Self::miss(&mut self.lock(), config, user_id)
+    }
+
+    /// The pending conversations, without any that expired
+    fn lock(&self) -> MutexGuard<'_, HashMap<(u64, u64), Pending>> {
+        let mut pending = self.pending.lock().expect("onboarding lock poisoned");
+        let now = Instant::now();
+        pending.retain(|_, conversation| conversation.expires > now);
+        pending
+    }
+
+    /// Use up an attempt in each of the member's conversations, ending those that ran out
+    fn miss(pending: &mut HashMap<(u64, u64), Pending>, config: &Config, user_id: u64) -> Reply {
+        let mut remaining = None;
+        pending.retain(|(guild_id, user), conversation| {
+            if *user != user_id {
+                return true;
+            }
+            let Some(onboarding_config) = config
+                .get_guild_config(*guild_id)
+                .and_then(|g| g.onboarding.as_ref())
+            else {
+                return false;
+            };
+            conversation.attempts += 1;
+            let left = onboarding_config
+                .max_attempts
+                .saturating_sub(conversation.attempts);
+            if left == 0 {
+                return false;
+            }
+            remaining = Some(remaining.map_or(left, |r: u32| r.max(left)));
+            true
+        });
+        match remaining {
+            Some(remaining) => Reply::Retry { remaining },
+            None => Reply::GaveUp,
+        }
+    }
+}
+
+/// The nickname a member would get from their reply, in the guild's
+/// `[guilds.nicknames]` format; `None` if it's too long for Discord
+pub fn suggested_nickname(guild_config: &GuildConfig, info: &MemberInfo) -> Option<String> {
+    let format = guild_config
+        .nicknames
+        .as_ref()
+        .map(|n| n.format.clone())
+        .unwrap_or_else(config::default_nickname_format);
+    let entry = OutputEntry {
+        callsign: info.callsign.clone(),
+        name: info.name.clone(),
+        suffix: String::new(),
+        emoji_separator: guild_config.output.emoji_separator.clone(),
+        booster: false,
+        grid: info.grid.clone(),
+        state: None,
+        country: None,
+        class: None,
+        dmr_id: None,
+        dxcc: None,
+        flag: None,
+        continent: None,
+        coordinates: None,
+        aprs_last_heard: None,
+        lotw_last_upload: None,
+        joined_at: None,
+    };
+    let nickname = render_template(&format, &entry);
+    (nickname.chars().count() <= MAX_NICKNAME_CHARS).then_some(nickname)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+
+    fn config() -> Config {
+        toml::from_str(
+            r#"
+            [discord]
+            token = "token"
+
+            [[guilds]]
+            guild_id = 1
+
+            [guilds.output]
+            repo = "jsvana/rosters"
+            path = "members.txt"
+            default_suffix = ""
+
+            [guilds.onboarding]
+            max_attempts = 2
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_reply_with_callsign() {
+        let (config, parser) = (config(), CallsignParser::new());
+        let onboarding = Onboarding::new();
+        assert_eq!(
+            onboarding.handle_reply(&config, &parser, 42, "W6JSV"),
+            Reply::Ignored
+        );
+
+        onboarding.start(1, 42, TIMEOUT);
+        match onboarding.handle_reply(&config, &parser, 42, "it's w6jsv - Jay") {
+            Reply::Parsed { guild_ids, info } => {
+                assert_eq!(guild_ids, [1]);
+                assert_eq!(info.callsign, "W6JSV");
+            }
+            other => panic!("unexpected reply: {:?}", other),
+        }
+        onboarding.finish(42);
+        assert_eq!(
+            onboarding.handle_reply(&config, &parser, 42, "W6JSV"),
+            Reply::Ignored
+        );
+    }
+
+    #[test]
+    fn test_suggested_nickname() {
+        let config = config();
+        let info = CallsignParser::new().parse("Jay W6JSV").unwrap();
+        assert_eq!(
+            suggested_nickname(&config.guilds[0], &info).as_deref(),
+            Some("Jay (W6JSV)")
+        );
+    }
+
+    #[test]
+    fn test_rejected_callsign_uses_an_attempt() {
+        let (config, parser) = (config(), CallsignParser::new());
+        let onboarding = Onboarding::new();
+        onboarding.start(1, 42, TIMEOUT);
+
+        assert!(matches!(
+            onboarding.handle_reply(&config, &parser, 42, "N0CALL"),
+            Reply::Parsed { .. }
+        ));
+        assert_eq!(
+            onboarding.reject(&config, 42),
+            Reply::Retry { remaining: 1 }
+        );
+        assert_eq!(onboarding.reject(&config, 42), Reply::GaveUp);
+        assert_eq!(
+            onboarding.handle_reply(&config, &parser, 42, "W6JSV"),
+            Reply::Ignored
+        );
+    }
+
+    #[test]
+    fn test_conversations_are_per_guild_and_expire() {
+        let (config, parser) = (config(), CallsignParser::new());
+        let onboarding = Onboarding::new();
+        onboarding.start(1, 42, TIMEOUT);
+        onboarding.start(2, 42, TIMEOUT);
+        onboarding.cancel(2, 42);
+        onboarding.start(3, 42, TIMEOUT);
+        onboarding.start(1, 43, Duration::ZERO);
+
+        match onboarding.handle_reply(&config, &parser, 42, "W6JSV") {
+            Reply::Parsed { guild_ids, .. } => assert_eq!(guild_ids, [1, 3]),
+            other => panic!("unexpected reply: {:?}", other),
+        }
+        assert_eq!(
+            onboarding.handle_reply(&config, &parser, 43, "W6JSV"),
+            Reply::Ignored
+        );
+        assert_eq!(onboarding.pending.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_attempts() {
+        let (config, parser) = (config(), CallsignParser::new());
+        let onboarding = Onboarding::new();
+        onboarding.start(1, 42, TIMEOUT);
+
+        assert_eq!(
+            onboarding.handle_reply(&config, &parser, 42, "no idea"),
+            Reply::Retry { remaining: 1 }
+        );
+        assert_eq!(
+            onboarding.handle_reply(&config, &parser, 42, "still no"),
+            Reply::GaveUp
+        );
+        assert_eq!(
+            onboarding.handle_reply(&config, &parser, 42, "W6JSV"),
+            Reply::Ignored
+        );
+    }
+}
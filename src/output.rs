@@ -1,33 +1,799 @@
-#[derive(Debug)]
+pub mod geo;
+pub mod html;
+pub mod stats;
+
+use crate::config::{GroupBy, OutputConfig, OutputFormat, SortKey, SortOrder};
+use crate::error::{Error, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use html::escape;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+use tracing::warn;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct OutputEntry {
     pub callsign: String,
     pub name: String,
     pub suffix: String,
     pub emoji_separator: String,
+    /// Whether the member is boosting the server
+    pub booster: bool,
+    /// Maidenhead grid square, from QRZ or the display name
+    pub grid: Option<String>,
+    pub state: Option<String>,
+    pub country: Option<String>,
+    /// License class letter from QRZ (e.g. "E", "G", "T")
+    pub class: Option<String>,
+    /// DMR radio ID from RadioID.net
+    pub dmr_id: Option<u32>,
+    /// DXCC entity name from the prefix table, e.g. "Canada"
+    pub dxcc: Option<String>,
+    /// Flag emoji for the DXCC entity's country
+    pub flag: Option<String>,
+    /// Two-letter continent of the DXCC entity, e.g. "NA"
+    pub continent: Option<String>,
+    /// Latitude and longitude from QRZ; never serialized, since they can pinpoint a home
+    #[serde(skip)]
+    pub coordinates: Option<(f64, f64)>,
+    /// Unix timestamp (seconds) any of the member's stations was last heard on APRS
+    pub aprs_last_heard: Option<i64>,
+    /// When the member last uploaded to LoTW, from ARRL's user activity list
+    pub lotw_last_upload: Option<NaiveDate>,
+    /// Unix timestamp (seconds) the member joined the server, used for sorting
+    #[serde(skip)]
+    pub joined_at: Option<i64>,
+}
+
+/// Longest name written to the output file, in characters
+pub const MAX_NAME_CHARS: usize = 64;
+
+/// Longest suffix written to the output file, in characters
+pub const MAX_SUFFIX_CHARS: usize = 32;
+
+/// Discord caps message content at 2000 characters
+pub const MAX_MESSAGE_LEN: usize = 2000;
+
+/// Characters that could break a line-oriented file or reorder text when displayed:
+/// control characters (including newlines), zero-width characters, and bidi overrides
+fn is_unsafe_char(c: char) -> bool {
+    c.is_control()
+        || matches!(c, '\u{200B}'..='\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}' | '\u{FEFF}')
+}
+
+/// Strip unsafe characters, collapse whitespace, and cap the length at `max_chars`,
+/// marking truncation with an ellipsis
+pub fn sanitize_field(field: &str, max_chars: usize) -> String {
+    let cleaned: String = field
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .filter(|c| !is_unsafe_char(*c))
+        .collect();
+    let collapsed = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if collapsed.chars().count() <= max_chars {
+        return collapsed;
+    }
+
+    let truncated: String = collapsed
+        .chars()
+        .take(max_chars.saturating_sub(1))
+        .collect();
+    format!("{}…", truncated.trim_end())
+}
+
+/// Truncate on a line boundary so lists aren't cut mid-entry
+pub fn truncate_message(content: &str) -> String {
+    if content.chars().count() <= MAX_MESSAGE_LEN {
+        return content.to_string();
+    }
+
+    const ELLIPSIS: &str = "\n…";
+    let budget = MAX_MESSAGE_LEN - ELLIPSIS.chars().count();
+    let mut output = String::new();
+    for line in content.lines() {
+        if output.chars().count() + line.chars().count() + 1 > budget {
+            break;
+        }
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(line);
+    }
+    output.push_str(ELLIPSIS);
+    output
+}
+
+/// Render one line from a template with `{callsign}`, `{emoji}`, `{name}`, `{suffix}`,
+/// `{grid}`, `{state}`, `{country}`, `{class}`, `{dmr_id}`, `{dxcc}`, `{flag}`,
+/// `{continent}`, `{aprs_last_heard}`, and `{lotw}` placeholders. Brackets left
+/// empty by a missing value are removed, so "{name} ({state})" renders as just the name
+/// without a state.
+pub fn render_template(template: &str, entry: &OutputEntry) -> String {
+    let optional = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(|v| sanitize_field(v, MAX_SUFFIX_CHARS))
+    };
+
+    let line = fill_dropping_empty_brackets(template, |placeholder| match placeholder {
+        "callsign" => Some(sanitize_field(&entry.callsign, MAX_NAME_CHARS)),
+        "emoji" => Some(sanitize_field(&entry.emoji_separator, MAX_SUFFIX_CHARS)),
+        "name" => Some(sanitize_field(&entry.name, MAX_NAME_CHARS)),
+        "suffix" => Some(sanitize_field(&entry.suffix, MAX_SUFFIX_CHARS)),
+        "grid" => Some(optional(&entry.grid).unwrap_or_default()),
+        "state" => Some(optional(&entry.state).unwrap_or_default()),
+        "country" => Some(optional(&entry.country).unwrap_or_default()),
+        "class" => Some(optional(&entry.class).unwrap_or_default()),
+        "dmr_id" => Some(entry.dmr_id.map(|id| id.to_string()).unwrap_or_default()),
+        "dxcc" => Some(optional(&entry.dxcc).unwrap_or_default()),
+        "flag" => Some(optional(&entry.flag).unwrap_or_default()),
+        "continent" => Some(optional(&entry.continent).unwrap_or_default()),
+        "aprs_last_heard" => Some(
+            entry
+                .aprs_last_heard
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string())
+                .unwrap_or_default(),
+        ),
+        "lotw" => Some(
+            entry
+                .lotw_last_upload
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+        ),
+        _ => None,
+    });
+
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `fill_placeholders`, also dropping any `(...)` or `[...]` group in the template that
+/// holds placeholders and renders blank. Brackets inside values are left alone.
+fn fill_dropping_empty_brackets(template: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['(', '[']) {
+        filled.push_str(&fill_placeholders(&rest[..start], &value));
+        let group = &rest[start..];
+        let close = if group.starts_with('(') { ')' } else { ']' };
+        let Some(end) = group.find(close) else {
+            filled.push_str(&group[..1]);
+            rest = &group[1..];
+            continue;
+        };
+        let inner = &group[1..end];
+        let inner_filled = fill_placeholders(inner, &value);
+        if !(inner.contains('{') && inner_filled.trim().is_empty()) {
+            filled.push_str(&group[..1]);
+            filled.push_str(&inner_filled);
+            filled.push(close);
+        }
+        rest = &group[end + 1..];
+    }
+    filled.push_str(&fill_placeholders(rest, &value));
+    filled
+}
+
+fn title_line(title: &str) -> String {
+    format!("# TITLE: {}\n", sanitize_field(title, MAX_NAME_CHARS))
 }
 
-pub fn generate_output_content(entries: Vec<OutputEntry>, title: Option<&str>) -> String {
+pub fn generate_output_content(
+    entries: Vec<OutputEntry>,
+    title: Option<&str>,
+    template: Option<&str>,
+) -> String {
     let mut output = String::new();
 
     // Write title header if configured
     if let Some(title_text) = title {
-        output.push_str(&format!("# TITLE: {}\n", title_text));
+        output.push_str(&title_line(title_text));
     }
 
-    // Sort entries by callsign for consistent output
-    let mut sorted_entries = entries;
-    sorted_entries.sort_by(|a, b| a.callsign.cmp(&b.callsign));
+    // Entries are written in the order given; RosterBuilder sorts them per `sort_by`
+    for entry in entries {
+        if let Some(template) = template {
+            output.push_str(&render_template(template, &entry));
+            output.push('\n');
+            continue;
+        }
 
-    for entry in sorted_entries {
         output.push_str(&format!(
             "{} {} {} {}\n",
-            entry.callsign, entry.emoji_separator, entry.name, entry.suffix
+            sanitize_field(&entry.callsign, MAX_NAME_CHARS),
+            sanitize_field(&entry.emoji_separator, MAX_SUFFIX_CHARS),
+            sanitize_field(&entry.name, MAX_NAME_CHARS),
+            sanitize_field(&entry.suffix, MAX_SUFFIX_CHARS)
+        ));
+    }
+
+    output
+}
+
+/// Header of the digital contact list CSV that AnyTone-style DMR CPS imports
+const CONTACT_LIST_HEADER: &str =
+    "No.,Radio ID,Callsign,Name,City,State,Country,Remarks,Call Type,Call Alert\n";
+
+/// Render a DMR digital contact list, one private-call contact per member with a DMR ID.
+/// Members without one can't be dialed, so they're left out.
+pub fn generate_csv(entries: &[OutputEntry]) -> String {
+    let mut output = String::from(CONTACT_LIST_HEADER);
+    let contacts = entries
+        .iter()
+        .filter_map(|entry| entry.dmr_id.map(|id| (id, entry)));
+    for (number, (dmr_id, entry)) in contacts.enumerate() {
+        let optional = |value: &Option<String>| {
+            csv_escape(&sanitize_field(
+                value.as_deref().unwrap_or_default(),
+                MAX_SUFFIX_CHARS,
+            ))
+        };
+        output.push_str(&format!(
+            "{},{},{},{},,{},{},,Private Call,None\n",
+            number + 1,
+            dmr_id,
+            csv_escape(&sanitize_field(&entry.callsign, MAX_NAME_CHARS)),
+            csv_escape(&sanitize_field(&entry.name, MAX_NAME_CHARS)),
+            optional(&entry.state),
+            optional(&entry.country),
+        ));
+    }
+    output
+}
+
+/// Render a minimal ADIF file with one record per member (CALL, NAME, GRIDSQUARE)
+pub fn generate_adif(entries: &[OutputEntry], title: Option<&str>) -> String {
+    // The header must not start with '<', and a '<' anywhere in it could be read as
+    // the start of a field, so the title goes in without any
+    let title = title
+        .map(|t| sanitize_field(&t.replace('<', ""), MAX_NAME_CHARS))
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| "Member roster".to_string());
+    let mut output = format!(
+        "{}\n{}{}<EOH>\n",
+        title,
+        adif_field("ADIF_VER", "3.1.4"),
+        adif_field("PROGRAMID", "discord-callsign-bot"),
+    );
+    for entry in entries {
+        output.push_str(&adif_field("CALL", &entry.callsign));
+        output.push_str(&adif_field("NAME", &entry.name));
+        if let Some(grid) = &entry.grid {
+            output.push_str(&adif_field("GRIDSQUARE", grid));
+        }
+        output.push_str("<EOR>\n");
+    }
+    output
+}
+
+/// Render a GeoJSON FeatureCollection with a Point per member who has a location:
+/// QRZ's coordinates rounded to about a kilometer, or else the center of their grid
+/// square. Members without either are left out.
+pub fn generate_geojson(entries: &[OutputEntry], title: Option<&str>) -> String {
+    let round = |degrees: f64| (degrees * 100.0).round() / 100.0;
+    let features: Vec<_> = entries
+        .iter()
+        .filter_map(|entry| {
+            let (lat, lon, source) = match (entry.coordinates, entry.grid.as_deref()) {
+                (Some((lat, lon)), _) => (round(lat), round(lon), "qrz"),
+                (None, Some(grid)) => {
+                    let (lat, lon) = geo::grid_center(grid)?;
+                    (lat, lon, "grid")
+                }
+                (None, None) => return None,
+            };
+            Some(serde_json::json!({
+                "type": "Feature",
+                // GeoJSON positions are longitude first
+                "geometry": {"type": "Point", "coordinates": [lon, lat]},
+                "properties": {
+                    "callsign": sanitize_field(&entry.callsign, MAX_NAME_CHARS),
+                    "name": sanitize_field(&entry.name, MAX_NAME_CHARS),
+                    "grid": entry.grid,
+                    "location_source": source,
+                },
+            }))
+        })
+        .collect();
+
+    let mut collection = serde_json::json!({
+        "type": "FeatureCollection",
+        "features": features,
+    });
+    if let Some(title) = title {
+        collection["name"] = sanitize_field(title, MAX_NAME_CHARS).into();
+    }
+    format!(
+        "{}\n",
+        serde_json::to_string_pretty(&collection).expect("GeoJSON serializes")
+    )
+}
+
+/// One `<NAME:LEN>value ` field. ADIF strings are ASCII, so other characters are
+/// dropped; a field left empty is omitted.
+pub fn adif_field(name: &str, value: &str) -> String {
+    let value: String = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic() || *c == ' ')
+        .collect();
+    let value = value.trim();
+    if value.is_empty() {
+        return String::new();
+    }
+    format!("<{}:{}>{} ", name, value.len(), value)
+}
+
+/// QRZ's license class letters, highest first; sections of other classes follow
+const CLASS_RANK: [&str; 5] = ["E", "A", "G", "T", "N"];
+
+/// Human-readable license class for QRZ's class letters
+pub fn class_name(class: &str) -> &str {
+    match class {
+        "E" => "Amateur Extra",
+        "A" => "Advanced",
+        "G" => "General",
+        "T" => "Technician",
+        "N" => "Novice",
+        other => other,
+    }
+}
+
+/// Split entries into titled sections, keeping the given order within each. Sections
+/// are alphabetical, except classes (highest first); entries without a value come last.
+pub fn group_entries(
+    entries: Vec<OutputEntry>,
+    group_by: GroupBy,
+) -> Vec<(String, Vec<OutputEntry>)> {
+    let mut sections: BTreeMap<(usize, String), Vec<OutputEntry>> = BTreeMap::new();
+    for entry in entries {
+        let key = match group_by {
+            GroupBy::Suffix => match entry.suffix.trim() {
+                "" => (1, "No suffix".to_string()),
+                suffix => (0, sanitize_field(suffix, MAX_SUFFIX_CHARS)),
+            },
+            GroupBy::Class => match entry.class.as_deref().map(str::trim) {
+                None | Some("") => (CLASS_RANK.len() + 1, "Unknown class".to_string()),
+                Some(class) => (
+                    CLASS_RANK
+                        .iter()
+                        .position(|c| c.eq_ignore_ascii_case(class))
+                        .unwrap_or(CLASS_RANK.len()),
+                    class_name(&class.to_uppercase()).to_string(),
+                ),
+            },
+            GroupBy::FirstLetter => match entry.callsign.chars().next() {
+                Some(c) => (0, c.to_uppercase().to_string()),
+                None => (1, "Other".to_string()),
+            },
+        };
+        sections.entry(key).or_default().push(entry);
+    }
+    sections
+        .into_iter()
+        .map(|((_, label), entries)| (label, entries))
+        .collect()
+}
+
+/// Render each section as a `# <SECTION> (<count>)` line followed by its entries
+pub fn generate_grouped_content(
+    entries: Vec<OutputEntry>,
+    group_by: GroupBy,
+    template: Option<&str>,
+) -> String {
+    group_entries(entries, group_by)
+        .into_iter()
+        .map(|(label, entries)| {
+            format!("# {} ({})\n", label.to_uppercase(), entries.len())
+                + &generate_output_content(entries, None, template)
+        })
+        .collect()
+}
+
+/// Page used by the html format when `html_template` isn't set
+const DEFAULT_HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body { font-family: system-ui, sans-serif; max-width: 48rem; margin: 2rem auto; padding: 0 1rem; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: 0.3rem 0.6rem; border-bottom: 1px solid #ddd; }
+tr.section th { padding-top: 1rem; background: #f4f4f4; }
+td.callsign { font-family: ui-monospace, monospace; }
+footer { color: #666; font-size: 0.9rem; margin-top: 1rem; }
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<table>
+<thead><tr><th>Callsign</th><th>Name</th><th>Suffix</th></tr></thead>
+<tbody>
+{rows}
+</tbody>
+</table>
+<footer>{count} operators &middot; updated {generated_at}</footer>
+</body>
+</html>
+"#;
+
+/// Replace `{placeholder}`s in one pass, so values containing braces are never expanded.
+/// Unknown placeholders are left as written.
+pub fn fill_placeholders(template: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let replacement = rest
+            .find('}')
+            .and_then(|end| value(&rest[1..end]).map(|v| (v, end)));
+        match replacement {
+            Some((v, end)) => {
+                filled.push_str(&v);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// Table rows for the html format, with a heading row per section when grouped
+fn html_rows(entries: Vec<OutputEntry>, group_by: Option<GroupBy>) -> String {
+    let row = |entry: &OutputEntry| {
+        let callsign = escape(&sanitize_field(&entry.callsign, MAX_NAME_CHARS));
+        format!(
+            "<tr><td class=\"callsign\"><a href=\"https://www.qrz.com/db/{}\">{}</a></td><td>{}</td><td>{}</td></tr>\n",
+            callsign,
+            callsign,
+            escape(&sanitize_field(&entry.name, MAX_NAME_CHARS)),
+            escape(&sanitize_field(&entry.suffix, MAX_SUFFIX_CHARS)),
+        )
+    };
+    let Some(group_by) = group_by else {
+        return entries.iter().map(row).collect();
+    };
+    group_entries(entries, group_by)
+        .into_iter()
+        .map(|(label, entries)| {
+            format!(
+                "<tr class=\"section\"><th colspan=\"3\">{} ({})</th></tr>\n",
+                escape(&label),
+                entries.len()
+            ) + &entries.iter().map(row).collect::<String>()
+        })
+        .collect()
+}
+
+/// Render the html format: `html_template` (or the built-in page) with `{title}`
+/// (defaulting to the guild name), `{count}`, `{guild_name}`, `{generated_at}`, and `{rows}`
+pub fn generate_html(
+    entries: Vec<OutputEntry>,
+    config: &OutputConfig,
+    context: &OutputContext,
+) -> String {
+    let custom = config.html_template.as_deref().and_then(|path| {
+        read_template(path)
+            .inspect_err(|e| warn!("{}; using the default", e))
+            .ok()
+    });
+    let template = custom.as_deref().unwrap_or(DEFAULT_HTML_TEMPLATE);
+
+    let count = entries.len();
+    let rows = html_rows(entries, config.group_by);
+    fill_placeholders(template, |key| {
+        let value = match key {
+            "title" => config.title.as_deref().unwrap_or(&context.guild_name),
+            "guild_name" => &context.guild_name,
+            "count" => return Some(count.to_string()),
+            "rows" => return Some(rows.clone()),
+            "generated_at" => {
+                return Some(
+                    context
+                        .generated_at
+                        .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string())
+                        .unwrap_or_default(),
+                )
+            }
+            _ => return None,
+        };
+        Some(escape(&sanitize_field(value, MAX_NAME_CHARS)))
+    })
+}
+
+fn read_template(path: &str) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|source| Error::Io {
+        context: format!("Failed to read html_template {}", path),
+        source,
+    })
+}
+
+/// Write to a sibling temp file and rename it over the target, so a crash
+/// mid-write never leaves a truncated file behind
+pub fn write_atomically(path: &Path, contents: &str) -> std::io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path).inspect_err(|_| {
+        if let Err(e) = std::fs::remove_file(&tmp) {
+            warn!("Failed to remove {}: {}", tmp.display(), e);
+        }
+    })
+}
+
+/// Values for the `{guild_name}` and `{generated_at}` placeholders of `header` and `footer`
+#[derive(Debug, Default)]
+pub struct OutputContext {
+    pub guild_name: String,
+    /// Left empty when rendering for the fingerprint, so a new timestamp alone
+    /// never counts as a change
+    pub generated_at: Option<DateTime<Utc>>,
+}
+
+/// Render a `header` or `footer` template with `{title}`, `{count}`, `{guild_name}`, and
+/// `{generated_at}` placeholders. Lines keep their layout; the result ends with a newline.
+pub fn render_frame(
+    template: &str,
+    config: &OutputConfig,
+    count: usize,
+    context: &OutputContext,
+) -> String {
+    let rendered = fill_placeholders(template, |placeholder| match placeholder {
+        "title" => Some(sanitize_field(
+            config.title.as_deref().unwrap_or_default(),
+            MAX_NAME_CHARS,
+        )),
+        "count" => Some(count.to_string()),
+        "guild_name" => Some(sanitize_field(&context.guild_name, MAX_NAME_CHARS)),
+        "generated_at" => Some(
+            context
+                .generated_at
+                .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string())
+                .unwrap_or_default(),
+        ),
+        _ => None,
+    });
+    format!("{}\n", rendered.trim_end_matches('\n'))
+}
+
+/// Render the file committed for a guild, including the header, alumni section,
+/// supporters line, and footer if enabled
+pub fn generate_guild_output(
+    entries: Vec<OutputEntry>,
+    alumni: Vec<OutputEntry>,
+    config: &OutputConfig,
+    context: &OutputContext,
+) -> String {
+    // Contact lists only hold current members
+    match config.format {
+        OutputFormat::Text => {}
+        OutputFormat::ChirpCsv => return generate_csv(&entries),
+        OutputFormat::Adif => return generate_adif(&entries, config.title.as_deref()),
+        OutputFormat::Html => return generate_html(entries, config, context),
+        OutputFormat::Geojson => return generate_geojson(&entries, config.title.as_deref()),
+    }
+
+    let supporters = if config.supporters_section {
+        generate_supporters_section(&entries)
+    } else {
+        String::new()
+    };
+    let summary = if config.include_summary {
+        generate_summary_section(&entries)
+    } else {
+        String::new()
+    };
+
+    let alumni = if config.alumni_section && !alumni.is_empty() {
+        format!(
+            "# ALUMNI\n{}",
+            generate_output_content(alumni, None, config.template.as_deref())
+        )
+    } else {
+        String::new()
+    };
+
+    let frame = |template: &Option<String>| {
+        template
+            .as_deref()
+            .map(|t| render_frame(t, config, entries.len(), context))
+            .unwrap_or_default()
+    };
+    let (header, footer) = (frame(&config.header), frame(&config.footer));
+
+    let body = match config.group_by {
+        Some(group_by) => generate_grouped_content(entries, group_by, config.template.as_deref()),
+        None => generate_output_content(entries, None, config.template.as_deref()),
+    };
+
+    // The header goes after the "# TITLE:" line, which stays first for existing readers
+    config.title.as_deref().map(title_line).unwrap_or_default()
+        + &header
+        + &body
+        + &alumni
+        + &supporters
+        + &summary
+        + &footer
+}
+
+/// Hash of everything a regeneration publishes, to skip sinks when nothing changed
+pub fn fingerprint(content: &str, entries: &[OutputEntry]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    // Entries carry fields the rendered file may omit, which the webhook still sends
+    serde_json::to_string(entries)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Render a "# SUPPORTERS:" line listing boosters by callsign, or nothing if there are none
+pub fn generate_supporters_section(entries: &[OutputEntry]) -> String {
+    let mut supporters: Vec<&str> = entries
+        .iter()
+        .filter(|e| e.booster)
+        .map(|e| e.callsign.as_str())
+        .collect();
+
+    if supporters.is_empty() {
+        return String::new();
+    }
+
+    supporters.sort();
+    format!("# SUPPORTERS: {}\n", supporters.join(", "))
+}
+
+/// "# US CALL DISTRICTS:" and "# PREFIXES:" count lines, for `include_summary`
+pub fn generate_summary_section(entries: &[OutputEntry]) -> String {
+    if entries.is_empty() {
+        return String::new();
+    }
+    let join = |counts: Vec<(String, usize)>| {
+        counts
+            .iter()
+            .map(|(label, n)| format!("{} {}", label, n))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut districts = stats::count(
+        entries
+            .iter()
+            .filter(|e| stats::is_us(&e.callsign))
+            .filter_map(|e| stats::district(&e.callsign))
+            .map(|digit| digit.to_string()),
+    );
+    // 0 through 9 reads better than by count
+    districts.sort();
+    let prefixes = stats::count(
+        entries
+            .iter()
+            .filter_map(|e| stats::prefix(&e.callsign))
+            .map(str::to_string),
+    );
+
+    let mut section = String::new();
+    if !districts.is_empty() {
+        section.push_str(&format!("# US CALL DISTRICTS: {}\n", join(districts)));
+    }
+    section.push_str(&format!("# PREFIXES: {}\n", join(prefixes)));
+    section
+}
+
+/// Render entries as CSV with a header row
+pub fn generate_csv_content(entries: &[OutputEntry]) -> String {
+    let mut output = String::from("callsign,name,suffix\n");
+
+    for entry in entries {
+        output.push_str(&format!(
+            "{},{},{}\n",
+            csv_escape(&entry.callsign),
+            csv_escape(&entry.name),
+            csv_escape(&entry.suffix)
         ));
     }
 
     output
 }
 
+/// Quote a CSV field if it contains a delimiter, quote, or newline. A leading `=`, `+`,
+/// `-`, or `@` is prefixed with a quote so spreadsheets don't evaluate names as formulas.
+pub fn csv_escape(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", field)
+    } else {
+        field.to_string()
+    };
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+/// Split one CSV line, honoring double-quoted fields with `""` escapes
+pub fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Line-level diff between two renderings: removed lines prefixed "- ", added lines "+ "
+pub fn diff_lines(old: &str, new: &str) -> Vec<String> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    let removed = old_lines
+        .iter()
+        .filter(|line| !new_lines.contains(line))
+        .map(|line| format!("- {}", line));
+    let added = new_lines
+        .iter()
+        .filter(|line| !old_lines.contains(line))
+        .map(|line| format!("+ {}", line));
+
+    removed.chain(added).collect()
+}
+
+/// Sort entries by the configured key, breaking ties by callsign. The order only applies
+/// to the key: unknown join dates stay last and ties stay in callsign order.
+pub fn sort_entries(entries: &mut [OutputEntry], sort_by: SortKey, order: SortOrder) {
+    let directed = |ordering: Ordering| match order {
+        SortOrder::Ascending => ordering,
+        SortOrder::Descending => ordering.reverse(),
+    };
+    entries.sort_by(|a, b| {
+        match sort_by {
+            SortKey::Callsign => directed(a.callsign.cmp(&b.callsign)),
+            SortKey::Name => directed(a.name.to_lowercase().cmp(&b.name.to_lowercase())),
+            SortKey::SuffixThenCallsign => directed(a.suffix.cmp(&b.suffix)),
+            // Members with an unknown join date sort after everyone else
+            SortKey::JoinDate => match (a.joined_at, b.joined_at) {
+                (Some(a), Some(b)) => directed(a.cmp(&b)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            SortKey::CallsignSuffix => {
+                directed(callsign_suffix(&a.callsign).cmp(callsign_suffix(&b.callsign)))
+            }
+        }
+        .then_with(|| a.callsign.cmp(&b.callsign))
+    });
+}
+
+/// The letters after a callsign's last digit ("JSV" for W6JSV), ignoring any
+/// "/P"-style portable designator, as used to order net rosters
+fn callsign_suffix(callsign: &str) -> &str {
+    let base = callsign.split('/').next().unwrap_or(callsign);
+    match base.rfind(|c: char| c.is_ascii_digit()) {
+        Some(i) => &base[i + 1..],
+        None => base,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -37,11 +803,11 @@ mod tests {
         let entries = vec![OutputEntry {
             callsign: "W6JSV".to_string(),
             name: "Jay".to_string(),
-            suffix: "".to_string(),
             emoji_separator: "📻".to_string(),
+            ..Default::default()
         }];
 
-        let result = generate_output_content(entries, None);
+        let result = generate_output_content(entries, None, None);
         assert_eq!(result, "W6JSV 📻 Jay \n");
     }
 
@@ -50,34 +816,639 @@ mod tests {
         let entries = vec![OutputEntry {
             callsign: "W6JSV".to_string(),
             name: "Jay".to_string(),
-            suffix: "".to_string(),
             emoji_separator: "📻".to_string(),
+            ..Default::default()
         }];
 
-        let result = generate_output_content(entries, Some("Test Title"));
+        let result = generate_output_content(entries, Some("Test Title"), None);
         assert!(result.starts_with("# TITLE: Test Title\n"));
     }
 
     #[test]
-    fn test_generate_output_content_sorts_by_callsign() {
+    fn test_sort_entries() {
+        let entry = |callsign: &str, name: &str, joined_at: Option<i64>| OutputEntry {
+            callsign: callsign.to_string(),
+            name: name.to_string(),
+            emoji_separator: "📻".to_string(),
+            joined_at,
+            ..Default::default()
+        };
+        let mut entries = vec![
+            entry("KI7QCF", "forrest", Some(200)),
+            entry("W6JSV", "Jay", None),
+            entry("AA1AA", "Zed", Some(100)),
+        ];
+        let callsigns = |entries: &[OutputEntry]| {
+            entries
+                .iter()
+                .map(|e| e.callsign.clone())
+                .collect::<Vec<_>>()
+        };
+
+        sort_entries(&mut entries, SortKey::Callsign, SortOrder::Ascending);
+        assert_eq!(callsigns(&entries), ["AA1AA", "KI7QCF", "W6JSV"]);
+
+        sort_entries(&mut entries, SortKey::Callsign, SortOrder::Descending);
+        assert_eq!(callsigns(&entries), ["W6JSV", "KI7QCF", "AA1AA"]);
+
+        sort_entries(&mut entries, SortKey::Name, SortOrder::Ascending);
+        assert_eq!(callsigns(&entries), ["KI7QCF", "W6JSV", "AA1AA"]);
+
+        sort_entries(&mut entries, SortKey::JoinDate, SortOrder::Ascending);
+        assert_eq!(callsigns(&entries), ["AA1AA", "KI7QCF", "W6JSV"]);
+
+        sort_entries(&mut entries, SortKey::CallsignSuffix, SortOrder::Ascending);
+        assert_eq!(callsigns(&entries), ["AA1AA", "W6JSV", "KI7QCF"]);
+
+        // Descending only flips the key: the unknown join date stays last and
+        // members who joined together stay in callsign order
+        entries.push(entry("K1ABC", "Al", Some(200)));
+        sort_entries(&mut entries, SortKey::JoinDate, SortOrder::Descending);
+        assert_eq!(callsigns(&entries), ["K1ABC", "KI7QCF", "AA1AA", "W6JSV"]);
+    }
+
+    #[test]
+    fn test_callsign_suffix() {
+        assert_eq!(callsign_suffix("W6JSV"), "JSV");
+        assert_eq!(callsign_suffix("KI7QCF/P"), "QCF");
+        assert_eq!(callsign_suffix("3DA0XYZ"), "XYZ");
+    }
+
+    #[test]
+    fn test_sanitize_field() {
+        assert_eq!(sanitize_field("Jay\nW1AW 📻 Evil", 64), "Jay W1AW 📻 Evil");
+        assert_eq!(sanitize_field("  \u{202E}Jay\u{200B}  ", 64), "Jay");
+        assert_eq!(sanitize_field("\u{0007}\t", 64), "");
+        assert_eq!(sanitize_field("🎉🎉🎉", 64), "🎉🎉🎉");
+
+        let long = "a".repeat(500);
+        let sanitized = sanitize_field(&long, MAX_NAME_CHARS);
+        assert_eq!(sanitized.chars().count(), MAX_NAME_CHARS);
+        assert!(sanitized.ends_with('…'));
+    }
+
+    #[test]
+    fn test_generate_output_content_keeps_one_line_per_entry() {
+        let entries = vec![OutputEntry {
+            callsign: "W6JSV".to_string(),
+            name: "Jay\nW1AW 📻 Fake".to_string(),
+            suffix: "\r\n".to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        }];
+
+        let result = generate_output_content(entries, None, None);
+        assert_eq!(result.lines().count(), 1);
+    }
+
+    #[test]
+    fn test_render_template() {
+        let mut entry = OutputEntry {
+            callsign: "W6JSV".to_string(),
+            name: "Jay".to_string(),
+            emoji_separator: "📻".to_string(),
+            grid: Some("CM87".to_string()),
+            state: Some("CA".to_string()),
+            country: Some("United States".to_string()),
+            class: Some("E".to_string()),
+            dmr_id: Some(3106001),
+            ..Default::default()
+        };
+        let template = "{callsign} {emoji} {name} {grid} ({state}) {suffix}";
+
+        assert_eq!(render_template(template, &entry), "W6JSV 📻 Jay CM87 (CA)");
+        assert_eq!(
+            render_template("{callsign} [{class}] {name}", &entry),
+            "W6JSV [E] Jay"
+        );
+        assert_eq!(
+            render_template("{callsign},{dmr_id},{name}", &entry),
+            "W6JSV,3106001,Jay"
+        );
+        assert_eq!(
+            render_template("{flag} {callsign} ({dxcc})", &entry),
+            "W6JSV"
+        );
+        entry.dxcc = Some("United States".to_string());
+        entry.flag = Some("🇺🇸".to_string());
+        entry.continent = Some("NA".to_string());
+        assert_eq!(
+            render_template("{flag} {callsign} ({dxcc}, {continent})", &entry),
+            "🇺🇸 W6JSV (United States, NA)"
+        );
+        assert_eq!(
+            render_template("{callsign} ({aprs_last_heard})", &entry),
+            "W6JSV"
+        );
+        entry.aprs_last_heard = Some(1_760_000_000);
+        assert_eq!(
+            render_template("{callsign} (heard {aprs_last_heard})", &entry),
+            "W6JSV (heard 2025-10-09 08:53 UTC)"
+        );
+        assert_eq!(render_template("{callsign} ({lotw})", &entry), "W6JSV");
+        entry.lotw_last_upload = NaiveDate::from_ymd_opt(2026, 10, 1);
+        assert_eq!(
+            render_template("{callsign} ({lotw})", &entry),
+            "W6JSV (2026-10-01)"
+        );
+
+        entry.grid = None;
+        entry.state = None;
+        assert_eq!(render_template(template, &entry), "W6JSV 📻 Jay");
+    }
+
+    #[test]
+    fn test_render_template_leaves_values_alone() {
+        let entry = OutputEntry {
+            callsign: "W6JSV".to_string(),
+            name: "Jay () {suffix}".to_string(),
+            suffix: "[]".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_template("{callsign} {name} ({state}) {suffix}", &entry),
+            "W6JSV Jay () {suffix} []"
+        );
+        assert_eq!(
+            render_template("{callsign} (QRT) [", &entry),
+            "W6JSV (QRT) ["
+        );
+    }
+
+    #[test]
+    fn test_generate_guild_output_alumni_section() {
+        let entry = |callsign: &str, suffix: &str| OutputEntry {
+            callsign: callsign.to_string(),
+            name: "Op".to_string(),
+            suffix: suffix.to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        };
+        let mut config: OutputConfig = toml::from_str(
+            r#"
+            repo = "jsvana/rosters"
+            path = "members.txt"
+            default_suffix = ""
+            alumni_section = true
+            "#,
+        )
+        .unwrap();
+
+        let output = generate_guild_output(
+            vec![entry("W6JSV", "")],
+            vec![entry("W1AW", "SK")],
+            &config,
+            &OutputContext::default(),
+        );
+        assert_eq!(output, "W6JSV 📻 Op \n# ALUMNI\nW1AW 📻 Op SK\n");
+
+        config.alumni_section = false;
+        let output = generate_guild_output(
+            vec![],
+            vec![entry("W1AW", "SK")],
+            &config,
+            &OutputContext::default(),
+        );
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn test_summary_section() {
+        let entry = |callsign: &str| OutputEntry {
+            callsign: callsign.to_string(),
+            name: "Op".to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        };
+        let mut config: OutputConfig = toml::from_str(
+            r#"
+            repo = "jsvana/rosters"
+            path = "members.txt"
+            default_suffix = ""
+            include_summary = true
+            footer = "73"
+            "#,
+        )
+        .unwrap();
+
+        let entries = vec![
+            entry("W6JSV"),
+            entry("KI7QCF"),
+            entry("N7XYZ"),
+            entry("VE3ABC"),
+        ];
+        let output =
+            generate_guild_output(entries.clone(), vec![], &config, &OutputContext::default());
+        assert!(output
+            .ends_with("# US CALL DISTRICTS: 6 1, 7 2\n# PREFIXES: KI 1, N 1, VE 1, W 1\n73\n"));
+
+        assert_eq!(
+            generate_summary_section(&[entry("VE3ABC")]),
+            "# PREFIXES: VE 1\n"
+        );
+        assert_eq!(generate_summary_section(&[]), "");
+
+        config.include_summary = false;
+        let output = generate_guild_output(entries, vec![], &config, &OutputContext::default());
+        assert!(!output.contains("# PREFIXES"));
+    }
+
+    #[test]
+    fn test_header_and_footer() {
+        let config: OutputConfig = toml::from_str(
+            r##"
+            repo = "jsvana/rosters"
+            path = "members.txt"
+            default_suffix = ""
+            title = "Club"
+            header = "# {guild_name}: {count} members\n# Updated {generated_at}"
+            footer = "# End of {title}\n"
+            "##,
+        )
+        .unwrap();
+        let entry = OutputEntry {
+            callsign: "W6JSV".to_string(),
+            name: "Jay".to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        };
+        let context = OutputContext {
+            guild_name: "Ham\nShack".to_string(),
+            generated_at: DateTime::from_timestamp(1_760_000_000, 0),
+        };
+
+        let output = generate_guild_output(vec![entry.clone()], vec![], &config, &context);
+        assert_eq!(
+            output,
+            "# TITLE: Club\n# Ham Shack: 1 members\n# Updated 2025-10-09 08:53 UTC\n\
+             W6JSV 📻 Jay \n# End of Club\n"
+        );
+
+        let stable = OutputContext {
+            generated_at: None,
+            ..context
+        };
+        let output = generate_guild_output(vec![entry], vec![], &config, &stable);
+        assert!(output.contains("# Updated \n"));
+
+        // Placeholders inside values aren't expanded
+        let braces = OutputContext {
+            guild_name: "{count} {title}".to_string(),
+            generated_at: None,
+        };
+        let frame = render_frame("# {guild_name}", &config, 1, &braces);
+        assert_eq!(frame, "# {count} {title}\n");
+    }
+
+    #[test]
+    fn test_group_entries() {
+        let entry = |callsign: &str, suffix: &str, class: Option<&str>| OutputEntry {
+            callsign: callsign.to_string(),
+            name: "Op".to_string(),
+            suffix: suffix.to_string(),
+            emoji_separator: "📻".to_string(),
+            class: class.map(str::to_string),
+            ..Default::default()
+        };
+        let entries = vec![
+            entry("AA1A", "", Some("T")),
+            entry("KI7QCF", "VE", None),
+            entry("N0CALL", "", Some("X")),
+            entry("W6JSV", "VE", Some("e")),
+        ];
+        let labels = |group_by| -> Vec<(String, usize)> {
+            group_entries(entries.clone(), group_by)
+                .into_iter()
+                .map(|(label, entries)| (label, entries.len()))
+                .collect()
+        };
+
+        assert_eq!(
+            labels(GroupBy::Class),
+            [
+                ("Amateur Extra".to_string(), 1),
+                ("Technician".to_string(), 1),
+                ("X".to_string(), 1),
+                ("Unknown class".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            labels(GroupBy::Suffix),
+            [("VE".to_string(), 2), ("No suffix".to_string(), 2)]
+        );
+        assert_eq!(
+            labels(GroupBy::FirstLetter),
+            [
+                ("A".to_string(), 1),
+                ("K".to_string(), 1),
+                ("N".to_string(), 1),
+                ("W".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grouped_output() {
+        let mut config: OutputConfig = toml::from_str(
+            r#"
+            repo = "jsvana/rosters"
+            path = "members.txt"
+            default_suffix = ""
+            title = "Club"
+            group_by = "suffix"
+            "#,
+        )
+        .unwrap();
+        let entry = |callsign: &str, suffix: &str| OutputEntry {
+            callsign: callsign.to_string(),
+            name: "Op".to_string(),
+            suffix: suffix.to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        };
+        let entries = vec![
+            entry("KI7QCF", ""),
+            entry("W1AW", "SK"),
+            entry("W6JSV", "SK"),
+        ];
+
+        let output =
+            generate_guild_output(entries.clone(), vec![], &config, &OutputContext::default());
+        assert_eq!(
+            output,
+            "# TITLE: Club\n# SK (2)\nW1AW 📻 Op SK\nW6JSV 📻 Op SK\n# NO SUFFIX (1)\nKI7QCF 📻 Op \n"
+        );
+
+        config.group_by = None;
+        let output = generate_guild_output(entries, vec![], &config, &OutputContext::default());
+        assert!(!output.contains("# SK"));
+    }
+
+    #[test]
+    fn test_html_format() {
+        let mut config: OutputConfig = toml::from_str(
+            r#"
+            repo = "jsvana/rosters"
+            path = "index.html"
+            default_suffix = ""
+            format = "html"
+            "#,
+        )
+        .unwrap();
+        let entry = |callsign: &str, name: &str| OutputEntry {
+            callsign: callsign.to_string(),
+            name: name.to_string(),
+            suffix: "73".to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        };
         let entries = vec![
+            entry("W6JSV", "Jay"),
+            entry("KI7QCF", "<script>alert(1)</script> {title}"),
+        ];
+        let context = OutputContext {
+            guild_name: "Ham & Eggs".to_string(),
+            generated_at: None,
+        };
+
+        let page = generate_guild_output(entries.clone(), vec![], &config, &context);
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<title>Ham &amp; Eggs</title>"));
+        assert!(page.contains(
+            "<tr><td class=\"callsign\"><a href=\"https://www.qrz.com/db/W6JSV\">W6JSV</a></td><td>Jay</td><td>73</td></tr>"
+        ));
+        assert!(page.contains("<td>&lt;script&gt;alert(1)&lt;/script&gt; {title}</td>"));
+        assert!(page.contains("2 operators"));
+
+        let dir = std::env::temp_dir().join(format!("callsign-bot-html-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("page.html");
+        std::fs::write(&path, "<h1>{title}</h1>{count}{unknown}\n{rows}").unwrap();
+        config.html_template = Some(path.to_str().unwrap().to_string());
+        config.title = Some("Club".to_string());
+        config.group_by = Some(GroupBy::Suffix);
+        let page = generate_guild_output(vec![entry("W6JSV", "Jay")], vec![], &config, &context);
+        assert_eq!(
+            page,
+            "<h1>Club</h1>1{unknown}\n<tr class=\"section\"><th colspan=\"3\">73 (1)</th></tr>\n\
+             <tr><td class=\"callsign\"><a href=\"https://www.qrz.com/db/W6JSV\">W6JSV</a></td><td>Jay</td><td>73</td></tr>\n"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_chirp_csv_format() {
+        let entry = |callsign: &str, name: &str, dmr_id: Option<u32>| OutputEntry {
+            callsign: callsign.to_string(),
+            name: name.to_string(),
+            suffix: "73".to_string(),
+            emoji_separator: "📻".to_string(),
+            dmr_id,
+            ..Default::default()
+        };
+        let config: OutputConfig = toml::from_str(
+            r#"
+            repo = "jsvana/rosters"
+            path = "members.csv"
+            default_suffix = ""
+            title = "Club"
+            format = "chirp-csv"
+            alumni_section = true
+            "#,
+        )
+        .unwrap();
+
+        let mut jay = entry("W6JSV", "Jay", Some(3106001));
+        jay.state = Some("CA".to_string());
+        jay.country = Some("United States".to_string());
+        let output = generate_guild_output(
+            vec![
+                jay,
+                entry("KI7QCF", "Forrest \"F\", Jr", Some(3106002)),
+                entry("AA1AA", "No DMR", None),
+                entry("N0CALL", "=HYPERLINK()", Some(3106003)),
+            ],
+            vec![entry("W1AW", "Hiram", Some(3106004))],
+            &config,
+            &OutputContext::default(),
+        );
+        assert_eq!(
+            output,
+            "No.,Radio ID,Callsign,Name,City,State,Country,Remarks,Call Type,Call Alert\n\
+             1,3106001,W6JSV,Jay,,CA,United States,,Private Call,None\n\
+             2,3106002,KI7QCF,\"Forrest \"\"F\"\", Jr\",,,,,Private Call,None\n\
+             3,3106003,N0CALL,'=HYPERLINK(),,,,,Private Call,None\n"
+        );
+    }
+
+    #[test]
+    fn test_adif_format() {
+        let entry = |callsign: &str, name: &str, grid: Option<&str>| OutputEntry {
+            callsign: callsign.to_string(),
+            name: name.to_string(),
+            emoji_separator: "📻".to_string(),
+            grid: grid.map(str::to_string),
+            ..Default::default()
+        };
+        let config: OutputConfig = toml::from_str(
+            r#"
+            repo = "jsvana/rosters"
+            path = "members.adi"
+            default_suffix = ""
+            title = "Club members"
+            format = "adif"
+            "#,
+        )
+        .unwrap();
+
+        let output = generate_guild_output(
+            vec![
+                entry("W6JSV", "Jay", Some("CM87")),
+                entry("KI7QCF", "José 📻", None),
+                entry("N0CALL", "🎙️", None),
+            ],
+            vec![],
+            &config,
+            &OutputContext::default(),
+        );
+        assert_eq!(
+            output,
+            "Club members\n<ADIF_VER:5>3.1.4 <PROGRAMID:20>discord-callsign-bot <EOH>\n\
+             <CALL:5>W6JSV <NAME:3>Jay <GRIDSQUARE:4>CM87 <EOR>\n\
+             <CALL:6>KI7QCF <NAME:3>Jos <EOR>\n\
+             <CALL:6>N0CALL <EOR>\n"
+        );
+
+        assert!(generate_adif(&[], Some("<EOH>Club")).starts_with("EOH>Club\n"));
+        assert!(generate_adif(&[], Some("<<")).starts_with("Member roster\n"));
+    }
+
+    #[test]
+    fn test_geojson_format() {
+        let entry = |callsign: &str, grid: Option<&str>, coordinates| OutputEntry {
+            callsign: callsign.to_string(),
+            name: "Op".to_string(),
+            emoji_separator: "📻".to_string(),
+            grid: grid.map(str::to_string),
+            coordinates,
+            ..Default::default()
+        };
+        let output = generate_geojson(
+            &[
+                entry("W6JSV", Some("CM87"), Some((37.774_93, -122.419_42))),
+                entry("KI7QCF", Some("CN87"), None),
+                entry("N0CALL", None, None),
+            ],
+            Some("Club members"),
+        );
+        let collection: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(collection["type"], "FeatureCollection");
+        assert_eq!(collection["name"], "Club members");
+
+        let features = collection["features"].as_array().unwrap();
+        assert_eq!(features.len(), 2);
+        assert_eq!(
+            features[0]["geometry"]["coordinates"],
+            serde_json::json!([-122.42, 37.77])
+        );
+        assert_eq!(features[0]["properties"]["location_source"], "qrz");
+        assert_eq!(features[1]["properties"]["callsign"], "KI7QCF");
+        assert_eq!(
+            features[1]["geometry"]["coordinates"],
+            serde_json::json!([-123.0, 47.5])
+        );
+        assert_eq!(features[1]["properties"]["location_source"], "grid");
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let mut entries = vec![OutputEntry {
+            callsign: "W6JSV".to_string(),
+            name: "Jay".to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        }];
+        let before = fingerprint("W6JSV 📻 Jay \n", &entries);
+        assert_eq!(before, fingerprint("W6JSV 📻 Jay \n", &entries));
+        assert_ne!(before, fingerprint("W6JSV 📻 Jay 73\n", &entries));
+
+        // Fields missing from the file still count, since the webhook sends them
+        entries[0].grid = Some("CM87".to_string());
+        assert_ne!(before, fingerprint("W6JSV 📻 Jay \n", &entries));
+    }
+
+    #[test]
+    fn test_generate_supporters_section() {
+        let mut entries = vec![
             OutputEntry {
-                callsign: "KI7QCF".to_string(),
-                name: "Forrest".to_string(),
-                suffix: "".to_string(),
+                callsign: "W6JSV".to_string(),
+                name: "Jay".to_string(),
                 emoji_separator: "📻".to_string(),
+                booster: true,
+                ..Default::default()
             },
             OutputEntry {
-                callsign: "AA1AA".to_string(),
-                name: "Alpha".to_string(),
-                suffix: "".to_string(),
+                callsign: "KI7QCF".to_string(),
+                name: "Forrest".to_string(),
                 emoji_separator: "📻".to_string(),
+                ..Default::default()
             },
         ];
 
-        let result = generate_output_content(entries, None);
-        let lines: Vec<&str> = result.lines().collect();
-        assert!(lines[0].starts_with("AA1AA"));
-        assert!(lines[1].starts_with("KI7QCF"));
+        assert_eq!(
+            generate_supporters_section(&entries),
+            "# SUPPORTERS: W6JSV\n"
+        );
+
+        entries[0].booster = false;
+        assert_eq!(generate_supporters_section(&entries), "");
+    }
+
+    #[test]
+    fn test_generate_csv_content_escapes_fields() {
+        let entries = vec![OutputEntry {
+            callsign: "W6JSV".to_string(),
+            name: "Smith, Jay \"J\"".to_string(),
+            suffix: "73".to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        }];
+
+        let result = generate_csv_content(&entries);
+        assert_eq!(
+            result,
+            "callsign,name,suffix\nW6JSV,\"Smith, Jay \"\"J\"\"\",73\n"
+        );
+        assert_eq!(csv_escape("-1"), "'-1");
+        assert_eq!(csv_escape("@sum, x"), "\"'@sum, x\"");
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let old = "# TITLE: Club\nN0CALL 📻 Nobody \nW6JSV 📻 Jay \n";
+        let new = "# TITLE: Club\nKI7QCF 📻 Forrest \nW6JSV 📻 Jay \n";
+
+        assert_eq!(
+            diff_lines(old, new),
+            vec!["- N0CALL 📻 Nobody ", "+ KI7QCF 📻 Forrest "]
+        );
+        assert!(diff_lines(new, new).is_empty());
+    }
+
+    #[test]
+    fn test_truncate_message_short_unchanged() {
+        assert_eq!(truncate_message("hello\nworld"), "hello\nworld");
+    }
+
+    #[test]
+    fn test_truncate_message_long_cuts_on_line() {
+        let content = (0..500)
+            .map(|i| format!("W{}ABC", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let truncated = truncate_message(&content);
+        assert!(truncated.chars().count() <= MAX_MESSAGE_LEN);
+        assert!(truncated.ends_with("\n…"));
+        assert!(truncated.starts_with("W0ABC\nW1ABC"));
     }
 }
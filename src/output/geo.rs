@@ -0,0 +1,110 @@
+/// Mean Earth radius used for great-circle distances
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+const KM_PER_MILE: f64 = 1.609_344;
+
+const COMPASS_POINTS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW",
+    "NNW",
+];
+
+/// Latitude and longitude in degrees of the center of a 4- or 6-character
+/// Maidenhead grid square, e.g. CM87 or CM87wj
+pub fn grid_center(grid: &str) -> Option<(f64, f64)> {
+    let chars: Vec<char> = grid.trim().to_uppercase().chars().collect();
+    if chars.len() != 4 && chars.len() != 6 {
+        return None;
+    }
+    let letter = |c: char, last: char| {
+        ('A'..=last)
+            .contains(&c)
+            .then(|| (c as u32 - 'A' as u32) as f64)
+    };
+    let digit = |c: char| c.to_digit(10).map(f64::from);
+
+    // Fields are 20°×10°, squares 2°×1°, and subsquares 5'×2.5'
+    let mut lon = -180.0 + letter(chars[0], 'R')? * 20.0 + digit(chars[2])? * 2.0;
+    let mut lat = -90.0 + letter(chars[1], 'R')? * 10.0 + digit(chars[3])?;
+    if chars.len() == 6 {
+        lon += letter(chars[4], 'X')? * 2.0 / 24.0 + 1.0 / 24.0;
+        lat += letter(chars[5], 'X')? / 24.0 + 0.5 / 24.0;
+    } else {
+        lon += 1.0;
+        lat += 0.5;
+    }
+    Some((lat, lon))
+}
+
+/// Great-circle distance in kilometers and initial bearing in degrees (0–360,
+/// clockwise from north) from one grid square to another
+pub fn distance_bearing(from: &str, to: &str) -> Option<(f64, f64)> {
+    let (lat1, lon1) = grid_center(from)?;
+    let (lat2, lon2) = grid_center(to)?;
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let dlon = (lon2 - lon1).to_radians();
+
+    let a =
+        ((lat2 - lat1) / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    let distance = 2.0 * EARTH_RADIUS_KM * a.sqrt().asin();
+
+    let y = dlon.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+    let bearing = (y.atan2(x).to_degrees() + 360.0) % 360.0;
+    Some((distance, bearing))
+}
+
+pub fn km_to_miles(km: f64) -> f64 {
+    km / KM_PER_MILE
+}
+
+/// The nearest of the 16 compass points, e.g. "NNE" for 20°
+pub fn compass_point(bearing: f64) -> &'static str {
+    let index = ((bearing.rem_euclid(360.0) + 11.25) / 22.5) as usize % 16;
+    COMPASS_POINTS[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_center() {
+        assert_eq!(grid_center("CM87"), Some((37.5, -123.0)));
+        let (lat, lon) = grid_center("cm87wj").unwrap();
+        assert!((lat - 37.396).abs() < 0.01, "{}", lat);
+        assert!((lon - -122.125).abs() < 0.01, "{}", lon);
+
+        assert_eq!(grid_center("CM8"), None);
+        assert_eq!(grid_center("SM87"), None);
+        assert_eq!(grid_center("CM87zz"), None);
+        assert_eq!(grid_center("W6JSV"), None);
+    }
+
+    #[test]
+    fn test_distance_bearing() {
+        let (distance, _) = distance_bearing("CM87", "CM87").unwrap();
+        assert!(distance.abs() < 1e-6);
+
+        // The Bay Area to Seattle's square is due north, 10° of latitude
+        let (distance, bearing) = distance_bearing("CM87", "CN87").unwrap();
+        assert!((distance - 1112.0).abs() < 5.0, "{}", distance);
+        assert_eq!(compass_point(bearing), "N");
+
+        // FN31 (Connecticut) is east-northeast of CM87
+        let (distance, bearing) = distance_bearing("CM87", "FN31").unwrap();
+        assert!((4000.0..4300.0).contains(&distance), "{}", distance);
+        assert_eq!(compass_point(bearing), "ENE");
+
+        assert_eq!(distance_bearing("CM87", "nope"), None);
+    }
+
+    #[test]
+    fn test_compass_point() {
+        assert_eq!(compass_point(0.0), "N");
+        assert_eq!(compass_point(359.0), "N");
+        assert_eq!(compass_point(20.0), "NNE");
+        assert_eq!(compass_point(180.0), "S");
+        assert_eq!(compass_point(270.0), "W");
+        assert!((km_to_miles(1.609_344) - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,197 @@
+/// Elements whose contents are never shown as text
+const HIDDEN_ELEMENTS: &[&str] = &["script", "style", "head", "noscript"];
+
+/// Elements that start a new line when rendered as text
+const BLOCK_ELEMENTS: &[&str] = &[
+    "br",
+    "p",
+    "div",
+    "li",
+    "tr",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "table",
+    "ul",
+    "ol",
+    "blockquote",
+];
+
+/// Convert untrusted HTML to plain text: tags and hidden elements are removed,
+/// block elements become line breaks, entities are decoded, and whitespace is
+/// collapsed. Markup is never passed through, so the result is safe to post.
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // Comments can contain '>' so they end at the first "-->"
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        let Some(end) = rest.find('>') else {
+            // Unterminated tag: drop the remainder rather than leak markup
+            rest = "";
+            break;
+        };
+        let tag = tag_name(&rest[1..end]);
+        rest = &rest[end + 1..];
+
+        if HIDDEN_ELEMENTS.contains(&tag.as_str()) {
+            let closing = format!("</{}", tag);
+            rest = find_ignore_case(rest, &closing)
+                .and_then(|pos| rest[pos..].find('>').map(|end| &rest[pos + end + 1..]))
+                .unwrap_or("");
+        } else if BLOCK_ELEMENTS.contains(&tag.as_str()) {
+            text.push('\n');
+        } else if tag == "td" || tag == "th" {
+            text.push(' ');
+        }
+    }
+    text.push_str(rest);
+
+    collapse_whitespace(&decode_entities(&text))
+}
+
+/// Lowercased element name of a tag body like "/p", "br/", or "a href=..."
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('/')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .to_ascii_lowercase()
+        .find(&needle.to_ascii_lowercase())
+}
+
+/// Escape text for use in HTML element content and quoted attribute values
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Decode named entities common in QRZ bios plus numeric character references; also
+/// unescapes XML text such as RSS fields
+pub fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let Some(replacement) = entity.and_then(decode_entity) else {
+            decoded.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+
+        decoded.push(replacement);
+        rest = &rest[entity.map_or(0, str::len) + 2..];
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    let named = match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        "copy" => Some('©'),
+        "mdash" => Some('—'),
+        "ndash" => Some('–'),
+        _ => None,
+    };
+    if named.is_some() {
+        return named;
+    }
+
+    let code = entity.strip_prefix('#')?;
+    let value = match code.strip_prefix(['x', 'X']) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+        None => code.parse().ok()?,
+    };
+    char::from_u32(value).filter(|c| !c.is_control() || *c == '\n')
+}
+
+/// Collapse runs of spaces within lines and drop blank lines
+fn collapse_whitespace(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape(r#"<b>"Jay" & 'co'</b>"#),
+            "&lt;b&gt;&quot;Jay&quot; &amp; &#39;co&#39;&lt;/b&gt;"
+        );
+        assert_eq!(html_to_text(&escape("a < b & c")), "a < b & c");
+    }
+
+    #[test]
+    fn test_html_to_text_strips_tags_and_hidden_elements() {
+        let html = "<html><head><title>x</title></head><body>\
+            <script>alert('hi')</script><style>p{}</style>\
+            <h1>Welcome to W6JSV</h1><p>Licensed in <b>2010</b>.<br>QRV on HF &amp; VHF</p>\
+            <!-- a > b --></body></html>";
+
+        assert_eq!(
+            html_to_text(html),
+            "Welcome to W6JSV\nLicensed in 2010.\nQRV on HF & VHF"
+        );
+    }
+
+    #[test]
+    fn test_html_to_text_decodes_entities() {
+        assert_eq!(
+            html_to_text("5 &lt; 10 &#38; &#x41; &bogus; &"),
+            "5 < 10 & A &bogus; &"
+        );
+    }
+
+    #[test]
+    fn test_html_to_text_never_leaks_markup() {
+        assert_eq!(html_to_text("Hello <a href=\"x"), "Hello");
+        assert_eq!(html_to_text("<SCRIPT>evil()</SCRIPT>ok"), "ok");
+        assert_eq!(html_to_text("<script>never closed"), "");
+    }
+}
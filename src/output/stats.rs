@@ -0,0 +1,121 @@
+use crate::output::{class_name, OutputEntry};
+use std::collections::HashMap;
+
+/// The call district of a callsign: the digit ending its prefix, e.g. 6 for W6JSV
+/// and 0 for 2E0ABC
+pub fn district(callsign: &str) -> Option<char> {
+    callsign.chars().rfind(char::is_ascii_digit)
+}
+
+/// The letters and digits before the call district, e.g. W for W6JSV and VE for VE3ABC
+pub fn prefix(callsign: &str) -> Option<&str> {
+    let end = callsign.rfind(|c: char| c.is_ascii_digit())?;
+    let prefix = &callsign[..end];
+    (!prefix.is_empty()).then_some(prefix)
+}
+
+/// US callsigns start with K, N, or W, or with AA through AL
+pub fn is_us(callsign: &str) -> bool {
+    let mut chars = callsign.chars();
+    match (chars.next(), chars.next()) {
+        (Some('K' | 'N' | 'W'), _) => true,
+        (Some('A'), Some(second)) => ('A'..='L').contains(&second),
+        _ => false,
+    }
+}
+
+/// How many entries have each license class, by `class_name`, most common first.
+/// Entries QRZ had no class for count as "Unknown".
+pub fn by_class(entries: &[OutputEntry]) -> Vec<(String, usize)> {
+    count(entries.iter().map(|entry| {
+        entry
+            .class
+            .as_deref()
+            .map_or("Unknown", class_name)
+            .to_string()
+    }))
+}
+
+/// How many entries are in each call district, most common first
+pub fn by_district(entries: &[OutputEntry]) -> Vec<(String, usize)> {
+    count(
+        entries.iter().map(|entry| {
+            district(&entry.callsign).map_or("?".to_string(), |digit| digit.to_string())
+        }),
+    )
+}
+
+/// Tally values, sorted by count (highest first) and then by value
+pub fn count(values: impl Iterator<Item = String>) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// `label: n` pairs on one line, e.g. "General: 3 · Technician: 1"
+pub fn describe(counts: &[(String, usize)]) -> String {
+    counts
+        .iter()
+        .map(|(label, n)| format!("{}: {}", label, n))
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(callsign: &str, class: Option<&str>) -> OutputEntry {
+        OutputEntry {
+            callsign: callsign.to_string(),
+            name: "Op".to_string(),
+            emoji_separator: "📻".to_string(),
+            class: class.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_district() {
+        assert_eq!(district("W6JSV"), Some('6'));
+        assert_eq!(district("KH6ABC"), Some('6'));
+        assert_eq!(district("2E0ABC"), Some('0'));
+        assert_eq!(district("A71AB"), Some('1'));
+        assert_eq!(district("ABC"), None);
+    }
+
+    #[test]
+    fn test_prefix_and_is_us() {
+        assert_eq!(prefix("W6JSV"), Some("W"));
+        assert_eq!(prefix("VE3ABC"), Some("VE"));
+        assert_eq!(prefix("2E0ABC"), Some("2E"));
+        assert_eq!(prefix("A71AB"), Some("A7"));
+        assert_eq!(prefix("ABC"), None);
+
+        assert!(is_us("W6JSV"));
+        assert!(is_us("KH6ABC"));
+        assert!(is_us("AL7AB"));
+        assert!(!is_us("AM1AB"));
+        assert!(!is_us("VE3ABC"));
+    }
+
+    #[test]
+    fn test_breakdowns() {
+        let entries = vec![
+            entry("W6JSV", Some("E")),
+            entry("KI7QCF", Some("G")),
+            entry("N7XYZ", Some("G")),
+            entry("VE3ABC", None),
+        ];
+        let classes = by_class(&entries);
+        assert_eq!(
+            describe(&classes),
+            "General: 2 · Amateur Extra: 1 · Unknown: 1"
+        );
+        assert_eq!(describe(&by_district(&entries)), "7: 2 · 3: 1 · 6: 1");
+    }
+}
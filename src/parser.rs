@@ -1,31 +1,444 @@
+use crate::config::ParserConfig;
+use anyhow::{Context, Result};
 use regex::Regex;
+use unicode_normalization::UnicodeNormalization;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MemberInfo {
     pub callsign: String,
     pub name: String,
+    pub grid: Option<String>,
 }
 
-pub struct CallsignParser {
-    // Matches amateur radio callsigns
-    // Format: [prefix(1-2 chars)][digit][suffix(1-4 chars)]
-    // Examples: W6JSV, KI7QCF, N0CALL, etc.
-    callsign_regex: Regex,
+/// What a single token of a display name represents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Callsign,
+    Grid,
+    Pronouns,
+    Emoji,
+    Separator,
+    Name,
 }
 
-impl CallsignParser {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub text: String,
+    pub kind: TokenKind,
+}
+
+/// A classification rule; the first rule returning `Some` decides a token's kind.
+/// Tokens no rule claims are treated as part of the name.
+pub trait ClassifierRule: Send + Sync {
+    fn classify(&self, token: &str) -> Option<TokenKind>;
+}
+
+/// Matches amateur radio callsigns, following the ITU Radio Regulations (Article 19)
+/// Format: [prefix][numeral(1-3 digits)][suffix(1-4 chars, starting and ending with a letter)]
+/// Examples: W6JSV, KI7QCF, N0CALL, 2E0ABC, 3DA0RU, A61AB, 1x1 calls like K6K,
+/// special event calls like W100AW
+///
+/// Slashed forms match when one part is a callsign: portable designators
+/// (W1AW/4, W6JSV/P) and operating prefixes (VE3/W6JSV).
+pub struct CallsignRule {
+    regex: Regex,
+}
+
+impl CallsignRule {
     pub fn new() -> Self {
         // Pattern explanation:
         // (?i) - case-insensitive flag
-        // \b - word boundary
-        // [A-Z0-9]{1,2} - 1-2 character prefix (can be letters or numbers)
-        // [0-9] - single digit
-        // [A-Z]{1,4} - 1-4 letter suffix
-        // \b - word boundary
-        let callsign_regex = Regex::new(r"(?i)\b([A-Z0-9]{1,2}[0-9][A-Z]{1,4})\b")
-            .expect("Failed to compile callsign regex");
+        // Prefix, per the ITU allocation forms:
+        //   [A-Z]{1,2} - one or two letters (W, KI, UA)
+        //   [0-9][A-Z]{1,2} - digit then one or two letters (2E, 9K, 3DA)
+        //   [A-Z][0-9] - letter then digit (A6, E5)
+        // [0-9]{1,3} - numeral; special event and club calls use up to three digits
+        // [A-Z]([A-Z0-9]{0,2}[A-Z])? - suffix of up to 4 characters, the last a letter
+        //   (ITU); the first is a letter too, so the numeral ends unambiguously
+        let regex = Regex::new(
+            r"(?i)^(?:[A-Z]{1,2}|[0-9][A-Z]{1,2}|[A-Z][0-9])[0-9]{1,3}[A-Z](?:[A-Z0-9]{0,2}[A-Z])?$",
+        )
+        .expect("Failed to compile callsign regex");
+        Self { regex }
+    }
+
+    /// The home callsign within a token, without portable designators or
+    /// operating prefixes: "W1AW/4" -> "W1AW", "VE3/W6JSV" -> "W6JSV".
+    /// Surrounding punctuation and a possessive are ignored: "W6JSV's" -> "W6JSV"
+    pub fn base<'a>(&self, token: &'a str) -> Option<&'a str> {
+        let parts: Vec<&str> = trim_word_punctuation(token).split('/').collect();
+        if parts.len() > 3 || parts.iter().any(|p| !is_slash_part(p)) {
+            return None;
+        }
+        // When a prefix also looks like a callsign (VP2E/W6JSV), the longer part wins
+        parts
+            .into_iter()
+            .filter(|p| self.regex.is_match(p))
+            .rev()
+            .max_by_key(|p| p.len())
+    }
+}
+
+impl Default for CallsignRule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A token without leading or trailing ASCII punctuation or a trailing possessive "'s".
+/// Slashes stay, so a dangling "W6JSV/" is still rejected.
+fn trim_word_punctuation(token: &str) -> &str {
+    let is_punctuation = |c: char| c.is_ascii_punctuation() && c != '/';
+    let word = token.trim_matches(is_punctuation);
+    ["'s", "'S", "’s", "’S"]
+        .iter()
+        .find_map(|possessive| word.strip_suffix(possessive))
+        .unwrap_or(word)
+        .trim_end_matches(is_punctuation)
+}
+
+/// A non-empty run of at most a prefix or callsign's worth of letters and digits
+fn is_slash_part(part: &str) -> bool {
+    (1..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+impl ClassifierRule for CallsignRule {
+    fn classify(&self, token: &str) -> Option<TokenKind> {
+        self.base(token).map(|_| TokenKind::Callsign)
+    }
+}
+
+/// Matches 4- or 6-character Maidenhead grid locators (e.g. CM87, CM87wj)
+pub struct GridRule {
+    regex: Regex,
+}
+
+impl GridRule {
+    pub fn new() -> Self {
+        let regex =
+            Regex::new(r"(?i)^[A-R]{2}[0-9]{2}([A-X]{2})?$").expect("Failed to compile grid regex");
+        Self { regex }
+    }
+}
+
+impl Default for GridRule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClassifierRule for GridRule {
+    fn classify(&self, token: &str) -> Option<TokenKind> {
+        self.regex.is_match(token).then_some(TokenKind::Grid)
+    }
+}
+
+/// Matches pronoun tags such as he/him, she/they, or they/them/theirs
+pub struct PronounRule {
+    regex: Regex,
+}
+
+impl PronounRule {
+    pub fn new() -> Self {
+        let word = "he|him|his|she|her|hers|they|them|theirs|it|its|xe|xem|ze|zir|any|all";
+        let regex = Regex::new(&format!(r"(?i)^({word})(/({word}))+$"))
+            .expect("Failed to compile pronoun regex");
+        Self { regex }
+    }
+}
+
+impl Default for PronounRule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClassifierRule for PronounRule {
+    fn classify(&self, token: &str) -> Option<TokenKind> {
+        self.regex.is_match(token).then_some(TokenKind::Pronouns)
+    }
+}
+
+/// Matches tokens made up entirely of emoji or other non-ASCII symbols
+pub struct EmojiRule;
+
+impl ClassifierRule for EmojiRule {
+    fn classify(&self, token: &str) -> Option<TokenKind> {
+        let all_symbols = token.chars().all(|c| !c.is_ascii() && !c.is_alphanumeric());
+        all_symbols.then_some(TokenKind::Emoji)
+    }
+}
+
+/// Matches standalone punctuation used to separate parts of a name
+pub struct SeparatorRule;
 
-        Self { callsign_regex }
+impl ClassifierRule for SeparatorRule {
+    fn classify(&self, token: &str) -> Option<TokenKind> {
+        token
+            .chars()
+            .all(|c| c.is_ascii_punctuation() || SEPARATOR_CHARS.contains(&c))
+            .then_some(TokenKind::Separator)
+    }
+}
+
+/// Longest prefix of a display name the parser looks at
+const MAX_DISPLAY_NAME_CHARS: usize = 256;
+
+/// Invisible formatting characters: soft hyphen, zero-width spaces and joiners,
+/// bidi marks and overrides, word joiners, variation selectors, the BOM, and tags
+const INVISIBLE_RANGES: &[(char, char)] = &[
+    ('\u{00AD}', '\u{00AD}'),
+    ('\u{180E}', '\u{180E}'),
+    ('\u{200B}', '\u{200F}'),
+    ('\u{202A}', '\u{202E}'),
+    ('\u{2060}', '\u{206F}'),
+    ('\u{FE00}', '\u{FE0F}'),
+    ('\u{FEFF}', '\u{FEFF}'),
+    ('\u{E0000}', '\u{E007F}'),
+];
+
+/// Prepare a display name for parsing.
+///
+/// NFKC folds decorative letters (𝐖𝟔𝐉𝐒𝐕, fullwidth Ｗ６ＪＳＶ) to plain ones, control and
+/// invisible formatting characters are dropped, and with `strip_emoji` emoji and other
+/// symbols become spaces so they can't glue onto a callsign.
+pub fn normalize_display_name(display_name: &str, strip_emoji: bool) -> String {
+    display_name
+        .nfkc()
+        .filter(|c| !is_invisible(*c))
+        .map(|c| {
+            if c.is_control() || (strip_emoji && is_symbol(c)) {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+fn is_invisible(c: char) -> bool {
+    INVISIBLE_RANGES
+        .iter()
+        .any(|(start, end)| (*start..=*end).contains(&c))
+}
+
+/// Emoji, flags, and decorative symbols, but not letters, separators, or apostrophes
+fn is_symbol(c: char) -> bool {
+    !c.is_ascii()
+        && !c.is_alphanumeric()
+        && !c.is_whitespace()
+        && !SEPARATOR_CHARS.contains(&c)
+        && c != '’'
+}
+
+/// Characters that always split tokens, regardless of surrounding text
+const BREAK_CHARS: &[char] = &['(', ')', '[', ']', '{', '}', '|', ',', ';', ':'];
+
+/// Non-ASCII dash and bullet characters treated like punctuation
+const SEPARATOR_CHARS: &[char] = &['–', '—', '•', '·'];
+
+/// Split a display name into raw tokens.
+///
+/// Whitespace and bracket-like characters always split. Dashes split a chunk only
+/// when one of its pieces isn't purely alphabetic, so "W6JSV-Jay" splits but
+/// "Mary-Jane" stays a single name token.
+pub fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+
+    for chunk in input.split_whitespace() {
+        let mut current = String::new();
+        for c in chunk.chars() {
+            if BREAK_CHARS.contains(&c) {
+                push_dash_split(&mut tokens, &current);
+                current.clear();
+                tokens.push(c.to_string());
+            } else {
+                current.push(c);
+            }
+        }
+        push_dash_split(&mut tokens, &current);
+    }
+
+    tokens
+}
+
+fn push_dash_split(tokens: &mut Vec<String>, chunk: &str) {
+    if chunk.is_empty() {
+        return;
+    }
+
+    let is_dash = |c: char| c == '-' || SEPARATOR_CHARS.contains(&c);
+    let pieces: Vec<&str> = chunk.split(is_dash).collect();
+    let hyphenated_word =
+        pieces.len() > 1 && pieces.iter().all(|p| p.chars().all(char::is_alphabetic));
+
+    if pieces.len() == 1 || hyphenated_word {
+        tokens.push(chunk.to_string());
+        return;
+    }
+
+    // Split on dashes, keeping each dash as its own separator token
+    let mut current = String::new();
+    for c in chunk.chars() {
+        if is_dash(c) {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            tokens.push(c.to_string());
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+}
+
+/// Turns the name tokens left after classification into a clean name
+///
+/// Symbols inside tokens (emoji, variation selectors) become word breaks, punctuation is
+/// trimmed from the edges of each word (keeping the dot of initials like "S."), then
+/// `[parser] name_strip_patterns` are removed and whitespace is collapsed.
+#[derive(Default)]
+pub struct NameCleaner {
+    strip_patterns: Vec<Regex>,
+}
+
+impl NameCleaner {
+    pub fn new(strip_patterns: Vec<Regex>) -> Self {
+        Self { strip_patterns }
+    }
+
+    pub fn clean(&self, tokens: &[&str]) -> String {
+        let words: Vec<String> = tokens
+            .iter()
+            .flat_map(|token| {
+                token
+                    .chars()
+                    .map(|c| if is_name_char(c) { c } else { ' ' })
+                    .collect::<String>()
+                    .split_whitespace()
+                    .filter_map(trim_word)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let mut name = words.join(" ");
+        for pattern in &self.strip_patterns {
+            name = pattern.replace_all(&name, " ").into_owned();
+        }
+        name.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Letters, digits, ASCII punctuation (trimmed later), and typographic apostrophes
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || (c.is_ascii() && !c.is_ascii_whitespace()) || c == '’'
+}
+
+/// Strip leading and trailing punctuation, keeping a trailing dot after a letter
+fn trim_word(word: &str) -> Option<String> {
+    let start = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+    let trimmed = start.trim_end_matches(|c: char| !c.is_alphanumeric());
+    let last = trimmed.chars().last()?;
+    if last.is_alphabetic() && start[trimmed.len()..].starts_with('.') {
+        Some(format!("{}.", trimmed))
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+pub struct CallsignParser {
+    rules: Vec<Box<dyn ClassifierRule>>,
+    /// `[parser] patterns`, tried in order before tokenizing
+    patterns: Vec<Regex>,
+    name_cleaner: NameCleaner,
+    /// Strips portable designators from whichever token is classified as the callsign
+    callsign_rule: CallsignRule,
+    /// Spots 6-character grids among tokens classified as callsigns
+    grid_rule: GridRule,
+    /// `[parser] strip_emoji`
+    strip_emoji: bool,
+}
+
+impl CallsignParser {
+    pub fn new() -> Self {
+        Self::with_rules(Self::default_rules())
+    }
+
+    /// Build a parser with a custom rule set, checked in order
+    pub fn with_rules(rules: Vec<Box<dyn ClassifierRule>>) -> Self {
+        Self {
+            rules,
+            patterns: Vec::new(),
+            name_cleaner: NameCleaner::default(),
+            callsign_rule: CallsignRule::new(),
+            grid_rule: GridRule::new(),
+            strip_emoji: false,
+        }
+    }
+
+    /// The default parser with `[parser]` settings applied
+    pub fn from_config(config: &ParserConfig) -> Result<Self> {
+        let patterns = config
+            .patterns
+            .iter()
+            .map(|p| {
+                let regex =
+                    Regex::new(p).with_context(|| format!("Invalid parser pattern: {}", p))?;
+                if !regex.capture_names().any(|name| name == Some("callsign")) {
+                    anyhow::bail!("Parser pattern has no `callsign` group: {}", p);
+                }
+                Ok(regex)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let strip_patterns = config
+            .name_strip_patterns
+            .iter()
+            .map(|p| {
+                Regex::new(p).with_context(|| format!("Invalid name_strip_patterns entry: {}", p))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            patterns,
+            name_cleaner: NameCleaner::new(strip_patterns),
+            strip_emoji: config.strip_emoji,
+            ..Self::new()
+        })
+    }
+
+    pub fn default_rules() -> Vec<Box<dyn ClassifierRule>> {
+        // Callsigns come first so special event calls shaped like a 6-character grid
+        // (OP20WC) aren't lost; `parse()` tells a grid like CM87wj from the callsign
+        vec![
+            Box::new(CallsignRule::new()),
+            Box::new(GridRule::new()),
+            Box::new(PronounRule::new()),
+            Box::new(EmojiRule),
+            Box::new(SeparatorRule),
+        ]
+    }
+
+    /// Normalize the start of a display name; only that much of a pathologically long
+    /// name is considered
+    fn prepare(&self, display_name: &str) -> String {
+        let display_name: String = display_name.chars().take(MAX_DISPLAY_NAME_CHARS).collect();
+        normalize_display_name(&display_name, self.strip_emoji)
+    }
+
+    /// Tokenize a display name and classify each token
+    pub fn classify(&self, display_name: &str) -> Vec<Token> {
+        tokenize(&self.prepare(display_name))
+            .into_iter()
+            .map(|text| {
+                let kind = self
+                    .rules
+                    .iter()
+                    .find_map(|rule| rule.classify(&text))
+                    .unwrap_or(TokenKind::Name);
+                Token { text, kind }
+            })
+            .collect()
     }
 
     /// Parse a Discord member's display name to extract callsign and name
@@ -33,46 +446,145 @@ impl CallsignParser {
     /// - "W6JSV - Jay" -> callsign: W6JSV, name: Jay
     /// - "Forrest KI7QCF" -> callsign: KI7QCF, name: Forrest
     /// - "Jay (W6JSV)" -> callsign: W6JSV, name: Jay
+    /// - "W1AW/4 Hiram" -> callsign: W1AW, name: Hiram
+    ///
+    /// A matching `[parser] patterns` entry takes precedence over these heuristics.
     pub fn parse(&self, display_name: &str) -> Option<MemberInfo> {
-        // Find the callsign in the display name
-        let callsign_match = self.callsign_regex.find(display_name)?;
-        let callsign = callsign_match.as_str().to_uppercase();
-
-        // Extract the name by removing the callsign and cleaning up
-        let mut name = display_name.to_string();
-
-        // Remove the callsign (use the original matched text, not the uppercased version)
-        name = name.replace(callsign_match.as_str(), "");
-
-        // Remove common separators and punctuation
-        name = name
-            .replace(" - ", " ")
-            .replace(" -", "")
-            .replace("- ", "")
-            .replace("(", "")
-            .replace(")", "")
-            .trim()
-            .to_string();
+        if let Some(info) = self.parse_with_patterns(display_name) {
+            return Some(info);
+        }
+
+        let tokens = self.classify(display_name);
+
+        // The first callsign token is the member's callsign, unless it's also shaped like
+        // a grid and another one isn't: in "W6JSV CM87wj", CM87wj is the grid
+        let is_callsign = |t: &Token| t.kind == TokenKind::Callsign;
+        let is_grid_shaped = |t: &Token| self.grid_rule.classify(&t.text).is_some();
+        let callsign_index = tokens
+            .iter()
+            .position(|t| is_callsign(t) && !is_grid_shaped(t))
+            .or_else(|| tokens.iter().position(is_callsign))?;
+        let text = &tokens[callsign_index].text;
+        let callsign = self.callsign_rule.base(text).unwrap_or(text).to_uppercase();
+
+        let grid_index = tokens
+            .iter()
+            .position(|t| t.kind == TokenKind::Grid)
+            .or_else(|| {
+                tokens
+                    .iter()
+                    .enumerate()
+                    .position(|(i, t)| i != callsign_index && is_callsign(t) && is_grid_shaped(t))
+            });
+        let grid = grid_index.map(|i| normalize_grid(&tokens[i].text));
+
+        // Any additional callsign-like tokens stay in the name, as before
+        let name_tokens: Vec<&str> = tokens
+            .iter()
+            .enumerate()
+            .filter(|(i, t)| {
+                t.kind == TokenKind::Name
+                    || (t.kind == TokenKind::Callsign
+                        && *i != callsign_index
+                        && Some(*i) != grid_index)
+            })
+            .map(|(_, t)| t.text.as_str())
+            .collect();
+        let mut name = self.name_cleaner.clean(&name_tokens);
 
         // If name is empty, use the callsign as the name
         if name.is_empty() {
             name = callsign.clone();
         }
 
-        Some(MemberInfo { callsign, name })
+        Some(MemberInfo {
+            callsign,
+            name,
+            grid,
+        })
+    }
+
+    /// Parse with the first configured pattern that matches and captures a valid callsign
+    fn parse_with_patterns(&self, display_name: &str) -> Option<MemberInfo> {
+        let display_name = self.prepare(display_name);
+
+        self.patterns.iter().find_map(|pattern| {
+            let captures = pattern.captures(display_name.trim())?;
+            // A capture that isn't a callsign ("[Admin] Jay") falls through like no match
+            let text = captures.name("callsign")?.as_str();
+            let callsign = self.callsign_rule.base(text.trim())?.to_uppercase();
+
+            let name_tokens: Vec<&str> = captures
+                .name("name")
+                .map(|m| m.as_str().split_whitespace().collect())
+                .unwrap_or_default();
+            let name = Some(self.name_cleaner.clean(&name_tokens))
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| callsign.clone());
+
+            Some(MemberInfo {
+                callsign,
+                name,
+                grid: None,
+            })
+        })
+    }
+
+    /// Callsigns mentioned in free text such as a chat message, uppercased and without
+    /// portable designators, in order of first mention. Punctuation around a word is
+    /// ignored, and grids are never taken for callsigns.
+    pub fn find_callsigns(&self, text: &str) -> Vec<String> {
+        let mut callsigns: Vec<String> = Vec::new();
+        for token in tokenize(&normalize_display_name(text, true)) {
+            let word = token.trim_matches(|c: char| !c.is_ascii_alphanumeric());
+            let kind = self.rules.iter().find_map(|rule| rule.classify(word));
+            if kind != Some(TokenKind::Callsign) || self.grid_rule.classify(word).is_some() {
+                continue;
+            }
+            let callsign = self.callsign_rule.base(word).unwrap_or(word).to_uppercase();
+            if !callsigns.contains(&callsign) {
+                callsigns.push(callsign);
+            }
+        }
+        callsigns
     }
 
     /// Validate if a string looks like a callsign
-    #[allow(dead_code)]
     pub fn is_callsign(&self, text: &str) -> bool {
-        self.callsign_regex.is_match(text)
+        self.classify(text)
+            .iter()
+            .any(|t| t.kind == TokenKind::Callsign)
+    }
+}
+
+impl Default for CallsignParser {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+/// Grid locators are conventionally written with an uppercase field and lowercase subsquare
+fn normalize_grid(grid: &str) -> String {
+    let (field, subsquare) = grid.split_at(grid.len().min(4));
+    format!("{}{}", field.to_uppercase(), subsquare.to_lowercase())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_pathological_names() {
+        let parser = CallsignParser::new();
+
+        let long = format!("W6JSV {}", "x".repeat(10_000));
+        assert_eq!(parser.parse(&long).unwrap().callsign, "W6JSV");
+
+        assert!(parser.parse("").is_none());
+        assert!(parser.parse("🎉🎉🎉").is_none());
+        assert_eq!(parser.parse("W6JSV\n- Jay").unwrap().callsign, "W6JSV");
+    }
+
     #[test]
     fn test_parse_callsign_dash_name() {
         let parser = CallsignParser::new();
@@ -105,6 +617,23 @@ mod tests {
         assert_eq!(result.name, "W6JSV");
     }
 
+    #[test]
+    fn test_find_callsigns() {
+        let parser = CallsignParser::new();
+        assert_eq!(
+            parser.find_callsigns(
+                "Worked **w6jsv** and KI7QCF/P on 20m, then W6JSV again. Grid CM87wj!"
+            ),
+            vec!["W6JSV", "KI7QCF"]
+        );
+        assert!(parser
+            .find_callsigns("See https://www.qrz.com/db/W6JSV")
+            .is_empty());
+        assert!(parser
+            .find_callsigns("Nice weather for FT8 today")
+            .is_empty());
+    }
+
     #[test]
     fn test_is_callsign() {
         let parser = CallsignParser::new();
@@ -115,6 +644,49 @@ mod tests {
         assert!(!parser.is_callsign("123456"));
     }
 
+    #[test]
+    fn test_international_callsigns() {
+        let parser = CallsignParser::new();
+        let calls = [
+            // North America
+            "W6JSV", "KI7QCF", "AA1A", "N0CALL", "VE3ABC", "VA7XYZ", "KH6ABC", "XE1ABC",
+            // Europe
+            "G4ABC", "M0XYZ", "2E0ABC", "2E1AA", "MM0ABC", "GM3ABC", "EI2ABC", "DL1ABC", "DO1XYZ",
+            "F5ABC", "ON4ABC", "PA3ABC", "IK2ABC", "IZ0ABC", "EA8ABC", "EC1A", "OY1ABC", "OH2ABC",
+            "SM5ABC", "SP9ABC", "9A1ABC", "S51ABC", "T77AA",
+            // Russia and neighbours
+            "RA1ABC", "UA9XXXX", "R1ANC", "RK3AWL", "UA0ABC", "UN7ABC", "4L1ABC", "R9A1B",
+            // Asia and Oceania
+            "JA1ABC", "7K1ABC", "8J1RL", "HS0ZAA", "BY1AA", "BA4ABC", "VU2ABC", "9K2XX", "A61AB",
+            "4X4ABC", "VK2ABC", "ZL1ABC", "E51ABC", "3D2AG", "YB0ABC", "DU1ABC",
+            // Africa and South America
+            "ZS6ABC", "3DA0RU", "5Z4ABC", "9J2ABC", "PY2ABC", "PP5XX", "LU1ABC", "CE3ABC",
+            // International organizations and special prefixes
+            "4U1UN", "1A0KM", "TO2FG",
+        ];
+        for call in calls {
+            assert!(parser.is_callsign(call), "{} should be a callsign", call);
+            assert_eq!(
+                parser.parse(&format!("Op {}", call)).unwrap().callsign,
+                call
+            );
+        }
+    }
+
+    #[test]
+    fn test_international_non_callsigns() {
+        let parser = CallsignParser::new();
+        for text in [
+            "123456", "ABCDEF", "W6", "6JSV", "12A", "W6JSV1", "ABC1DEF", "A1BCDEF", "2024",
+        ] {
+            assert!(
+                !parser.is_callsign(text),
+                "{} should not be a callsign",
+                text
+            );
+        }
+    }
+
     #[test]
     fn test_parse_lowercase_callsign_only() {
         let parser = CallsignParser::new();
@@ -146,4 +718,399 @@ mod tests {
         assert_eq!(result.callsign, "W6JSV");
         assert_eq!(result.name, "Jay");
     }
+
+    #[test]
+    fn test_parse_no_callsign() {
+        let parser = CallsignParser::new();
+        assert!(parser.parse("Just A Name").is_none());
+        assert!(parser.parse("").is_none());
+    }
+
+    #[test]
+    fn test_parse_callsign_attached_dash() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("W6JSV-Jay").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.name, "Jay");
+    }
+
+    #[test]
+    fn test_parse_keeps_hyphenated_name() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("Mary-Jane KI7QCF").unwrap();
+        assert_eq!(result.callsign, "KI7QCF");
+        assert_eq!(result.name, "Mary-Jane");
+    }
+
+    #[test]
+    fn test_parse_brackets_and_pipes() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("[W6JSV] | Jay").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.name, "Jay");
+    }
+
+    #[test]
+    fn test_parse_callsign_with_trailing_punctuation() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("Jay W6JSV.").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.name, "Jay");
+
+        let result = parser.parse("W6JSV's shack").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.name, "shack");
+
+        assert_eq!(parser.parse("Jay, W1AW/4!").unwrap().callsign, "W1AW");
+        assert_eq!(parser.parse("W6JSV’s").unwrap().callsign, "W6JSV");
+        assert!(parser.parse("Jay's radio.").is_none());
+    }
+
+    #[test]
+    fn test_parse_grid() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("W6JSV Jay cm87WJ").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.name, "Jay");
+        assert_eq!(result.grid.as_deref(), Some("CM87wj"));
+    }
+
+    #[test]
+    fn test_parse_pronouns() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("Jay (He/Him) W6JSV").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.name, "Jay");
+        let kinds: Vec<TokenKind> = parser
+            .classify("Jay (He/Him) W6JSV")
+            .iter()
+            .map(|t| t.kind)
+            .collect();
+        assert!(kinds.contains(&TokenKind::Pronouns));
+    }
+
+    #[test]
+    fn test_parse_strips_emoji_tokens() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("📻 W6JSV Jay 🎙️").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.name, "Jay");
+    }
+
+    #[test]
+    fn test_parse_unicode_dash_separator() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("W6JSV — Jay").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.name, "Jay");
+    }
+
+    #[test]
+    fn test_parse_multi_word_name() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("Jay Smith, W6JSV").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.name, "Jay Smith");
+    }
+
+    #[test]
+    fn test_parse_first_callsign_wins() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("W6JSV KI7QCF").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.name, "KI7QCF");
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(tokenize("Jay (W6JSV)"), vec!["Jay", "(", "W6JSV", ")"]);
+        assert_eq!(tokenize("W6JSV - Jay"), vec!["W6JSV", "-", "Jay"]);
+        assert_eq!(tokenize("W6JSV-Jay"), vec!["W6JSV", "-", "Jay"]);
+        assert_eq!(tokenize("Mary-Jane"), vec!["Mary-Jane"]);
+        assert_eq!(tokenize("  spaced   out  "), vec!["spaced", "out"]);
+        assert!(tokenize("").is_empty());
+    }
+
+    #[test]
+    fn test_classify_token_kinds() {
+        let parser = CallsignParser::new();
+        let kinds: Vec<TokenKind> = parser
+            .classify("W6JSV - Jay (he/him) CM87 📻")
+            .into_iter()
+            .map(|t| t.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Callsign,
+                TokenKind::Separator,
+                TokenKind::Name,
+                TokenKind::Separator,
+                TokenKind::Pronouns,
+                TokenKind::Separator,
+                TokenKind::Grid,
+                TokenKind::Emoji,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grid_rule_does_not_match_callsigns() {
+        let rule = GridRule::new();
+        assert!(rule.classify("CM87").is_some());
+        assert!(rule.classify("FN31pr").is_some());
+        assert!(rule.classify("W6JSV").is_none());
+        assert!(rule.classify("ZZ99").is_none());
+    }
+
+    #[test]
+    fn test_pronoun_rule() {
+        let rule = PronounRule::new();
+        assert!(rule.classify("she/her").is_some());
+        assert!(rule.classify("they/them/theirs").is_some());
+        assert!(rule.classify("he").is_none());
+        assert!(rule.classify("and/or").is_none());
+    }
+
+    #[test]
+    fn test_parse_one_by_one_calls() {
+        let parser = CallsignParser::new();
+        for call in ["K6K", "W1A", "N0Q"] {
+            let result = parser.parse(&format!("{} Field Day", call)).unwrap();
+            assert_eq!(result.callsign, call);
+            assert_eq!(result.name, "Field Day");
+        }
+    }
+
+    #[test]
+    fn test_parse_special_event_calls() {
+        let parser = CallsignParser::new();
+        assert_eq!(parser.parse("W100AW").unwrap().callsign, "W100AW");
+        assert_eq!(
+            parser.parse("gb50rsgb - RSGB").unwrap().callsign,
+            "GB50RSGB"
+        );
+        assert_eq!(parser.parse("TM100ABC").unwrap().callsign, "TM100ABC");
+        assert!(!parser.is_callsign("W10000AW"));
+    }
+
+    #[test]
+    fn test_parse_portable_designators() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("W1AW/4 - Hiram").unwrap();
+        assert_eq!(result.callsign, "W1AW");
+        assert_eq!(result.name, "Hiram");
+        assert_eq!(parser.parse("Jay (w6jsv/p)").unwrap().callsign, "W6JSV");
+        assert_eq!(parser.parse("W6JSV/QRP").unwrap().callsign, "W6JSV");
+        assert_eq!(parser.parse("W6JSV/MM").unwrap().callsign, "W6JSV");
+        assert_eq!(parser.parse("Jay W6JSV/AM").unwrap().name, "Jay");
+    }
+
+    #[test]
+    fn test_parse_operating_prefixes() {
+        let parser = CallsignParser::new();
+        assert_eq!(parser.parse("VE3/W6JSV Jay").unwrap().callsign, "W6JSV");
+        assert_eq!(parser.parse("KH6/K6K").unwrap().callsign, "K6K");
+        assert_eq!(parser.parse("VP2E/W6JSV/P").unwrap().callsign, "W6JSV");
+    }
+
+    #[test]
+    fn test_slashed_non_callsigns() {
+        let parser = CallsignParser::new();
+        assert!(parser.parse("Jay (he/him)").is_none());
+        assert!(parser.parse("and/or").is_none());
+        assert!(!parser.is_callsign("W6JSV/"));
+        assert!(!parser.is_callsign("/4"));
+        assert!(!parser.is_callsign("a/b/c/W6JSV"));
+    }
+
+    #[test]
+    fn test_parse_grid_is_not_a_special_event_call() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("W6JSV CM87wj").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.grid.as_deref(), Some("CM87wj"));
+        assert_eq!(result.name, "W6JSV");
+
+        let result = parser.parse("CM87wj W6JSV Jay").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.grid.as_deref(), Some("CM87wj"));
+        assert_eq!(result.name, "Jay");
+    }
+
+    #[test]
+    fn test_parse_grid_shaped_special_event_call() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("OP20WC - Expo").unwrap();
+        assert_eq!(result.callsign, "OP20WC");
+        assert_eq!(result.name, "Expo");
+        assert_eq!(result.grid, None);
+
+        let result = parser.parse("OP20WC JO21").unwrap();
+        assert_eq!(result.callsign, "OP20WC");
+        assert_eq!(result.grid.as_deref(), Some("JO21"));
+        assert!(parser.is_callsign("OP20WC"));
+    }
+
+    #[test]
+    fn test_numbers_with_units_are_not_callsigns() {
+        let parser = CallsignParser::new();
+        for text in ["100K", "1080P", "4K", "FT8", "20M"] {
+            assert!(!parser.is_callsign(text), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_cleans_name() {
+        let parser = CallsignParser::new();
+        let result = parser.parse("W6JSV — Jay S. (he/him) 🎙️").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.name, "Jay S.");
+
+        assert_eq!(parser.parse("\"Jay\"! W6JSV").unwrap().name, "Jay");
+        assert_eq!(parser.parse("W6JSV Jay🎙️Smith").unwrap().name, "Jay Smith");
+        assert_eq!(
+            parser.parse("W6JSV Jay Smith Jr.,").unwrap().name,
+            "Jay Smith Jr."
+        );
+        assert_eq!(parser.parse("W6JSV *** ~~~").unwrap().name, "W6JSV");
+    }
+
+    #[test]
+    fn test_name_cleaner_keeps_unicode_names() {
+        let cleaner = NameCleaner::default();
+        assert_eq!(
+            cleaner.clean(&["José", "Müller-Lüdenscheidt"]),
+            "José Müller-Lüdenscheidt"
+        );
+        assert_eq!(cleaner.clean(&["O’Brien", "D'Arcy"]), "O’Brien D'Arcy");
+        assert_eq!(cleaner.clean(&["Юрий"]), "Юрий");
+        assert_eq!(cleaner.clean(&["👨‍👩‍👧", "Kim"]), "Kim");
+    }
+
+    #[test]
+    fn test_name_strip_patterns() {
+        let config = ParserConfig {
+            name_strip_patterns: vec![
+                r"(?i)\b(ARC|ARES)\b".to_string(),
+                r"(?i)^dr\.? ".to_string(),
+            ],
+            ..Default::default()
+        };
+        let parser = CallsignParser::from_config(&config).unwrap();
+        assert_eq!(
+            parser.parse("Dr. Jay Smith ARES W6JSV").unwrap().name,
+            "Jay Smith"
+        );
+        assert_eq!(parser.parse("W6JSV ARC").unwrap().name, "W6JSV");
+
+        let invalid = ParserConfig {
+            name_strip_patterns: vec!["(".to_string()],
+            ..Default::default()
+        };
+        assert!(CallsignParser::from_config(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_with_patterns() {
+        let config = ParserConfig {
+            patterns: vec![
+                r"^(?P<name>[^|]+)\|\s*(?P<callsign>\w+)$".to_string(),
+                r"^\[(?P<callsign>[^\]]+)\]$".to_string(),
+            ],
+            ..Default::default()
+        };
+        let parser = CallsignParser::from_config(&config).unwrap();
+
+        // The club format wins over the heuristic, which would take "AB1CD" as the callsign
+        let result = parser.parse("AB1CD Smith | w6jsv").unwrap();
+        assert_eq!(result.callsign, "W6JSV");
+        assert_eq!(result.name, "AB1CD Smith");
+
+        let result = parser.parse("[W1AW/4]").unwrap();
+        assert_eq!(result.callsign, "W1AW");
+        assert_eq!(result.name, "W1AW");
+
+        // Captures that aren't callsigns are skipped, so "[Admin]" isn't a callsign
+        assert!(parser.parse("[Admin]").is_none());
+        let result = parser.parse("[KI7QCF]").unwrap();
+        assert_eq!(result.callsign, "KI7QCF");
+        assert!(parser.parse("Jay Smith | Admin").is_none());
+        assert_eq!(parser.parse("W6JSV Jay | Admin").unwrap().callsign, "W6JSV");
+
+        // Names matching no pattern fall back to the heuristic
+        let result = parser.parse("Forrest KI7QCF").unwrap();
+        assert_eq!(result.callsign, "KI7QCF");
+        assert_eq!(result.name, "Forrest");
+    }
+
+    #[test]
+    fn test_parse_messy_unicode_names() {
+        let parser = CallsignParser::new();
+        let parse = |name: &str| {
+            let info = parser.parse(name).unwrap();
+            (info.callsign, info.name)
+        };
+        let expected = ("W6JSV".to_string(), "Jay".to_string());
+
+        // Mathematical bold and fullwidth letters
+        assert_eq!(parse("𝐖𝟔𝐉𝐒𝐕 - 𝐉𝐚𝐲"), expected);
+        assert_eq!(parse("Ｗ６ＪＳＶ Ｊａｙ"), expected);
+        // Zero-width space inside the callsign, bidi override, and a BOM
+        assert_eq!(parse("W6\u{200B}JSV Jay"), expected);
+        assert_eq!(parse("\u{202E}Jay W6JSV"), expected);
+        assert_eq!(parse("\u{FEFF}W6JSV | Jay"), expected);
+        // Flags and ZWJ emoji sequences around the name
+        assert_eq!(parse("🇺🇸 W6JSV Jay 👨\u{200D}👩\u{200D}👧"), expected);
+        assert_eq!(parse("Jay ✨ W6JSV ✨"), expected);
+        assert_eq!(parse("Ja\u{00AD}y (W6JSV)"), expected);
+
+        // Emoji glued to the callsign only parse with strip_emoji
+        assert!(parser.parse("🔥W6JSV🔥 Jay").is_none());
+        let stripping = CallsignParser::from_config(&ParserConfig {
+            strip_emoji: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let info = stripping.parse("🔥W6JSV🔥 Jay♡").unwrap();
+        assert_eq!(
+            (info.callsign.as_str(), info.name.as_str()),
+            ("W6JSV", "Jay")
+        );
+        assert_eq!(stripping.parse("O’Brien – W6JSV").unwrap().name, "O’Brien");
+    }
+
+    #[test]
+    fn test_normalize_display_name() {
+        assert_eq!(normalize_display_name("𝓙𝓪𝔂\u{200D}", false), "Jay");
+        assert_eq!(normalize_display_name("Jay\tW6JSV", false), "Jay W6JSV");
+        assert_eq!(normalize_display_name("📻W6JSV", false), "📻W6JSV");
+        assert_eq!(normalize_display_name("📻W6JSV", true), " W6JSV");
+    }
+
+    #[test]
+    fn test_patterns_need_callsign_group() {
+        let config = ParserConfig {
+            patterns: vec![r"^(?P<name>.+)$".to_string()],
+            ..Default::default()
+        };
+        assert!(CallsignParser::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_custom_rule_set() {
+        struct ClubTagRule;
+        impl ClassifierRule for ClubTagRule {
+            fn classify(&self, token: &str) -> Option<TokenKind> {
+                (token == "ARC").then_some(TokenKind::Separator)
+            }
+        }
+
+        let mut rules = CallsignParser::default_rules();
+        rules.insert(0, Box::new(ClubTagRule));
+        let parser = CallsignParser::with_rules(rules);
+
+        let result = parser.parse("Jay W6JSV ARC").unwrap();
+        assert_eq!(result.name, "Jay");
+    }
 }
@@ -0,0 +1,510 @@
+use crate::config::GuildConfig;
+use crate::error;
+use crate::output::{generate_guild_output, OutputContext};
+use crate::qrz::{CallsignInfo, QrzClient};
+use crate::roster;
+use anyhow::Result;
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tracing::{info, info_span, warn, Instrument};
+
+pub use crate::aprs::AprsClient;
+pub use crate::dxcc::{DxccStore, DxccTable};
+pub use crate::lotw::{LotwStore, LotwUsers};
+pub use crate::metrics::Metrics;
+pub use crate::radioid::RadioIdClient;
+pub use crate::roster::{CallsignConflict, QrzStatus, Roster, RosterBuilder};
+
+/// A guild member as the pipeline sees it, whichever `MemberSource` it came from
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Member {
+    pub user_id: u64,
+    /// Account username, the last name tried for a callsign
+    pub username: String,
+    /// Name shown across Discord, if the user set one
+    pub global_name: Option<String>,
+    /// Server nickname
+    pub nick: Option<String>,
+    pub bot: bool,
+    /// IDs of the member's roles
+    pub roles: Vec<u64>,
+    /// Unix timestamp (seconds) of when the member joined
+    pub joined_at: Option<i64>,
+    /// Whether the member boosts the server
+    pub booster: bool,
+}
+
+impl Member {
+    /// The name the server shows: nickname, then global name, then username
+    pub fn display_name(&self) -> &str {
+        self.nick
+            .as_deref()
+            .or(self.global_name.as_deref())
+            .unwrap_or(&self.username)
+    }
+}
+
+/// A guild role, matched against `suffix_role_pattern`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Role {
+    pub name: String,
+    /// The highest matching role wins
+    pub position: u16,
+}
+
+/// How a member was listed when they left the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepartedMember {
+    pub callsign: String,
+    pub name: String,
+    /// Unix timestamp (seconds) of the departure
+    pub departed_at: u64,
+}
+
+/// Where a guild's members, roles (by ID), and name come from; Discord in production
+#[async_trait]
+pub trait MemberSource: Send + Sync {
+    async fn members(&self, guild_id: u64) -> Result<Vec<Member>>;
+    async fn roles(&self, guild_id: u64) -> Result<HashMap<u64, Role>>;
+    async fn guild_name(&self, guild_id: u64) -> Result<String>;
+}
+
+/// Told how each QRZ lookup went, so repeated failures reach an admin; the bot's
+/// `[alerts]` in production
+pub trait LookupAlerts: Send + Sync {
+    fn lookup_succeeded(&self);
+    fn lookup_failed(&self, error: &error::Error);
+}
+
+/// Where parsed callsigns are looked up; QRZ in production. A missing callsign
+/// is `Error::NotFound` so it can be told apart from the service being down.
+#[async_trait]
+pub trait CallsignLookup: Send + Sync {
+    async fn lookup_callsign(&self, callsign: &str) -> error::Result<CallsignInfo>;
+}
+
+#[async_trait]
+impl CallsignLookup for QrzClient {
+    async fn lookup_callsign(&self, callsign: &str) -> error::Result<CallsignInfo> {
+        QrzClient::lookup_callsign(self, callsign).await
+    }
+}
+
+/// One run of fetch → parse → lookup → render for a guild; the alumni section
+/// is already part of the content
+pub struct Generation {
+    pub members: Vec<Member>,
+    pub roster: Roster,
+    /// Rendered without `{generated_at}`, for the fingerprint
+    pub stable_content: String,
+    pub content: String,
+}
+
+/// Turns a guild's members into the rendered roster, without publishing it or
+/// touching anything else in Discord
+pub struct Pipeline<'a> {
+    pub source: &'a dyn MemberSource,
+    pub builder: RosterBuilder<'a>,
+}
+
+impl Pipeline<'_> {
+    /// `skip_user`, `member_suffixes`, and `paid_dues` are passed to `RosterBuilder::build`;
+    /// a suffix a member chose in `member_suffixes` takes precedence over one from
+    /// `suffix_role_pattern`
+    pub async fn generate(
+        &self,
+        guild_config: &GuildConfig,
+        skip_user: Option<u64>,
+        member_suffixes: HashMap<u64, String>,
+        departed: HashMap<u64, DepartedMember>,
+        paid_dues: Option<HashSet<u64>>,
+    ) -> Result<Generation> {
+        info!("Fetching members from guild {}", guild_config.guild_id);
+        let members = self
+            .source
+            .members(guild_config.guild_id)
+            .instrument(info_span!("fetch"))
+            .await?;
+        info!("Found {} members", members.len());
+
+        let mut suffixes = self.role_suffixes(guild_config, &members).await;
+        suffixes.extend(member_suffixes);
+        let roster = self
+            .builder
+            .build(
+                guild_config,
+                &members,
+                skip_user,
+                &suffixes,
+                paid_dues.as_ref(),
+            )
+            .await;
+        let alumni = roster::alumni_entries(guild_config, departed, &members, &roster.entries);
+
+        let mut context = OutputContext {
+            guild_name: self.guild_name(guild_config).await,
+            generated_at: None,
+        };
+        let render = |context: &OutputContext| {
+            info_span!("format").in_scope(|| {
+                generate_guild_output(
+                    roster.entries.clone(),
+                    alumni.clone(),
+                    &guild_config.output,
+                    context,
+                )
+            })
+        };
+        // The fingerprint ignores {generated_at}, or every regeneration would publish
+        let stable_content = render(&context);
+        let content = if guild_config.output.uses_placeholder("{generated_at}") {
+            context.generated_at = Some(chrono::Utc::now());
+            render(&context)
+        } else {
+            stable_content.clone()
+        };
+
+        Ok(Generation {
+            members,
+            roster,
+            stable_content,
+            content,
+        })
+    }
+
+    async fn role_suffixes(
+        &self,
+        guild_config: &GuildConfig,
+        members: &[Member],
+    ) -> HashMap<u64, String> {
+        let Some(pattern) = &guild_config.suffix_role_pattern else {
+            return HashMap::new();
+        };
+        // Checked when the config is loaded
+        let Ok(pattern) = Regex::new(pattern) else {
+            return HashMap::new();
+        };
+
+        match self.source.roles(guild_config.guild_id).await {
+            Ok(roles) => roster::role_suffixes(&pattern, members, &roles),
+            Err(e) => {
+                warn!(
+                    "Failed to fetch roles for guild {}; skipping role suffixes: {}",
+                    guild_config.guild_id, e
+                );
+                HashMap::new()
+            }
+        }
+    }
+
+    async fn guild_name(&self, guild_config: &GuildConfig) -> String {
+        let fallback = || guild_config.guild_id.to_string();
+        if !guild_config.output.uses_placeholder("{guild_name}") {
+            return fallback();
+        }
+        match self.source.guild_name(guild_config.guild_id).await {
+            Ok(name) => name,
+            Err(e) => {
+                warn!(
+                    "Failed to fetch name of guild {}: {}",
+                    guild_config.guild_id, e
+                );
+                fallback()
+            }
+        }
+    }
+}
+
+/// In-memory stand-ins for Discord and QRZ, so the pipeline runs in tests
+/// without credentials
+#[cfg(test)]
+pub mod mock {
+    use super::*;
+    use crate::error::Error;
+
+    #[derive(Default)]
+    pub struct MockMembers {
+        pub name: String,
+        pub members: Vec<Member>,
+        pub roles: HashMap<u64, Role>,
+    }
+
+    impl MockMembers {
+        /// Add a member whose nickname is `nick`; `roles` are IDs in `self.roles`
+        pub fn with_member(mut self, user_id: u64, nick: &str, roles: &[u64]) -> Self {
+            self.members.push(Member {
+                user_id,
+                username: format!("user{}", user_id),
+                nick: Some(nick.to_string()),
+                roles: roles.to_vec(),
+                ..Default::default()
+            });
+            self
+        }
+
+        pub fn with_role(mut self, role_id: u64, name: &str, position: u16) -> Self {
+            self.roles.insert(
+                role_id,
+                Role {
+                    name: name.to_string(),
+                    position,
+                },
+            );
+            self
+        }
+    }
+
+    #[async_trait]
+    impl MemberSource for MockMembers {
+        async fn members(&self, _guild_id: u64) -> Result<Vec<Member>> {
+            Ok(self.members.clone())
+        }
+
+        async fn roles(&self, _guild_id: u64) -> Result<HashMap<u64, Role>> {
+            Ok(self.roles.clone())
+        }
+
+        async fn guild_name(&self, _guild_id: u64) -> Result<String> {
+            Ok(self.name.clone())
+        }
+    }
+
+    /// Answers from a fixed table; every other callsign is `NotFound`
+    #[derive(Default)]
+    pub struct MockLookup {
+        pub records: HashMap<String, CallsignInfo>,
+    }
+
+    impl MockLookup {
+        pub fn with_record(mut self, callsign: &str, fname: &str, name: &str) -> Self {
+            self.records.insert(
+                callsign.to_string(),
+                CallsignInfo {
+                    fname: Some(fname.to_string()),
+                    name: Some(name.to_string()),
+                    ..Default::default()
+                },
+            );
+            self
+        }
+    }
+
+    #[async_trait]
+    impl CallsignLookup for MockLookup {
+        async fn lookup_callsign(&self, callsign: &str) -> error::Result<CallsignInfo> {
+            self.records
+                .get(callsign)
+                .cloned()
+                .ok_or_else(|| Error::NotFound(format!("{} not in mock", callsign)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::{MockLookup, MockMembers};
+    use super::*;
+    use crate::config::GuildConfig;
+    use crate::parser::CallsignParser;
+    use std::sync::Arc;
+
+    fn guild_config(extra: &str) -> GuildConfig {
+        toml::from_str(&format!(
+            r##"
+            guild_id = 1
+            {}
+
+            [output]
+            repo = "jsvana/rosters"
+            path = "members.txt"
+            default_suffix = ""
+            header = "# {{guild_name}}"
+            "##,
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_generate_end_to_end() {
+        let source = MockMembers {
+            name: "Test Club".to_string(),
+            ..Default::default()
+        }
+        .with_role(10, "ARRL", 1)
+        .with_member(1, "W6JSV - Jay", &[10])
+        .with_member(2, "KI7QCF", &[])
+        .with_member(3, "Not a ham", &[])
+        .with_member(4, "N0CALL Bot", &[])
+        .with_member(5, "K6ABC Al", &[10]);
+        let lookup = MockLookup::default()
+            .with_record("KI7QCF", "Jane", "Doe")
+            .with_record("K6ABC", "Al", "Smith");
+        let (parser, metrics) = (CallsignParser::new(), Metrics::new());
+        let pipeline = Pipeline {
+            source: &source,
+            builder: RosterBuilder {
+                parser: &parser,
+                qrz_client: Some(&lookup),
+                radioid_client: None,
+                aprs_client: None,
+                dxcc: None,
+                lotw: None,
+                alerts: None,
+                metrics: &metrics,
+                excluded_user_ids: &[],
+            },
+        };
+
+        let generation = pipeline
+            .generate(
+                &guild_config(r#"suffix_role_pattern = "^(ARRL)$""#),
+                Some(4),
+                HashMap::from([(2, "73".to_string()), (5, "QRP".to_string())]),
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(generation.members.len(), 5);
+        let listed: Vec<_> = generation
+            .roster
+            .entries
+            .iter()
+            .map(|e| (e.callsign.as_str(), e.name.as_str(), e.suffix.as_str()))
+            .collect();
+        // W6JSV isn't in the mock so keeps the Discord name; the role suffix applies.
+        // K6ABC has the role too, but the suffix they chose wins.
+        assert_eq!(
+            listed,
+            vec![
+                ("K6ABC", "Al", "QRP"),
+                ("KI7QCF", "Jane", "73"),
+                ("W6JSV", "Jay", "ARRL")
+            ]
+        );
+        assert_eq!(
+            generation.roster.unresolved,
+            HashSet::from(["W6JSV".to_string()])
+        );
+        assert!(generation.content.starts_with("# Test Club\n"));
+        assert_eq!(generation.content, generation.stable_content);
+    }
+
+    #[tokio::test]
+    async fn test_generate_without_lookup_or_guild_name() {
+        let source = MockMembers::default().with_member(1, "W6JSV Jay", &[]);
+        let (parser, metrics) = (CallsignParser::new(), Metrics::new());
+        let pipeline = Pipeline {
+            source: &source,
+            builder: RosterBuilder {
+                parser: &parser,
+                qrz_client: None,
+                radioid_client: None,
+                aprs_client: None,
+                dxcc: None,
+                lotw: None,
+                alerts: None,
+                metrics: &metrics,
+                excluded_user_ids: &[],
+            },
+        };
+
+        let mut config = guild_config("");
+        config.output.header = None;
+        let generation = pipeline
+            .generate(&config, None, HashMap::new(), HashMap::new(), None)
+            .await
+            .unwrap();
+        assert_eq!(generation.content, "W6JSV 📻 Jay \n");
+    }
+
+    #[tokio::test]
+    async fn test_generate_resolves_dxcc_without_lookup() {
+        let source = MockMembers::default()
+            .with_member(1, "VE3ABC Ann", &[])
+            .with_member(2, "W6JSV Jay", &[]);
+        let (parser, metrics, dxcc) = (CallsignParser::new(), Metrics::new(), DxccTable::builtin());
+        let pipeline = Pipeline {
+            source: &source,
+            builder: RosterBuilder {
+                parser: &parser,
+                qrz_client: None,
+                radioid_client: None,
+                aprs_client: None,
+                dxcc: Some(Arc::new(dxcc)),
+                lotw: None,
+                alerts: None,
+                metrics: &metrics,
+                excluded_user_ids: &[],
+            },
+        };
+
+        let mut config = guild_config("");
+        config.output.header = None;
+        config.output.country_flags = true;
+        let generation = pipeline
+            .generate(&config, None, HashMap::new(), HashMap::new(), None)
+            .await
+            .unwrap();
+        let ann = &generation.roster.entries[0];
+        assert_eq!(ann.country.as_deref(), Some("Canada"));
+        assert_eq!(ann.dxcc.as_deref(), Some("Canada"));
+        assert_eq!(ann.continent.as_deref(), Some("NA"));
+        assert_eq!(generation.content, "VE3ABC 📻 Ann 🇨🇦\nW6JSV 📻 Jay 🇺🇸\n");
+    }
+
+    #[tokio::test]
+    async fn test_generate_marks_and_hides_unpaid_dues() {
+        let source = MockMembers::default()
+            .with_member(1, "W6JSV Jay", &[])
+            .with_member(2, "KI7QCF Jane", &[]);
+        let (parser, metrics) = (CallsignParser::new(), Metrics::new());
+        let pipeline = Pipeline {
+            source: &source,
+            builder: RosterBuilder {
+                parser: &parser,
+                qrz_client: None,
+                radioid_client: None,
+                aprs_client: None,
+                dxcc: None,
+                lotw: None,
+                alerts: None,
+                metrics: &metrics,
+                excluded_user_ids: &[],
+            },
+        };
+
+        let mut config = guild_config("[dues]\npaid_marker = \"✅\"\nunpaid_marker = \"⏳\"");
+        config.output.header = None;
+        let generation = pipeline
+            .generate(
+                &config,
+                None,
+                HashMap::new(),
+                HashMap::new(),
+                Some(HashSet::from([1])),
+            )
+            .await
+            .unwrap();
+        assert_eq!(generation.content, "KI7QCF 📻 Jane ⏳\nW6JSV 📻 Jay ✅\n");
+
+        config.dues.as_mut().unwrap().hide_unpaid = true;
+        let generation = pipeline
+            .generate(
+                &config,
+                None,
+                HashMap::new(),
+                HashMap::new(),
+                Some(HashSet::from([1])),
+            )
+            .await
+            .unwrap();
+        assert_eq!(generation.content, "W6JSV 📻 Jay ✅\n");
+        // Still checked for nickname enforcement
+        assert_eq!(generation.roster.by_user.len(), 2);
+    }
+}
@@ -0,0 +1,263 @@
+use crate::config::PropagationConfig;
+use anyhow::{Context, Result};
+use regex::Regex;
+use reqwest::header::USER_AGENT;
+use serenity::all::{CreateEmbed, CreateEmbedFooter};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Solar indices and HF band conditions, as published by hamqsl.com
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolarReport {
+    /// When N0NBH last updated the data, e.g. "16 Oct 2026 1243 GMT"
+    pub updated: Option<String>,
+    pub solar_flux: Option<String>,
+    pub a_index: Option<String>,
+    pub k_index: Option<String>,
+    pub sunspots: Option<String>,
+    pub xray: Option<String>,
+    pub geomag_field: Option<String>,
+    pub signal_noise: Option<String>,
+    /// Band groups in published order, e.g. "80m-40m"
+    pub bands: Vec<BandConditions>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandConditions {
+    pub name: String,
+    pub day: Option<String>,
+    pub night: Option<String>,
+}
+
+/// Parse hamqsl.com's `solarxml.php`. The format is flat enough that each value is
+/// pulled out by tag name rather than with a full XML parser.
+pub fn parse(xml: &str) -> Result<SolarReport> {
+    if !xml.contains("<solardata>") {
+        anyhow::bail!("Solar data has no <solardata> element");
+    }
+    let band = Regex::new(r#"<band name="([^"]+)" time="(day|night)">\s*([^<]*?)\s*</band>"#)
+        .expect("Failed to compile band regex");
+
+    let mut bands: Vec<BandConditions> = Vec::new();
+    for captures in band.captures_iter(xml) {
+        let name = &captures[1];
+        let condition = Some(captures[3].to_string()).filter(|c| !c.is_empty());
+        let index = match bands.iter().position(|b| b.name == name) {
+            Some(index) => index,
+            None => {
+                bands.push(BandConditions {
+                    name: name.to_string(),
+                    day: None,
+                    night: None,
+                });
+                bands.len() - 1
+            }
+        };
+        match &captures[2] {
+            "day" => bands[index].day = condition,
+            _ => bands[index].night = condition,
+        }
+    }
+
+    Ok(SolarReport {
+        updated: tag(xml, "updated"),
+        solar_flux: tag(xml, "solarflux"),
+        a_index: tag(xml, "aindex"),
+        k_index: tag(xml, "kindex"),
+        sunspots: tag(xml, "sunspots"),
+        xray: tag(xml, "xray"),
+        geomag_field: tag(xml, "geomagfield"),
+        signal_noise: tag(xml, "signalnoise"),
+        bands,
+    })
+}
+
+/// The trimmed text of the first `<name>...</name>`, if present and not empty
+fn tag(xml: &str, name: &str) -> Option<String> {
+    let open = format!("<{}>", name);
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&format!("</{}>", name))?;
+    let value = xml[start..end].trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Name/value pairs for the embed, skipping anything hamqsl.com didn't report
+pub fn fields(report: &SolarReport) -> Vec<(String, String, bool)> {
+    let indices = [
+        ("Solar flux", &report.solar_flux),
+        ("A index", &report.a_index),
+        ("K index", &report.k_index),
+        ("Sunspots", &report.sunspots),
+        ("X-ray", &report.xray),
+        ("Geomagnetic field", &report.geomag_field),
+        ("Noise", &report.signal_noise),
+    ];
+    let mut fields: Vec<(String, String, bool)> = indices
+        .into_iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.clone()?, true)))
+        .collect();
+
+    let unknown = || "?".to_string();
+    for band in &report.bands {
+        fields.push((
+            band.name.clone(),
+            format!(
+                "Day: {}\nNight: {}",
+                band.day.clone().unwrap_or_else(unknown),
+                band.night.clone().unwrap_or_else(unknown)
+            ),
+            true,
+        ));
+    }
+    fields
+}
+
+pub fn embed(report: &SolarReport) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .title("☀️ Solar and band conditions")
+        .url("https://www.hamqsl.com/solar.html")
+        .fields(fields(report));
+    if let Some(updated) = &report.updated {
+        embed = embed.footer(CreateEmbedFooter::new(format!(
+            "N0NBH, updated {}",
+            updated
+        )));
+    }
+    embed
+}
+
+/// Fetches the solar report, caching it since hamqsl.com only updates every few hours
+pub struct PropagationClient {
+    client: reqwest::Client,
+    url: String,
+    cache: Mutex<Option<(Instant, SolarReport)>>,
+    cache_ttl: Duration,
+}
+
+impl PropagationClient {
+    pub fn new(config: &PropagationConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: config.url.clone(),
+            cache: Mutex::new(None),
+            cache_ttl: Duration::from_secs(config.cache_ttl_secs),
+        }
+    }
+
+    pub async fn report(&self) -> Result<SolarReport> {
+        if let Some(report) = self.cached() {
+            debug!("Solar data cache hit");
+            return Ok(report);
+        }
+
+        debug!("Fetching solar data from {}", self.url);
+        let response = self
+            .client
+            .get(&self.url)
+            .header(USER_AGENT, "discord-callsign-bot")
+            .send()
+            .await
+            .context("Failed to send request for solar data")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Solar data returned error {}", response.status());
+        }
+
+        let body = response.text().await.context("Failed to read solar data")?;
+        let report = parse(&body)?;
+
+        *self.cache.lock().expect("Solar data cache lock poisoned") =
+            Some((Instant::now(), report.clone()));
+
+        Ok(report)
+    }
+
+    fn cached(&self) -> Option<SolarReport> {
+        let cache = self.cache.lock().expect("Solar data cache lock poisoned");
+        cache
+            .as_ref()
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < self.cache_ttl)
+            .map(|(_, report)| report.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOLAR_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<solar>
+<solardata>
+<source url="http://www.hamqsl.com/solar.html">N0NBH</source>
+<updated> 16 Oct 2026 1243 GMT</updated>
+<solarflux>152</solarflux>
+<aindex> 8</aindex>
+<kindex> 2</kindex>
+<kindexnt>No Report</kindexnt>
+<xray> B5.6</xray>
+<sunspots>121</sunspots>
+<geomagfield>QUIET</geomagfield>
+<signalnoise>S0-S1</signalnoise>
+<calculatedconditions>
+<band name="80m-40m" time="day">Fair</band>
+<band name="30m-20m" time="day">Good</band>
+<band name="80m-40m" time="night">Good</band>
+<band name="30m-20m" time="night">Fair</band>
+<band name="12m-10m" time="day">Poor</band>
+</calculatedconditions>
+</solardata>
+</solar>"#;
+
+    #[test]
+    fn test_parse() {
+        let report = parse(SOLAR_XML).unwrap();
+        assert_eq!(report.updated.as_deref(), Some("16 Oct 2026 1243 GMT"));
+        assert_eq!(report.solar_flux.as_deref(), Some("152"));
+        assert_eq!(report.a_index.as_deref(), Some("8"));
+        assert_eq!(report.k_index.as_deref(), Some("2"));
+        assert_eq!(report.geomag_field.as_deref(), Some("QUIET"));
+        assert_eq!(
+            report.bands,
+            vec![
+                BandConditions {
+                    name: "80m-40m".to_string(),
+                    day: Some("Fair".to_string()),
+                    night: Some("Good".to_string()),
+                },
+                BandConditions {
+                    name: "30m-20m".to_string(),
+                    day: Some("Good".to_string()),
+                    night: Some("Fair".to_string()),
+                },
+                BandConditions {
+                    name: "12m-10m".to_string(),
+                    day: Some("Poor".to_string()),
+                    night: None,
+                },
+            ]
+        );
+
+        assert!(parse("<html>Service unavailable</html>").is_err());
+    }
+
+    #[test]
+    fn test_fields() {
+        let mut report = parse(SOLAR_XML).unwrap();
+        report.xray = None;
+        let fields = fields(&report);
+        assert_eq!(
+            fields[0],
+            ("Solar flux".to_string(), "152".to_string(), true)
+        );
+        assert!(!fields.iter().any(|(name, _, _)| name == "X-ray"));
+        assert_eq!(
+            fields.last().unwrap(),
+            &(
+                "12m-10m".to_string(),
+                "Day: Poor\nNight: ?".to_string(),
+                true
+            )
+        );
+    }
+}
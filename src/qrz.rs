@@ -1,22 +1,201 @@
+use crate::backoff::Backoff;
 use crate::config::QrzConfig;
-use anyhow::{Context, Result};
-use qrz_xml::{ApiVersion, QrzXmlClient};
-use tracing::{debug, info};
+use crate::error::{Error, Result, ResultExt};
+use crate::output::html::html_to_text;
+use crate::output::write_atomically;
+use chrono::{NaiveDate, Utc};
+use qrz_xml::{ApiVersion, QrzXmlClient, QrzXmlError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Usage is written to the `UsageStore` every this many requests, and when the quota runs out,
+/// so a restart undercounts by fewer than this
+const PERSIST_USAGE_EVERY: u32 = 10;
 
 pub struct QrzClient {
     client: QrzXmlClient,
+    cache: Mutex<HashMap<String, (Instant, CallsignInfo)>>,
+    cache_ttl: Duration,
+    /// When QRZ last said each callsign doesn't exist, kept apart from `cache` so
+    /// misses expire sooner: new licensees show up within days
+    not_found: Mutex<HashMap<String, Instant>>,
+    not_found_ttl: Duration,
+    max_retries: u32,
+    backoff: Backoff,
+    limiter: QuotaLimiter,
+    /// Where the day's usage is persisted; `None` for one-off clients like `validate`
+    usage_store: Option<Arc<dyn UsageStore>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CallsignInfo {
     pub fname: Option<String>,
     pub name: Option<String>,
     pub nickname: Option<String>,
+    /// Hours offset from UTC, as reported by QRZ (e.g. "-8")
+    pub gmt_offset: Option<String>,
+    /// "Y" if the operator's location observes daylight saving time
+    pub dst: Option<String>,
+    /// URL of the operator's primary profile image
+    pub image: Option<String>,
+    /// City of the mailing address (QRZ `addr2`)
+    pub city: Option<String>,
+    /// Maidenhead grid square
+    pub grid: Option<String>,
+    /// Latitude and longitude in degrees, usually geocoded from the address
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    /// US state (or province) abbreviation
+    pub state: Option<String>,
+    pub country: Option<String>,
+    /// License class, e.g. "E" (Extra), "G" (General), or "T" (Technician)
+    pub class: Option<String>,
+    /// When the current license took effect (QRZ `efdate`), e.g. after an upgrade
+    pub license_effective: Option<NaiveDate>,
+    /// When the current license expires (QRZ `expdate`)
+    pub license_expires: Option<NaiveDate>,
+}
+
+/// QRZ reports a dead session key in a few ways; some requests only surface it
+/// as an API error message such as "Invalid session key"
+pub fn is_session_error(error: &QrzXmlError) -> bool {
+    match error {
+        QrzXmlError::ApiError { message } => message.to_lowercase().contains("session"),
+        other => other.should_reauthenticate(),
+    }
+}
+
+/// Requests made to QRZ on one UTC day; persisted so restarts don't reset the count
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QrzUsage {
+    pub date: NaiveDate,
+    pub lookups: u32,
+}
+
+/// Where `QrzUsage` is kept between runs; the bot's state file in production
+pub trait UsageStore: Send + Sync {
+    fn qrz_usage(&self) -> Option<QrzUsage>;
+    fn set_qrz_usage(&self, usage: QrzUsage) -> anyhow::Result<()>;
+}
+
+/// Whether a request to QRZ may go ahead
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Acquire {
+    Granted(QrzUsage),
+    /// The per-minute rate is used up; try again after this long
+    Wait(Duration),
+    /// The day's quota is used up; `first` is set for the first refusal of the day
+    Exhausted {
+        first: bool,
+    },
+}
+
+/// Paces requests with a token bucket holding up to a minute's worth, and refuses
+/// them once the day's quota (per `[qrz] tier`) is used
+pub struct QuotaLimiter {
+    per_minute: u32,
+    daily_limit: u32,
+    state: Mutex<LimiterState>,
+}
+
+struct LimiterState {
+    tokens: f64,
+    refilled_at: Instant,
+    usage: QrzUsage,
+    exhausted_on: Option<NaiveDate>,
+}
+
+impl QuotaLimiter {
+    /// `usage` is what was persisted before a restart, if anything
+    pub fn new(
+        per_minute: u32,
+        daily_limit: u32,
+        usage: Option<QrzUsage>,
+        now: Instant,
+        today: NaiveDate,
+    ) -> Self {
+        let usage = usage
+            .filter(|usage| usage.date == today)
+            .unwrap_or(QrzUsage {
+                date: today,
+                lookups: 0,
+            });
+        Self {
+            per_minute,
+            daily_limit,
+            state: Mutex::new(LimiterState {
+                tokens: per_minute as f64,
+                refilled_at: now,
+                usage,
+                exhausted_on: None,
+            }),
+        }
+    }
+
+    pub fn daily_limit(&self) -> u32 {
+        self.daily_limit
+    }
+
+    pub fn try_acquire(&self, now: Instant, today: NaiveDate) -> Acquire {
+        let mut state = self.state.lock().expect("QRZ limiter lock poisoned");
+        if state.usage.date != today {
+            state.usage = QrzUsage {
+                date: today,
+                lookups: 0,
+            };
+        }
+        if state.usage.lookups >= self.daily_limit {
+            let first = state.exhausted_on != Some(today);
+            state.exhausted_on = Some(today);
+            return Acquire::Exhausted { first };
+        }
+
+        let per_second = self.per_minute as f64 / 60.0;
+        let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * per_second).min(self.per_minute as f64);
+        state.refilled_at = now;
+        if state.tokens < 1.0 {
+            return Acquire::Wait(Duration::from_secs_f64((1.0 - state.tokens) / per_second));
+        }
+
+        state.tokens -= 1.0;
+        state.usage.lookups += 1;
+        Acquire::Granted(state.usage)
+    }
+}
+
+/// A cached lookup as saved to `[qrz] cache_file`
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedLookup {
+    callsign: String,
+    /// Unix timestamp (seconds) of the lookup
+    fetched_at: i64,
+    info: CallsignInfo,
+}
+
+/// When a saved lookup was fetched, as an `Instant`, or `None` if it's no longer fresh
+fn restored_at(fetched_at: i64, now_unix: i64, now: Instant, ttl: Duration) -> Option<Instant> {
+    let age = Duration::from_secs(u64::try_from(now_unix - fetched_at).unwrap_or_default());
+    if age >= ttl {
+        return None;
+    }
+    now.checked_sub(age)
+}
+
+/// QRZ dates are `YYYY-MM-DD`; unknown dates are empty or `0000-00-00`
+fn parse_date(value: Option<&str>) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value?.trim(), "%Y-%m-%d").ok()
 }
 
 impl QrzClient {
-    /// Create a new QRZ client and authenticate with credentials
-    pub async fn new(config: &QrzConfig) -> Result<Self> {
+    /// Create a new QRZ client and authenticate with credentials. With `usage_store`, the
+    /// day's usage picks up where the last run left off and is saved as it grows.
+    pub async fn new(config: &QrzConfig, usage_store: Option<Arc<dyn UsageStore>>) -> Result<Self> {
         info!("Initializing QRZ XML API client");
 
         let client = QrzXmlClient::new(&config.username, &config.password, ApiVersion::Current)
@@ -24,30 +203,299 @@ impl QrzClient {
 
         info!("Successfully authenticated with QRZ.com");
 
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            cache: Mutex::new(HashMap::new()),
+            cache_ttl: Duration::from_secs(config.cache_ttl_secs),
+            not_found: Mutex::new(HashMap::new()),
+            not_found_ttl: Duration::from_secs(config.not_found_ttl_secs),
+            max_retries: config.max_retries,
+            backoff: Backoff::new(
+                Duration::from_millis(config.retry_initial_backoff_ms),
+                Duration::from_millis(config.retry_max_backoff_ms),
+            ),
+            limiter: QuotaLimiter::new(
+                config.lookups_per_minute(),
+                config.daily_limit(),
+                usage_store.as_ref().and_then(|store| store.qrz_usage()),
+                Instant::now(),
+                Utc::now().date_naive(),
+            ),
+            usage_store,
+        })
+    }
+
+    /// Wait for the rate limiter, failing with `Error::RateLimited` once the day's quota is
+    /// used up so callers fall back to parsed names without contacting QRZ
+    async fn acquire(&self) -> Result<()> {
+        loop {
+            match self
+                .limiter
+                .try_acquire(Instant::now(), Utc::now().date_naive())
+            {
+                Acquire::Granted(usage) => {
+                    if usage.lookups % PERSIST_USAGE_EVERY == 0
+                        || usage.lookups == self.limiter.daily_limit()
+                    {
+                        self.persist_usage(usage);
+                    }
+                    return Ok(());
+                }
+                Acquire::Wait(delay) => {
+                    debug!("QRZ rate limit reached; waiting {:?}", delay);
+                    tokio::time::sleep(delay).await;
+                }
+                Acquire::Exhausted { first } => {
+                    if first {
+                        warn!(
+                            "QRZ daily quota of {} requests used up; skipping lookups until midnight UTC",
+                            self.limiter.daily_limit()
+                        );
+                    }
+                    return Err(Error::RateLimited(format!(
+                        "QRZ daily quota of {} requests is used up",
+                        self.limiter.daily_limit()
+                    )));
+                }
+            }
+        }
+    }
+
+    fn persist_usage(&self, usage: QrzUsage) {
+        if let Some(store) = &self.usage_store {
+            if let Err(e) = store.set_qrz_usage(usage) {
+                warn!("Failed to save QRZ usage: {:?}", e);
+            }
+        }
+    }
+
+    /// Authenticate against QRZ to confirm the credentials work
+    pub async fn verify(&self) -> Result<()> {
+        self.client
+            .authenticate()
+            .await
+            .context("QRZ authentication failed")
     }
 
-    /// Lookup a callsign and retrieve name information
+    /// Lookup a callsign and retrieve name information, using cached results (and
+    /// cached "not found" answers) while fresh
     pub async fn lookup_callsign(&self, callsign: &str) -> Result<CallsignInfo> {
+        if let Some(info) = self.cached(callsign) {
+            debug!("QRZ cache hit for {}", callsign);
+            return Ok(info);
+        }
+        if self.recently_not_found(callsign) {
+            debug!("QRZ negative cache hit for {}", callsign);
+            return Err(Error::NotFound(format!(
+                "{} was not found on QRZ recently",
+                callsign
+            )));
+        }
+
         debug!("Looking up callsign: {}", callsign);
 
-        let record = self
-            .client
-            .lookup_callsign(callsign)
-            .await
-            .context("Failed to lookup callsign")?;
+        let record = match self.lookup_with_retry(callsign).await {
+            Ok(record) => record,
+            Err(e) => {
+                if matches!(e, Error::NotFound(_)) {
+                    self.remember_not_found(callsign);
+                }
+                return Err(e);
+            }
+        };
 
         let info = CallsignInfo {
             fname: record.fname,
             name: record.name,
             nickname: record.nickname,
+            gmt_offset: record.gmt_offset,
+            dst: record.dst,
+            image: record.image,
+            city: record.addr2,
+            grid: record.grid,
+            lat: record.lat,
+            lon: record.lon,
+            state: record.state,
+            country: record.country,
+            class: record.class,
+            license_effective: parse_date(record.efdate.as_deref()),
+            license_expires: parse_date(record.expdate.as_deref()),
         };
 
         debug!("QRZ lookup result for {}: {:?}", callsign, info);
 
+        self.cache
+            .lock()
+            .expect("QRZ cache lock poisoned")
+            .insert(callsign.to_string(), (Instant::now(), info.clone()));
+
         Ok(info)
     }
 
+    /// Look up a callsign, retrying transient failures with jittered backoff and
+    /// re-authenticating once when QRZ reports the session key has expired
+    async fn lookup_with_retry(&self, callsign: &str) -> Result<qrz_xml::CallsignInfo> {
+        let mut attempt = 0;
+        let mut reauthenticated = false;
+        loop {
+            self.acquire().await?;
+            let error = match self.client.lookup_callsign(callsign).await {
+                Ok(record) => return Ok(record),
+                Err(e) => e,
+            };
+
+            if is_session_error(&error) {
+                if reauthenticated {
+                    return Err(error).context("QRZ session still invalid after re-authenticating");
+                }
+                self.renew_session(callsign).await?;
+                reauthenticated = true;
+                continue;
+            }
+
+            if !error.is_retryable() || attempt >= self.max_retries {
+                return Err(error).context("Failed to lookup callsign");
+            }
+            attempt += 1;
+
+            let delay = self.backoff.jittered(attempt);
+            warn!(
+                "QRZ lookup for {} failed ({}); retry {}/{} in {:?}",
+                callsign, error, attempt, self.max_retries, delay
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn renew_session(&self, context: &str) -> Result<()> {
+        warn!("QRZ session expired ({}); re-authenticating", context);
+        self.client
+            .reauthenticate()
+            .await
+            .context("QRZ re-authentication failed")
+    }
+
+    /// Fetch an operator's QRZ biography as plain text
+    pub async fn lookup_biography(&self, callsign: &str) -> Result<String> {
+        debug!("Fetching biography for: {}", callsign);
+
+        self.acquire().await?;
+        let biography = match self.client.lookup_biography(callsign).await {
+            Err(e) if is_session_error(&e) => {
+                self.renew_session(callsign).await?;
+                self.acquire().await?;
+                self.client.lookup_biography(callsign).await
+            }
+            result => result,
+        }
+        .context("Failed to fetch biography")?;
+
+        Ok(html_to_text(biography.html()))
+    }
+
+    /// Exercise the session with a lookup of `ping_callsign` every `interval`,
+    /// renewing it as soon as QRZ reports it expired rather than on the next real lookup
+    pub fn spawn_keepalive(self: &Arc<Self>, interval: Duration, ping_callsign: String) {
+        let qrz = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            // The first tick fires immediately, right after the initial login
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                // A spent quota means no lookups to keep the session alive for
+                if qrz.acquire().await.is_err() {
+                    continue;
+                }
+                match qrz.client.lookup_callsign(&ping_callsign).await {
+                    // Not found still proves the session key is valid
+                    Ok(_) | Err(QrzXmlError::CallsignNotFound { .. }) => {
+                        debug!("QRZ keepalive ok");
+                    }
+                    Err(e) if is_session_error(&e) => {
+                        if let Err(e) = qrz.renew_session("keepalive").await {
+                            warn!("{:?}", e);
+                        }
+                    }
+                    Err(e) => warn!("QRZ keepalive failed: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Fill the cache from `[qrz] cache_file`, skipping expired lookups; returns how many
+    /// were restored
+    pub fn load_cache(&self, path: &str) -> Result<usize> {
+        let saved: Vec<SavedLookup> = std::fs::read_to_string(path)
+            .and_then(|contents| serde_json::from_str(&contents).map_err(io::Error::from))
+            .map_err(|source| Error::Io {
+                context: format!("Failed to load QRZ cache {}", path),
+                source,
+            })?;
+
+        let (now, now_unix) = (Instant::now(), Utc::now().timestamp());
+        let mut cache = self.cache.lock().expect("QRZ cache lock poisoned");
+        let before = cache.len();
+        for lookup in saved {
+            if let Some(fetched_at) = restored_at(lookup.fetched_at, now_unix, now, self.cache_ttl)
+            {
+                cache.insert(lookup.callsign, (fetched_at, lookup.info));
+            }
+        }
+        Ok(cache.len() - before)
+    }
+
+    /// Write the cache's fresh lookups to `[qrz] cache_file`; returns how many were saved
+    pub fn save_cache(&self, path: &str) -> Result<usize> {
+        let now_unix = Utc::now().timestamp();
+        let saved: Vec<SavedLookup> = self
+            .cache
+            .lock()
+            .expect("QRZ cache lock poisoned")
+            .iter()
+            .filter(|(_, (fetched_at, _))| fetched_at.elapsed() < self.cache_ttl)
+            .map(|(callsign, (fetched_at, info))| SavedLookup {
+                callsign: callsign.clone(),
+                fetched_at: now_unix - fetched_at.elapsed().as_secs() as i64,
+                info: info.clone(),
+            })
+            .collect();
+
+        serde_json::to_string(&saved)
+            .map_err(io::Error::from)
+            .and_then(|contents| write_atomically(Path::new(path), &contents))
+            .map_err(|source| Error::Io {
+                context: format!("Failed to save QRZ cache {}", path),
+                source,
+            })?;
+        Ok(saved.len())
+    }
+
+    fn cached(&self, callsign: &str) -> Option<CallsignInfo> {
+        let cache = self.cache.lock().expect("QRZ cache lock poisoned");
+        cache
+            .get(callsign)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < self.cache_ttl)
+            .map(|(_, info)| info.clone())
+    }
+
+    fn recently_not_found(&self, callsign: &str) -> bool {
+        let not_found = self.not_found.lock().expect("QRZ cache lock poisoned");
+        not_found
+            .get(callsign)
+            .is_some_and(|at| at.elapsed() < self.not_found_ttl)
+    }
+
+    fn remember_not_found(&self, callsign: &str) {
+        if self.not_found_ttl.is_zero() {
+            return;
+        }
+        let mut not_found = self.not_found.lock().expect("QRZ cache lock poisoned");
+        // Typos and made-up calls never come back, so drop expired ones as we go
+        not_found.retain(|_, at| at.elapsed() < self.not_found_ttl);
+        not_found.insert(callsign.to_string(), Instant::now());
+    }
+
     /// Get the best display name from QRZ data
     /// Prioritizes: nickname > fname > name
     pub fn get_display_name(info: &CallsignInfo) -> Option<String> {
@@ -77,12 +525,124 @@ impl QrzClient {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_session_error() {
+        assert!(is_session_error(&QrzXmlError::SessionExpired));
+        assert!(is_session_error(&QrzXmlError::NoSessionKey));
+        assert!(is_session_error(&QrzXmlError::api_error(
+            "Invalid session key"
+        )));
+        assert!(!is_session_error(&QrzXmlError::api_error(
+            "Subscription required"
+        )));
+        assert!(!is_session_error(&QrzXmlError::callsign_not_found(
+            "N0CALL"
+        )));
+    }
+
+    #[test]
+    fn test_quota_limiter_paces_requests() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let start = Instant::now();
+        let limiter = QuotaLimiter::new(60, 1000, None, start, today);
+
+        // A minute's worth can go at once, then one per second
+        for _ in 0..60 {
+            assert!(matches!(
+                limiter.try_acquire(start, today),
+                Acquire::Granted(_)
+            ));
+        }
+        assert_eq!(
+            limiter.try_acquire(start, today),
+            Acquire::Wait(Duration::from_secs(1))
+        );
+        assert!(matches!(
+            limiter.try_acquire(start + Duration::from_secs(1), today),
+            Acquire::Granted(QrzUsage { lookups: 61, .. })
+        ));
+    }
+
+    #[test]
+    fn test_quota_limiter_daily_limit() {
+        let yesterday = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let start = Instant::now();
+        let persisted = QrzUsage {
+            date: today,
+            lookups: 99,
+        };
+        let limiter = QuotaLimiter::new(60, 100, Some(persisted), start, today);
+
+        assert_eq!(
+            limiter.try_acquire(start, today),
+            Acquire::Granted(QrzUsage {
+                date: today,
+                lookups: 100
+            })
+        );
+        assert_eq!(
+            limiter.try_acquire(start, today),
+            Acquire::Exhausted { first: true }
+        );
+        assert_eq!(
+            limiter.try_acquire(start, today),
+            Acquire::Exhausted { first: false }
+        );
+
+        // The quota resets at midnight UTC
+        let tomorrow = today.succ_opt().unwrap();
+        assert_eq!(
+            limiter.try_acquire(start, tomorrow),
+            Acquire::Granted(QrzUsage {
+                date: tomorrow,
+                lookups: 1
+            })
+        );
+
+        // Usage persisted on an earlier day doesn't count
+        let stale = QrzUsage {
+            date: yesterday,
+            lookups: 100,
+        };
+        let limiter = QuotaLimiter::new(60, 100, Some(stale), start, today);
+        assert!(matches!(
+            limiter.try_acquire(start, today),
+            Acquire::Granted(QrzUsage { lookups: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_restored_at() {
+        let now = Instant::now();
+        let ttl = Duration::from_secs(3600);
+        assert_eq!(
+            restored_at(1_000, 1_060, now, ttl),
+            now.checked_sub(Duration::from_secs(60))
+        );
+        assert_eq!(restored_at(1_000, 4_600, now, ttl), None);
+        // A clock that went backwards makes the lookup brand new rather than invalid
+        assert_eq!(restored_at(1_060, 1_000, now, ttl), Some(now));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(
+            parse_date(Some("2031-04-15")),
+            NaiveDate::from_ymd_opt(2031, 4, 15)
+        );
+        assert_eq!(parse_date(Some("0000-00-00")), None);
+        assert_eq!(parse_date(Some("")), None);
+        assert_eq!(parse_date(None), None);
+    }
+
     #[test]
     fn test_display_name_nickname_priority() {
         let info = CallsignInfo {
             fname: Some("John".to_string()),
             name: Some("Smith".to_string()),
             nickname: Some("Jay".to_string()),
+            ..Default::default()
         };
         assert_eq!(QrzClient::get_display_name(&info), Some("Jay".to_string()));
     }
@@ -93,6 +653,7 @@ mod tests {
             fname: Some("John".to_string()),
             name: Some("Smith".to_string()),
             nickname: None,
+            ..Default::default()
         };
         assert_eq!(QrzClient::get_display_name(&info), Some("John".to_string()));
     }
@@ -103,6 +664,7 @@ mod tests {
             fname: None,
             name: Some("Smith".to_string()),
             nickname: None,
+            ..Default::default()
         };
         assert_eq!(
             QrzClient::get_display_name(&info),
@@ -116,6 +678,7 @@ mod tests {
             fname: None,
             name: None,
             nickname: None,
+            ..Default::default()
         };
         assert_eq!(QrzClient::get_display_name(&info), None);
     }
@@ -126,6 +689,7 @@ mod tests {
             fname: Some("".to_string()),
             name: Some("".to_string()),
             nickname: Some("".to_string()),
+            ..Default::default()
         };
         assert_eq!(QrzClient::get_display_name(&info), None);
     }
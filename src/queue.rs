@@ -0,0 +1,183 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::sync::Mutex;
+use tokio::sync::Notify;
+
+/// What happened to an event offered to the queue
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Enqueued {
+    /// First pending event for this member
+    New,
+    /// The member already had a pending event; they're merged
+    Coalesced,
+    /// The queue was full; the guild still regenerates but the member isn't tracked
+    Dropped,
+}
+
+impl Enqueued {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::New => "new",
+            Self::Coalesced => "coalesced",
+            Self::Dropped => "dropped",
+        }
+    }
+}
+
+/// Events waiting for a guild's next regeneration
+#[derive(Debug, Default)]
+pub struct PendingGuild {
+    pub users: HashSet<u64>,
+    pub causes: BTreeSet<&'static str>,
+}
+
+impl PendingGuild {
+    /// Metrics label for the regeneration this batch triggers
+    pub fn cause(&self) -> &'static str {
+        match self.causes.len() {
+            1 => self.causes.iter().next().copied().unwrap_or("coalesced"),
+            _ => "coalesced",
+        }
+    }
+}
+
+/// Bounded queue of member events, coalesced by guild and user ID.
+///
+/// Every regeneration rebuilds the whole guild, so many events for one guild
+/// collapse into a single regeneration. The capacity bounds how many distinct
+/// members are tracked; beyond it events are dropped but their guild is still
+/// marked for regeneration, so no change is ever lost.
+pub struct EventQueue {
+    capacity: usize,
+    pending: Mutex<BTreeMap<u64, PendingGuild>>,
+    notify: Notify,
+}
+
+impl EventQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            pending: Mutex::new(BTreeMap::new()),
+            notify: Notify::new(),
+        }
+    }
+
+    pub fn push(&self, guild_id: u64, user_id: u64, cause: &'static str) -> Enqueued {
+        let mut pending = self.pending.lock().expect("event queue lock poisoned");
+        let depth = Self::depth_of(&pending);
+
+        let guild = pending.entry(guild_id).or_default();
+        guild.causes.insert(cause);
+
+        let result = if guild.users.contains(&user_id) {
+            Enqueued::Coalesced
+        } else if depth >= self.capacity {
+            Enqueued::Dropped
+        } else {
+            guild.users.insert(user_id);
+            Enqueued::New
+        };
+
+        drop(pending);
+        self.notify.notify_one();
+        result
+    }
+
+    /// Mark a guild for regeneration without tracking a member, as for scheduled runs.
+    /// This never counts toward the capacity.
+    pub fn push_guild(&self, guild_id: u64, cause: &'static str) {
+        self.pending
+            .lock()
+            .expect("event queue lock poisoned")
+            .entry(guild_id)
+            .or_default()
+            .causes
+            .insert(cause);
+        self.notify.notify_one();
+    }
+
+    /// Distinct members with pending events across all guilds
+    pub fn depth(&self) -> usize {
+        Self::depth_of(&self.pending.lock().expect("event queue lock poisoned"))
+    }
+
+    fn depth_of(pending: &BTreeMap<u64, PendingGuild>) -> usize {
+        pending.values().map(|g| g.users.len()).sum()
+    }
+
+    /// Wait until at least one guild has pending events
+    pub async fn wait_for_event(&self) {
+        loop {
+            // Register interest before checking so a push in between isn't missed
+            let notified = self.notify.notified();
+            if !self
+                .pending
+                .lock()
+                .expect("event queue lock poisoned")
+                .is_empty()
+            {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Take every pending guild, leaving the queue empty
+    pub fn take_all(&self) -> BTreeMap<u64, PendingGuild> {
+        std::mem::take(&mut *self.pending.lock().expect("event queue lock poisoned"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_coalesces_by_user() {
+        let queue = EventQueue::new(10);
+        assert_eq!(queue.push(1, 100, "member_update"), Enqueued::New);
+        assert_eq!(queue.push(1, 100, "member_update"), Enqueued::Coalesced);
+        assert_eq!(queue.push(1, 101, "member_addition"), Enqueued::New);
+        assert_eq!(queue.push(2, 100, "member_update"), Enqueued::New);
+        assert_eq!(queue.depth(), 3);
+    }
+
+    #[test]
+    fn test_push_drops_past_capacity_but_keeps_guild() {
+        let queue = EventQueue::new(1);
+        assert_eq!(queue.push(1, 100, "member_update"), Enqueued::New);
+        assert_eq!(queue.push(2, 200, "member_removal"), Enqueued::Dropped);
+        assert_eq!(queue.depth(), 1);
+
+        let pending = queue.pending.lock().unwrap();
+        assert!(pending.contains_key(&2));
+    }
+
+    #[tokio::test]
+    async fn test_push_guild_coalesces_with_member_events() {
+        let queue = EventQueue::new(1);
+        queue.push(1, 100, "member_update");
+        queue.push_guild(1, "schedule");
+        queue.push_guild(2, "schedule");
+        assert_eq!(queue.depth(), 1);
+
+        queue.wait_for_event().await;
+        let batch = queue.take_all();
+        assert_eq!(batch[&1].cause(), "coalesced");
+        assert!(batch[&2].users.is_empty());
+        assert_eq!(batch[&2].cause(), "schedule");
+    }
+
+    #[tokio::test]
+    async fn test_wait_then_take_all() {
+        let queue = EventQueue::new(10);
+        queue.push(1, 100, "member_update");
+        queue.push(1, 101, "member_removal");
+
+        queue.wait_for_event().await;
+        let batch = queue.take_all();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[&1].users.len(), 2);
+        assert_eq!(batch[&1].cause(), "coalesced");
+        assert_eq!(queue.depth(), 0);
+    }
+}
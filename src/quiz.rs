@@ -0,0 +1,310 @@
+pub use crate::config::Pool;
+use crate::config::QuizConfig;
+use anyhow::{Context, Result};
+use reqwest::header::USER_AGENT;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+/// Prefix of the answer buttons' custom IDs: `quiz:answer:<pool>:<question>:<choice>`
+pub const ANSWER_PREFIX: &str = "quiz:answer:";
+
+/// Prefix of the "next question" button's custom ID: `quiz:next:<pool>`
+pub const NEXT_PREFIX: &str = "quiz:next:";
+
+const LETTERS: [char; 4] = ['A', 'B', 'C', 'D'];
+
+/// One multiple-choice question from an NCVEC pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct Question {
+    /// e.g. "T1A01"
+    pub id: String,
+    pub text: String,
+    /// Always four, in A-D order
+    pub choices: Vec<String>,
+    /// Index into `choices`
+    pub answer: usize,
+}
+
+impl Question {
+    pub fn answer_letter(&self) -> char {
+        LETTERS[self.answer]
+    }
+
+    /// "**T1A01** (Technician)", the question, then each choice on its own line
+    pub fn prompt(&self, pool: Pool) -> String {
+        let mut lines = vec![
+            format!("**{}** ({})", self.id, pool.name()),
+            self.text.clone(),
+        ];
+        lines.push(String::new());
+        for (letter, choice) in LETTERS.iter().zip(&self.choices) {
+            lines.push(format!("**{}.** {}", letter, choice));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Parse the NCVEC plain-text pool: each question starts with a line like
+/// `T1A01 (C) [97.1]`, then the question, then `A.` through `D.`, and ends with `~~`.
+/// Questions that need a figure are skipped, since the diagrams can't be shown.
+pub fn parse_pool(text: &str) -> Vec<Question> {
+    text.split("~~").filter_map(parse_question).collect()
+}
+
+fn parse_question(block: &str) -> Option<Question> {
+    let mut lines = block.lines().map(str::trim).filter(|line| !line.is_empty());
+    // Skip anything before the header, such as the pool's title page
+    let header = lines.find(|line| is_header(line))?;
+    let (id, rest) = header.split_once(' ')?;
+    let letter = rest.trim().strip_prefix('(')?.chars().next()?;
+    let answer = LETTERS.iter().position(|l| *l == letter)?;
+
+    let mut text = Vec::new();
+    let mut choices: Vec<String> = Vec::new();
+    for line in lines {
+        let next = LETTERS.get(choices.len()).map(|l| format!("{}.", l));
+        match next {
+            Some(prefix) if line.starts_with(&prefix) => {
+                choices.push(line[prefix.len()..].trim().to_string())
+            }
+            // Wrapped lines continue the question or the last choice
+            _ => match choices.last_mut() {
+                Some(choice) => {
+                    choice.push(' ');
+                    choice.push_str(line);
+                }
+                None => text.push(line),
+            },
+        }
+    }
+
+    let text = text.join(" ");
+    if choices.len() != LETTERS.len() || text.is_empty() || text.to_lowercase().contains("figure") {
+        return None;
+    }
+    Some(Question {
+        id: id.to_string(),
+        text,
+        choices,
+        answer,
+    })
+}
+
+/// "T1A01 (C)" or "E9H11 (B) [97.3]"
+fn is_header(line: &str) -> bool {
+    let bytes = line.as_bytes();
+    bytes.len() >= 9
+        && matches!(bytes[0], b'T' | b'G' | b'E')
+        && bytes[1].is_ascii_digit()
+        && bytes[2].is_ascii_uppercase()
+        && bytes[3].is_ascii_digit()
+        && bytes[4].is_ascii_digit()
+        && bytes[5] == b' '
+        && bytes[6] == b'('
+}
+
+/// The question pools configured in `[quiz]`, loaded once at startup
+pub struct QuizPools {
+    pools: HashMap<Pool, Vec<Question>>,
+}
+
+impl QuizPools {
+    /// Read or download each configured pool; one that fails is left out with a warning
+    pub async fn load(config: &QuizConfig) -> Self {
+        let client = reqwest::Client::new();
+        let mut pools = HashMap::new();
+        for pool in Pool::ALL {
+            let Some(source) = config.source(pool) else {
+                continue;
+            };
+            match read_pool(&client, source).await {
+                Ok(questions) if !questions.is_empty() => {
+                    info!(
+                        "Loaded {} {} questions from {}",
+                        questions.len(),
+                        pool.name(),
+                        source
+                    );
+                    pools.insert(pool, questions);
+                }
+                Ok(_) => warn!("No {} questions found in {}", pool.name(), source),
+                Err(e) => warn!("Failed to load {} questions: {:?}", pool.name(), e),
+            }
+        }
+        Self { pools }
+    }
+
+    pub fn questions(&self, pool: Pool) -> &[Question] {
+        self.pools.get(&pool).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn question(&self, pool: Pool, id: &str) -> Option<&Question> {
+        self.questions(pool).iter().find(|q| q.id == id)
+    }
+}
+
+/// A pool from a URL or a file path
+async fn read_pool(client: &reqwest::Client, source: &str) -> Result<Vec<Question>> {
+    let text = if source.starts_with("https://") || source.starts_with("http://") {
+        let response = client
+            .get(source)
+            .header(USER_AGENT, "discord-callsign-bot")
+            .send()
+            .await
+            .with_context(|| format!("Failed to download question pool from {}", source))?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Downloading question pool from {} returned {}",
+                source,
+                response.status()
+            );
+        }
+        response
+            .text()
+            .await
+            .with_context(|| format!("Failed to read question pool from {}", source))?
+    } else {
+        std::fs::read_to_string(source)
+            .with_context(|| format!("Failed to read question pool: {}", source))?
+    };
+    Ok(parse_pool(&text))
+}
+
+/// A member's quiz record; kept in `Storage`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuizStats {
+    pub answered: u32,
+    pub correct: u32,
+    /// Correct answers in a row, reset by a wrong one
+    pub streak: u32,
+    pub best_streak: u32,
+}
+
+impl QuizStats {
+    pub fn record(&mut self, correct: bool) {
+        self.answered += 1;
+        if correct {
+            self.correct += 1;
+            self.streak += 1;
+            self.best_streak = self.best_streak.max(self.streak);
+        } else {
+            self.streak = 0;
+        }
+    }
+
+    /// "Streak 3 (best 5) · 12/15 correct"
+    pub fn describe(&self) -> String {
+        format!(
+            "Streak {} (best {}) · {}/{} correct",
+            self.streak, self.best_streak, self.correct, self.answered
+        )
+    }
+}
+
+/// Custom ID of the button for `choice` on a question
+pub fn answer_id(pool: Pool, question: &Question, choice: usize) -> String {
+    format!("{}{}:{}:{}", ANSWER_PREFIX, pool.id(), question.id, choice)
+}
+
+/// The pool, question ID, and choice from an answer button's custom ID
+pub fn parse_answer_id(custom_id: &str) -> Option<(Pool, &str, usize)> {
+    let mut parts = custom_id.strip_prefix(ANSWER_PREFIX)?.split(':');
+    let pool = Pool::from_id(parts.next()?)?;
+    let question = parts.next()?;
+    let choice = parts.next()?.parse().ok().filter(|c| *c < LETTERS.len())?;
+    Some((pool, question, choice))
+}
+
+/// Custom ID of the button that asks another question from `pool`
+pub fn next_id(pool: Pool) -> String {
+    format!("{}{}", NEXT_PREFIX, pool.id())
+}
+
+pub fn letter(choice: usize) -> char {
+    LETTERS[choice]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POOL: &str = "\
+2022-2026 Technician Class Question Pool
+
+SUBELEMENT T1 - COMMISSION'S RULES
+T1A01 (C) [97.1]
+Which of the following is part of the Basis and Purpose of the Amateur Radio Service?
+A. Providing personal radio communications for as many citizens as possible
+B. Providing communications for international non-profit organizations
+C. Advancing skills in the technical and communication phases of the radio art
+D. All these choices are correct
+~~
+T1A02 (C) [97.1]
+Which agency regulates and enforces the rules for the Amateur Radio Service in the
+United States?
+A. FEMA
+B. Homeland Security
+C. The FCC
+D. All these choices are
+correct
+~~
+T6C02 (A)
+What is component 1 in figure T-1?
+A. Resistor
+B. Transistor
+C. Battery
+D. Connector
+~~
+T1A03 (D) [97.119(b)(2)]
+A. Only one choice
+~~
+";
+
+    #[test]
+    fn test_parse_pool() {
+        let questions = parse_pool(POOL);
+        assert_eq!(questions.len(), 2);
+        assert_eq!(questions[0].id, "T1A01");
+        assert_eq!(questions[0].answer_letter(), 'C');
+        assert_eq!(
+            questions[1].text,
+            "Which agency regulates and enforces the rules for the Amateur Radio Service in the United States?"
+        );
+        assert_eq!(questions[1].choices[3], "All these choices are correct");
+    }
+
+    #[test]
+    fn test_prompt() {
+        let question = &parse_pool(POOL)[1];
+        assert_eq!(
+            question.prompt(Pool::Technician),
+            "**T1A02** (Technician)\n\
+             Which agency regulates and enforces the rules for the Amateur Radio Service in the United States?\n\n\
+             **A.** FEMA\n**B.** Homeland Security\n**C.** The FCC\n**D.** All these choices are correct"
+        );
+    }
+
+    #[test]
+    fn test_answer_ids() {
+        let question = &parse_pool(POOL)[0];
+        let id = answer_id(Pool::General, question, 2);
+        assert_eq!(id, "quiz:answer:general:T1A01:2");
+        assert_eq!(parse_answer_id(&id), Some((Pool::General, "T1A01", 2)));
+        assert_eq!(parse_answer_id("quiz:answer:general:T1A01:4"), None);
+        assert_eq!(parse_answer_id("quiz:answer:novice:T1A01:0"), None);
+        assert_eq!(parse_answer_id("suffix:choose"), None);
+        assert_eq!(next_id(Pool::Extra), "quiz:next:extra");
+    }
+
+    #[test]
+    fn test_quiz_stats() {
+        let mut stats = QuizStats::default();
+        for correct in [true, true, false, true] {
+            stats.record(correct);
+        }
+        assert_eq!(stats.streak, 1);
+        assert_eq!(stats.best_streak, 2);
+        assert_eq!(stats.describe(), "Streak 1 (best 2) · 3/4 correct");
+    }
+}
@@ -0,0 +1,116 @@
+use crate::config::RadioIdConfig;
+use anyhow::{Context, Result};
+use reqwest::header::USER_AGENT;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Response from RadioID.net's `/api/dmr/user/` endpoint
+#[derive(Debug, Deserialize)]
+struct UserResponse {
+    #[serde(default)]
+    results: Vec<UserRecord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserRecord {
+    id: u32,
+    callsign: String,
+}
+
+/// Looks up DMR radio IDs by callsign on RadioID.net
+pub struct RadioIdClient {
+    client: reqwest::Client,
+    url: String,
+    /// `None` results are cached too, so unregistered callsigns aren't re-queried
+    cache: Mutex<HashMap<String, (Instant, Option<u32>)>>,
+    cache_ttl: Duration,
+}
+
+impl RadioIdClient {
+    pub fn new(config: &RadioIdConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: config.url.clone(),
+            cache: Mutex::new(HashMap::new()),
+            cache_ttl: Duration::from_secs(config.cache_ttl_secs),
+        }
+    }
+
+    /// The callsign's DMR ID, or `None` if it has never registered one
+    pub async fn lookup_dmr_id(&self, callsign: &str) -> Result<Option<u32>> {
+        if let Some(dmr_id) = self.cached(callsign) {
+            debug!("RadioID cache hit for {}", callsign);
+            return Ok(dmr_id);
+        }
+
+        debug!("Looking up DMR ID for {}", callsign);
+        let response = self
+            .client
+            .get(&self.url)
+            .header(USER_AGENT, "discord-callsign-bot")
+            .query(&[("callsign", callsign)])
+            .send()
+            .await
+            .context("Failed to send request to RadioID")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("RadioID returned error {}", response.status());
+        }
+
+        let body: UserResponse = response
+            .json()
+            .await
+            .context("Failed to parse RadioID response")?;
+        let dmr_id = first_id(&body, callsign);
+
+        self.cache
+            .lock()
+            .expect("RadioID cache lock poisoned")
+            .insert(callsign.to_string(), (Instant::now(), dmr_id));
+
+        Ok(dmr_id)
+    }
+
+    fn cached(&self, callsign: &str) -> Option<Option<u32>> {
+        let cache = self.cache.lock().expect("RadioID cache lock poisoned");
+        cache
+            .get(callsign)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < self.cache_ttl)
+            .map(|(_, dmr_id)| *dmr_id)
+    }
+}
+
+/// Operators may hold several IDs (one per radio); the lowest is their first registration
+fn first_id(response: &UserResponse, callsign: &str) -> Option<u32> {
+    response
+        .results
+        .iter()
+        .filter(|record| record.callsign.eq_ignore_ascii_case(callsign))
+        .map(|record| record.id)
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_id() {
+        let response: UserResponse = serde_json::from_str(
+            r#"{"count": 3, "results": [
+                {"id": 3106002, "callsign": "W6JSV", "fname": "Jay"},
+                {"id": 3106001, "callsign": "W6JSV", "fname": "Jay"},
+                {"id": 3100001, "callsign": "W6JSVA", "fname": "Other"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(first_id(&response, "W6JSV"), Some(3106001));
+        assert_eq!(first_id(&response, "N0CALL"), None);
+
+        let empty: UserResponse = serde_json::from_str(r#"{"count": 0, "results": []}"#).unwrap();
+        assert_eq!(first_id(&empty, "W6JSV"), None);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::config::RestConfig;
+use crate::pipeline::Metrics;
+use serenity::all::{GuildId, Http, Member, UserId};
+use serenity::http::{RatelimitInfo, Route};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// The most members Discord returns per request
+const PAGE_SIZE: u64 = 1000;
+
+/// Paces the bot's bulk REST work so it leaves room for slash commands, and reports
+/// when Discord rate limits it anyway. Serenity still retries every 429 itself.
+pub struct RestLimits {
+    reserve: i64,
+    edit_interval: Duration,
+    next_edit: Mutex<Instant>,
+    metrics: Metrics,
+}
+
+impl RestLimits {
+    pub fn new(config: &RestConfig, metrics: Metrics) -> Self {
+        Self {
+            reserve: config.reserve,
+            edit_interval: Duration::from_millis(config.edit_interval_ms),
+            next_edit: Mutex::new(Instant::now()),
+            metrics,
+        }
+    }
+
+    /// Every member of a guild, page by page. Once a page leaves no more than
+    /// `reserve` requests in the bucket, waits for it to reset before the next.
+    pub async fn fetch_members(
+        &self,
+        http: &Http,
+        guild_id: GuildId,
+    ) -> serenity::Result<Vec<Member>> {
+        let mut members = Vec::new();
+        let mut after = None;
+        loop {
+            let page = http
+                .get_guild_members(guild_id, Some(PAGE_SIZE), after)
+                .await?;
+            let full = page.len() as u64 == PAGE_SIZE;
+            after = page.last().map(|m| m.user.id.get());
+            members.extend(page);
+            if !full {
+                return Ok(members);
+            }
+
+            let bucket = bucket(http, Route::GuildMembers { guild_id }).await;
+            if let Some((remaining, limit, _)) = bucket {
+                debug!(
+                    "Fetched {} members of guild {}; {}/{} requests left",
+                    members.len(),
+                    guild_id,
+                    remaining,
+                    limit
+                );
+                self.metrics
+                    .discord_rate_limit_remaining
+                    .with_label_values(&["guild_members"])
+                    .set(remaining);
+            }
+            if let Some(wait) = bucket.and_then(|(remaining, _, reset_after)| {
+                pace_delay(remaining, self.reserve, reset_after)
+            }) {
+                info!(
+                    "Pausing member fetch for guild {} for {:?} to stay under the rate limit",
+                    guild_id, wait
+                );
+                tokio::time::sleep(wait).await;
+            }
+        }
+    }
+
+    /// Wait for the next slot for a nickname or role edit, `edit_interval_ms` apart
+    pub async fn before_edit(&self, user_id: UserId) {
+        let now = Instant::now();
+        let slot = {
+            let mut next = self.next_edit.lock().await;
+            let slot = (*next).max(now);
+            *next = slot + self.edit_interval;
+            slot
+        };
+        if slot > now {
+            debug!("Pacing edit for user {} by {:?}", user_id, slot - now);
+            tokio::time::sleep_until(slot).await;
+        }
+    }
+
+    /// Called when serenity has to wait out a rate limit
+    pub fn rate_limited(&self, info: &RatelimitInfo) {
+        warn!(
+            "Discord rate limited {:?} {} for {:?} (limit {}{})",
+            info.method,
+            info.path,
+            info.timeout,
+            info.limit,
+            if info.global { ", global" } else { "" }
+        );
+        let global = if info.global { "true" } else { "false" };
+        self.metrics
+            .discord_rate_limits
+            .with_label_values(&[global])
+            .inc();
+        self.metrics
+            .discord_rate_limit_wait_seconds
+            .observe(info.timeout.as_secs_f64());
+    }
+}
+
+/// Requests left, the bucket's limit, and the time until it resets, once Discord has reported them
+async fn bucket(http: &Http, route: Route<'_>) -> Option<(i64, i64, Option<Duration>)> {
+    let routes = http.ratelimiter.as_ref()?.routes();
+    let routes = routes.read().await;
+    let ratelimit = routes.get(&route.ratelimiting_bucket())?.lock().await;
+    Some((
+        ratelimit.remaining(),
+        ratelimit.limit(),
+        ratelimit.reset_after(),
+    ))
+}
+
+/// How long to wait before the next request so `reserve` requests stay available
+fn pace_delay(remaining: i64, reserve: i64, reset_after: Option<Duration>) -> Option<Duration> {
+    if remaining > reserve {
+        return None;
+    }
+    reset_after.filter(|wait| !wait.is_zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pace_delay() {
+        let reset = Some(Duration::from_secs(2));
+        assert_eq!(pace_delay(5, 2, reset), None);
+        assert_eq!(pace_delay(2, 2, reset), reset);
+        assert_eq!(pace_delay(0, 0, reset), reset);
+        // Nothing to wait for when Discord hasn't said when the bucket resets
+        assert_eq!(pace_delay(0, 2, None), None);
+        assert_eq!(pace_delay(0, 2, Some(Duration::ZERO)), None);
+    }
+
+    #[tokio::test]
+    async fn test_before_edit_spaces_edits() {
+        let limits = RestLimits::new(
+            &RestConfig {
+                reserve: 1,
+                edit_interval_ms: 20,
+            },
+            Metrics::new(),
+        );
+        let started = Instant::now();
+        for id in 1..=3 {
+            limits.before_edit(UserId::new(id)).await;
+        }
+        // The first edit goes right away; the next two wait a slot each
+        assert!(started.elapsed() >= Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,383 @@
+use crate::config::RepeaterBookConfig;
+use crate::dxcluster;
+use anyhow::{Context, Result};
+use reqwest::header::USER_AGENT;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// US and Canadian abbreviations, since RepeaterBook wants the full name
+const REGIONS: [(&str, &str); 65] = [
+    ("AL", "Alabama"),
+    ("AK", "Alaska"),
+    ("AZ", "Arizona"),
+    ("AR", "Arkansas"),
+    ("CA", "California"),
+    ("CO", "Colorado"),
+    ("CT", "Connecticut"),
+    ("DE", "Delaware"),
+    ("DC", "District of Columbia"),
+    ("FL", "Florida"),
+    ("GA", "Georgia"),
+    ("HI", "Hawaii"),
+    ("ID", "Idaho"),
+    ("IL", "Illinois"),
+    ("IN", "Indiana"),
+    ("IA", "Iowa"),
+    ("KS", "Kansas"),
+    ("KY", "Kentucky"),
+    ("LA", "Louisiana"),
+    ("ME", "Maine"),
+    ("MD", "Maryland"),
+    ("MA", "Massachusetts"),
+    ("MI", "Michigan"),
+    ("MN", "Minnesota"),
+    ("MS", "Mississippi"),
+    ("MO", "Missouri"),
+    ("MT", "Montana"),
+    ("NE", "Nebraska"),
+    ("NV", "Nevada"),
+    ("NH", "New Hampshire"),
+    ("NJ", "New Jersey"),
+    ("NM", "New Mexico"),
+    ("NY", "New York"),
+    ("NC", "North Carolina"),
+    ("ND", "North Dakota"),
+    ("OH", "Ohio"),
+    ("OK", "Oklahoma"),
+    ("OR", "Oregon"),
+    ("PA", "Pennsylvania"),
+    ("PR", "Puerto Rico"),
+    ("RI", "Rhode Island"),
+    ("SC", "South Carolina"),
+    ("SD", "South Dakota"),
+    ("TN", "Tennessee"),
+    ("TX", "Texas"),
+    ("UT", "Utah"),
+    ("VT", "Vermont"),
+    ("VA", "Virginia"),
+    ("WA", "Washington"),
+    ("WV", "West Virginia"),
+    ("WI", "Wisconsin"),
+    ("WY", "Wyoming"),
+    ("AB", "Alberta"),
+    ("BC", "British Columbia"),
+    ("MB", "Manitoba"),
+    ("NB", "New Brunswick"),
+    ("NL", "Newfoundland and Labrador"),
+    ("NS", "Nova Scotia"),
+    ("NT", "Northwest Territories"),
+    ("NU", "Nunavut"),
+    ("ON", "Ontario"),
+    ("PE", "Prince Edward Island"),
+    ("QC", "Quebec"),
+    ("SK", "Saskatchewan"),
+    ("YT", "Yukon"),
+];
+
+/// Digital modes, by the "Yes"/"No" column RepeaterBook reports each in
+const DIGITAL_MODES: [(&str, &str); 5] = [
+    ("DMR", "DMR"),
+    ("D-Star", "D-STAR"),
+    ("System Fusion", "Fusion"),
+    ("NXDN", "NXDN"),
+    ("APCO P-25", "P25"),
+];
+
+/// Response from RepeaterBook's `export.php`; every value arrives as a string
+#[derive(Debug, Deserialize)]
+struct ExportResponse {
+    #[serde(default)]
+    results: Vec<HashMap<String, serde_json::Value>>,
+}
+
+/// A repeater listed in RepeaterBook
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repeater {
+    pub callsign: String,
+    /// Output (what you listen on) in MHz
+    pub frequency: f64,
+    /// Input (what you transmit on) in MHz
+    pub input: f64,
+    /// CTCSS tone to access it, e.g. "100.0"
+    pub tone: Option<String>,
+    pub city: String,
+    /// "FM", then any digital modes
+    pub modes: Vec<&'static str>,
+    /// "OPEN", "CLOSED", or "PRIVATE"
+    pub access: String,
+    pub on_air: bool,
+}
+
+impl Repeater {
+    /// The band of the output frequency, e.g. "2m"
+    pub fn band(&self) -> Option<&'static str> {
+        dxcluster::band(self.frequency * 1000.0)
+    }
+
+    /// "**W6ABC** 146.940 (-0.600, PL 100.0) · San Francisco · FM, DMR"
+    pub fn describe(&self) -> String {
+        let offset = self.input - self.frequency;
+        let mut access = if offset.abs() < 0.0005 {
+            "simplex".to_string()
+        } else {
+            format!("{:+.3}", offset)
+        };
+        if let Some(tone) = &self.tone {
+            access.push_str(&format!(", PL {}", tone));
+        }
+        let mut line = format!(
+            "**{}** {:.3} ({}) · {}",
+            self.callsign, self.frequency, access, self.city
+        );
+        if !self.modes.is_empty() {
+            line.push_str(&format!(" · {}", self.modes.join(", ")));
+        }
+        if !self.access.eq_ignore_ascii_case("open") && !self.access.is_empty() {
+            line.push_str(&format!(" · {}", self.access.to_lowercase()));
+        }
+        line
+    }
+}
+
+/// A RepeaterBook query: a city within a state or province, or a whole one
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub city: Option<String>,
+    pub state: String,
+}
+
+/// Parse "San Francisco, CA", "Vancouver, British Columbia", or just "Oregon".
+/// A lone word has to be a state or province, since cities are ambiguous without one.
+pub fn parse_location(location: &str) -> Option<Location> {
+    let (city, state) = match location.rsplit_once(',') {
+        Some((city, state)) => (Some(city.trim()), state.trim()),
+        None => (None, location.trim()),
+    };
+    let state = match REGIONS
+        .iter()
+        .find(|(abbreviation, name)| {
+            state.eq_ignore_ascii_case(abbreviation) || state.eq_ignore_ascii_case(name)
+        })
+        .map(|(_, name)| name.to_string())
+    {
+        Some(name) => name,
+        None if city.is_some() && !state.is_empty() => state.to_string(),
+        None => return None,
+    };
+    match city {
+        Some("") => None,
+        city => Some(Location {
+            city: city.map(str::to_string),
+            state,
+        }),
+    }
+}
+
+/// Looks up repeaters by location on RepeaterBook
+pub struct RepeaterBookClient {
+    client: reqwest::Client,
+    url: String,
+    user_agent: String,
+    cache: Mutex<HashMap<Location, (Instant, Vec<Repeater>)>>,
+    cache_ttl: Duration,
+}
+
+impl RepeaterBookClient {
+    pub fn new(config: &RepeaterBookConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: config.url.clone(),
+            // RepeaterBook asks API users to identify themselves with a contact
+            user_agent: match &config.contact {
+                Some(contact) => format!("discord-callsign-bot ({})", contact),
+                None => "discord-callsign-bot".to_string(),
+            },
+            cache: Mutex::new(HashMap::new()),
+            cache_ttl: Duration::from_secs(config.cache_ttl_secs),
+        }
+    }
+
+    /// Every repeater listed for the location, on the air or not
+    pub async fn search(&self, location: &Location) -> Result<Vec<Repeater>> {
+        if let Some(repeaters) = self.cached(location) {
+            debug!("RepeaterBook cache hit for {:?}", location);
+            return Ok(repeaters);
+        }
+
+        debug!("Looking up repeaters for {:?}", location);
+        let mut query = vec![("state", location.state.as_str())];
+        if let Some(city) = &location.city {
+            query.push(("city", city.as_str()));
+        }
+        let response = self
+            .client
+            .get(&self.url)
+            .header(USER_AGENT, &self.user_agent)
+            .query(&query)
+            .send()
+            .await
+            .context("Failed to send request to RepeaterBook")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("RepeaterBook returned error {}", response.status());
+        }
+
+        let body: ExportResponse = response
+            .json()
+            .await
+            .context("Failed to parse RepeaterBook response")?;
+        let repeaters = parse_results(body.results);
+
+        self.cache
+            .lock()
+            .expect("RepeaterBook cache lock poisoned")
+            .insert(location.clone(), (Instant::now(), repeaters.clone()));
+
+        Ok(repeaters)
+    }
+
+    fn cached(&self, location: &Location) -> Option<Vec<Repeater>> {
+        let cache = self.cache.lock().expect("RepeaterBook cache lock poisoned");
+        cache
+            .get(location)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < self.cache_ttl)
+            .map(|(_, repeaters)| repeaters.clone())
+    }
+}
+
+/// Rows without a callsign or readable frequencies are skipped
+fn parse_results(results: Vec<HashMap<String, serde_json::Value>>) -> Vec<Repeater> {
+    results
+        .into_iter()
+        .filter_map(|row| {
+            let field = |name: &str| {
+                let value = match row.get(name)? {
+                    serde_json::Value::String(s) => s.trim().to_string(),
+                    serde_json::Value::Number(n) => n.to_string(),
+                    _ => return None,
+                };
+                (!value.is_empty()).then_some(value)
+            };
+            let yes = |name: &str| field(name).is_some_and(|v| v.eq_ignore_ascii_case("yes"));
+
+            let frequency: f64 = field("Frequency")?.parse().ok()?;
+            let mut modes = Vec::new();
+            if yes("FM Analog") {
+                modes.push("FM");
+            }
+            modes.extend(
+                DIGITAL_MODES
+                    .iter()
+                    .filter(|(column, _)| yes(column))
+                    .map(|(_, mode)| *mode),
+            );
+            Some(Repeater {
+                callsign: field("Callsign")?.to_uppercase(),
+                frequency,
+                input: field("Input Freq")
+                    .and_then(|f| f.parse().ok())
+                    .unwrap_or(frequency),
+                tone: field("PL").filter(|pl| pl != "CSQ"),
+                city: field("Nearest City").unwrap_or_default(),
+                modes,
+                access: field("Use").unwrap_or_default(),
+                on_air: field("Operational Status")
+                    .is_none_or(|status| !status.eq_ignore_ascii_case("off-air")),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_location() {
+        assert_eq!(
+            parse_location("San Francisco, CA"),
+            Some(Location {
+                city: Some("San Francisco".to_string()),
+                state: "California".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_location(" oregon "),
+            Some(Location {
+                city: None,
+                state: "Oregon".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_location("Vancouver, british columbia").unwrap().state,
+            "British Columbia"
+        );
+        // Unknown states and provinces are passed through when a city is given
+        assert_eq!(
+            parse_location("Springfield, Somewhere").unwrap().state,
+            "Somewhere"
+        );
+        assert_eq!(parse_location("Springfield"), None);
+        assert_eq!(parse_location(", CA"), None);
+        assert_eq!(parse_location("Springfield,"), None);
+    }
+
+    #[test]
+    fn test_parse_results() {
+        let response: ExportResponse = serde_json::from_str(
+            r#"{"count": 3, "results": [
+                {"Callsign": "w6abc", "Frequency": "146.94000", "Input Freq": "146.34000",
+                 "PL": "100.0", "Nearest City": "San Francisco", "Use": "OPEN",
+                 "Operational Status": "On-air", "FM Analog": "Yes", "DMR": "Yes", "D-Star": "No"},
+                {"Callsign": "K6XYZ", "Frequency": "442.12500", "Input Freq": "447.12500",
+                 "PL": "CSQ", "Nearest City": "Oakland", "Use": "CLOSED",
+                 "Operational Status": "Off-air", "FM Analog": "No", "System Fusion": "Yes"},
+                {"Callsign": "", "Frequency": "147.00000"}
+            ]}"#,
+        )
+        .unwrap();
+        let repeaters = parse_results(response.results);
+        assert_eq!(repeaters.len(), 2);
+        assert_eq!(repeaters[0].callsign, "W6ABC");
+        assert_eq!(repeaters[0].modes, vec!["FM", "DMR"]);
+        assert_eq!(repeaters[0].band(), Some("2m"));
+        assert!(repeaters[0].on_air);
+        assert_eq!(repeaters[1].tone, None);
+        assert_eq!(repeaters[1].band(), Some("70cm"));
+        assert!(!repeaters[1].on_air);
+    }
+
+    #[test]
+    fn test_describe() {
+        let repeater = Repeater {
+            callsign: "W6ABC".to_string(),
+            frequency: 146.94,
+            input: 146.34,
+            tone: Some("100.0".to_string()),
+            city: "San Francisco".to_string(),
+            modes: vec!["FM", "DMR"],
+            access: "OPEN".to_string(),
+            on_air: true,
+        };
+        assert_eq!(
+            repeater.describe(),
+            "**W6ABC** 146.940 (-0.600, PL 100.0) · San Francisco · FM, DMR"
+        );
+
+        let repeater = Repeater {
+            input: 442.125,
+            frequency: 442.125,
+            tone: None,
+            modes: vec![],
+            access: "CLOSED".to_string(),
+            ..repeater
+        };
+        assert_eq!(
+            repeater.describe(),
+            "**W6ABC** 442.125 (simplex) · San Francisco · closed"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use crate::config::LicensedRoleConfig;
+use crate::pipeline::{Member, QrzStatus};
+use std::collections::{HashMap, HashSet};
+
+/// A licensed role to grant or revoke
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoleChange {
+    pub user_id: u64,
+    pub grant: bool,
+}
+
+/// Work out who should gain or lose the licensed role. With `require_qrz`, the
+/// role is only granted once QRZ confirms the callsign, and only revoked when QRZ
+/// says it doesn't exist; a QRZ outage leaves roles as they are. Members in
+/// `exempt` (excluded, or listed only through an override) keep whatever they have.
+pub fn plan(
+    config: &LicensedRoleConfig,
+    members: &[Member],
+    licensed: &HashMap<u64, QrzStatus>,
+    exempt: &HashSet<u64>,
+) -> Vec<RoleChange> {
+    members
+        .iter()
+        .filter(|member| !exempt.contains(&member.user_id))
+        .filter_map(|member| {
+            let user_id = member.user_id;
+            let has_role = member.roles.contains(&config.role_id);
+
+            let should_have = match (licensed.get(&user_id), config.require_qrz) {
+                (None, _) => Some(false),
+                (Some(_), false) | (Some(QrzStatus::Verified), true) => Some(true),
+                (Some(QrzStatus::NotFound), true) => Some(false),
+                (Some(QrzStatus::Unknown), true) => None,
+            }?;
+
+            (should_have != has_role).then_some(RoleChange {
+                user_id,
+                grant: should_have,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROLE: u64 = 50;
+
+    fn member(user_id: u64, has_role: bool) -> Member {
+        Member {
+            user_id,
+            roles: if has_role { vec![ROLE] } else { Vec::new() },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        let members = vec![
+            member(1, false),
+            member(2, true),
+            member(3, true),
+            member(4, false),
+            member(5, true),
+        ];
+        let licensed = HashMap::from([
+            (1, QrzStatus::Verified),
+            (2, QrzStatus::NotFound),
+            (4, QrzStatus::Unknown),
+            (5, QrzStatus::Unknown),
+        ]);
+
+        let mut config = LicensedRoleConfig {
+            role_id: ROLE,
+            require_qrz: false,
+        };
+        assert_eq!(
+            plan(&config, &members, &licensed, &HashSet::new()),
+            vec![
+                RoleChange {
+                    user_id: 1,
+                    grant: true
+                },
+                RoleChange {
+                    user_id: 3,
+                    grant: false
+                },
+                RoleChange {
+                    user_id: 4,
+                    grant: true
+                },
+            ]
+        );
+
+        config.require_qrz = true;
+        assert_eq!(
+            plan(&config, &members, &licensed, &HashSet::new()),
+            vec![
+                RoleChange {
+                    user_id: 1,
+                    grant: true
+                },
+                RoleChange {
+                    user_id: 2,
+                    grant: false
+                },
+                RoleChange {
+                    user_id: 3,
+                    grant: false
+                },
+            ]
+        );
+
+        // Member 3 is listed through an override, so their role stays
+        assert_eq!(
+            plan(&config, &members, &licensed, &HashSet::from([2, 3])),
+            vec![RoleChange {
+                user_id: 1,
+                grant: true
+            }]
+        );
+    }
+}
@@ -0,0 +1,827 @@
+use crate::aprs::AprsClient;
+use crate::config::{DedupPolicy, GuildConfig, NameSource};
+use crate::dxcc::DxccTable;
+use crate::error::Error;
+use crate::lotw::LotwUsers;
+use crate::metrics::Metrics;
+use crate::output::{sanitize_field, sort_entries, OutputEntry, MAX_NAME_CHARS, MAX_SUFFIX_CHARS};
+use crate::parser::{CallsignParser, MemberInfo};
+use crate::pipeline::{CallsignLookup, DepartedMember, LookupAlerts, Member, Role};
+use crate::qrz::QrzClient;
+use crate::radioid::RadioIdClient;
+use regex::Regex;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{info, info_span, instrument, warn, Instrument};
+
+/// Turns a guild's member list into sorted, deduplicated roster entries
+pub struct RosterBuilder<'a> {
+    pub parser: &'a CallsignParser,
+    pub qrz_client: Option<&'a dyn CallsignLookup>,
+    pub radioid_client: Option<&'a RadioIdClient>,
+    pub aprs_client: Option<&'a AprsClient>,
+    /// Resolves callsigns to DXCC entities and flags
+    pub dxcc: Option<Arc<DxccTable>>,
+    /// Marks which members upload to LoTW
+    pub lotw: Option<Arc<LotwUsers>>,
+    /// Told about each QRZ lookup so repeated failures reach an admin
+    pub alerts: Option<&'a dyn LookupAlerts>,
+    pub metrics: &'a Metrics,
+    /// `discord.exclude_user_ids`
+    pub excluded_user_ids: &'a [u64],
+}
+
+impl RosterBuilder<'_> {
+    /// Bots (music bots, moderation bots, ...) and excluded users never get an entry
+    pub fn is_excluded(&self, member: &Member) -> bool {
+        member.bot || self.excluded_user_ids.contains(&member.user_id)
+    }
+
+    /// Find a callsign in the member's name fields, returning it along with the
+    /// name it came from (or the username if none parsed)
+    pub fn parse_member(&self, member: &Member) -> (Option<MemberInfo>, String) {
+        // Try to find a valid callsign in multiple name fields
+        // Priority: nick -> global_name -> username
+        let name_fields = [
+            member.nick.as_ref(),
+            member.global_name.as_ref(),
+            Some(&member.username),
+        ];
+
+        name_fields
+            .iter()
+            .filter_map(|field| {
+                field.map(|name| {
+                    let parsed = self.parser.parse(name);
+                    (parsed, name.clone())
+                })
+            })
+            .find(|(parsed, _)| parsed.is_some())
+            .unwrap_or((None, member.username.clone()))
+    }
+
+    /// The callsigns `build()` would look up on QRZ: parsed ones, leaving out members whose
+    /// override sets their identity or skips QRZ
+    pub fn qrz_callsigns(
+        &self,
+        guild_config: &GuildConfig,
+        members: &[Member],
+    ) -> BTreeSet<String> {
+        members
+            .iter()
+            .filter(|member| !self.is_excluded(member))
+            .filter(|member| {
+                !guild_config
+                    .get_override(&member.user_id.to_string())
+                    .is_some_and(|o| o.skip_qrz || o.callsign.is_some() || o.name.is_some())
+            })
+            .filter_map(|member| self.parse_member(member).0)
+            .map(|parsed| parsed.callsign)
+            .collect()
+    }
+
+    /// Parse, override, and enrich every member; `skip_user` is normally the bot itself,
+    /// `member_suffixes` holds suffixes members chose for themselves, by user ID, and
+    /// `paid_dues` the members paid up this year (`None` leaves out `[guilds.dues]`)
+    #[instrument(name = "parse", skip_all, fields(members = members.len()))]
+    pub async fn build(
+        &self,
+        guild_config: &GuildConfig,
+        members: &[Member],
+        skip_user: Option<u64>,
+        member_suffixes: &HashMap<u64, String>,
+        paid_dues: Option<&HashSet<u64>>,
+    ) -> Roster {
+        let guild_label = guild_config.guild_id.to_string();
+
+        let mut candidates = Vec::new();
+        let mut licensed = HashMap::new();
+        let mut unresolved = HashSet::new();
+
+        for member in members {
+            // Skip the bot itself
+            if Some(member.user_id) == skip_user {
+                info!("Skipping bot user: {}", member.username);
+                continue;
+            }
+            if self.is_excluded(member) {
+                info!("Skipping excluded user: {}", member.username);
+                continue;
+            }
+
+            let (parsed, display_name) = self.parse_member(member);
+
+            info!(
+                "Processing member: {} (parsed: {})",
+                display_name,
+                if parsed.is_some() { "✓" } else { "✗" }
+            );
+
+            let booster = member.booster;
+            let joined_at = member.joined_at;
+
+            // Check if there's a manual override for this user
+            let user_id = member.user_id.to_string();
+            let override_config = guild_config.get_override(&user_id);
+
+            // Suffix precedence: config override, then the member's own /suffix choice, then the default
+            let suffix = override_config
+                .and_then(|o| o.suffix.clone())
+                .or_else(|| member_suffixes.get(&member.user_id).cloned())
+                .unwrap_or_else(|| guild_config.output.default_suffix.clone());
+
+            let emoji_separator = override_config
+                .and_then(|o| o.emoji.clone())
+                .unwrap_or_else(|| guild_config.output.emoji_separator.clone());
+
+            let hide_location = override_config.is_some_and(|o| o.hide_location);
+
+            // Overrides that only change the suffix or emoji keep the parsed callsign
+            // and QRZ enrichment; they only replace the identity when they set one
+            // (or when nothing could be parsed)
+            let identity_override = override_config
+                .filter(|o| o.callsign.is_some() || o.name.is_some() || parsed.is_none());
+
+            if let Some(override_config) = identity_override {
+                info!("Using override for user {}", user_id);
+
+                // Use the parsed callsign if available
+
+                let callsign = override_config
+                    .callsign
+                    .clone()
+                    .or_else(|| parsed.as_ref().map(|p| p.callsign.clone()))
+                    .unwrap_or_else(|| "UNKNOWN".to_string());
+
+                let name = pick_name(
+                    &guild_config.output.name_priority,
+                    override_config.name.as_deref(),
+                    parsed.as_ref().map(|p| p.name.as_str()),
+                    None,
+                )
+                .or_else(|| parsed.as_ref().map(|p| p.name.clone()))
+                .unwrap_or_else(|| display_name.clone());
+
+                candidates.push(Candidate {
+                    user_id: member.user_id,
+                    from_override: true,
+                    entry: OutputEntry {
+                        callsign,
+                        name,
+                        suffix,
+                        emoji_separator,
+                        booster,
+                        grid: parsed.and_then(|p| p.grid).filter(|_| !hide_location),
+                        state: None,
+                        country: None,
+                        class: None,
+                        dmr_id: None,
+                        dxcc: None,
+                        flag: None,
+                        continent: None,
+                        coordinates: None,
+                        aprs_last_heard: None,
+                        lotw_last_upload: None,
+                        joined_at,
+                    },
+                });
+            } else if let Some(parsed) = parsed {
+                // Successfully parsed callsign from one of the name fields
+                let mut qrz_name = None;
+                // A grid in the display name is used unless QRZ has one
+                let mut grid = parsed.grid.clone();
+                let mut state = None;
+                let mut country = None;
+                let mut class = None;
+                let mut coordinates = None;
+                let mut qrz_status = QrzStatus::Unknown;
+
+                // Try to get name from QRZ if client is available and the member allows it
+                let skip_qrz = override_config.is_some_and(|o| o.skip_qrz);
+                if let Some(qrz_client) = self.qrz_client.filter(|_| !skip_qrz) {
+                    let started = Instant::now();
+                    let result = qrz_client
+                        .lookup_callsign(&parsed.callsign)
+                        .instrument(info_span!("lookup", callsign = %parsed.callsign))
+                        .await;
+                    self.metrics
+                        .qrz_lookup_seconds
+                        .with_label_values(&[if result.is_ok() { "ok" } else { "error" }])
+                        .observe(started.elapsed().as_secs_f64());
+                    if let Some(alerts) = self.alerts {
+                        match &result {
+                            // A missing callsign still means QRZ is working
+                            Ok(_) | Err(Error::NotFound(_)) => alerts.lookup_succeeded(),
+                            Err(e) => alerts.lookup_failed(e),
+                        }
+                    }
+
+                    match result {
+                        Ok(qrz_info) => {
+                            qrz_status = QrzStatus::Verified;
+                            qrz_name = QrzClient::get_display_name(&qrz_info);
+                            if qrz_name.is_none() {
+                                info!(
+                                    "No name found in QRZ for {}, using Discord name: {}",
+                                    parsed.callsign, parsed.name
+                                );
+                            }
+                            grid = qrz_info.grid.or(grid);
+                            state = qrz_info.state;
+                            country = qrz_info.country;
+                            class = qrz_info.class;
+                            coordinates = qrz_info.lat.zip(qrz_info.lon);
+                        }
+                        Err(e) => {
+                            if matches!(e, Error::NotFound(_)) {
+                                qrz_status = QrzStatus::NotFound;
+                            }
+                            unresolved.insert(parsed.callsign.clone());
+                            self.metrics.qrz_lookup_errors.inc();
+                            warn!(
+                                "Failed to lookup callsign {} in QRZ: {:?}. Using Discord name: {}",
+                                parsed.callsign, e, parsed.name
+                            );
+                        }
+                    }
+                }
+
+                licensed.insert(member.user_id, qrz_status);
+                if hide_location {
+                    grid = None;
+                    coordinates = None;
+                }
+
+                let name = pick_name(
+                    &guild_config.output.name_priority,
+                    None,
+                    Some(&parsed.name),
+                    qrz_name.as_deref(),
+                )
+                .unwrap_or_else(|| parsed.name.clone());
+                if qrz_name.as_ref() == Some(&name) {
+                    info!("Using QRZ name '{}' for callsign {}", name, parsed.callsign);
+                }
+
+                candidates.push(Candidate {
+                    user_id: member.user_id,
+                    from_override: false,
+                    entry: OutputEntry {
+                        callsign: parsed.callsign.clone(),
+                        name,
+                        suffix,
+                        emoji_separator,
+                        booster,
+                        grid,
+                        state,
+                        country,
+                        class,
+                        dmr_id: None,
+                        dxcc: None,
+                        flag: None,
+                        continent: None,
+                        coordinates,
+                        aprs_last_heard: None,
+                        lotw_last_upload: None,
+                        joined_at,
+                    },
+                });
+            } else {
+                self.metrics
+                    .parse_failures
+                    .with_label_values(&[guild_label.as_str()])
+                    .inc();
+                info!(
+                    "Could not parse callsign from display name: {}",
+                    display_name
+                );
+            }
+        }
+
+        // One member with a pathological name shouldn't corrupt the output for everyone
+        for candidate in &mut candidates {
+            sanitize_entry(&mut candidate.entry);
+        }
+
+        // Kept before deduplication and flair, so every listed member has their own entry
+        let by_user: HashMap<u64, OutputEntry> = candidates
+            .iter()
+            .map(|c| (c.user_id, c.entry.clone()))
+            .collect();
+
+        // Checked by callsign once entries are deduplicated
+        let hidden_locations: HashSet<String> = candidates
+            .iter()
+            .filter(|c| {
+                guild_config
+                    .get_override(&c.user_id.to_string())
+                    .is_some_and(|o| o.hide_location)
+            })
+            .map(|c| c.entry.callsign.clone())
+            .collect();
+
+        // After `by_user`, so members hidden for unpaid dues still have their nicknames checked
+        if let (Some(dues), Some(paid_dues)) = (&guild_config.dues, paid_dues) {
+            if dues.hide_unpaid {
+                candidates.retain(|c| paid_dues.contains(&c.user_id));
+            }
+            for candidate in &mut candidates {
+                let marker = if paid_dues.contains(&candidate.user_id) {
+                    &dues.paid_marker
+                } else {
+                    &dues.unpaid_marker
+                };
+                if let Some(marker) = marker {
+                    candidate.entry.suffix = append_flair(&candidate.entry.suffix, marker);
+                }
+            }
+        }
+
+        let total = candidates.len();
+        let (mut unique_entries, conflicts) = dedup_entries(candidates, guild_config.output.dedup);
+        for conflict in &conflicts {
+            warn!(
+                "Duplicate callsign {} claimed by users {:?}; keeping {:?}",
+                conflict.callsign, conflict.user_ids, conflict.kept
+            );
+        }
+
+        info!(
+            "Built {} entries (filtered {} duplicates)",
+            unique_entries.len(),
+            total - unique_entries.len()
+        );
+
+        // Override entries are looked up too, so every listed callsign gets its DMR ID
+        if let Some(radioid_client) = self.radioid_client {
+            for entry in &mut unique_entries {
+                match radioid_client.lookup_dmr_id(&entry.callsign).await {
+                    Ok(dmr_id) => entry.dmr_id = dmr_id,
+                    Err(e) => warn!("Failed to look up DMR ID for {}: {:?}", entry.callsign, e),
+                }
+            }
+        }
+
+        // Members who hid their location aren't tracked either
+        if let Some(aprs_client) = self.aprs_client {
+            for entry in unique_entries
+                .iter_mut()
+                .filter(|e| !hidden_locations.contains(&e.callsign))
+            {
+                match aprs_client.last_position(&entry.callsign).await {
+                    Ok(position) => {
+                        entry.aprs_last_heard = position.map(|p| p.last_heard.timestamp())
+                    }
+                    Err(e) => warn!(
+                        "Failed to look up APRS position for {}: {:?}",
+                        entry.callsign, e
+                    ),
+                }
+            }
+        }
+
+        // Resolved from the prefix table, so international members get a country even
+        // when QRZ is unavailable or has no record
+        if let Some(dxcc) = &self.dxcc {
+            for entry in &mut unique_entries {
+                let Some(entity) = dxcc.lookup(&entry.callsign) else {
+                    continue;
+                };
+                entry.country.get_or_insert_with(|| entity.name.clone());
+                entry.dxcc = Some(entity.name.clone());
+                entry.flag = entity.flag();
+                entry.continent = Some(entity.continent.clone());
+                if guild_config.output.country_flags {
+                    if let Some(flag) = &entry.flag {
+                        entry.suffix = append_flair(&entry.suffix, flag);
+                    }
+                }
+            }
+        }
+
+        if let Some(lotw) = &self.lotw {
+            for entry in &mut unique_entries {
+                entry.lotw_last_upload = lotw.last_upload(&entry.callsign);
+            }
+        }
+
+        if !guild_config.output.class_badges.is_empty() {
+            for entry in &mut unique_entries {
+                if let Some(badge) = class_badge(&guild_config.output.class_badges, entry) {
+                    entry.suffix = append_flair(&entry.suffix, badge);
+                }
+            }
+        }
+
+        if let Some(flair) = &guild_config.output.booster_flair {
+            for entry in unique_entries.iter_mut().filter(|e| e.booster) {
+                entry.suffix = append_flair(&entry.suffix, flair);
+            }
+        }
+
+        sort_entries(
+            &mut unique_entries,
+            guild_config.output.sort_by,
+            guild_config.output.sort_order,
+        );
+
+        Roster {
+            entries: unique_entries,
+            conflicts,
+            by_user,
+            licensed,
+            unresolved,
+        }
+    }
+}
+
+/// Suffixes derived from members' role names via `suffix_role_pattern`, by user ID.
+/// When several of a member's roles match, the highest role wins.
+pub fn role_suffixes(
+    pattern: &Regex,
+    members: &[Member],
+    roles: &HashMap<u64, Role>,
+) -> HashMap<u64, String> {
+    members
+        .iter()
+        .filter_map(|member| {
+            let suffix = member
+                .roles
+                .iter()
+                .filter_map(|id| roles.get(id))
+                .filter_map(|role| {
+                    let captures = pattern.captures(&role.name)?;
+                    let suffix = captures.get(1).or_else(|| captures.get(0))?;
+                    Some((role.position, suffix.as_str().trim().to_string()))
+                })
+                .filter(|(_, suffix)| !suffix.is_empty())
+                .max_by_key(|(position, _)| *position)?
+                .1;
+            Some((member.user_id, suffix))
+        })
+        .collect()
+}
+
+/// Entries for members who left while listed, skipping anyone who is back in the
+/// guild or whose callsign is still on the roster
+pub fn alumni_entries(
+    guild_config: &GuildConfig,
+    departed: HashMap<u64, DepartedMember>,
+    members: &[Member],
+    listed: &[OutputEntry],
+) -> Vec<OutputEntry> {
+    let output = &guild_config.output;
+    let mut alumni: Vec<OutputEntry> = departed
+        .into_iter()
+        .filter(|(user_id, _)| !members.iter().any(|m| m.user_id == *user_id))
+        .filter(|(_, d)| !listed.iter().any(|e| e.callsign == d.callsign))
+        .map(|(_, departed)| {
+            let mut entry = OutputEntry {
+                callsign: departed.callsign,
+                name: departed.name,
+                suffix: output.alumni_suffix.clone(),
+                emoji_separator: output.emoji_separator.clone(),
+                booster: false,
+                grid: None,
+                state: None,
+                country: None,
+                class: None,
+                dmr_id: None,
+                dxcc: None,
+                flag: None,
+                continent: None,
+                coordinates: None,
+                aprs_last_heard: None,
+                lotw_last_upload: None,
+                joined_at: None,
+            };
+            sanitize_entry(&mut entry);
+            entry
+        })
+        .collect();
+
+    sort_entries(&mut alumni, output.sort_by, output.sort_order);
+    alumni
+}
+
+/// A built roster plus the callsigns claimed by more than one member
+#[derive(Debug)]
+pub struct Roster {
+    pub entries: Vec<OutputEntry>,
+    pub conflicts: Vec<CallsignConflict>,
+    /// Each member's entry before deduplication and flair, by user ID
+    pub by_user: HashMap<u64, OutputEntry>,
+    /// Members whose name parsed to a callsign, with what QRZ said about it
+    pub licensed: HashMap<u64, QrzStatus>,
+    /// Callsigns whose QRZ lookup failed or found nothing, so they're listed with their
+    /// Discord name until `Handler::run_qrz_backfill()` resolves them
+    pub unresolved: HashSet<String>,
+}
+
+/// Whether QRZ confirmed a parsed callsign
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QrzStatus {
+    Verified,
+    NotFound,
+    /// QRZ isn't configured or the lookup failed for another reason
+    Unknown,
+}
+
+/// Several members resolved to the same callsign
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallsignConflict {
+    pub callsign: String,
+    /// Every member claiming the callsign, in member-list order
+    pub user_ids: Vec<u64>,
+    /// The members whose entries were kept under the dedup policy
+    pub kept: Vec<u64>,
+}
+
+/// The first non-empty name among `priority`'s sources
+fn pick_name(
+    priority: &[NameSource],
+    override_name: Option<&str>,
+    discord: Option<&str>,
+    qrz: Option<&str>,
+) -> Option<String> {
+    priority
+        .iter()
+        .filter_map(|source| match source {
+            NameSource::Override => override_name,
+            NameSource::Discord => discord,
+            NameSource::Qrz => qrz,
+        })
+        .find(|name| !name.trim().is_empty())
+        .map(str::to_string)
+}
+
+/// An entry along with where it came from, before deduplication
+struct Candidate {
+    entry: OutputEntry,
+    user_id: u64,
+    /// The callsign or name was set by an override rather than parsed
+    from_override: bool,
+}
+
+/// Collapse entries sharing a callsign according to `policy`, reporting each conflict
+fn dedup_entries(
+    candidates: Vec<Candidate>,
+    policy: DedupPolicy,
+) -> (Vec<OutputEntry>, Vec<CallsignConflict>) {
+    let mut groups: Vec<Vec<Candidate>> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for candidate in candidates {
+        match index.get(&candidate.entry.callsign) {
+            Some(&i) => groups[i].push(candidate),
+            None => {
+                index.insert(candidate.entry.callsign.clone(), groups.len());
+                groups.push(vec![candidate]);
+            }
+        }
+    }
+
+    let mut entries = Vec::new();
+    let mut conflicts = Vec::new();
+    for mut group in groups {
+        if group.len() == 1 {
+            entries.extend(group.pop().map(|c| c.entry));
+            continue;
+        }
+
+        let user_ids: Vec<u64> = group.iter().map(|c| c.user_id).collect();
+        let kept: Vec<Candidate> = match policy {
+            DedupPolicy::All => group,
+            DedupPolicy::First => group.into_iter().take(1).collect(),
+            DedupPolicy::Override => {
+                let keep = group.iter().position(|c| c.from_override).unwrap_or(0);
+                vec![group.swap_remove(keep)]
+            }
+        };
+
+        conflicts.push(CallsignConflict {
+            callsign: kept[0].entry.callsign.clone(),
+            user_ids,
+            kept: kept.iter().map(|c| c.user_id).collect(),
+        });
+        entries.extend(kept.into_iter().map(|c| c.entry));
+    }
+
+    (entries, conflicts)
+}
+
+/// Clean up an entry's free-text fields, falling back to the callsign for an empty name
+fn sanitize_entry(entry: &mut OutputEntry) {
+    entry.callsign = sanitize_field(&entry.callsign, MAX_SUFFIX_CHARS);
+    entry.name = sanitize_field(&entry.name, MAX_NAME_CHARS);
+    entry.suffix = sanitize_field(&entry.suffix, MAX_SUFFIX_CHARS);
+    entry.emoji_separator = sanitize_field(&entry.emoji_separator, MAX_SUFFIX_CHARS);
+
+    if entry.name.is_empty() {
+        entry.name = entry.callsign.clone();
+    }
+}
+
+/// Look up the badge for an entry's license class; keys match case-insensitively
+fn class_badge<'a>(badges: &'a HashMap<String, String>, entry: &OutputEntry) -> Option<&'a str> {
+    let class = entry.class.as_deref()?;
+    badges
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(class))
+        .map(|(_, badge)| badge.as_str())
+}
+
+/// Append a booster flair to an entry's suffix, space-separated if the suffix is non-empty
+fn append_flair(suffix: &str, flair: &str) -> String {
+    if suffix.is_empty() {
+        flair.to_string()
+    } else {
+        format!("{} {}", suffix, flair)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(callsign: &str, user_id: u64, from_override: bool) -> Candidate {
+        Candidate {
+            entry: OutputEntry {
+                callsign: callsign.to_string(),
+                name: callsign.to_string(),
+                emoji_separator: "📻".to_string(),
+                ..Default::default()
+            },
+            user_id,
+            from_override,
+        }
+    }
+
+    fn candidates() -> Vec<Candidate> {
+        vec![
+            candidate("W1AW", 1, false),
+            candidate("W6JSV", 2, false),
+            candidate("W1AW", 3, true),
+        ]
+    }
+
+    #[test]
+    fn test_dedup_keep_first() {
+        let (entries, conflicts) = dedup_entries(candidates(), DedupPolicy::First);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            conflicts,
+            vec![CallsignConflict {
+                callsign: "W1AW".to_string(),
+                user_ids: vec![1, 3],
+                kept: vec![1],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_pick_name() {
+        use NameSource::*;
+        let pick = |priority: &[NameSource], discord| {
+            pick_name(priority, Some("Override"), discord, Some("Qrz"))
+        };
+        assert_eq!(
+            pick(&[Override, Qrz, Discord], Some("Jay")).as_deref(),
+            Some("Override")
+        );
+        assert_eq!(pick(&[Discord, Qrz], Some("Jay")).as_deref(), Some("Jay"));
+        // An empty parsed name falls through to the next source
+        assert_eq!(pick(&[Discord, Qrz], Some(" ")).as_deref(), Some("Qrz"));
+        assert_eq!(pick(&[Discord], None), None);
+        assert_eq!(pick(&[], Some("Jay")), None);
+    }
+
+    #[test]
+    fn test_dedup_keep_override_and_keep_all() {
+        let (entries, conflicts) = dedup_entries(candidates(), DedupPolicy::Override);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(conflicts[0].kept, vec![3]);
+
+        let (entries, conflicts) = dedup_entries(candidates(), DedupPolicy::All);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(conflicts[0].kept, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_role_suffixes() {
+        let role = |id: u64, name: &str, position: u16| {
+            let role = Role {
+                name: name.to_string(),
+                position,
+            };
+            (id, role)
+        };
+        let roles: HashMap<u64, Role> = [
+            role(10, "suffix: VE", 1),
+            role(11, "suffix: ARRL", 5),
+            role(12, "Moderators", 9),
+            role(13, "suffix:  ", 3),
+        ]
+        .into_iter()
+        .collect();
+        let member = |id: u64, role_ids: &[u64]| Member {
+            user_id: id,
+            roles: role_ids.to_vec(),
+            ..Default::default()
+        };
+        let members = vec![
+            member(1, &[10, 12]),
+            member(2, &[10, 11]),
+            member(3, &[12, 13]),
+        ];
+
+        let pattern = Regex::new("^suffix:(.*)$").unwrap();
+        let suffixes = role_suffixes(&pattern, &members, &roles);
+        assert_eq!(suffixes.get(&1).map(String::as_str), Some("VE"));
+        assert_eq!(suffixes.get(&2).map(String::as_str), Some("ARRL"));
+        assert!(!suffixes.contains_key(&3));
+
+        let whole_match = Regex::new("^(?:VE|ARRL)$|ARRL").unwrap();
+        let suffixes = role_suffixes(&whole_match, &members, &roles);
+        assert_eq!(suffixes.get(&2).map(String::as_str), Some("ARRL"));
+    }
+
+    #[test]
+    fn test_is_excluded() {
+        let (parser, metrics) = (CallsignParser::new(), Metrics::new());
+        let builder = RosterBuilder {
+            parser: &parser,
+            qrz_client: None,
+            radioid_client: None,
+            aprs_client: None,
+            dxcc: None,
+            lotw: None,
+            alerts: None,
+            metrics: &metrics,
+            excluded_user_ids: &[2],
+        };
+        let member = |id: u64, bot: bool| Member {
+            user_id: id,
+            bot,
+            ..Default::default()
+        };
+
+        assert!(!builder.is_excluded(&member(1, false)));
+        assert!(builder.is_excluded(&member(1, true)));
+        assert!(builder.is_excluded(&member(2, false)));
+    }
+
+    #[test]
+    fn test_qrz_callsigns() {
+        let (parser, metrics) = (CallsignParser::new(), Metrics::new());
+        let builder = RosterBuilder {
+            parser: &parser,
+            qrz_client: None,
+            radioid_client: None,
+            aprs_client: None,
+            dxcc: None,
+            lotw: None,
+            alerts: None,
+            metrics: &metrics,
+            excluded_user_ids: &[5],
+        };
+        let guild_config: GuildConfig = toml::from_str(
+            r#"
+            guild_id = 1
+
+            [output]
+            path = "members.txt"
+            default_suffix = ""
+
+            [overrides."2"]
+            callsign = "KI7QCF"
+
+            [overrides."3"]
+            skip_qrz = true
+
+            [overrides."4"]
+            suffix = "73"
+            "#,
+        )
+        .unwrap();
+        let member = |id: u64, nick: &str| Member {
+            user_id: id,
+            username: format!("user{}", id),
+            nick: Some(nick.to_string()),
+            ..Default::default()
+        };
+        let members = vec![
+            member(1, "W6JSV Jay"),
+            member(2, "Forrest"),
+            member(3, "N0CALL Private"),
+            member(4, "W1AW Hiram"),
+            member(5, "K6XYZ Excluded"),
+            member(6, "Not a ham"),
+        ];
+
+        assert_eq!(
+            builder.qrz_callsigns(&guild_config, &members),
+            BTreeSet::from(["W1AW".to_string(), "W6JSV".to_string()])
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use crate::config::Config;
+use crate::events;
+use crate::output::{generate_csv_content, generate_output_content};
+use crate::pipeline::Metrics;
+use crate::state::{GatewayState, GuildRoster, RosterStore};
+use crate::storage::Storage;
+use anyhow::{Context, Result};
+use axum::extract::{Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+#[derive(Clone)]
+struct AppState {
+    rosters: RosterStore,
+    metrics: Metrics,
+    gateway: GatewayState,
+    calendars: Calendars,
+}
+
+/// The `/event` events of guilds with `[guilds.events]`, for `/events.ics`
+#[derive(Clone)]
+pub struct Calendars {
+    storage: Arc<Storage>,
+    /// Calendar name per guild
+    names: HashMap<u64, String>,
+}
+
+impl Calendars {
+    pub fn new(config: &Config, storage: Arc<Storage>) -> Self {
+        let names = config
+            .guilds
+            .iter()
+            .filter(|guild| guild.events.is_some())
+            .map(|guild| (guild.guild_id, guild.calendar_name()))
+            .collect();
+        Self { storage, names }
+    }
+
+    /// The requested guild's feed; the guild ID may be omitted when only one has events
+    fn render(
+        &self,
+        guild_id: Option<u64>,
+    ) -> std::result::Result<String, (StatusCode, &'static str)> {
+        let (guild_id, name) = match guild_id {
+            Some(id) => self.names.get_key_value(&id).ok_or((
+                StatusCode::NOT_FOUND,
+                "Events aren't enabled for that guild",
+            ))?,
+            None => {
+                let mut names = self.names.iter();
+                match (names.next(), names.next()) {
+                    (Some(only), None) => only,
+                    (None, _) => {
+                        return Err((StatusCode::NOT_FOUND, "Events aren't enabled for any guild"))
+                    }
+                    (Some(_), Some(_)) => {
+                        return Err((
+                            StatusCode::BAD_REQUEST,
+                            "Multiple guilds have events; specify ?guild_id=",
+                        ))
+                    }
+                }
+            }
+        };
+        Ok(events::calendar(
+            *guild_id,
+            name,
+            &self.storage.events(*guild_id),
+            chrono::Utc::now(),
+        ))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CalendarQuery {
+    guild_id: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RosterQuery {
+    guild_id: Option<u64>,
+    #[serde(default)]
+    format: RosterFormat,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum RosterFormat {
+    #[default]
+    Json,
+    Csv,
+    #[serde(alias = "plain")]
+    Text,
+}
+
+/// Serve the roster, calendar, health, and metrics endpoints until the process exits
+pub async fn serve(
+    bind: &str,
+    rosters: RosterStore,
+    metrics: Metrics,
+    gateway: GatewayState,
+    calendars: Calendars,
+) -> Result<()> {
+    let app = router(AppState {
+        rosters,
+        metrics,
+        gateway,
+        calendars,
+    });
+
+    let listener = tokio::net::TcpListener::bind(bind)
+        .await
+        .with_context(|| format!("Failed to bind HTTP server to {}", bind))?;
+
+    info!("HTTP server listening on {}", bind);
+
+    axum::serve(listener, app)
+        .await
+        .context("HTTP server exited with an error")?;
+
+    Ok(())
+}
+
+fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/roster", get(roster))
+        .route("/events.ics", get(calendar))
+        .route("/healthz", get(healthz))
+        .route("/metrics", get(prometheus_metrics))
+        .with_state(state)
+}
+
+async fn roster(State(state): State<AppState>, Query(query): Query<RosterQuery>) -> Response {
+    let roster = match select_roster(&state.rosters, query.guild_id) {
+        Ok(roster) => roster,
+        Err((status, message)) => return (status, message).into_response(),
+    };
+
+    let generated_at = [("x-roster-generated-at", roster.generated_at.to_string())];
+
+    match query.format {
+        RosterFormat::Json => (generated_at, Json(roster.entries)).into_response(),
+        RosterFormat::Csv => (
+            generated_at,
+            [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+            generate_csv_content(&roster.entries),
+        )
+            .into_response(),
+        RosterFormat::Text => (
+            generated_at,
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            generate_output_content(roster.entries, roster.title.as_deref(), None),
+        )
+            .into_response(),
+    }
+}
+
+/// Pick the requested guild's roster; the guild ID may be omitted when only one is known
+fn select_roster(
+    rosters: &RosterStore,
+    guild_id: Option<u64>,
+) -> std::result::Result<GuildRoster, (StatusCode, &'static str)> {
+    match guild_id {
+        Some(id) => rosters.get(id).ok_or((
+            StatusCode::NOT_FOUND,
+            "No roster generated for that guild yet",
+        )),
+        None => {
+            let mut all = rosters.all();
+            match all.len() {
+                0 => Err((StatusCode::SERVICE_UNAVAILABLE, "No roster generated yet")),
+                1 => Ok(all.drain().next().map(|(_, roster)| roster).unwrap()),
+                _ => Err((
+                    StatusCode::BAD_REQUEST,
+                    "Multiple guilds configured; specify ?guild_id=",
+                )),
+            }
+        }
+    }
+}
+
+async fn calendar(State(state): State<AppState>, Query(query): Query<CalendarQuery>) -> Response {
+    match state.calendars.render(query.guild_id) {
+        Ok(feed) => (
+            [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+            feed,
+        )
+            .into_response(),
+        Err((status, message)) => (status, message).into_response(),
+    }
+}
+
+/// The gateway's status as JSON; 503 while it's disconnected or hasn't connected yet
+async fn healthz(State(state): State<AppState>) -> Response {
+    let gateway = state.gateway.status();
+    let status = if gateway.connected {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(serde_json::json!({ "gateway": gateway }))).into_response()
+}
+
+async fn prometheus_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::OutputEntry;
+    use crate::state::MemberCounts;
+
+    fn entry(callsign: &str) -> OutputEntry {
+        OutputEntry {
+            callsign: callsign.to_string(),
+            name: "Op".to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_select_roster_single_guild_without_id() {
+        let store = RosterStore::new();
+        assert!(select_roster(&store, None).is_err());
+
+        store.update(1, None, vec![entry("W6JSV")], MemberCounts::default());
+        assert_eq!(select_roster(&store, None).unwrap().entries.len(), 1);
+
+        store.update(2, None, vec![], MemberCounts::default());
+        assert!(select_roster(&store, None).is_err());
+        assert_eq!(select_roster(&store, Some(2)).unwrap().entries.len(), 0);
+        assert!(select_roster(&store, Some(3)).is_err());
+    }
+
+    fn calendars(guild_ids: &[u64]) -> Calendars {
+        Calendars {
+            storage: Arc::new(Storage::open(None).unwrap()),
+            names: guild_ids
+                .iter()
+                .map(|id| (*id, "Test Club".to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_calendar_selects_guild() {
+        assert!(calendars(&[]).render(None).is_err());
+
+        let single = calendars(&[1]);
+        let event = events::ClubEvent::new("Weekly Net", "0 3 * * WED", 60, None, None, 42);
+        single.storage.add_event(1, event.unwrap()).unwrap();
+        let feed = single.render(None).unwrap();
+        assert!(feed.contains("SUMMARY:Weekly Net"));
+        assert!(single
+            .render(Some(1))
+            .unwrap()
+            .contains("SUMMARY:Weekly Net"));
+        assert!(single.render(Some(2)).is_err());
+
+        let multiple = calendars(&[1, 2]);
+        assert!(multiple.render(None).is_err());
+        assert!(!multiple.render(Some(2)).unwrap().contains("BEGIN:VEVENT"));
+    }
+
+    #[tokio::test]
+    async fn test_healthz_reports_gateway() {
+        let state = AppState {
+            rosters: RosterStore::new(),
+            metrics: Metrics::new(),
+            gateway: GatewayState::new(),
+            calendars: calendars(&[]),
+        };
+        let response = healthz(State(state.clone())).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        state.gateway.connected();
+        let response = healthz(State(state)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
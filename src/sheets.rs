@@ -0,0 +1,283 @@
+use crate::config::SheetsConfig;
+use crate::output::OutputEntry;
+use anyhow::{Context, Result};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+const SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+const API_BASE: &str = "https://sheets.googleapis.com/v4/spreadsheets";
+
+/// Refresh access tokens this long before Google says they expire
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// Column headers of the roster tab, matching the cells from `row()`
+const HEADER: [&str; 8] = [
+    "Callsign", "Name", "Suffix", "Grid", "State", "Country", "Class", "DMR ID",
+];
+
+/// Writes rosters to a Google Sheet tab, authenticating as a service account
+pub struct SheetsClient {
+    client: reqwest::Client,
+    /// Access tokens by service account email
+    tokens: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+/// The fields we need from a service account's JSON key file
+#[derive(Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    #[serde(default = "default_token_uri")]
+    token_uri: String,
+}
+
+fn default_token_uri() -> String {
+    "https://oauth2.googleapis.com/token".to_string()
+}
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: u64,
+    exp: u64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+impl SheetsClient {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replace the contents of the configured tab with a header row and one row per entry
+    pub async fn write_roster(&self, config: &SheetsConfig, entries: &[OutputEntry]) -> Result<()> {
+        let token = self.access_token(config).await?;
+        let range = quote_tab(&config.tab);
+
+        info!(
+            "Writing {} entries to sheet {} tab {}",
+            entries.len(),
+            config.spreadsheet_id,
+            config.tab
+        );
+
+        // Write before clearing, so a failed write leaves the previous roster in place
+        let rows = rows(entries);
+        let mut update = values_url(&config.spreadsheet_id, &format!("{}!A1", range))?;
+        // RAW keeps names like "=HYPERLINK(...)" from being evaluated as formulas
+        update
+            .query_pairs_mut()
+            .append_pair("valueInputOption", "RAW");
+        let body = json!({
+            "majorDimension": "ROWS",
+            "values": rows,
+        });
+        self.send(self.client.put(update).bearer_auth(&token).json(&body))
+            .await
+            .context("Failed to write sheet tab")?;
+
+        // Then clear below the new rows so ones from a longer previous roster don't linger
+        let clear = values_url(
+            &config.spreadsheet_id,
+            &format!("{}:clear", leftover_range(&config.tab, rows.len())),
+        )?;
+        self.send(self.client.post(clear).bearer_auth(&token).json(&json!({})))
+            .await
+            .context("Failed to clear leftover sheet rows")?;
+        Ok(())
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<()> {
+        let response = request
+            .send()
+            .await
+            .context("Failed to send request to Google Sheets")?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Google Sheets returned error {}: {}", status, body);
+        }
+        Ok(())
+    }
+
+    /// A cached access token, or a new one from a signed JWT assertion
+    async fn access_token(&self, config: &SheetsConfig) -> Result<String> {
+        let path = config
+            .credentials_path
+            .clone()
+            .or_else(|| std::env::var("GOOGLE_APPLICATION_CREDENTIALS").ok())
+            .context("Google Sheets needs credentials_path or GOOGLE_APPLICATION_CREDENTIALS")?;
+        let key: ServiceAccountKey = serde_json::from_str(
+            &std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read service account key {}", path))?,
+        )
+        .with_context(|| format!("Invalid service account key {}", path))?;
+
+        if let Some((token, expires)) = self
+            .tokens
+            .lock()
+            .expect("sheets token lock poisoned")
+            .get(&key.client_email)
+        {
+            if Instant::now() < *expires {
+                return Ok(token.clone());
+            }
+        }
+
+        debug!("Requesting Google access token for {}", key.client_email);
+        let now = crate::state::unix_now();
+        let claims = Claims {
+            iss: &key.client_email,
+            scope: SCOPE,
+            aud: &key.token_uri,
+            iat: now,
+            exp: now + 3600,
+        };
+        let assertion = jsonwebtoken::encode(
+            &Header::new(Algorithm::RS256),
+            &claims,
+            &EncodingKey::from_rsa_pem(key.private_key.as_bytes())
+                .context("Invalid service account private key")?,
+        )
+        .context("Failed to sign service account assertion")?;
+
+        let response = self
+            .client
+            .post(&key.token_uri)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", assertion.as_str()),
+            ])
+            .send()
+            .await
+            .context("Failed to request Google access token")?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Google token endpoint returned error {}: {}", status, body);
+        }
+        let token: TokenResponse = response
+            .json()
+            .await
+            .context("Failed to parse Google token response")?;
+
+        let expires = Instant::now()
+            + Duration::from_secs(token.expires_in).saturating_sub(TOKEN_EXPIRY_MARGIN);
+        self.tokens
+            .lock()
+            .expect("sheets token lock poisoned")
+            .insert(key.client_email, (token.access_token.clone(), expires));
+        Ok(token.access_token)
+    }
+}
+
+impl Default for SheetsClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `.../spreadsheets/{id}/values/{range}` with both segments percent-encoded
+fn values_url(spreadsheet_id: &str, range: &str) -> Result<Url> {
+    let mut url = Url::parse(API_BASE)?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("Invalid Sheets API URL"))?
+        .push(spreadsheet_id)
+        .push("values")
+        .push(range);
+    Ok(url)
+}
+
+/// A tab name in A1 notation, quoted so spaces and punctuation are allowed
+fn quote_tab(tab: &str) -> String {
+    format!("'{}'", tab.replace('\'', "''"))
+}
+
+/// Every row of the tab after the first `written`, through column Z
+fn leftover_range(tab: &str, written: usize) -> String {
+    format!("{}!A{}:Z", quote_tab(tab), written + 1)
+}
+
+fn rows(entries: &[OutputEntry]) -> Vec<Vec<String>> {
+    std::iter::once(HEADER.iter().map(|h| h.to_string()).collect())
+        .chain(entries.iter().map(row))
+        .collect()
+}
+
+fn row(entry: &OutputEntry) -> Vec<String> {
+    vec![
+        entry.callsign.clone(),
+        entry.name.clone(),
+        entry.suffix.clone(),
+        entry.grid.clone().unwrap_or_default(),
+        entry.state.clone().unwrap_or_default(),
+        entry.country.clone().unwrap_or_default(),
+        entry.class.clone().unwrap_or_default(),
+        entry.dmr_id.map(|id| id.to_string()).unwrap_or_default(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows() {
+        let entry = OutputEntry {
+            callsign: "W6JSV".to_string(),
+            name: "Jay".to_string(),
+            suffix: "🏔️".to_string(),
+            emoji_separator: "📻".to_string(),
+            grid: Some("CM97".to_string()),
+            country: Some("United States".to_string()),
+            class: Some("E".to_string()),
+            dmr_id: Some(3106216),
+            ..Default::default()
+        };
+        let rows = rows(&[entry]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][0], "Callsign");
+        assert_eq!(
+            rows[1],
+            [
+                "W6JSV",
+                "Jay",
+                "🏔️",
+                "CM97",
+                "",
+                "United States",
+                "E",
+                "3106216"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_leftover_range_starts_below_written_rows() {
+        assert_eq!(leftover_range("Roster", 3), "'Roster'!A4:Z");
+    }
+
+    #[test]
+    fn test_values_url_quotes_tab() {
+        let range = format!("{}!A1", quote_tab("Club's Roster"));
+        assert_eq!(
+            values_url("abc123", &range).unwrap().as_str(),
+            "https://sheets.googleapis.com/v4/spreadsheets/abc123/values/'Club''s%20Roster'!A1"
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::{OwnedRwLockReadGuard, RwLock};
+use tracing::info;
+
+/// Tracks in-flight regenerations so shutdown can wait for them to finish
+#[derive(Clone, Default)]
+pub struct InFlight {
+    shutting_down: Arc<AtomicBool>,
+    lock: Arc<RwLock<()>>,
+}
+
+impl InFlight {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a unit of work; returns `None` once shutdown has begun.
+    /// Shutdown waits until every returned guard is dropped.
+    pub async fn begin(&self) -> Option<OwnedRwLockReadGuard<()>> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return None;
+        }
+        Some(self.lock.clone().read_owned().await)
+    }
+
+    /// Refuse new work and wait for in-flight work to finish
+    pub async fn drain(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        let _ = self.lock.write().await;
+    }
+}
+
+/// Resolve when the process receives SIGINT or SIGTERM
+pub async fn wait_for_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install SIGINT handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received SIGINT"),
+        _ = terminate => info!("Received SIGTERM"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_waits_for_in_flight_work() {
+        let in_flight = InFlight::new();
+        let guard = in_flight.begin().await.unwrap();
+
+        let draining = {
+            let in_flight = in_flight.clone();
+            tokio::spawn(async move { in_flight.drain().await })
+        };
+
+        tokio::task::yield_now().await;
+        assert!(!draining.is_finished());
+        assert!(in_flight.begin().await.is_none());
+
+        drop(guard);
+        draining.await.unwrap();
+    }
+}
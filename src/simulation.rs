@@ -0,0 +1,356 @@
+use crate::config::Config;
+use crate::output::OutputEntry;
+use crate::parser::CallsignParser;
+use crate::pipeline::{DxccTable, Member, Metrics, RosterBuilder};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+/// A gateway event as written to the recording file, one JSON object per line
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecordedEvent {
+    /// Full member list fetched at startup, used as the replay baseline
+    Snapshot {
+        at: DateTime<Utc>,
+        guild_id: u64,
+        members: Vec<Member>,
+    },
+    MemberAddition {
+        at: DateTime<Utc>,
+        guild_id: u64,
+        member: Member,
+    },
+    MemberRemoval {
+        at: DateTime<Utc>,
+        guild_id: u64,
+        user_id: u64,
+    },
+    MemberUpdate {
+        at: DateTime<Utc>,
+        guild_id: u64,
+        member: Member,
+    },
+}
+
+impl RecordedEvent {
+    fn at(&self) -> DateTime<Utc> {
+        match self {
+            Self::Snapshot { at, .. }
+            | Self::MemberAddition { at, .. }
+            | Self::MemberRemoval { at, .. }
+            | Self::MemberUpdate { at, .. } => *at,
+        }
+    }
+
+    fn guild_id(&self) -> u64 {
+        match self {
+            Self::Snapshot { guild_id, .. }
+            | Self::MemberAddition { guild_id, .. }
+            | Self::MemberRemoval { guild_id, .. }
+            | Self::MemberUpdate { guild_id, .. } => *guild_id,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Snapshot { members, .. } => format!("snapshot of {} members", members.len()),
+            Self::MemberAddition { member, .. } => format!("member joined: {}", member.username),
+            Self::MemberRemoval { user_id, .. } => format!("member left: {}", user_id),
+            Self::MemberUpdate { member, .. } => format!("member updated: {}", member.username),
+        }
+    }
+}
+
+/// Appends gateway events to a JSONL file for later replay
+pub struct EventRecorder {
+    file: Mutex<File>,
+}
+
+impl EventRecorder {
+    pub fn open(path: &str) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open event recording file: {}", path))?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Write an event; failures are logged rather than interrupting the bot
+    pub fn record(&self, event: &RecordedEvent) {
+        let line = match serde_json::to_string(event) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to serialize recorded event: {}", e);
+                return;
+            }
+        };
+
+        let mut file = self.file.lock().expect("event recorder lock poisoned");
+        if let Err(e) = writeln!(file, "{}", line) {
+            warn!("Failed to write recorded event: {}", e);
+        }
+    }
+
+    /// Make sure every recorded event has reached disk
+    pub fn flush(&self) -> Result<()> {
+        let file = self.file.lock().expect("event recorder lock poisoned");
+        file.sync_data()
+            .context("Failed to flush event recording file")
+    }
+}
+
+/// A change between two consecutive rosters, serialized as MQTT member events
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "event")]
+pub enum EntryChange {
+    #[serde(rename = "joined")]
+    Added { callsign: String, name: String },
+    #[serde(rename = "left")]
+    Removed { callsign: String },
+    #[serde(rename = "changed")]
+    Renamed {
+        callsign: String,
+        old: String,
+        new: String,
+    },
+}
+
+impl std::fmt::Display for EntryChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added { callsign, name } => write!(f, "+ {} {}", callsign, name),
+            Self::Removed { callsign } => write!(f, "- {}", callsign),
+            Self::Renamed { callsign, old, new } => {
+                write!(f, "~ {} name changed: {} -> {}", callsign, old, new)
+            }
+        }
+    }
+}
+
+/// Compare two rosters by callsign
+pub fn diff_entries(old: &[OutputEntry], new: &[OutputEntry]) -> Vec<EntryChange> {
+    let old_by_call: BTreeMap<&str, &OutputEntry> =
+        old.iter().map(|e| (e.callsign.as_str(), e)).collect();
+    let new_by_call: BTreeMap<&str, &OutputEntry> =
+        new.iter().map(|e| (e.callsign.as_str(), e)).collect();
+
+    let mut changes = Vec::new();
+
+    for (callsign, entry) in &new_by_call {
+        match old_by_call.get(callsign) {
+            None => changes.push(EntryChange::Added {
+                callsign: callsign.to_string(),
+                name: entry.name.clone(),
+            }),
+            Some(previous) if previous.name != entry.name => changes.push(EntryChange::Renamed {
+                callsign: callsign.to_string(),
+                old: previous.name.clone(),
+                new: entry.name.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for callsign in old_by_call.keys() {
+        if !new_by_call.contains_key(callsign) {
+            changes.push(EntryChange::Removed {
+                callsign: callsign.to_string(),
+            });
+        }
+    }
+
+    changes
+}
+
+/// Replay a recording offline, printing how each event changed the roster.
+/// QRZ lookups are skipped so replays are deterministic and need no credentials.
+pub async fn replay(path: &str, config: &Config) -> Result<()> {
+    let file = File::open(path).with_context(|| format!("Failed to open recording: {}", path))?;
+
+    let parser = CallsignParser::from_config(&config.parser)?;
+    let metrics = Metrics::new();
+    let dxcc = DxccTable::from_config(config.dxcc.as_ref())?;
+    let builder = RosterBuilder {
+        parser: &parser,
+        qrz_client: None,
+        radioid_client: None,
+        aprs_client: None,
+        dxcc: Some(Arc::new(dxcc)),
+        lotw: None,
+        alerts: None,
+        metrics: &metrics,
+        excluded_user_ids: &config.discord.exclude_user_ids,
+    };
+
+    let mut members: HashMap<u64, BTreeMap<u64, Member>> = HashMap::new();
+    let mut rosters: HashMap<u64, Vec<OutputEntry>> = HashMap::new();
+
+    for (line_number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.with_context(|| format!("Failed to read {}", path))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let event: RecordedEvent = serde_json::from_str(&line)
+            .with_context(|| format!("Invalid event on line {}", line_number + 1))?;
+
+        let guild_id = event.guild_id();
+        let Some(guild_config) = config.get_guild_config(guild_id) else {
+            continue;
+        };
+
+        println!("[{}] guild {}: {}", event.at(), guild_id, event.describe());
+
+        let guild_members = members.entry(guild_id).or_default();
+        apply_event(guild_members, event);
+
+        let snapshot: Vec<Member> = guild_members.values().cloned().collect();
+        let entries = builder
+            .build(guild_config, &snapshot, None, &HashMap::new(), None)
+            .await
+            .entries;
+
+        let previous = rosters.entry(guild_id).or_default();
+        let changes = diff_entries(previous, &entries);
+        if changes.is_empty() {
+            println!("    (no roster changes)");
+        }
+        for change in changes {
+            println!("    {}", change);
+        }
+        *previous = entries;
+    }
+
+    Ok(())
+}
+
+fn apply_event(members: &mut BTreeMap<u64, Member>, event: RecordedEvent) {
+    match event {
+        RecordedEvent::Snapshot {
+            members: snapshot, ..
+        } => {
+            *members = snapshot.into_iter().map(|m| (m.user_id, m)).collect();
+        }
+        RecordedEvent::MemberAddition { member, .. }
+        | RecordedEvent::MemberUpdate { member, .. } => {
+            members.insert(member.user_id, member);
+        }
+        RecordedEvent::MemberRemoval { user_id, .. } => {
+            members.remove(&user_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(id: u64, nick: &str) -> Member {
+        Member {
+            user_id: id,
+            username: format!("user{}", id),
+            nick: Some(nick.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn entry(callsign: &str, name: &str) -> OutputEntry {
+        OutputEntry {
+            callsign: callsign.to_string(),
+            name: name.to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_event_round_trip() {
+        let event = RecordedEvent::MemberAddition {
+            at: Utc::now(),
+            guild_id: 7,
+            member: member(1, "W6JSV - Jay"),
+        };
+
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains("\"type\":\"member_addition\""));
+
+        match serde_json::from_str(&json).unwrap() {
+            RecordedEvent::MemberAddition {
+                guild_id, member, ..
+            } => {
+                assert_eq!(guild_id, 7);
+                assert_eq!(member.nick.as_deref(), Some("W6JSV - Jay"));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_apply_event() {
+        let mut members = BTreeMap::new();
+        apply_event(
+            &mut members,
+            RecordedEvent::Snapshot {
+                at: Utc::now(),
+                guild_id: 1,
+                members: vec![member(1, "W6JSV"), member(2, "KI7QCF")],
+            },
+        );
+        assert_eq!(members.len(), 2);
+
+        apply_event(
+            &mut members,
+            RecordedEvent::MemberRemoval {
+                at: Utc::now(),
+                guild_id: 1,
+                user_id: 2,
+            },
+        );
+        apply_event(
+            &mut members,
+            RecordedEvent::MemberUpdate {
+                at: Utc::now(),
+                guild_id: 1,
+                member: member(1, "W6JSV - Jay"),
+            },
+        );
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[&1].nick.as_deref(), Some("W6JSV - Jay"));
+    }
+
+    #[test]
+    fn test_diff_entries() {
+        let old = vec![entry("W6JSV", "Jay"), entry("N0CALL", "Nobody")];
+        let new = vec![entry("W6JSV", "Jay S"), entry("KI7QCF", "Forrest")];
+
+        let changes = diff_entries(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                EntryChange::Added {
+                    callsign: "KI7QCF".to_string(),
+                    name: "Forrest".to_string()
+                },
+                EntryChange::Renamed {
+                    callsign: "W6JSV".to_string(),
+                    old: "Jay".to_string(),
+                    new: "Jay S".to_string()
+                },
+                EntryChange::Removed {
+                    callsign: "N0CALL".to_string()
+                },
+            ]
+        );
+        assert_eq!(changes[0].to_string(), "+ KI7QCF Forrest");
+    }
+}
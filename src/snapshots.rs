@@ -0,0 +1,204 @@
+use crate::output::OutputEntry;
+use crate::simulation::{diff_entries, EntryChange};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Format of snapshot IDs, which are also their file names; they sort by time
+const ID_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// A published roster: the rendered file and the entries the other sinks received
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub guild_id: u64,
+    pub taken_at: DateTime<Utc>,
+    pub content: String,
+    pub entries: Vec<OutputEntry>,
+}
+
+impl Snapshot {
+    pub fn id(&self) -> String {
+        self.taken_at.format(ID_FORMAT).to_string()
+    }
+}
+
+/// Timestamped snapshots under `<dir>/<guild_id>/<id>.json`, pruned to the newest `keep`
+pub struct SnapshotStore {
+    dir: PathBuf,
+    keep: usize,
+}
+
+impl SnapshotStore {
+    pub fn new(dir: impl Into<PathBuf>, keep: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            keep: keep.max(1),
+        }
+    }
+
+    fn guild_dir(&self, guild_id: u64) -> PathBuf {
+        self.dir.join(guild_id.to_string())
+    }
+
+    /// Write a snapshot and delete the oldest beyond `keep`; returns its ID
+    pub fn save(&self, snapshot: &Snapshot) -> Result<String> {
+        let dir = self.guild_dir(snapshot.guild_id);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create snapshot directory {}", dir.display()))?;
+        let id = snapshot.id();
+        let path = dir.join(format!("{}.json", id));
+        let json = serde_json::to_string(snapshot).context("Failed to serialize snapshot")?;
+        fs::write(&path, json)
+            .with_context(|| format!("Failed to write snapshot {}", path.display()))?;
+
+        for old in self.ids(snapshot.guild_id)?.into_iter().skip(self.keep) {
+            let path = dir.join(format!("{}.json", old));
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to prune snapshot {}", path.display()))?;
+        }
+        Ok(id)
+    }
+
+    /// Snapshot IDs for a guild, newest first
+    pub fn ids(&self, guild_id: u64) -> Result<Vec<String>> {
+        let dir = self.guild_dir(guild_id);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut ids = fs::read_dir(&dir)
+            .with_context(|| format!("Failed to list snapshots in {}", dir.display()))?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let id = path.file_stem()?.to_str()?;
+                (path.extension()? == "json" && parse_id(id).is_some()).then(|| id.to_string())
+            })
+            .collect::<Vec<_>>();
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(ids)
+    }
+
+    pub fn load(&self, guild_id: u64, id: &str) -> Result<Snapshot> {
+        if parse_id(id).is_none() {
+            anyhow::bail!("{} isn't a snapshot ID (like 20261016T120000Z)", id);
+        }
+        let path = self.guild_dir(guild_id).join(format!("{}.json", id));
+        read_snapshot(&path)
+    }
+
+    /// The newest `limit` snapshots, each with its changes from the one before it
+    pub fn history(
+        &self,
+        guild_id: u64,
+        limit: usize,
+    ) -> Result<Vec<(Snapshot, Vec<EntryChange>)>> {
+        let ids = self.ids(guild_id)?;
+        let snapshots = ids
+            .iter()
+            .take(limit + 1)
+            .map(|id| self.load(guild_id, id))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(snapshots
+            .iter()
+            .enumerate()
+            .take(limit)
+            .map(|(i, snapshot)| {
+                let changes = snapshots
+                    .get(i + 1)
+                    .map(|previous| diff_entries(&previous.entries, &snapshot.entries))
+                    .unwrap_or_default();
+                (snapshot.clone(), changes)
+            })
+            .collect())
+    }
+}
+
+fn read_snapshot(path: &Path) -> Result<Snapshot> {
+    let json = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read snapshot {}", path.display()))?;
+    serde_json::from_str(&json)
+        .with_context(|| format!("Failed to parse snapshot {}", path.display()))
+}
+
+fn parse_id(id: &str) -> Option<DateTime<Utc>> {
+    chrono::NaiveDateTime::parse_from_str(id, ID_FORMAT)
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+/// One line per snapshot for `/roster history` and the `rollback` listing
+pub fn describe(snapshot: &Snapshot, changes: &[EntryChange]) -> String {
+    let count = |f: fn(&EntryChange) -> bool| changes.iter().filter(|c| f(c)).count();
+    let added = count(|c| matches!(c, EntryChange::Added { .. }));
+    let removed = count(|c| matches!(c, EntryChange::Removed { .. }));
+    let renamed = count(|c| matches!(c, EntryChange::Renamed { .. }));
+    let mut line = format!(
+        "`{}` {} - {} entries",
+        snapshot.id(),
+        snapshot.taken_at.format("%Y-%m-%d %H:%M UTC"),
+        snapshot.entries.len()
+    );
+    if added + removed + renamed > 0 {
+        line.push_str(&format!(" (+{} −{} ~{})", added, removed, renamed));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(callsign: &str) -> OutputEntry {
+        OutputEntry {
+            callsign: callsign.to_string(),
+            name: "Op".to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn snapshot(seconds: i64, callsigns: &[&str]) -> Snapshot {
+        Snapshot {
+            guild_id: 1,
+            taken_at: DateTime::from_timestamp(1_760_000_000 + seconds, 0).unwrap(),
+            content: callsigns.join("\n"),
+            entries: callsigns.iter().map(|c| entry(c)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_save_prune_and_history() {
+        let dir =
+            std::env::temp_dir().join(format!("callsign-bot-snapshots-{}", std::process::id()));
+        let store = SnapshotStore::new(&dir, 2);
+
+        store.save(&snapshot(0, &["W6JSV"])).unwrap();
+        store.save(&snapshot(60, &["W6JSV", "KI7QCF"])).unwrap();
+        let newest = snapshot(120, &["KI7QCF"]);
+        let id = store.save(&newest).unwrap();
+        assert_eq!(id, "20251009T085520Z");
+
+        // Only the newest two are kept
+        assert_eq!(
+            store.ids(1).unwrap(),
+            vec!["20251009T085520Z", "20251009T085420Z"]
+        );
+        assert_eq!(store.load(1, &id).unwrap(), newest);
+        assert!(store.load(1, "../../etc/passwd").is_err());
+        assert!(store.ids(2).unwrap().is_empty());
+
+        let history = store.history(1, 5).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            describe(&history[0].0, &history[0].1),
+            "`20251009T085520Z` 2025-10-09 08:55 UTC - 1 entries (+0 −1 ~0)"
+        );
+        assert_eq!(
+            describe(&history[1].0, &history[1].1),
+            "`20251009T085420Z` 2025-10-09 08:54 UTC - 2 entries"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,346 @@
+use crate::config::Override;
+use crate::output::OutputEntry;
+use crate::pipeline::CallsignConflict;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The most recently generated roster for a guild
+#[derive(Debug, Clone)]
+pub struct GuildRoster {
+    pub title: Option<String>,
+    pub entries: Vec<OutputEntry>,
+    /// Unix timestamp (seconds) of the generation
+    pub generated_at: u64,
+    pub counts: MemberCounts,
+}
+
+/// Who the generation saw, for `/stats`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MemberCounts {
+    /// Members considered for the roster, leaving out bots and excluded users
+    pub members: usize,
+    /// Those whose name parsed to a callsign
+    pub parsed: usize,
+}
+
+/// Shared, in-memory view of the latest roster for every guild
+#[derive(Clone, Default)]
+pub struct RosterStore {
+    inner: Arc<RwLock<HashMap<u64, GuildRoster>>>,
+    /// Last callsign parsed for each member, keyed by guild then user ID
+    known_callsigns: Arc<RwLock<HashMap<u64, HashMap<u64, String>>>>,
+    /// Overrides approved at runtime, keyed by guild then Discord user ID
+    approved_overrides: Arc<RwLock<HashMap<u64, HashMap<String, Override>>>>,
+    /// Duplicate callsigns found by the latest build, so reports aren't repeated
+    conflicts: Arc<RwLock<HashMap<u64, Vec<CallsignConflict>>>>,
+    /// `output::fingerprint()` of the last successfully published output per guild
+    published: Arc<RwLock<HashMap<u64, u64>>>,
+    /// `Roster::unresolved` from each guild's latest build, awaiting the QRZ backfill
+    unresolved: Arc<RwLock<HashMap<u64, HashSet<String>>>>,
+}
+
+impl RosterStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(
+        &self,
+        guild_id: u64,
+        title: Option<String>,
+        entries: Vec<OutputEntry>,
+        counts: MemberCounts,
+    ) {
+        let roster = GuildRoster {
+            title,
+            entries,
+            generated_at: unix_now(),
+            counts,
+        };
+        self.inner
+            .write()
+            .expect("roster store lock poisoned")
+            .insert(guild_id, roster);
+    }
+
+    pub fn get(&self, guild_id: u64) -> Option<GuildRoster> {
+        self.inner
+            .read()
+            .expect("roster store lock poisoned")
+            .get(&guild_id)
+            .cloned()
+    }
+
+    /// Whether a guild's latest roster lists `callsign`, without copying the roster
+    pub fn lists_callsign(&self, guild_id: u64, callsign: &str) -> bool {
+        self.inner
+            .read()
+            .expect("roster store lock poisoned")
+            .get(&guild_id)
+            .is_some_and(|roster| roster.entries.iter().any(|e| e.callsign == callsign))
+    }
+
+    /// Snapshot of all rosters, keyed by guild ID
+    pub fn all(&self) -> HashMap<u64, GuildRoster> {
+        self.inner
+            .read()
+            .expect("roster store lock poisoned")
+            .clone()
+    }
+
+    /// Remember the callsign a member was last listed under
+    pub fn remember_callsign(&self, guild_id: u64, user_id: u64, callsign: String) {
+        self.known_callsigns
+            .write()
+            .expect("roster store lock poisoned")
+            .entry(guild_id)
+            .or_default()
+            .insert(user_id, callsign);
+    }
+
+    /// The callsign a member was listed under in an earlier roster, if any
+    pub fn known_callsign(&self, guild_id: u64, user_id: u64) -> Option<String> {
+        self.known_callsigns
+            .read()
+            .expect("roster store lock poisoned")
+            .get(&guild_id)?
+            .get(&user_id)
+            .cloned()
+    }
+
+    pub fn approve_override(&self, guild_id: u64, user_id: u64, override_config: Override) {
+        self.approved_overrides
+            .write()
+            .expect("roster store lock poisoned")
+            .entry(guild_id)
+            .or_default()
+            .insert(user_id.to_string(), override_config);
+    }
+
+    /// Overrides approved at runtime for a guild; these last until restart
+    pub fn approved_overrides(&self, guild_id: u64) -> HashMap<String, Override> {
+        self.approved_overrides
+            .read()
+            .expect("roster store lock poisoned")
+            .get(&guild_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn published_fingerprint(&self, guild_id: u64) -> Option<u64> {
+        self.published
+            .read()
+            .expect("roster store lock poisoned")
+            .get(&guild_id)
+            .copied()
+    }
+
+    pub fn set_published_fingerprint(&self, guild_id: u64, fingerprint: u64) {
+        self.published
+            .write()
+            .expect("roster store lock poisoned")
+            .insert(guild_id, fingerprint);
+    }
+
+    /// Replace a guild's callsigns awaiting a QRZ retry with those from its latest build
+    pub fn set_unresolved(&self, guild_id: u64, callsigns: HashSet<String>) {
+        self.unresolved
+            .write()
+            .expect("roster store lock poisoned")
+            .insert(guild_id, callsigns);
+    }
+
+    pub fn unresolved(&self, guild_id: u64) -> HashSet<String> {
+        self.unresolved
+            .read()
+            .expect("roster store lock poisoned")
+            .get(&guild_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Record a guild's latest conflicts, returning whether they differ from the last build's
+    pub fn update_conflicts(&self, guild_id: u64, conflicts: Vec<CallsignConflict>) -> bool {
+        let mut all = self.conflicts.write().expect("roster store lock poisoned");
+        let previous = all.get(&guild_id).map(Vec::as_slice).unwrap_or_default();
+        if previous == conflicts.as_slice() {
+            return false;
+        }
+        all.insert(guild_id, conflicts);
+        true
+    }
+}
+
+/// Whether the Discord gateway is connected, for `/healthz` and reconnect resyncs
+#[derive(Clone, Default)]
+pub struct GatewayState {
+    inner: Arc<RwLock<GatewayStatus>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GatewayStatus {
+    pub connected: bool,
+    /// Unix timestamp (seconds) of the last connect or disconnect; 0 before the first connect
+    pub since: u64,
+    /// Times the gateway came back after a disconnect
+    pub reconnects: u64,
+}
+
+impl GatewayState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> GatewayStatus {
+        self.inner
+            .read()
+            .expect("gateway state lock poisoned")
+            .clone()
+    }
+
+    /// Record a ready or resume; returns how many seconds the gateway was down,
+    /// or `None` for the first connect and repeated notifications
+    pub fn connected(&self) -> Option<u64> {
+        self.connected_at(unix_now())
+    }
+
+    pub fn disconnected(&self) {
+        self.disconnected_at(unix_now());
+    }
+
+    fn connected_at(&self, now: u64) -> Option<u64> {
+        let mut status = self.inner.write().expect("gateway state lock poisoned");
+        if status.connected {
+            return None;
+        }
+        let down = (status.since != 0).then(|| now.saturating_sub(status.since));
+        if down.is_some() {
+            status.reconnects += 1;
+        }
+        status.connected = true;
+        status.since = now;
+        down
+    }
+
+    fn disconnected_at(&self, now: u64) {
+        let mut status = self.inner.write().expect("gateway state lock poisoned");
+        if status.connected {
+            status.connected = false;
+            status.since = now;
+        }
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_and_get() {
+        let store = RosterStore::new();
+        assert!(store.get(1).is_none());
+
+        store.update(
+            1,
+            Some("Club".to_string()),
+            vec![OutputEntry {
+                callsign: "W6JSV".to_string(),
+                name: "Jay".to_string(),
+                emoji_separator: "📻".to_string(),
+                ..Default::default()
+            }],
+            MemberCounts {
+                members: 3,
+                parsed: 1,
+            },
+        );
+
+        let roster = store.get(1).unwrap();
+        assert_eq!(roster.title.as_deref(), Some("Club"));
+        assert_eq!(roster.entries.len(), 1);
+        assert_eq!(roster.counts.parsed, 1);
+        assert_eq!(store.all().len(), 1);
+        assert!(store.lists_callsign(1, "W6JSV"));
+        assert!(!store.lists_callsign(1, "KI7QCF"));
+        assert!(!store.lists_callsign(2, "W6JSV"));
+    }
+
+    #[test]
+    fn test_known_callsigns_and_approved_overrides() {
+        let store = RosterStore::new();
+        assert!(store.known_callsign(1, 42).is_none());
+
+        store.remember_callsign(1, 42, "W6JSV".to_string());
+        assert_eq!(store.known_callsign(1, 42).as_deref(), Some("W6JSV"));
+        assert!(store.known_callsign(2, 42).is_none());
+
+        store.approve_override(
+            1,
+            42,
+            Override {
+                callsign: Some("W6JSV".to_string()),
+                name: None,
+                suffix: None,
+                emoji: None,
+                skip_qrz: false,
+                hide_location: false,
+            },
+        );
+        assert!(store.approved_overrides(1).contains_key("42"));
+        assert!(store.approved_overrides(2).is_empty());
+    }
+
+    #[test]
+    fn test_unresolved() {
+        let store = RosterStore::new();
+        assert!(store.unresolved(1).is_empty());
+
+        store.set_unresolved(1, HashSet::from(["W6JSV".to_string()]));
+        assert!(store.unresolved(1).contains("W6JSV"));
+        assert!(store.unresolved(2).is_empty());
+
+        // Each build replaces the last
+        store.set_unresolved(1, HashSet::new());
+        assert!(store.unresolved(1).is_empty());
+    }
+
+    #[test]
+    fn test_gateway_state() {
+        let gateway = GatewayState::new();
+        assert!(!gateway.status().connected);
+
+        // The first connect isn't a reconnect
+        assert_eq!(gateway.connected_at(100), None);
+        assert_eq!(gateway.connected_at(105), None);
+
+        gateway.disconnected_at(200);
+        gateway.disconnected_at(210);
+        assert_eq!(
+            gateway.status(),
+            GatewayStatus {
+                connected: false,
+                since: 200,
+                reconnects: 0,
+            }
+        );
+
+        assert_eq!(gateway.connected_at(290), Some(90));
+        assert_eq!(
+            gateway.status(),
+            GatewayStatus {
+                connected: true,
+                since: 290,
+                reconnects: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,1049 @@
+use crate::applications::Application;
+use crate::config::Override;
+use crate::events::ClubEvent;
+use crate::morse::CwScore;
+use crate::nets::{Net, NetRecord};
+use crate::output::write_atomically;
+use crate::pipeline::DepartedMember;
+use crate::qrz::{QrzUsage, UsageStore};
+use crate::quiz::QuizStats;
+use crate::swap::Listing;
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::info;
+
+/// Feed item IDs remembered per feed; comfortably more than a feed lists at once
+const MAX_SEEN_BULLETINS: usize = 500;
+
+/// Everything the bot persists between restarts
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredState {
+    /// Suffixes members picked with /suffix, keyed by guild then user ID
+    #[serde(default)]
+    suffixes: BTreeMap<u64, BTreeMap<u64, String>>,
+    /// Members who left while listed on the roster, keyed by guild then user ID
+    #[serde(default)]
+    departed: BTreeMap<u64, BTreeMap<u64, DepartedMember>>,
+    /// Overrides from onboarding DMs and /override, keyed by guild then user ID
+    #[serde(default)]
+    member_overrides: BTreeMap<u64, BTreeMap<u64, Override>>,
+    /// IDs of gists the bot created, keyed by guild
+    #[serde(default)]
+    gists: BTreeMap<u64, String>,
+    /// Nets in progress, keyed by guild
+    #[serde(default)]
+    active_nets: BTreeMap<u64, Net>,
+    /// Finished nets, for attendance stats, keyed by guild
+    #[serde(default)]
+    net_history: BTreeMap<u64, Vec<NetRecord>>,
+    /// Members who asked for license expiration DMs, keyed by guild
+    #[serde(default)]
+    license_dm_opt_ins: BTreeMap<u64, BTreeSet<u64>>,
+    /// The expiration date each member was last warned about, keyed by guild then user ID
+    #[serde(default)]
+    license_dms_sent: BTreeMap<u64, BTreeMap<u64, NaiveDate>>,
+    /// Members who opted out of QRZ lookups with /privacy, keyed by guild
+    #[serde(default)]
+    qrz_opt_outs: BTreeMap<u64, BTreeSet<u64>>,
+    /// Members who asked with /privacy to be kept off maps, keyed by guild
+    #[serde(default)]
+    map_opt_outs: BTreeMap<u64, BTreeSet<u64>>,
+    /// Morse practice results from /cw, keyed by guild then user ID
+    #[serde(default)]
+    cw_scores: BTreeMap<u64, BTreeMap<u64, CwScore>>,
+    /// Exam quiz streaks from /quiz, keyed by guild then user ID
+    #[serde(default)]
+    quiz_stats: BTreeMap<u64, BTreeMap<u64, QuizStats>>,
+    /// Years each member's dues were marked paid with /dues, keyed by guild then user ID
+    #[serde(default)]
+    dues_paid: BTreeMap<u64, BTreeMap<u64, BTreeSet<i32>>>,
+    /// Recurring events from /event create, keyed by guild
+    #[serde(default)]
+    events: BTreeMap<u64, Vec<ClubEvent>>,
+    /// /apply submissions awaiting review, keyed by guild then user ID
+    #[serde(default)]
+    applications: BTreeMap<u64, BTreeMap<u64, Application>>,
+    /// Active classifieds from /swap post, keyed by guild
+    #[serde(default)]
+    swap_listings: BTreeMap<u64, Vec<Listing>>,
+    /// Topics members offered to mentor with /elmer register, keyed by guild then user ID
+    #[serde(default)]
+    elmers: BTreeMap<u64, BTreeMap<u64, BTreeSet<String>>>,
+    /// IDs of bulletin feed items already relayed, oldest first, keyed by guild then feed URL
+    #[serde(default)]
+    bulletins_seen: BTreeMap<u64, BTreeMap<String, Vec<String>>>,
+    /// The club summary last posted for each contest, keyed by guild then contest name
+    #[serde(default)]
+    contest_summaries: BTreeMap<u64, BTreeMap<String, String>>,
+    /// Requests made to QRZ today, counted against `[qrz]`'s daily limit
+    #[serde(default)]
+    qrz_usage: Option<QrzUsage>,
+}
+
+/// Small JSON-file store for member choices. Without a configured path,
+/// state is kept in memory only and lost on restart.
+pub struct Storage {
+    path: Option<PathBuf>,
+    state: Mutex<StoredState>,
+}
+
+impl Storage {
+    pub fn open(path: Option<&str>) -> Result<Self> {
+        let Some(path) = path else {
+            info!("No [storage] path configured; member choices won't survive restarts");
+            return Ok(Self {
+                path: None,
+                state: Mutex::new(StoredState::default()),
+            });
+        };
+
+        let path = PathBuf::from(path);
+        let state = if path.exists() {
+            let contents = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read storage file: {}", path.display()))?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse storage file: {}", path.display()))?
+        } else {
+            StoredState::default()
+        };
+
+        Ok(Self {
+            path: Some(path),
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Suffixes chosen by members of a guild, keyed by user ID
+    pub fn suffixes(&self, guild_id: u64) -> HashMap<u64, String> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .suffixes
+            .get(&guild_id)
+            .map(|users| users.iter().map(|(k, v)| (*k, v.clone())).collect())
+            .unwrap_or_default()
+    }
+
+    /// Set or clear (`None`) a member's chosen suffix and persist it
+    pub fn set_suffix(&self, guild_id: u64, user_id: u64, suffix: Option<String>) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let guild = state.suffixes.entry(guild_id).or_default();
+        match suffix {
+            Some(suffix) => guild.insert(user_id, suffix),
+            None => guild.remove(&user_id),
+        };
+        self.save(&state)
+    }
+
+    /// Members who left a guild, keyed by user ID
+    pub fn departed(&self, guild_id: u64) -> HashMap<u64, DepartedMember> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .departed
+            .get(&guild_id)
+            .map(|users| users.iter().map(|(k, v)| (*k, v.clone())).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn record_departure(
+        &self,
+        guild_id: u64,
+        user_id: u64,
+        member: DepartedMember,
+    ) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        state
+            .departed
+            .entry(guild_id)
+            .or_default()
+            .insert(user_id, member);
+        self.save(&state)
+    }
+
+    /// Drop a departure when the member rejoins; a no-op for members who never left
+    pub fn forget_departure(&self, guild_id: u64, user_id: u64) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let removed = state
+            .departed
+            .get_mut(&guild_id)
+            .and_then(|users| users.remove(&user_id))
+            .is_some();
+        if removed {
+            self.save(&state)?;
+        }
+        Ok(())
+    }
+
+    /// Overrides stored by onboarding and /override, keyed like `[guilds.overrides]`
+    pub fn member_overrides(&self, guild_id: u64) -> HashMap<String, Override> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .member_overrides
+            .get(&guild_id)
+            .map(|users| {
+                users
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn set_member_override(
+        &self,
+        guild_id: u64,
+        user_id: u64,
+        member_override: Override,
+    ) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        state
+            .member_overrides
+            .entry(guild_id)
+            .or_default()
+            .insert(user_id, member_override);
+        self.save(&state)
+    }
+
+    /// Drop a member's stored override, returning whether there was one
+    pub fn remove_member_override(&self, guild_id: u64, user_id: u64) -> Result<bool> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let removed = state
+            .member_overrides
+            .get_mut(&guild_id)
+            .and_then(|users| users.remove(&user_id))
+            .is_some();
+        if removed {
+            self.save(&state)?;
+        }
+        Ok(removed)
+    }
+
+    /// The gist the bot created for a guild, if any
+    pub fn gist_id(&self, guild_id: u64) -> Option<String> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .gists
+            .get(&guild_id)
+            .cloned()
+    }
+
+    pub fn set_gist_id(&self, guild_id: u64, id: String) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        state.gists.insert(guild_id, id);
+        self.save(&state)
+    }
+
+    /// The net in progress in a guild, if any
+    pub fn active_net(&self, guild_id: u64) -> Option<Net> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .active_nets
+            .get(&guild_id)
+            .cloned()
+    }
+
+    /// Read and change a guild's active net under the lock, then persist it, so
+    /// concurrent check-ins can't overwrite each other
+    pub fn update_active_net<T>(
+        &self,
+        guild_id: u64,
+        update: impl FnOnce(&mut Option<Net>) -> T,
+    ) -> Result<T> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let mut net = state.active_nets.remove(&guild_id);
+        let result = update(&mut net);
+        if let Some(net) = net {
+            state.active_nets.insert(guild_id, net);
+        }
+        self.save(&state)?;
+        Ok(result)
+    }
+
+    /// Finished nets in a guild, oldest first
+    pub fn net_history(&self, guild_id: u64) -> Vec<NetRecord> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .net_history
+            .get(&guild_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn record_net(&self, guild_id: u64, record: NetRecord) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        state.net_history.entry(guild_id).or_default().push(record);
+        self.save(&state)
+    }
+
+    /// Members of a guild who opted in to license expiration DMs
+    pub fn license_dm_opt_ins(&self, guild_id: u64) -> Vec<u64> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .license_dm_opt_ins
+            .get(&guild_id)
+            .map(|users| users.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn set_license_dm_opt_in(&self, guild_id: u64, user_id: u64, opted_in: bool) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let users = state.license_dm_opt_ins.entry(guild_id).or_default();
+        if opted_in {
+            users.insert(user_id);
+        } else {
+            users.remove(&user_id);
+        }
+        self.save(&state)
+    }
+
+    /// Members of a guild who opted out of QRZ lookups
+    pub fn qrz_opt_outs(&self, guild_id: u64) -> Vec<u64> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .qrz_opt_outs
+            .get(&guild_id)
+            .map(|users| users.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn set_qrz_opt_out(&self, guild_id: u64, user_id: u64, opted_out: bool) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let users = state.qrz_opt_outs.entry(guild_id).or_default();
+        if opted_out {
+            users.insert(user_id);
+        } else {
+            users.remove(&user_id);
+        }
+        self.save(&state)
+    }
+
+    /// Members of a guild who asked to be kept off maps
+    pub fn map_opt_outs(&self, guild_id: u64) -> Vec<u64> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .map_opt_outs
+            .get(&guild_id)
+            .map(|users| users.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn set_map_opt_out(&self, guild_id: u64, user_id: u64, opted_out: bool) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let users = state.map_opt_outs.entry(guild_id).or_default();
+        if opted_out {
+            users.insert(user_id);
+        } else {
+            users.remove(&user_id);
+        }
+        self.save(&state)
+    }
+
+    /// The expiration date a member was last warned about, so each license is warned once
+    pub fn license_dm_sent(&self, guild_id: u64, user_id: u64) -> Option<NaiveDate> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .license_dms_sent
+            .get(&guild_id)
+            .and_then(|users| users.get(&user_id))
+            .copied()
+    }
+
+    pub fn record_license_dm(&self, guild_id: u64, user_id: u64, expires: NaiveDate) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        state
+            .license_dms_sent
+            .entry(guild_id)
+            .or_default()
+            .insert(user_id, expires);
+        self.save(&state)
+    }
+
+    /// A member's Morse practice results so far
+    pub fn cw_score(&self, guild_id: u64, user_id: u64) -> CwScore {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .cw_scores
+            .get(&guild_id)
+            .and_then(|users| users.get(&user_id))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Grade a copy of `sent` and persist the member's updated results
+    pub fn record_cw_attempt(
+        &self,
+        guild_id: u64,
+        user_id: u64,
+        sent: &str,
+        copied: &str,
+        wpm: u32,
+    ) -> Result<CwScore> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let score = state
+            .cw_scores
+            .entry(guild_id)
+            .or_default()
+            .entry(user_id)
+            .or_default();
+        score.record(sent, copied, wpm);
+        let score = score.clone();
+        self.save(&state)?;
+        Ok(score)
+    }
+
+    /// Count a quiz answer toward the member's streak and persist it
+    pub fn record_quiz_answer(
+        &self,
+        guild_id: u64,
+        user_id: u64,
+        correct: bool,
+    ) -> Result<QuizStats> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let stats = state
+            .quiz_stats
+            .entry(guild_id)
+            .or_default()
+            .entry(user_id)
+            .or_default();
+        stats.record(correct);
+        let stats = stats.clone();
+        self.save(&state)?;
+        Ok(stats)
+    }
+
+    /// Members of a guild whose dues are paid for `year`
+    pub fn dues_paid(&self, guild_id: u64, year: i32) -> HashSet<u64> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .dues_paid
+            .get(&guild_id)
+            .map(|users| {
+                users
+                    .iter()
+                    .filter(|(_, years)| years.contains(&year))
+                    .map(|(user_id, _)| *user_id)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Each registered mentor's topics
+    pub fn elmers(&self, guild_id: u64) -> BTreeMap<u64, BTreeSet<String>> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .elmers
+            .get(&guild_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Register a member as a mentor, replacing any topics they gave before
+    pub fn register_elmer(
+        &self,
+        guild_id: u64,
+        user_id: u64,
+        topics: BTreeSet<String>,
+    ) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        state
+            .elmers
+            .entry(guild_id)
+            .or_default()
+            .insert(user_id, topics);
+        self.save(&state)
+    }
+
+    /// Returns whether the member was registered
+    pub fn unregister_elmer(&self, guild_id: u64, user_id: u64) -> Result<bool> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let removed = state
+            .elmers
+            .get_mut(&guild_id)
+            .is_some_and(|elmers| elmers.remove(&user_id).is_some());
+        if removed {
+            self.save(&state)?;
+        }
+        Ok(removed)
+    }
+
+    /// IDs of a feed's items already relayed to the guild, or `None` if it has never
+    /// been checked
+    pub fn seen_bulletins(&self, guild_id: u64, feed_url: &str) -> Option<HashSet<String>> {
+        let state = self.state.lock().expect("storage lock poisoned");
+        state
+            .bulletins_seen
+            .get(&guild_id)
+            .and_then(|feeds| feeds.get(feed_url))
+            .map(|ids| ids.iter().cloned().collect())
+    }
+
+    /// Record feed items as relayed, forgetting the oldest beyond `MAX_SEEN_BULLETINS`.
+    /// An empty `ids` still marks the feed as checked.
+    pub fn mark_bulletins_seen(
+        &self,
+        guild_id: u64,
+        feed_url: &str,
+        ids: impl IntoIterator<Item = String>,
+    ) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let seen = state
+            .bulletins_seen
+            .entry(guild_id)
+            .or_default()
+            .entry(feed_url.to_string())
+            .or_default();
+        for id in ids {
+            if !seen.contains(&id) {
+                seen.push(id);
+            }
+        }
+        let excess = seen.len().saturating_sub(MAX_SEEN_BULLETINS);
+        seen.drain(..excess);
+        self.save(&state)
+    }
+
+    /// The club summary last posted for a contest, so restarts don't repost it
+    pub fn contest_summary(&self, guild_id: u64, contest: &str) -> Option<String> {
+        let state = self.state.lock().expect("storage lock poisoned");
+        state
+            .contest_summaries
+            .get(&guild_id)
+            .and_then(|contests| contests.get(contest))
+            .cloned()
+    }
+
+    pub fn set_contest_summary(&self, guild_id: u64, contest: &str, summary: String) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        state
+            .contest_summaries
+            .entry(guild_id)
+            .or_default()
+            .insert(contest.to_string(), summary);
+        self.save(&state)
+    }
+
+    /// Mark a member's dues paid or unpaid for `year`; returns whether anything changed
+    pub fn set_dues_paid(
+        &self,
+        guild_id: u64,
+        user_id: u64,
+        year: i32,
+        paid: bool,
+    ) -> Result<bool> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let users = state.dues_paid.entry(guild_id).or_default();
+        let changed = if paid {
+            users.entry(user_id).or_default().insert(year)
+        } else {
+            let removed = users
+                .get_mut(&user_id)
+                .is_some_and(|years| years.remove(&year));
+            users.retain(|_, years| !years.is_empty());
+            removed
+        };
+        if changed {
+            self.save(&state)?;
+        }
+        Ok(changed)
+    }
+
+    /// A guild's recurring events, in the order they were created
+    pub fn events(&self, guild_id: u64) -> Vec<ClubEvent> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .events
+            .get(&guild_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Store a new event under the next free ID, returning it with that ID
+    pub fn add_event(&self, guild_id: u64, mut event: ClubEvent) -> Result<ClubEvent> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let events = state.events.entry(guild_id).or_default();
+        event.id = events.iter().map(|e| e.id).max().unwrap_or(0) + 1;
+        events.push(event.clone());
+        self.save(&state)?;
+        Ok(event)
+    }
+
+    /// Remove an event, returning it if it existed
+    pub fn remove_event(&self, guild_id: u64, id: u32) -> Result<Option<ClubEvent>> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let events = state.events.entry(guild_id).or_default();
+        let Some(index) = events.iter().position(|e| e.id == id) else {
+            return Ok(None);
+        };
+        let removed = events.remove(index);
+        self.save(&state)?;
+        Ok(Some(removed))
+    }
+
+    /// Remember the occurrence a Discord scheduled event was created for
+    pub fn set_discord_event_start(
+        &self,
+        guild_id: u64,
+        id: u32,
+        start: DateTime<Utc>,
+    ) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let Some(event) = state
+            .events
+            .get_mut(&guild_id)
+            .and_then(|events| events.iter_mut().find(|e| e.id == id))
+        else {
+            return Ok(());
+        };
+        event.discord_event_start = Some(start);
+        self.save(&state)
+    }
+
+    pub fn application(&self, guild_id: u64, user_id: u64) -> Option<Application> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .applications
+            .get(&guild_id)?
+            .get(&user_id)
+            .cloned()
+    }
+
+    /// Save a member's application, replacing one they submitted before
+    pub fn save_application(
+        &self,
+        guild_id: u64,
+        user_id: u64,
+        application: Application,
+    ) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        state
+            .applications
+            .entry(guild_id)
+            .or_default()
+            .insert(user_id, application);
+        self.save(&state)
+    }
+
+    /// Remove a member's application once it's reviewed, returning it if it was pending
+    pub fn take_application(&self, guild_id: u64, user_id: u64) -> Result<Option<Application>> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let removed = state
+            .applications
+            .get_mut(&guild_id)
+            .and_then(|applications| applications.remove(&user_id));
+        if removed.is_some() {
+            self.save(&state)?;
+        }
+        Ok(removed)
+    }
+
+    pub fn swap_listings(&self, guild_id: u64) -> Vec<Listing> {
+        self.state
+            .lock()
+            .expect("storage lock poisoned")
+            .swap_listings
+            .get(&guild_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Store a new listing under the next free ID, returning it with that ID
+    pub fn add_swap_listing(&self, guild_id: u64, mut listing: Listing) -> Result<Listing> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let listings = state.swap_listings.entry(guild_id).or_default();
+        listing.id = listings.iter().map(|l| l.id).max().unwrap_or(0) + 1;
+        listings.push(listing.clone());
+        self.save(&state)?;
+        Ok(listing)
+    }
+
+    /// Remember the channel message showing a listing
+    pub fn set_swap_message(&self, guild_id: u64, id: u32, message_id: u64) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let Some(listing) = state
+            .swap_listings
+            .get_mut(&guild_id)
+            .and_then(|listings| listings.iter_mut().find(|l| l.id == id))
+        else {
+            return Ok(());
+        };
+        listing.message_id = Some(message_id);
+        self.save(&state)
+    }
+
+    /// Remove a listing, returning it if it existed
+    pub fn remove_swap_listing(&self, guild_id: u64, id: u32) -> Result<Option<Listing>> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let listings = state.swap_listings.entry(guild_id).or_default();
+        let Some(index) = listings.iter().position(|l| l.id == id) else {
+            return Ok(None);
+        };
+        let removed = listings.remove(index);
+        self.save(&state)?;
+        Ok(Some(removed))
+    }
+
+    /// Remove and return the listings that expired by `now`
+    pub fn remove_expired_swap_listings(
+        &self,
+        guild_id: u64,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Listing>> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        let listings = state.swap_listings.entry(guild_id).or_default();
+        let (expired, active): (Vec<Listing>, Vec<Listing>) =
+            listings.drain(..).partition(|l| l.is_expired(now));
+        *listings = active;
+        if !expired.is_empty() {
+            self.save(&state)?;
+        }
+        Ok(expired)
+    }
+
+    fn save(&self, state: &StoredState) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let contents = serde_json::to_string_pretty(state).context("Failed to serialize state")?;
+        write_atomically(path, &contents)
+            .with_context(|| format!("Failed to write storage file: {}", path.display()))
+    }
+}
+
+impl UsageStore for Storage {
+    fn qrz_usage(&self) -> Option<QrzUsage> {
+        self.state.lock().expect("storage lock poisoned").qrz_usage
+    }
+
+    fn set_qrz_usage(&self, usage: QrzUsage) -> Result<()> {
+        let mut state = self.state.lock().expect("storage lock poisoned");
+        state.qrz_usage = Some(usage);
+        self.save(&state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suffixes_persist_across_reopen() {
+        let dir = std::env::temp_dir().join(format!("callsign-bot-storage-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        let path_str = path.to_str().unwrap();
+
+        let storage = Storage::open(Some(path_str)).unwrap();
+        storage.set_suffix(1, 42, Some("73".to_string())).unwrap();
+        storage.set_suffix(1, 43, Some("QRV".to_string())).unwrap();
+        storage.set_suffix(1, 43, None).unwrap();
+        let usage = QrzUsage {
+            date: NaiveDate::from_ymd_opt(2026, 10, 16).unwrap(),
+            lookups: 120,
+        };
+        storage.set_qrz_usage(usage).unwrap();
+        storage
+            .set_contest_summary(
+                1,
+                "CQ WW DX CW",
+                "**CQ WW DX CW**: 1 club entry".to_string(),
+            )
+            .unwrap();
+
+        let reopened = Storage::open(Some(path_str)).unwrap();
+        assert_eq!(reopened.qrz_usage(), Some(usage));
+        assert_eq!(
+            reopened.contest_summary(1, "CQ WW DX CW").as_deref(),
+            Some("**CQ WW DX CW**: 1 club entry")
+        );
+        assert_eq!(reopened.contest_summary(2, "CQ WW DX CW"), None);
+        let suffixes = reopened.suffixes(1);
+        assert_eq!(suffixes.get(&42).map(String::as_str), Some("73"));
+        assert!(!suffixes.contains_key(&43));
+        assert!(reopened.suffixes(2).is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_departures() {
+        let storage = Storage::open(None).unwrap();
+        let member = DepartedMember {
+            callsign: "W1AW".to_string(),
+            name: "Hiram".to_string(),
+            departed_at: 1_700_000_000,
+        };
+        storage.record_departure(1, 42, member.clone()).unwrap();
+        assert_eq!(storage.departed(1).get(&42), Some(&member));
+        assert!(storage.departed(2).is_empty());
+
+        storage.forget_departure(1, 42).unwrap();
+        storage.forget_departure(1, 43).unwrap();
+        assert!(storage.departed(1).is_empty());
+    }
+
+    #[test]
+    fn test_member_overrides() {
+        let storage = Storage::open(None).unwrap();
+        let member_override = Override {
+            callsign: Some("W6JSV".to_string()),
+            name: Some("Jay".to_string()),
+            suffix: None,
+            emoji: None,
+            skip_qrz: false,
+            hide_location: false,
+        };
+        storage
+            .set_member_override(1, 42, member_override.clone())
+            .unwrap();
+        let overrides = storage.member_overrides(1);
+        assert_eq!(
+            overrides.get("42").and_then(|o| o.callsign.as_deref()),
+            Some("W6JSV")
+        );
+        assert!(storage.member_overrides(2).is_empty());
+
+        assert!(storage.remove_member_override(1, 42).unwrap());
+        assert!(!storage.remove_member_override(1, 42).unwrap());
+        assert!(storage.member_overrides(1).is_empty());
+    }
+
+    #[test]
+    fn test_gist_ids() {
+        let storage = Storage::open(None).unwrap();
+        assert_eq!(storage.gist_id(1), None);
+        storage.set_gist_id(1, "abc123".to_string()).unwrap();
+        assert_eq!(storage.gist_id(1).as_deref(), Some("abc123"));
+        assert_eq!(storage.gist_id(2), None);
+    }
+
+    #[test]
+    fn test_active_nets() {
+        let storage = Storage::open(None).unwrap();
+        assert_eq!(storage.active_net(1), None);
+
+        let started_at = chrono::DateTime::from_timestamp(1_760_000_000, 0).unwrap();
+        storage
+            .update_active_net(1, |net| *net = Some(Net::new("Sunday Net", 42, started_at)))
+            .unwrap();
+        assert_eq!(storage.active_net(1).unwrap().name, "Sunday Net");
+        assert_eq!(storage.active_net(2), None);
+
+        let ended = storage.update_active_net(1, Option::take).unwrap().unwrap();
+        assert_eq!(ended.started_by, 42);
+        assert_eq!(storage.active_net(1), None);
+
+        storage.record_net(1, NetRecord::from(&ended)).unwrap();
+        assert_eq!(storage.net_history(1)[0].name, "Sunday Net");
+        assert!(storage.net_history(2).is_empty());
+    }
+
+    #[test]
+    fn test_license_dms() {
+        let storage = Storage::open(None).unwrap();
+        storage.set_license_dm_opt_in(1, 10, true).unwrap();
+        storage.set_license_dm_opt_in(1, 11, true).unwrap();
+        storage.set_license_dm_opt_in(1, 10, false).unwrap();
+        assert_eq!(storage.license_dm_opt_ins(1), vec![11]);
+        assert!(storage.license_dm_opt_ins(2).is_empty());
+
+        let expires = NaiveDate::from_ymd_opt(2026, 11, 1).unwrap();
+        assert_eq!(storage.license_dm_sent(1, 11), None);
+        storage.record_license_dm(1, 11, expires).unwrap();
+        assert_eq!(storage.license_dm_sent(1, 11), Some(expires));
+    }
+
+    #[test]
+    fn test_qrz_opt_outs() {
+        let storage = Storage::open(None).unwrap();
+        storage.set_qrz_opt_out(1, 10, true).unwrap();
+        storage.set_qrz_opt_out(1, 11, true).unwrap();
+        storage.set_qrz_opt_out(1, 11, false).unwrap();
+        assert_eq!(storage.qrz_opt_outs(1), vec![10]);
+        assert!(storage.qrz_opt_outs(2).is_empty());
+
+        storage.set_map_opt_out(1, 12, true).unwrap();
+        assert_eq!(storage.map_opt_outs(1), vec![12]);
+        assert_eq!(storage.qrz_opt_outs(1), vec![10]);
+    }
+
+    #[test]
+    fn test_cw_scores() {
+        let storage = Storage::open(None).unwrap();
+        assert_eq!(storage.cw_score(1, 42), CwScore::default());
+        storage
+            .record_cw_attempt(1, 42, "W6JSV", "W6JSV", 20)
+            .unwrap();
+        let score = storage
+            .record_cw_attempt(1, 42, "KI7QCF", "KI7", 25)
+            .unwrap();
+        assert_eq!(score.attempts, 2);
+        assert_eq!(score.correct, 1);
+        assert_eq!(storage.cw_score(1, 42), score);
+        assert_eq!(storage.cw_score(2, 42), CwScore::default());
+    }
+
+    #[test]
+    fn test_events() {
+        let storage = Storage::open(None).unwrap();
+        let event = |name| ClubEvent::new(name, "0 3 * * WED", 60, None, None, 42).unwrap();
+        assert_eq!(storage.add_event(1, event("Net")).unwrap().id, 1);
+        assert_eq!(storage.add_event(1, event("Meeting")).unwrap().id, 2);
+        assert_eq!(storage.add_event(2, event("Net")).unwrap().id, 1);
+
+        let start = Utc::now();
+        storage.set_discord_event_start(1, 2, start).unwrap();
+        assert_eq!(storage.events(1)[1].discord_event_start, Some(start));
+
+        assert_eq!(storage.remove_event(1, 1).unwrap().unwrap().name, "Net");
+        assert_eq!(storage.remove_event(1, 1).unwrap(), None);
+        // IDs aren't reused while later events remain
+        assert_eq!(storage.add_event(1, event("Field Day")).unwrap().id, 3);
+        assert_eq!(storage.events(1).len(), 2);
+    }
+
+    #[test]
+    fn test_dues_paid() {
+        let storage = Storage::open(None).unwrap();
+        assert!(storage.set_dues_paid(1, 42, 2026, true).unwrap());
+        assert!(!storage.set_dues_paid(1, 42, 2026, true).unwrap());
+        storage.set_dues_paid(1, 43, 2025, true).unwrap();
+        assert_eq!(storage.dues_paid(1, 2026), HashSet::from([42]));
+        assert_eq!(storage.dues_paid(1, 2025), HashSet::from([43]));
+        assert!(storage.dues_paid(2, 2026).is_empty());
+
+        assert!(storage.set_dues_paid(1, 42, 2026, false).unwrap());
+        assert!(!storage.set_dues_paid(1, 42, 2026, false).unwrap());
+        assert!(storage.dues_paid(1, 2026).is_empty());
+    }
+
+    #[test]
+    fn test_applications() {
+        use crate::applications::Verification;
+        let storage = Storage::open(None).unwrap();
+        let application = Application {
+            callsign: "W6JSV".to_string(),
+            name: "Jay".to_string(),
+            verification: Verification::Unchecked,
+            submitted_at: Utc::now(),
+        };
+        storage
+            .save_application(1, 42, application.clone())
+            .unwrap();
+        assert_eq!(storage.application(1, 42), Some(application.clone()));
+        assert_eq!(storage.application(2, 42), None);
+
+        assert_eq!(storage.take_application(1, 42).unwrap(), Some(application));
+        assert_eq!(storage.take_application(1, 42).unwrap(), None);
+        assert_eq!(storage.application(1, 42), None);
+    }
+
+    #[test]
+    fn test_swap_listings() {
+        use crate::swap::{ListingDetails, ListingKind};
+        let storage = Storage::open(None).unwrap();
+        let now = Utc::now();
+        let listing = |item, expire_days| {
+            let details = ListingDetails {
+                kind: ListingKind::ForSale,
+                item,
+                price: None,
+                condition: None,
+                description: None,
+            };
+            Listing::new(details, 42, "W6JSV", "Jay", now, expire_days)
+        };
+        let radio = storage.add_swap_listing(1, listing("Radio", 30)).unwrap();
+        let tuner = storage.add_swap_listing(1, listing("Tuner", 0)).unwrap();
+        assert_eq!((radio.id, tuner.id), (1, 2));
+
+        storage.set_swap_message(1, 1, 3000).unwrap();
+        assert_eq!(storage.swap_listings(1)[0].message_id, Some(3000));
+
+        let expired = storage.remove_expired_swap_listings(1, now).unwrap();
+        assert_eq!(expired, vec![tuner]);
+        assert_eq!(storage.swap_listings(1).len(), 1);
+
+        assert!(storage.remove_swap_listing(1, 1).unwrap().is_some());
+        assert!(storage.remove_swap_listing(1, 1).unwrap().is_none());
+        assert!(storage.swap_listings(1).is_empty());
+    }
+
+    #[test]
+    fn test_elmers() {
+        let storage = Storage::open(None).unwrap();
+        let topics = BTreeSet::from(["antennas".to_string(), "hf".to_string()]);
+        storage.register_elmer(1, 42, topics.clone()).unwrap();
+        storage
+            .register_elmer(1, 42, BTreeSet::from(["ft8".to_string()]))
+            .unwrap();
+        storage.register_elmer(1, 43, topics).unwrap();
+        assert_eq!(storage.elmers(1).len(), 2);
+        assert_eq!(storage.elmers(1)[&42], BTreeSet::from(["ft8".to_string()]));
+        assert!(storage.elmers(2).is_empty());
+
+        assert!(storage.unregister_elmer(1, 42).unwrap());
+        assert!(!storage.unregister_elmer(1, 42).unwrap());
+        assert!(!storage.elmers(1).contains_key(&42));
+    }
+
+    #[test]
+    fn test_bulletins_seen() {
+        let storage = Storage::open(None).unwrap();
+        let feed = "https://www.arrl.org/news/rss";
+        assert_eq!(storage.seen_bulletins(1, feed), None);
+        storage.mark_bulletins_seen(1, feed, []).unwrap();
+        assert_eq!(storage.seen_bulletins(1, feed), Some(HashSet::new()));
+
+        storage
+            .mark_bulletins_seen(1, feed, (0..MAX_SEEN_BULLETINS).map(|i| i.to_string()))
+            .unwrap();
+        storage
+            .mark_bulletins_seen(1, feed, ["1".to_string(), "new".to_string()])
+            .unwrap();
+        let seen = storage.seen_bulletins(1, feed).unwrap();
+        assert_eq!(seen.len(), MAX_SEEN_BULLETINS);
+        assert!(seen.contains("new"));
+        assert!(!seen.contains("0"));
+        assert_eq!(storage.seen_bulletins(2, feed), None);
+    }
+
+    #[test]
+    fn test_quiz_stats() {
+        let storage = Storage::open(None).unwrap();
+        storage.record_quiz_answer(1, 42, true).unwrap();
+        storage.record_quiz_answer(1, 42, true).unwrap();
+        let stats = storage.record_quiz_answer(1, 42, false).unwrap();
+        assert_eq!(stats.answered, 3);
+        assert_eq!(stats.streak, 0);
+        assert_eq!(stats.best_streak, 2);
+        let stats = storage.record_quiz_answer(2, 42, true).unwrap();
+        assert_eq!(stats.answered, 1);
+    }
+
+    #[test]
+    fn test_in_memory_without_path() {
+        let storage = Storage::open(None).unwrap();
+        storage.set_suffix(1, 42, Some("73".to_string())).unwrap();
+        assert_eq!(storage.suffixes(1).len(), 1);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::events::MAX_DESCRIPTION_CHARS;
+use crate::output::{sanitize_field, MAX_NAME_CHARS};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serenity::all::{CreateEmbed, CreateEmbedFooter, Timestamp};
+
+/// What a `/swap post` listing offers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ListingKind {
+    ForSale,
+    Wanted,
+    Trade,
+}
+
+impl ListingKind {
+    pub const ALL: [ListingKind; 3] = [
+        ListingKind::ForSale,
+        ListingKind::Wanted,
+        ListingKind::Trade,
+    ];
+
+    /// Used in command choices
+    pub fn id(self) -> &'static str {
+        match self {
+            ListingKind::ForSale => "for-sale",
+            ListingKind::Wanted => "wanted",
+            ListingKind::Trade => "trade",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ListingKind::ForSale => "For sale",
+            ListingKind::Wanted => "Wanted",
+            ListingKind::Trade => "For trade",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<ListingKind> {
+        ListingKind::ALL.into_iter().find(|kind| kind.id() == id)
+    }
+
+    fn emoji(self) -> &'static str {
+        match self {
+            ListingKind::ForSale => "🏷️",
+            ListingKind::Wanted => "🔎",
+            ListingKind::Trade => "🔁",
+        }
+    }
+}
+
+/// A classified from `/swap post`; kept in `Storage` until it's closed or expires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Listing {
+    /// Unique within the guild, for `/swap close`
+    pub id: u32,
+    pub kind: ListingKind,
+    pub item: String,
+    pub price: Option<String>,
+    pub condition: Option<String>,
+    pub description: Option<String>,
+    pub user_id: u64,
+    /// The poster's callsign and name on the roster when they posted
+    pub callsign: String,
+    pub name: String,
+    pub posted_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// The listing's message in the swap channel, once posted
+    pub message_id: Option<u64>,
+}
+
+/// What `/swap post` was given; fields are sanitized by `Listing::new()`
+pub struct ListingDetails<'a> {
+    pub kind: ListingKind,
+    pub item: &'a str,
+    pub price: Option<&'a str>,
+    pub condition: Option<&'a str>,
+    pub description: Option<&'a str>,
+}
+
+impl Listing {
+    /// `id` is filled in by `Storage::add_swap_listing()`
+    pub fn new(
+        details: ListingDetails,
+        user_id: u64,
+        callsign: &str,
+        name: &str,
+        now: DateTime<Utc>,
+        expire_days: u32,
+    ) -> Self {
+        Self {
+            id: 0,
+            kind: details.kind,
+            item: sanitize_field(details.item, MAX_NAME_CHARS),
+            price: details.price.map(|p| sanitize_field(p, MAX_NAME_CHARS)),
+            condition: details.condition.map(|c| sanitize_field(c, MAX_NAME_CHARS)),
+            description: details
+                .description
+                .map(|d| sanitize_field(d, MAX_DESCRIPTION_CHARS)),
+            user_id,
+            callsign: callsign.to_string(),
+            name: name.to_string(),
+            posted_at: now,
+            expires_at: now + Duration::days(expire_days.into()),
+            message_id: None,
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+
+    /// "W6JSV (Jay)", or just the callsign when the roster has no name
+    fn poster(&self) -> String {
+        if self.name.is_empty() {
+            self.callsign.clone()
+        } else {
+            format!("{} ({})", self.callsign, self.name)
+        }
+    }
+
+    /// "🏷️ For sale: Yaesu FT-817"
+    fn title(&self) -> String {
+        format!("{} {}: {}", self.kind.emoji(), self.kind.name(), self.item)
+    }
+}
+
+/// Name/value/inline triples shown on a listing's embed
+pub fn fields(listing: &Listing) -> Vec<(&'static str, String, bool)> {
+    let mut fields = Vec::new();
+    if let Some(price) = &listing.price {
+        fields.push(("Price", price.clone(), true));
+    }
+    if let Some(condition) = &listing.condition {
+        fields.push(("Condition", condition.clone(), true));
+    }
+    let contact = match listing.kind {
+        ListingKind::Wanted => "Wanted by",
+        ListingKind::ForSale | ListingKind::Trade => "Seller",
+    };
+    fields.push((
+        contact,
+        format!("{}\n<@{}>", listing.poster(), listing.user_id),
+        false,
+    ));
+    fields.push((
+        "Expires",
+        format!("<t:{}:D>", listing.expires_at.timestamp()),
+        true,
+    ));
+    fields
+}
+
+pub fn embed(listing: &Listing) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .title(listing.title())
+        .fields(fields(listing))
+        .footer(CreateEmbedFooter::new(format!(
+            "Listing {} · /swap close {} when it's done",
+            listing.id, listing.id
+        )));
+    if let Some(description) = &listing.description {
+        embed = embed.description(description);
+    }
+    if let Ok(posted_at) = Timestamp::from_unix_timestamp(listing.posted_at.timestamp()) {
+        embed = embed.timestamp(posted_at);
+    }
+    embed
+}
+
+pub fn message_link(guild_id: u64, channel_id: u64, message_id: u64) -> String {
+    format!(
+        "https://discord.com/channels/{}/{}/{}",
+        guild_id, channel_id, message_id
+    )
+}
+
+/// One line per active listing, linking to its message, under `heading`
+pub fn list(heading: &str, listings: &[Listing], guild_id: u64, channel_id: u64) -> String {
+    if listings.is_empty() {
+        return "No active swap listings.".to_string();
+    }
+    let mut lines = vec![format!("**{}** ({})", heading, listings.len())];
+    lines.extend(listings.iter().map(|listing| {
+        let mut line = format!("`{}` {}", listing.id, listing.title());
+        if let Some(price) = &listing.price {
+            line.push_str(&format!(", {}", price));
+        }
+        line.push_str(&format!(" from {}", listing.callsign));
+        if let Some(message_id) = listing.message_id {
+            line.push_str(&format!(
+                " · {}",
+                message_link(guild_id, channel_id, message_id)
+            ));
+        }
+        line.push_str(&format!(
+            " · expires <t:{}:R>",
+            listing.expires_at.timestamp()
+        ));
+        line
+    }));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn listing() -> Listing {
+        let now = Utc.with_ymd_and_hms(2026, 10, 14, 3, 0, 0).unwrap();
+        let details = ListingDetails {
+            kind: ListingKind::ForSale,
+            item: "Yaesu FT-817",
+            price: Some("$400"),
+            condition: Some("Excellent"),
+            description: Some("Includes the battery pack"),
+        };
+        let mut listing = Listing::new(details, 42, "W6JSV", "Jay", now, 30);
+        listing.id = 3;
+        listing
+    }
+
+    #[test]
+    fn test_kind_ids() {
+        for kind in ListingKind::ALL {
+            assert_eq!(ListingKind::from_id(kind.id()), Some(kind));
+        }
+        assert_eq!(ListingKind::from_id("free"), None);
+    }
+
+    #[test]
+    fn test_expiry() {
+        let listing = listing();
+        assert_eq!(
+            listing.expires_at,
+            Utc.with_ymd_and_hms(2026, 11, 13, 3, 0, 0).unwrap()
+        );
+        assert!(!listing.is_expired(listing.expires_at - Duration::seconds(1)));
+        assert!(listing.is_expired(listing.expires_at));
+    }
+
+    #[test]
+    fn test_fields() {
+        let mut listing = listing();
+        assert_eq!(listing.title(), "🏷️ For sale: Yaesu FT-817");
+        assert_eq!(
+            fields(&listing),
+            vec![
+                ("Price", "$400".to_string(), true),
+                ("Condition", "Excellent".to_string(), true),
+                ("Seller", "W6JSV (Jay)\n<@42>".to_string(), false),
+                ("Expires", "<t:1794538800:D>".to_string(), true),
+            ]
+        );
+
+        listing.kind = ListingKind::Wanted;
+        listing.price = None;
+        listing.condition = None;
+        listing.name = String::new();
+        assert_eq!(fields(&listing)[0].0, "Wanted by");
+        assert_eq!(fields(&listing)[0].1, "W6JSV\n<@42>");
+    }
+
+    #[test]
+    fn test_list() {
+        assert_eq!(list("Swap", &[], 1, 2), "No active swap listings.");
+        let mut listing = listing();
+        listing.message_id = Some(3000);
+        assert_eq!(
+            list("Swap digest", &[listing], 1, 2),
+            "**Swap digest** (1)\n`3` 🏷️ For sale: Yaesu FT-817, $400 from W6JSV \
+             · https://discord.com/channels/1/2/3000 · expires <t:1794538800:R>"
+        );
+    }
+}
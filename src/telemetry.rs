@@ -0,0 +1,90 @@
+use crate::config::{LogFormat, LoggingConfig, TelemetryConfig};
+use anyhow::{Context, Result};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Keeps the OTLP exporter alive; call `shutdown()` before exiting to flush spans
+pub struct Telemetry {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Telemetry {
+    pub fn shutdown(self) {
+        if let Some(provider) = self.provider {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to flush OpenTelemetry spans: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Install the global subscriber: text or JSON log output with span timings, plus
+/// OTLP trace export when `[telemetry] otlp_endpoint` is configured
+pub fn init(config: Option<&TelemetryConfig>, logging: &LoggingConfig) -> Result<Telemetry> {
+    let filter = logging.directives()?.into_iter().fold(
+        EnvFilter::from_default_env().add_directive("qrz_xml=off".parse().unwrap()),
+        |filter, directive| filter.add_directive(directive),
+    );
+
+    // Closing a span logs its busy/idle time, so slow stages show up in plain logs too
+    let (text_layer, json_layer) = match logging.format {
+        LogFormat::Text => (
+            Some(tracing_subscriber::fmt::layer().with_span_events(FmtSpan::CLOSE)),
+            None,
+        ),
+        // Span fields (guild_id, callsign, ...) are flattened into each line's "span"
+        // and "spans" so log stores can query them
+        LogFormat::Json => (
+            None,
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(true)
+                    .with_span_events(FmtSpan::CLOSE),
+            ),
+        ),
+    };
+
+    let provider = config
+        .and_then(|c| c.otlp_endpoint.as_deref().map(|endpoint| (c, endpoint)))
+        .map(|(config, endpoint)| {
+            let exporter = opentelemetry_otlp::SpanExporter::builder()
+                .with_http()
+                .with_endpoint(endpoint)
+                .build()
+                .context("Failed to create OTLP span exporter")?;
+
+            Ok::<_, anyhow::Error>(
+                SdkTracerProvider::builder()
+                    .with_batch_exporter(exporter)
+                    .with_resource(
+                        Resource::builder()
+                            .with_service_name(config.service_name.clone())
+                            .build(),
+                    )
+                    .build(),
+            )
+        })
+        .transpose()?;
+
+    let otel_layer = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer("discord-callsign-bot"))
+    });
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(text_layer)
+        .with(json_layer)
+        .with(otel_layer)
+        .try_init()
+        .context("Failed to initialize logging")?;
+
+    Ok(Telemetry { provider })
+}
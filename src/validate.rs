@@ -0,0 +1,97 @@
+use crate::config::schedule::Schedule;
+use crate::config::Config;
+use crate::github::GitHubClient;
+use crate::qrz::QrzClient;
+use anyhow::Result;
+use serenity::all::{GuildId, Http};
+use tracing::{error, info};
+
+/// Verify every credential and target in the config, logging each check's result
+pub async fn validate_config(config: &Config) -> Result<()> {
+    let mut failures = 0;
+    let mut check = |name: String, result: Result<()>| match result {
+        Ok(()) => info!("✓ {}", name),
+        Err(e) => {
+            error!("✗ {}: {:#}", name, e);
+            failures += 1;
+        }
+    };
+
+    info!(
+        "✓ Config parsed ({} guild(s) configured)",
+        config.guilds.len()
+    );
+
+    if let Some(schedule_config) = &config.schedule {
+        let result = Schedule::from_config(schedule_config);
+        let name = match &result {
+            Ok(schedule) => format!("Schedule ({})", schedule),
+            Err(_) => "Schedule".to_string(),
+        };
+        check(name, result.map(|_| ()));
+    }
+
+    // Discord: the token must work and the bot must be in each configured guild
+    let http = Http::new(&config.discord.token);
+    match http.get_current_user().await {
+        Ok(user) => {
+            check(format!("Discord token (bot user {})", user.name), Ok(()));
+            for guild_config in &config.guilds {
+                let result = http
+                    .get_guild(GuildId::new(guild_config.guild_id))
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| anyhow::anyhow!("bot cannot access guild: {}", e));
+                check(format!("Discord guild {}", guild_config.guild_id), result);
+            }
+        }
+        Err(e) => check("Discord token".to_string(), Err(anyhow::anyhow!("{}", e))),
+    }
+
+    // QRZ is optional, but if configured the credentials must authenticate
+    if let Some(qrz_config) = &config.qrz {
+        let result = match QrzClient::new(qrz_config, None).await {
+            Ok(client) => client.verify().await,
+            Err(e) => Err(e),
+        };
+        check(
+            format!("QRZ credentials ({})", qrz_config.username),
+            result.map_err(Into::into),
+        );
+    } else {
+        info!("- QRZ not configured, skipping");
+    }
+
+    // GitHub: token present and able to push to every output repository
+    match GitHubClient::new(&config.github) {
+        Ok(github) => {
+            for guild_config in &config.guilds {
+                let output = &guild_config.output;
+                if let Some(repo) = &output.repo {
+                    check(
+                        format!("GitHub push access to {}", repo),
+                        github.check_repo_access(repo).await.map_err(Into::into),
+                    );
+                }
+                if let Some(id) = output.gist.as_ref().and_then(|g| g.id.as_deref()) {
+                    check(
+                        format!("GitHub gist {}", id),
+                        github
+                            .get_gist_content(id, output.gist_filename())
+                            .await
+                            .map(|_| ())
+                            .map_err(Into::into),
+                    );
+                }
+            }
+        }
+        Err(e) => check("GitHub token".to_string(), Err(e.into())),
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{} check(s) failed", failures);
+    }
+
+    info!("All checks passed");
+    Ok(())
+}
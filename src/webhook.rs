@@ -0,0 +1,163 @@
+use crate::config::WebhookConfig;
+use crate::output::OutputEntry;
+use anyhow::{Context, Result};
+use reqwest::header::{AUTHORIZATION, USER_AGENT};
+use serde::Serialize;
+use tracing::info;
+
+pub struct WebhookClient {
+    client: reqwest::Client,
+}
+
+/// Body of a Discord-compatible webhook message
+#[derive(Serialize)]
+struct MessagePayload<'a> {
+    content: &'a str,
+    allowed_mentions: AllowedMentions,
+}
+
+#[derive(Serialize)]
+struct AllowedMentions {
+    parse: [&'static str; 0],
+}
+
+/// Roster JSON shared by the webhook and MQTT
+#[derive(Serialize)]
+pub struct RosterPayload<'a> {
+    guild_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
+    count: usize,
+    entries: &'a [OutputEntry],
+}
+
+impl<'a> RosterPayload<'a> {
+    pub fn new(guild_id: u64, title: Option<&'a str>, entries: &'a [OutputEntry]) -> Self {
+        Self {
+            guild_id,
+            title,
+            count: entries.len(),
+            entries,
+        }
+    }
+}
+
+impl WebhookClient {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// POST the roster as JSON to the configured webhook URL
+    pub async fn post_roster(
+        &self,
+        config: &WebhookConfig,
+        guild_id: u64,
+        title: Option<&str>,
+        entries: &[OutputEntry],
+    ) -> Result<()> {
+        let payload = RosterPayload::new(guild_id, title, entries);
+
+        info!(
+            "Posting {} entries to webhook {}",
+            entries.len(),
+            config.url
+        );
+
+        let mut request = self
+            .client
+            .post(&config.url)
+            .header(USER_AGENT, "discord-callsign-bot")
+            .json(&payload);
+
+        if let Some(token) = &config.bearer_token {
+            request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+
+        let response = request
+            .send()
+            .await
+            .context("Failed to send request to webhook")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Webhook returned error {}: {}", status, body);
+        }
+
+        Ok(())
+    }
+
+    /// POST a plain message to a Discord-compatible webhook, with mentions disabled
+    pub async fn post_message(&self, url: &str, content: &str) -> Result<()> {
+        let payload = MessagePayload {
+            content,
+            allowed_mentions: AllowedMentions { parse: [] },
+        };
+
+        let response = self
+            .client
+            .post(url)
+            .header(USER_AGENT, "discord-callsign-bot")
+            .json(&payload)
+            .send()
+            .await
+            .context("Failed to send request to webhook")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Webhook returned error {}: {}", status, body);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for WebhookClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_payload_disables_mentions() {
+        let payload = MessagePayload {
+            content: "+ KI7QCF Forrest",
+            allowed_mentions: AllowedMentions { parse: [] },
+        };
+        assert_eq!(
+            serde_json::to_string(&payload).unwrap(),
+            r#"{"content":"+ KI7QCF Forrest","allowed_mentions":{"parse":[]}}"#
+        );
+    }
+
+    #[test]
+    fn test_roster_payload_serialization() {
+        let entries = vec![OutputEntry {
+            callsign: "W6JSV".to_string(),
+            name: "Jay".to_string(),
+            suffix: "73".to_string(),
+            emoji_separator: "📻".to_string(),
+            ..Default::default()
+        }];
+        let payload = RosterPayload {
+            guild_id: 42,
+            title: None,
+            count: entries.len(),
+            entries: &entries,
+        };
+
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["guild_id"], 42);
+        assert_eq!(json["count"], 1);
+        assert!(json.get("title").is_none());
+        assert_eq!(json["entries"][0]["callsign"], "W6JSV");
+        assert_eq!(json["entries"][0]["name"], "Jay");
+    }
+}
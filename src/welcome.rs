@@ -0,0 +1,114 @@
+use crate::output::class_name;
+use crate::parser::MemberInfo;
+use crate::qrz::{CallsignInfo, QrzClient};
+use serenity::all::CreateEmbed;
+
+/// "City, ST, Country", skipping whatever QRZ doesn't have
+pub fn qth(info: &CallsignInfo) -> Option<String> {
+    let parts: Vec<&str> = [&info.city, &info.state, &info.country]
+        .into_iter()
+        .filter_map(|part| part.as_deref().filter(|p| !p.is_empty()))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// Name/value pairs shown on the card; QRZ data wins over the parsed display name
+pub fn fields(member: &MemberInfo, qrz: Option<&CallsignInfo>) -> Vec<(&'static str, String)> {
+    let name = qrz
+        .and_then(QrzClient::get_display_name)
+        .unwrap_or_else(|| member.name.clone());
+    let grid = qrz
+        .and_then(|info| info.grid.clone())
+        .or_else(|| member.grid.clone());
+
+    let mut fields = Vec::new();
+    if !name.is_empty() {
+        fields.push(("Name", name));
+    }
+    if let Some(qth) = qrz.and_then(qth) {
+        fields.push(("QTH", qth));
+    }
+    if let Some(grid) = grid.filter(|g| !g.is_empty()) {
+        fields.push(("Grid", grid));
+    }
+    if let Some(class) = qrz.and_then(|info| info.class.as_deref()) {
+        if !class.is_empty() {
+            fields.push(("Class", class_name(class).to_string()));
+        }
+    }
+    fields
+}
+
+/// The welcome embed: callsign linking to QRZ, profile fields, and the QRZ photo
+pub fn card(member: &MemberInfo, qrz: Option<&CallsignInfo>) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .title(&member.callsign)
+        .url(format!("https://www.qrz.com/db/{}", member.callsign))
+        .fields(
+            fields(member, qrz)
+                .into_iter()
+                .map(|(name, value)| (name, value, true)),
+        );
+    if let Some(image) = qrz
+        .and_then(|info| info.image.as_deref())
+        .filter(|url| url.starts_with("https://"))
+    {
+        embed = embed.thumbnail(image);
+    }
+    embed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member() -> MemberInfo {
+        MemberInfo {
+            callsign: "W6JSV".to_string(),
+            name: "Jay".to_string(),
+            grid: Some("CM87".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_fields_from_qrz() {
+        let info = CallsignInfo {
+            fname: Some("Jay".to_string()),
+            name: Some("Vana".to_string()),
+            city: Some("San Francisco".to_string()),
+            state: Some("CA".to_string()),
+            country: Some("United States".to_string()),
+            grid: Some("CM87ux".to_string()),
+            class: Some("E".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            fields(&member(), Some(&info)),
+            vec![
+                ("Name", "Jay".to_string()),
+                ("QTH", "San Francisco, CA, United States".to_string()),
+                ("Grid", "CM87ux".to_string()),
+                ("Class", "Amateur Extra".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fields_without_qrz() {
+        assert_eq!(
+            fields(&member(), None),
+            vec![("Name", "Jay".to_string()), ("Grid", "CM87".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_qth_skips_missing_parts() {
+        let info = CallsignInfo {
+            state: Some("".to_string()),
+            country: Some("Canada".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(qth(&info).as_deref(), Some("Canada"));
+        assert_eq!(qth(&CallsignInfo::default()), None);
+    }
+}